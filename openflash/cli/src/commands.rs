@@ -2,7 +2,10 @@
 
 use crate::{create_progress_bar, format_size, parse_address, Cli};
 use colored::Colorize;
+use openflash_core::ecc::EccAlgorithm;
 use openflash_core::scripting::*;
+use openflash_core::spi_nand::SpiNandEccCapture;
+use openflash_core::write_ops::{ReadRetryMethod, RecoveryOptions};
 use std::path::PathBuf;
 
 type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
@@ -78,6 +81,7 @@ pub fn detect(cli: &Cli) -> Result<()> {
 }
 
 /// Read/dump chip
+#[allow(clippy::too_many_arguments)]
 pub fn read(
     cli: &Cli,
     output: PathBuf,
@@ -85,9 +89,17 @@ pub fn read(
     length: Option<&str>,
     oob: bool,
     skip_bad: bool,
+    ecc: &str,
+    otp: bool,
 ) -> Result<()> {
     let start_addr = parse_address(start)?;
     let length_val = length.map(|l| parse_address(l)).transpose()?;
+    let on_die_ecc = match ecc.to_lowercase().as_str() {
+        "on" => SpiNandEccCapture::Corrected,
+        "off" | "raw" => SpiNandEccCapture::Raw,
+        "both" => SpiNandEccCapture::Both,
+        other => return Err(format!("Unknown on-die ECC mode: {}", other).into()),
+    };
 
     let mut of = OpenFlash::new();
     of.connect_with_config(ConnectionConfig {
@@ -118,6 +130,8 @@ pub fn read(
         length: length_val,
         include_oob: oob,
         skip_bad_blocks: skip_bad,
+        on_die_ecc,
+        include_otp: otp,
         ..Default::default()
     })?;
 
@@ -130,6 +144,14 @@ pub fn read(
         let oob_path = output.with_extension("oob");
         std::fs::write(&oob_path, oob_data)?;
     }
+    if let Some(raw_data) = &result.raw_data {
+        let raw_path = output.with_extension("raw");
+        std::fs::write(&raw_path, raw_data)?;
+    }
+    if let Some(otp_dump) = &result.otp {
+        let otp_path = output.with_extension("otp.json");
+        std::fs::write(&otp_path, serde_json::to_string_pretty(otp_dump)?)?;
+    }
 
     if !cli.quiet {
         println!("\n{}", "Read complete:".green().bold());
@@ -236,6 +258,120 @@ pub fn verify(cli: &Cli, file: PathBuf, start: &str) -> Result<()> {
     Ok(())
 }
 
+/// Recover an uncorrectable page with re-reads and read-retry
+#[allow(clippy::too_many_arguments)]
+pub fn recover(
+    cli: &Cli,
+    block: u32,
+    page: u32,
+    method: &str,
+    levels: u8,
+    registers: Option<&str>,
+    table: Option<PathBuf>,
+    values: Option<&str>,
+    reads: u8,
+    ecc_strength: u8,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let method = match method.to_lowercase().as_str() {
+        "none" => ReadRetryMethod::None,
+        "onfi" | "micron" => ReadRetryMethod::OnfiSetFeatures { levels },
+        "toshiba" | "kioxia" => ReadRetryMethod::Toshiba,
+        "hynix" | "skhynix" => {
+            let registers = parse_byte_list(registers.ok_or("hynix needs --registers")?)?;
+            let table_path = table.ok_or("hynix needs --table (retry table read from OTP)")?;
+            if registers.is_empty() {
+                return Err("hynix needs at least one retry register".into());
+            }
+            let table_data = std::fs::read(&table_path)?;
+            if table_data.len() % registers.len() != 0 {
+                return Err(format!(
+                    "Retry table is {} bytes, not a multiple of {} registers",
+                    table_data.len(),
+                    registers.len()
+                )
+                .into());
+            }
+            let table = table_data
+                .chunks(registers.len())
+                .map(|row| row.to_vec())
+                .collect();
+            ReadRetryMethod::Hynix { registers, table }
+        }
+        "spinand" | "spi-nand" => {
+            let register = parse_byte_list(registers.ok_or("spinand needs --registers")?)?;
+            let register = match register[..] {
+                [register] => register,
+                _ => return Err("spinand takes exactly one feature register".into()),
+            };
+            let values = parse_byte_list(values.ok_or("spinand needs --values")?)?;
+            ReadRetryMethod::SpiNandFeature { register, values }
+        }
+        other => return Err(format!("Unknown read-retry method: {}", other).into()),
+    };
+
+    let mut of = OpenFlash::new();
+    of.connect_with_config(ConnectionConfig {
+        port: cli.port.clone(),
+        ..Default::default()
+    })?;
+
+    if !cli.quiet {
+        println!(
+            "{} block {} page {} ({} reads/level, up to {} retry levels)",
+            "Recovering".green(),
+            block.to_string().yellow(),
+            page.to_string().yellow(),
+            reads,
+            method.max_level()
+        );
+    }
+
+    let result = of.recover_page(
+        block,
+        page,
+        &RecoveryOptions {
+            method,
+            reads_per_level: reads,
+            ecc_algorithm: EccAlgorithm::Bch { t: ecc_strength },
+        },
+    )?;
+
+    if let Some(path) = &output {
+        std::fs::write(path, &result.data)?;
+    }
+
+    match cli.format.as_str() {
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "block": result.block,
+                "page": result.page,
+                "recovered": result.recovered,
+                "retry_level": result.retry_level,
+                "reads": result.reads,
+                "corrected_bits": result.corrected_bits,
+                "unstable_bits": result.unstable_bits,
+            }))?
+        ),
+        _ => {
+            if result.recovered {
+                println!("\n{}", "Page recovered:".green().bold());
+            } else {
+                println!("\n{}", "Page still uncorrectable:".red().bold());
+            }
+            println!("  Retry level:    {}", result.retry_level);
+            println!("  Reads:          {}", result.reads);
+            println!("  Corrected bits: {}", result.corrected_bits);
+            println!("  Unstable bits:  {}", result.unstable_bits);
+            if let Some(path) = &output {
+                println!("  Saved to:       {}", path.display().to_string().cyan());
+            }
+        }
+    }
+    Ok(())
+}
+
 /// AI analysis
 pub fn analyze(
     cli: &Cli,
@@ -1548,3 +1684,14 @@ fn uuid_simple() -> String {
         .as_nanos();
     format!("{:016x}", ts)
 }
+
+/// Parse a comma-separated list of byte values ("0xB7,0xB8" or "183,184")
+fn parse_byte_list(s: &str) -> Result<Vec<u8>> {
+    s.split(',')
+        .map(|item| {
+            let value = parse_address(item.trim())?;
+            u8::try_from(value)
+                .map_err(|_| format!("{} does not fit in a byte", item.trim()).into())
+        })
+        .collect()
+}
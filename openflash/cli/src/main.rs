@@ -6,6 +6,7 @@
 //! openflash detect                  # Detect connected chip
 //! openflash read -o dump.bin        # Read full chip
 //! openflash write -i firmware.bin   # Write firmware
+//! openflash recover 12 5            # Recover block 12 page 5 via read-retry
 //! openflash analyze dump.bin        # AI analysis
 //! openflash batch jobs.toml         # Run batch jobs
 //! openflash server start            # Start server mode (v2.0)
@@ -76,6 +77,14 @@ enum Commands {
         /// Skip bad blocks
         #[arg(long, default_value = "true")]
         skip_bad: bool,
+
+        /// SPI NAND on-die ECC (on, off, both); off and both save raw page + OOB to <output>.raw
+        #[arg(long, default_value = "on")]
+        ecc: String,
+
+        /// SPI NOR: save unique ID, security registers and OTP to <output>.otp.json
+        #[arg(long)]
+        otp: bool,
     },
 
     /// Write/program flash chip
@@ -127,6 +136,47 @@ enum Commands {
         start: String,
     },
 
+    /// Recover an uncorrectable page with re-reads and read-retry
+    Recover {
+        /// Block number
+        block: u32,
+
+        /// Page within block
+        page: u32,
+
+        /// Read-retry method (none, onfi, micron, toshiba, hynix, spinand)
+        #[arg(short, long, default_value = "onfi")]
+        method: String,
+
+        /// Number of read-retry levels (ONFI/Micron)
+        #[arg(long, default_value = "8")]
+        levels: u8,
+
+        /// Retry registers, comma separated (Hynix), or the feature register (SPI NAND)
+        #[arg(long)]
+        registers: Option<String>,
+
+        /// Hynix retry table read from OTP: one byte per register for each level
+        #[arg(long)]
+        table: Option<PathBuf>,
+
+        /// SPI NAND feature values per level, comma separated, level 0 first
+        #[arg(long)]
+        values: Option<String>,
+
+        /// Reads per retry level for majority voting
+        #[arg(long, default_value = "5")]
+        reads: u8,
+
+        /// ECC strength used to judge recovery (BCH t)
+        #[arg(long, default_value = "8")]
+        ecc_strength: u8,
+
+        /// Write recovered page data to file
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
     /// AI-powered dump analysis
     Analyze {
         /// Input dump file (or use last read)
@@ -502,6 +552,8 @@ fn main() {
             length,
             oob,
             skip_bad,
+            ecc,
+            otp,
         } => commands::read(
             &cli,
             output.clone(),
@@ -509,6 +561,8 @@ fn main() {
             length.as_deref(),
             *oob,
             *skip_bad,
+            ecc,
+            *otp,
         ),
         Commands::Write {
             input,
@@ -523,6 +577,30 @@ fn main() {
             force,
         } => commands::erase(&cli, start.as_deref(), length.as_deref(), *force),
         Commands::Verify { file, start } => commands::verify(&cli, file.clone(), start),
+        Commands::Recover {
+            block,
+            page,
+            method,
+            levels,
+            registers,
+            table,
+            values,
+            reads,
+            ecc_strength,
+            output,
+        } => commands::recover(
+            &cli,
+            *block,
+            *page,
+            method,
+            *levels,
+            registers.as_deref(),
+            table.clone(),
+            values.as_deref(),
+            *reads,
+            *ecc_strength,
+            output.clone(),
+        ),
         Commands::Analyze {
             input,
             output,
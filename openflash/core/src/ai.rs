@@ -13,9 +13,14 @@
 //! - Memory map generation
 //! - AI report export
 
+use crate::pairing::PagePairing;
+use crate::randomizer::{detect_randomizer, known_lfsrs, RandomizerDetection};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// Minimum entropy drop (bits/byte) from descrambling for a chunk to count as scrambled
+const SCRAMBLED_ENTROPY_DROP: f64 = 1.0;
+
 // ============================================================================
 // Data Structures
 // ============================================================================
@@ -54,6 +59,8 @@ impl Confidence {
 pub enum PatternType {
     /// Encrypted data (high entropy, no structure)
     Encrypted,
+    /// Data XORed with a controller randomizer (LFSR) sequence
+    Scrambled,
     /// Compressed data (high entropy with headers)
     Compressed,
     /// Executable code (specific byte patterns)
@@ -230,6 +237,20 @@ pub struct WearAnalysis {
     pub wear_distribution: WearDistribution,
     pub estimated_remaining_life_percent: f32,
     pub recommendations: Vec<String>,
+    /// Blocks with data only in lower pages (pseudo-SLC)
+    #[serde(default)]
+    pub slc_mode_blocks: Vec<usize>,
+    /// Blocks with upper pages programmed over erased lower pages
+    #[serde(default)]
+    pub torn_pair_blocks: Vec<usize>,
+}
+
+/// Lower/upper page usage of a block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PairedBlockState {
+    Normal,
+    SlcMode,
+    TornPair,
 }
 
 /// Wear distribution stats (v1.4)
@@ -355,6 +376,7 @@ pub struct AiAnalyzer {
     block_size: usize,
     oob_size: usize,
     deep_scan: bool,
+    pairing: Option<PagePairing>,
 }
 
 impl Default for AiAnalyzer {
@@ -370,6 +392,7 @@ impl AiAnalyzer {
             block_size: pages_per_block,
             oob_size: Self::estimate_oob_size(page_size),
             deep_scan: false,
+            pairing: None,
         }
     }
 
@@ -383,6 +406,12 @@ impl AiAnalyzer {
         self
     }
 
+    /// Use MLC/TLC page pairing in wear analysis
+    pub fn with_pairing(mut self, pairing: PagePairing) -> Self {
+        self.pairing = Some(pairing);
+        self
+    }
+
     fn estimate_oob_size(page_size: usize) -> usize {
         match page_size {
             512 => 16,
@@ -462,12 +491,13 @@ impl AiAnalyzer {
     pub fn detect_patterns(&self, data: &[u8]) -> Vec<DetectedPattern> {
         let mut patterns: Vec<DetectedPattern> = Vec::new();
         let mut offset = 0;
+        let randomizer = detect_randomizer(data, self.page_size, &known_lfsrs());
 
         while offset < data.len() {
             let chunk_size = (self.page_size * 4).min(data.len() - offset);
             let chunk = &data[offset..offset + chunk_size];
 
-            if let Some(pattern) = self.analyze_chunk(chunk, offset) {
+            if let Some(pattern) = self.analyze_chunk(chunk, offset, randomizer.as_ref()) {
                 // Merge with previous pattern if same type
                 if let Some(last) = patterns.last_mut() {
                     if last.pattern_type == pattern.pattern_type && last.end_offset == offset {
@@ -485,7 +515,12 @@ impl AiAnalyzer {
         patterns
     }
 
-    fn analyze_chunk(&self, chunk: &[u8], offset: usize) -> Option<DetectedPattern> {
+    fn analyze_chunk(
+        &self,
+        chunk: &[u8],
+        offset: usize,
+        randomizer: Option<&RandomizerDetection>,
+    ) -> Option<DetectedPattern> {
         let entropy = self.calculate_entropy(chunk);
         let zero_ratio = chunk.iter().filter(|&&b| b == 0x00).count() as f32 / chunk.len() as f32;
         let ff_ratio = chunk.iter().filter(|&&b| b == 0xFF).count() as f32 / chunk.len() as f32;
@@ -547,21 +582,47 @@ impl AiAnalyzer {
             return Some(pattern);
         }
 
-        // High entropy without structure = likely encrypted
-        if entropy > 7.5 {
+        // High entropy that the detected randomizer removes = scrambled, not encrypted
+        let descrambled_entropy = randomizer
+            .filter(|_| entropy > 7.5)
+            .map(|detection| self.descrambled_entropy(chunk, offset, detection));
+        if let (Some(detection), Some(descrambled)) = (randomizer, descrambled_entropy) {
+            if entropy - descrambled < SCRAMBLED_ENTROPY_DROP {
+                // Descrambling didn't help: this chunk isn't randomizer output
+                return self.high_entropy_pattern(chunk, offset, entropy);
+            }
             let mut details = HashMap::new();
             details.insert("entropy".to_string(), format!("{:.2}", entropy));
+            details.insert(
+                "descrambled_entropy".to_string(),
+                format!("{:.2}", descrambled),
+            );
+            details.insert("lfsr".to_string(), detection.randomizer.lfsr.name.clone());
+            details.insert(
+                "seed_strategy".to_string(),
+                format!("{:?}", detection.randomizer.seed),
+            );
 
             return Some(DetectedPattern {
-                pattern_type: PatternType::Encrypted,
+                pattern_type: PatternType::Scrambled,
                 start_offset: offset,
                 end_offset: offset + chunk.len(),
-                confidence: Confidence::from_score(((entropy - 7.0) / 1.0) as f32),
-                description: "High-entropy data (likely encrypted)".to_string(),
+                confidence: Confidence::from_score(
+                    detection.matched_pages as f32 / detection.pages_tested.max(1) as f32 + 0.5,
+                ),
+                description: format!(
+                    "Scrambled data ({} randomizer)",
+                    detection.randomizer.lfsr.name
+                ),
                 details,
             });
         }
 
+        // High entropy without structure = likely encrypted
+        if entropy > 7.5 {
+            return self.high_entropy_pattern(chunk, offset, entropy);
+        }
+
         // Medium-high entropy = compressed or structured
         if entropy > 5.0 {
             return Some(DetectedPattern {
@@ -577,6 +638,46 @@ impl AiAnalyzer {
         None
     }
 
+    fn high_entropy_pattern(
+        &self,
+        chunk: &[u8],
+        offset: usize,
+        entropy: f64,
+    ) -> Option<DetectedPattern> {
+        let mut details = HashMap::new();
+        details.insert("entropy".to_string(), format!("{:.2}", entropy));
+
+        Some(DetectedPattern {
+            pattern_type: PatternType::Encrypted,
+            start_offset: offset,
+            end_offset: offset + chunk.len(),
+            confidence: Confidence::from_score(((entropy - 7.0) / 1.0) as f32),
+            description: "High-entropy data (likely encrypted)".to_string(),
+            details,
+        })
+    }
+
+    /// Entropy of `chunk` after removing the detected randomizer's keystream
+    fn descrambled_entropy(
+        &self,
+        chunk: &[u8],
+        offset: usize,
+        detection: &RandomizerDetection,
+    ) -> f64 {
+        let page_size = self.page_size.max(1);
+        let first_page = offset / page_size;
+        let mut data = chunk.to_vec();
+        for (idx, page_data) in data.chunks_mut(page_size).enumerate() {
+            // Erased pages are never scrambled
+            if !page_data.iter().all(|&b| b == 0xFF) {
+                detection
+                    .randomizer
+                    .descramble_page((first_page + idx) as u32, page_data);
+            }
+        }
+        self.calculate_entropy(&data)
+    }
+
     fn detect_repeating_pattern(&self, chunk: &[u8], offset: usize) -> Option<DetectedPattern> {
         // Check for short repeating patterns (2-16 bytes)
         for pattern_len in 2..=16 {
@@ -1687,11 +1788,36 @@ impl AiAnalyzer {
 
         let mut erase_estimates: Vec<(usize, u32)> = Vec::new();
         let mut block_entropies: Vec<(usize, f64)> = Vec::new();
+        let mut slc_mode_blocks = Vec::new();
+        let mut torn_pair_blocks = Vec::new();
+        let pairing = self
+            .pairing
+            .as_ref()
+            .filter(|p| p.bits_per_cell() > 1 && p.pages_per_block() as usize == self.block_size);
 
         for block in 0..num_blocks {
             let start = block * block_bytes;
             let end = (start + block_bytes).min(data.len());
-            let block_data = &data[start..end];
+            let mut block_data = &data[start..end];
+
+            // Pseudo-SLC blocks leave upper pages erased; judge them by their lower pages
+            let lower_data;
+            if let Some(pairing) = pairing {
+                match self.classify_paired_block(block_data, pairing) {
+                    PairedBlockState::SlcMode => {
+                        slc_mode_blocks.push(block);
+                        lower_data = pairing
+                            .lower_pages()
+                            .iter()
+                            .flat_map(|&p| self.page_slice(block_data, p))
+                            .copied()
+                            .collect::<Vec<u8>>();
+                        block_data = &lower_data;
+                    }
+                    PairedBlockState::TornPair => torn_pair_blocks.push(block),
+                    PairedBlockState::Normal => {}
+                }
+            }
 
             // Estimate erase count based on various heuristics
             let entropy = self.calculate_entropy(block_data);
@@ -1765,6 +1891,20 @@ impl AiAnalyzer {
                 .push("Wear concentrated in few blocks. Check for hot data patterns.".to_string());
         }
 
+        if !slc_mode_blocks.is_empty() {
+            recommendations.push(format!(
+                "{} blocks hold data only in lower pages (pseudo-SLC). Read them in SLC mode.",
+                slc_mode_blocks.len()
+            ));
+        }
+
+        if !torn_pair_blocks.is_empty() {
+            recommendations.push(format!(
+                "{} blocks have upper pages without their lower page. Data may be corrupt.",
+                torn_pair_blocks.len()
+            ));
+        }
+
         Some(WearAnalysis {
             estimated_erase_counts: erase_estimates,
             hottest_blocks,
@@ -1777,9 +1917,44 @@ impl AiAnalyzer {
             },
             estimated_remaining_life_percent: remaining_life.max(0.0).min(100.0),
             recommendations,
+            slc_mode_blocks,
+            torn_pair_blocks,
         })
     }
 
+    fn page_slice<'a>(&self, block_data: &'a [u8], page: u32) -> &'a [u8] {
+        let start = (page as usize * self.page_size).min(block_data.len());
+        let end = (start + self.page_size).min(block_data.len());
+        &block_data[start..end]
+    }
+
+    /// Compare lower and upper page usage within a block
+    fn classify_paired_block(&self, block_data: &[u8], pairing: &PagePairing) -> PairedBlockState {
+        let erased = |page: u32| self.page_slice(block_data, page).iter().all(|&b| b == 0xFF);
+
+        let mut lower_used = 0;
+        let mut upper_used = 0;
+        for page in 0..pairing.pages_per_block() {
+            if erased(page) {
+                continue;
+            }
+            if pairing.is_lower(page) {
+                lower_used += 1;
+            } else if pairing.earlier_paired_pages(page).into_iter().any(&erased) {
+                return PairedBlockState::TornPair;
+            } else {
+                upper_used += 1;
+            }
+        }
+
+        // A partly written normal block keeps its upper pages close behind the lower ones
+        if upper_used == 0 && lower_used * 2 > pairing.lower_pages().len() {
+            PairedBlockState::SlcMode
+        } else {
+            PairedBlockState::Normal
+        }
+    }
+
     // ========================================================================
     // v1.4: Memory Map Generation
     // ========================================================================
@@ -1803,6 +1978,7 @@ impl AiAnalyzer {
                 PatternType::Empty => ("Empty", "#333333"),
                 PatternType::Zeroed => ("Zeroed", "#222222"),
                 PatternType::Encrypted => ("Encrypted", "#ff4444"),
+                PatternType::Scrambled => ("Scrambled", "#ff8888"),
                 PatternType::Compressed => ("Compressed", "#44ff44"),
                 PatternType::Executable => ("Executable", "#4444ff"),
                 PatternType::Text => ("Text", "#ffff44"),
@@ -2045,6 +2221,118 @@ mod tests {
         assert_eq!(patterns[0].pattern_type, PatternType::Zeroed);
     }
 
+    #[test]
+    fn test_scrambled_not_encrypted() {
+        use crate::randomizer::{Randomizer, SeedStrategy};
+
+        let analyzer = AiAnalyzer::new(2048, 64);
+        let randomizer = Randomizer::new(known_lfsrs()[0].clone(), SeedStrategy::PageNumber);
+
+        // Scrambled padding pages followed by scrambled payload
+        let mut data = Vec::new();
+        for page in 0..16u32 {
+            let mut page_data = if page < 4 {
+                vec![0xFFu8; 2048]
+            } else {
+                (0..2048u32)
+                    .map(|i| b'a' + ((i * 7 + page) % 26) as u8)
+                    .collect()
+            };
+            randomizer.descramble_page(page, &mut page_data);
+            data.extend(page_data);
+        }
+
+        let result = analyzer.analyze(&data);
+        assert!(result
+            .patterns
+            .iter()
+            .any(|p| p.pattern_type == PatternType::Scrambled));
+        assert_eq!(result.encryption_probability, 0.0);
+    }
+
+    #[test]
+    fn test_scrambled_mixed_with_random() {
+        use crate::randomizer::{Randomizer, SeedStrategy};
+
+        let analyzer = AiAnalyzer::new(2048, 64);
+        let randomizer = Randomizer::new(known_lfsrs()[0].clone(), SeedStrategy::PageNumber);
+
+        // Alternating 4-page chunks: scrambled padding/text, then random bytes
+        let mut state = 0x2545_F491u32;
+        let mut data = Vec::new();
+        for page in 0..16u32 {
+            let page_data = if (page / 4) % 2 == 0 {
+                let mut page_data = if page < 4 {
+                    vec![0xFFu8; 2048]
+                } else {
+                    (0..2048u32)
+                        .map(|i| b'a' + ((i * 7 + page) % 26) as u8)
+                        .collect()
+                };
+                randomizer.descramble_page(page, &mut page_data);
+                page_data
+            } else {
+                (0..2048)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 17;
+                        state ^= state << 5;
+                        (state >> 24) as u8
+                    })
+                    .collect()
+            };
+            data.extend(page_data);
+        }
+
+        let patterns = analyzer.detect_patterns(&data);
+        let chunk = 2048 * 4;
+        let type_at = |offset: usize| {
+            patterns
+                .iter()
+                .find(|p| p.start_offset <= offset && offset < p.end_offset)
+                .map(|p| p.pattern_type.clone())
+        };
+        assert_eq!(type_at(0), Some(PatternType::Scrambled));
+        assert_eq!(type_at(chunk), Some(PatternType::Encrypted));
+        assert_eq!(type_at(2 * chunk), Some(PatternType::Scrambled));
+        assert_eq!(type_at(3 * chunk), Some(PatternType::Encrypted));
+    }
+
+    #[test]
+    fn test_wear_analysis_with_pairing() {
+        let pairing = PagePairing::staircase(2, 1, 8).unwrap();
+        let analyzer = AiAnalyzer::new(512, 8).with_pairing(pairing.clone());
+        let page = |fill: bool, seed: u32| -> Vec<u8> {
+            if fill {
+                (0..512u32).map(|i| (i * 7 + seed) as u8).collect()
+            } else {
+                vec![0xFF; 512]
+            }
+        };
+
+        let mut data = Vec::new();
+        for block in 0..6u32 {
+            for p in 0..8u32 {
+                let fill = match block {
+                    // Pseudo-SLC: lower pages only
+                    1 => pairing.is_lower(p),
+                    // Upper page 2 written over erased lower page 0
+                    4 => p == 2,
+                    _ => true,
+                };
+                data.extend(page(fill, block * 8 + p));
+            }
+        }
+
+        let wear = analyzer.analyze_wear_leveling(&data, &[]).unwrap();
+        assert_eq!(wear.slc_mode_blocks, vec![1]);
+        assert_eq!(wear.torn_pair_blocks, vec![4]);
+        assert!(wear
+            .recommendations
+            .iter()
+            .any(|r| r.contains("pseudo-SLC")));
+    }
+
     #[test]
     fn test_text_detection() {
         let analyzer = AiAnalyzer::new(512, 32);
@@ -1,6 +1,7 @@
 //! Data analysis module for OpenFlash
 //! Detects filesystem signatures and analyzes NAND dumps
 
+use crate::bbm::BbmPolicy;
 use serde::{Deserialize, Serialize};
 
 /// Known filesystem signature
@@ -86,6 +87,8 @@ const SIGNATURES: &[SignatureDef] = &[
 pub struct Analyzer {
     page_size: usize,
     block_size: usize,
+    /// Marker policy and OOB size when bad blocks come from a raw dump
+    bbm: Option<(BbmPolicy, usize)>,
 }
 
 impl Default for Analyzer {
@@ -100,14 +103,34 @@ impl Analyzer {
         Self {
             page_size,
             block_size: pages_per_block,
+            bbm: None,
         }
     }
 
+    /// Detect bad blocks from factory markers in a raw dump
+    ///
+    /// The dump passed to `analyze_dump` then has `oob_size` bytes of OOB
+    /// after every page. Only the marker scan sees the OOB; signature
+    /// offsets and page counts refer to the main data.
+    pub fn with_bbm_policy(mut self, policy: BbmPolicy, oob_size: usize) -> Self {
+        self.bbm = Some((policy, oob_size));
+        self
+    }
+
     /// Analyze a NAND dump
     pub fn analyze_dump(&self, data: &[u8]) -> AnalysisResult {
-        let signatures_found = self.find_all_signatures(data);
-        let (empty_pages, data_pages) = self.count_pages(data);
         let bad_blocks = self.detect_bad_blocks(data);
+        let main = match &self.bbm {
+            Some((_, oob_size)) if *oob_size > 0 => std::borrow::Cow::Owned(
+                data.chunks(self.page_size + oob_size)
+                    .flat_map(|page| &page[..page.len().min(self.page_size)])
+                    .copied()
+                    .collect(),
+            ),
+            _ => std::borrow::Cow::Borrowed(data),
+        };
+        let signatures_found = self.find_all_signatures(&main);
+        let (empty_pages, data_pages) = self.count_pages(&main);
 
         let filesystem_type = self.determine_filesystem(&signatures_found);
 
@@ -199,6 +222,10 @@ impl Analyzer {
 
     /// Detect bad blocks by checking spare area markers
     fn detect_bad_blocks(&self, data: &[u8]) -> Vec<u32> {
+        if let Some((policy, oob_size)) = &self.bbm {
+            return policy.scan_raw_dump(data, self.page_size, *oob_size, self.block_size as u32);
+        }
+
         let mut bad = Vec::new();
         let block_bytes = self.page_size * self.block_size;
 
@@ -292,6 +319,24 @@ mod tests {
         assert_eq!(result.data_pages, 0);
     }
 
+    #[test]
+    fn test_bad_blocks_with_bbm_policy() {
+        // 4 blocks of 4 raw pages (512 + 16); block 2 marked in OOB byte 5
+        let mut data = vec![0xFFu8; 4 * 4 * 528];
+        data[2 * 4 * 528 + 512 + 5] = 0x00;
+
+        let policy = BbmPolicy {
+            oob_offset: 5,
+            ..BbmPolicy::default()
+        };
+        let analyzer = Analyzer::new(512, 4).with_bbm_policy(policy, 16);
+        let result = analyzer.analyze_dump(&data);
+        assert_eq!(result.bad_blocks, vec![2]);
+        // Page counts use the main data, not a 512-byte stride over raw pages
+        assert_eq!(result.empty_pages, 16);
+        assert_eq!(result.data_pages, 0);
+    }
+
     #[test]
     fn test_entropy_calculation() {
         let analyzer = Analyzer::default();
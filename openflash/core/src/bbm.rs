@@ -0,0 +1,318 @@
+//! Factory bad-block marker policies
+//!
+//! Vendors put the factory bad-block marker in different places: the
+//! first, second or last page of the block, OOB byte 0 (large page) or 5
+//! (x8 small page), and the 16-bit word at OOB offset 0 on x16 parts. A `BbmPolicy` describes
+//! where to look and scans raw dumps or live chips with it.
+
+use crate::onfi::{CellType, NandChipInfo};
+use crate::write_ops::BadBlockReason;
+use serde::{Deserialize, Serialize};
+
+/// Page of a block holding a bad-block marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarkerPage {
+    First,
+    Second,
+    Last,
+}
+
+impl MarkerPage {
+    pub fn index(&self, pages_per_block: u32) -> u32 {
+        match self {
+            MarkerPage::First => 0,
+            MarkerPage::Second => 1.min(pages_per_block.saturating_sub(1)),
+            MarkerPage::Last => pages_per_block.saturating_sub(1),
+        }
+    }
+}
+
+/// Where factory bad-block markers live and how to read them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BbmPolicy {
+    /// Pages checked; a marker in any of them makes the block bad
+    pub pages: Vec<MarkerPage>,
+    /// Marker byte offset within the OOB area
+    pub oob_offset: usize,
+    /// x16 parts mark with a 16-bit word, both bytes are checked
+    pub x16: bool,
+    /// Set bits a marker byte needs to count as good (8 = exactly 0xFF)
+    ///
+    /// MLC parts allow one flipped bit, since the marker itself can suffer
+    /// from bit errors.
+    pub min_good_bits: u8,
+}
+
+impl Default for BbmPolicy {
+    /// ONFI: first page, OOB byte 0
+    fn default() -> Self {
+        Self {
+            pages: vec![MarkerPage::First],
+            oob_offset: 0,
+            x16: false,
+            min_good_bits: 8,
+        }
+    }
+}
+
+impl BbmPolicy {
+    /// Vendor preset for a chip
+    pub fn for_chip(info: &NandChipInfo) -> Self {
+        let manufacturer = info.manufacturer.to_lowercase();
+        let vendor = |names: &[&str]| names.iter().any(|n| manufacturer.contains(n));
+        let slc = info.cell_type == CellType::SLC;
+
+        let pages = if vendor(&["samsung", "hynix"]) && !slc {
+            vec![MarkerPage::Last]
+        } else if vendor(&[
+            "samsung", "hynix", "toshiba", "kioxia", "macronix", "spansion", "amd", "esmt",
+        ]) || info.page_size <= 512
+        {
+            vec![MarkerPage::First, MarkerPage::Second]
+        } else {
+            vec![MarkerPage::First]
+        };
+
+        Self {
+            pages,
+            // x16 small-page parts mark word 0, not byte 5
+            oob_offset: if info.page_size <= 512 && info.bus_width != 16 {
+                5
+            } else {
+                0
+            },
+            x16: info.bus_width == 16,
+            min_good_bits: if slc { 8 } else { 7 },
+        }
+    }
+
+    /// Page indices to read for one block, without duplicates
+    pub fn marker_pages(&self, pages_per_block: u32) -> Vec<u32> {
+        let mut pages: Vec<u32> = self
+            .pages
+            .iter()
+            .map(|p| p.index(pages_per_block))
+            .collect();
+        pages.sort_unstable();
+        pages.dedup();
+        pages
+    }
+
+    /// Check one page's OOB area
+    pub fn is_bad_marker(&self, oob: &[u8]) -> bool {
+        let width = if self.x16 { 2 } else { 1 };
+        match oob.get(self.oob_offset..self.oob_offset + width) {
+            Some(marker) => marker
+                .iter()
+                .any(|b| (b.count_ones() as u8) < self.min_good_bits),
+            None => false,
+        }
+    }
+
+    /// Scan a raw dump (each page followed by its OOB) for marked blocks
+    pub fn scan_raw_dump(
+        &self,
+        data: &[u8],
+        page_size: usize,
+        oob_size: usize,
+        pages_per_block: u32,
+    ) -> Vec<u32> {
+        let raw_page = page_size + oob_size;
+        let block_bytes = raw_page * pages_per_block as usize;
+        if block_bytes == 0 {
+            return Vec::new();
+        }
+
+        let marker_pages = self.marker_pages(pages_per_block);
+        data.chunks(block_bytes)
+            .enumerate()
+            .filter(|(_, block)| {
+                marker_pages.iter().any(|&page| {
+                    let start = page as usize * raw_page + page_size;
+                    block
+                        .get(start..start + oob_size)
+                        .is_some_and(|oob| self.is_bad_marker(oob))
+                })
+            })
+            .map(|(block, _)| block as u32)
+            .collect()
+    }
+
+    /// Scan a live chip; `read_oob` returns the OOB area of (block, page)
+    pub fn scan_chip<E, R>(
+        &self,
+        total_blocks: u32,
+        pages_per_block: u32,
+        mut read_oob: R,
+    ) -> Result<Vec<u32>, E>
+    where
+        R: FnMut(u32, u32) -> Result<Vec<u8>, E>,
+    {
+        let marker_pages = self.marker_pages(pages_per_block);
+        let mut bad = Vec::new();
+        for block in 0..total_blocks {
+            for &page in &marker_pages {
+                if self.is_bad_marker(&read_oob(block, page)?) {
+                    bad.push(block);
+                    break;
+                }
+            }
+        }
+        Ok(bad)
+    }
+
+    /// Trust but verify: test every block and compare with its marker
+    ///
+    /// `test_block` erases and programs a block, returning the failure if
+    /// any. Erasing a marked block destroys its factory marker, so the
+    /// returned checks must be kept (e.g. in a `BadBlockTable`).
+    pub fn verify_markers<E, T>(
+        &self,
+        total_blocks: u32,
+        marked_bad: &[u32],
+        mut test_block: T,
+    ) -> Result<Vec<MarkerCheck>, E>
+    where
+        T: FnMut(u32) -> Result<Option<BadBlockReason>, E>,
+    {
+        (0..total_blocks)
+            .map(|block| {
+                Ok(MarkerCheck {
+                    block,
+                    marked_bad: marked_bad.contains(&block),
+                    test_failure: test_block(block)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A block's factory marker compared with an erase/program test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarkerCheck {
+    pub block: u32,
+    pub marked_bad: bool,
+    /// `EraseFail` or `ProgramFail` when the test failed
+    pub test_failure: Option<BadBlockReason>,
+}
+
+impl MarkerCheck {
+    /// Markers are trusted; an unmarked block that fails the test is bad too
+    pub fn is_bad(&self) -> bool {
+        self.marked_bad || self.test_failure.is_some()
+    }
+
+    /// Marker and test disagree
+    pub fn is_mismatch(&self) -> bool {
+        self.marked_bad != self.test_failure.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onfi::get_chip_info;
+
+    fn raw_dump(blocks: u32, marks: &[(u32, u32, usize, u8)]) -> Vec<u8> {
+        let mut data = vec![0xFFu8; (blocks * 4 * (512 + 16)) as usize];
+        for &(block, page, offset, value) in marks {
+            data[((block * 4 + page) * 528) as usize + 512 + offset] = value;
+        }
+        data
+    }
+
+    #[test]
+    fn test_scan_raw_dump_marker_pages() {
+        // Block 1: marker in the second page; block 2: in the last page
+        let data = raw_dump(4, &[(1, 1, 0, 0x00), (2, 3, 0, 0x00)]);
+
+        let first_only = BbmPolicy::default();
+        assert!(first_only.scan_raw_dump(&data, 512, 16, 4).is_empty());
+
+        let first_second = BbmPolicy {
+            pages: vec![MarkerPage::First, MarkerPage::Second],
+            ..BbmPolicy::default()
+        };
+        assert_eq!(first_second.scan_raw_dump(&data, 512, 16, 4), vec![1]);
+
+        let last = BbmPolicy {
+            pages: vec![MarkerPage::Last],
+            ..BbmPolicy::default()
+        };
+        assert_eq!(last.scan_raw_dump(&data, 512, 16, 4), vec![2]);
+    }
+
+    #[test]
+    fn test_marker_offset_width_and_bitflips() {
+        let small_page = BbmPolicy {
+            oob_offset: 5,
+            ..BbmPolicy::default()
+        };
+        let mut oob = [0xFFu8; 16];
+        oob[0] = 0x00;
+        assert!(!small_page.is_bad_marker(&oob));
+        oob[5] = 0x00;
+        assert!(small_page.is_bad_marker(&oob));
+
+        let x16 = BbmPolicy {
+            x16: true,
+            ..BbmPolicy::default()
+        };
+        assert!(x16.is_bad_marker(&[0xFF, 0x00, 0xFF]));
+
+        let mlc = BbmPolicy {
+            min_good_bits: 7,
+            ..BbmPolicy::default()
+        };
+        assert!(!mlc.is_bad_marker(&[0xFE]));
+        assert!(mlc.is_bad_marker(&[0xFC]));
+    }
+
+    #[test]
+    fn test_vendor_presets() {
+        let mut info = get_chip_info(&[0xEC, 0xDA]).unwrap();
+        info.cell_type = CellType::SLC;
+        let policy = BbmPolicy::for_chip(&info);
+        assert_eq!(policy.pages, vec![MarkerPage::First, MarkerPage::Second]);
+        assert_eq!(policy.min_good_bits, 8);
+
+        info.cell_type = CellType::MLC;
+        assert_eq!(BbmPolicy::for_chip(&info).pages, vec![MarkerPage::Last]);
+
+        info.manufacturer = "Micron".into();
+        info.bus_width = 16;
+        let policy = BbmPolicy::for_chip(&info);
+        assert_eq!(policy.pages, vec![MarkerPage::First]);
+        assert!(policy.x16);
+
+        info.page_size = 512;
+        assert_eq!(BbmPolicy::for_chip(&info).oob_offset, 0);
+        info.bus_width = 8;
+        assert_eq!(BbmPolicy::for_chip(&info).oob_offset, 5);
+    }
+
+    #[test]
+    fn test_scan_chip_and_verify() {
+        let policy = BbmPolicy::default();
+        let bad = policy
+            .scan_chip::<(), _>(4, 64, |block, _| {
+                Ok(vec![if block == 3 { 0x00 } else { 0xFF }; 64])
+            })
+            .unwrap();
+        assert_eq!(bad, vec![3]);
+
+        // Block 3's marker is false, block 1 fails erase without a marker
+        let checks = policy
+            .verify_markers::<(), _>(4, &bad, |block| {
+                Ok((block == 1).then_some(BadBlockReason::EraseFail))
+            })
+            .unwrap();
+        let mismatches: Vec<u32> = checks
+            .iter()
+            .filter(|c| c.is_mismatch())
+            .map(|c| c.block)
+            .collect();
+        assert_eq!(mismatches, vec![1, 3]);
+        assert!(checks[1].is_bad() && checks[3].is_bad() && !checks[0].is_bad());
+    }
+}
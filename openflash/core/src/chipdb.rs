@@ -0,0 +1,598 @@
+//! Data-driven chip database
+//!
+//! Chip parameters for every supported family live in versioned JSON files
+//! under `core/chipdb/`. The same files are compiled into the library as the
+//! built-in database, and additional files placed in user override
+//! directories are layered on top at runtime, so a chip can be added without
+//! rebuilding.
+//!
+//! ## File format
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "family": "spi_nor",
+//!   "chips": [
+//!     { "ids": ["EF 40 18"], "manufacturer": "Winbond", "model": "W25Q128JV", ... }
+//!   ]
+//! }
+//! ```
+//!
+//! `family` is one of `nand`, `spi_nand`, `spi_nor`, `emmc` or `ufs`; the
+//! remaining chip fields are those of the family's chip info struct. Each
+//! entry of `ids` is an [`IdPattern`].
+
+use crate::emmc::EmmcChipInfo;
+use crate::onfi::NandChipInfo;
+use crate::spi_nand::SpiNandChipInfo;
+use crate::spi_nor::SpiNorChipInfo;
+use crate::ufs::UfsDeviceInfo;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Chip database format version understood by this build
+pub const CHIP_DB_VERSION: u32 = 1;
+
+/// Environment variable holding extra database directories (path-list syntax)
+pub const CHIP_DB_PATH_ENV: &str = "OPENFLASH_CHIPDB_PATH";
+
+const BUILTIN_FILES: [&str; 5] = [
+    include_str!("../chipdb/nand.json"),
+    include_str!("../chipdb/spi_nand.json"),
+    include_str!("../chipdb/spi_nor.json"),
+    include_str!("../chipdb/emmc.json"),
+    include_str!("../chipdb/ufs.json"),
+];
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Chip database loading errors
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChipDbError {
+    /// File could not be read
+    Io { path: String, message: String },
+    /// File is not a valid database file
+    Parse { source: String, message: String },
+    /// File uses a format version this build does not understand
+    UnsupportedVersion { source: String, version: u32 },
+    /// Malformed chip ID pattern
+    InvalidPattern(String),
+}
+
+impl std::fmt::Display for ChipDbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChipDbError::Io { path, message } => write!(f, "Cannot read {}: {}", path, message),
+            ChipDbError::Parse { source, message } => {
+                write!(f, "Invalid chip database {}: {}", source, message)
+            }
+            ChipDbError::UnsupportedVersion { source, version } => write!(
+                f,
+                "Chip database {} has unsupported version {} (expected {})",
+                source, version, CHIP_DB_VERSION
+            ),
+            ChipDbError::InvalidPattern(p) => write!(f, "Invalid chip ID pattern: {}", p),
+        }
+    }
+}
+
+impl std::error::Error for ChipDbError {}
+
+// ============================================================================
+// ID Patterns
+// ============================================================================
+
+/// Chip ID pattern
+///
+/// Space-separated tokens, matched against the ID bytes in order:
+/// - `EC`, `0xEC` - exact byte (hex)
+/// - `??` - any single byte
+/// - `'BJTD4R'` - ASCII bytes (eMMC/UFS product names)
+/// - `*` - any remaining bytes (only as the last token)
+///
+/// Without a trailing `*` the ID must have exactly the pattern's length.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct IdPattern {
+    bytes: Vec<Option<u8>>,
+    prefix: bool,
+}
+
+impl IdPattern {
+    pub fn parse(pattern: &str) -> Result<Self, ChipDbError> {
+        let invalid = || ChipDbError::InvalidPattern(pattern.to_string());
+        let mut bytes = Vec::new();
+        let mut prefix = false;
+        let mut rest = pattern.trim();
+
+        while !rest.is_empty() {
+            if prefix {
+                return Err(invalid());
+            }
+            let token_len = if let Some(quoted) = rest.strip_prefix('\'') {
+                let end = quoted.find('\'').ok_or_else(invalid)?;
+                bytes.extend(quoted[..end].bytes().map(Some));
+                end + 2
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let token = &rest[..end];
+                match token {
+                    "*" => prefix = true,
+                    "??" => bytes.push(None),
+                    _ => {
+                        let hex = token.trim_start_matches("0x").trim_start_matches("0X");
+                        if hex.len() != 2 {
+                            return Err(invalid());
+                        }
+                        bytes.push(Some(u8::from_str_radix(hex, 16).map_err(|_| invalid())?));
+                    }
+                }
+                end
+            };
+            rest = rest[token_len..].trim_start();
+        }
+
+        if bytes.is_empty() && !prefix {
+            return Err(invalid());
+        }
+        Ok(Self { bytes, prefix })
+    }
+
+    /// Check whether an ID matches this pattern
+    pub fn matches(&self, id: &[u8]) -> bool {
+        let length_ok = if self.prefix {
+            id.len() >= self.bytes.len()
+        } else {
+            id.len() == self.bytes.len()
+        };
+        length_ok
+            && self
+                .bytes
+                .iter()
+                .zip(id)
+                .all(|(p, b)| p.map_or(true, |p| p == *b))
+    }
+
+    /// Ranking among matching patterns: full IDs first, then most fixed bytes
+    fn specificity(&self) -> (bool, usize) {
+        (
+            !self.prefix,
+            self.bytes.iter().filter(|b| b.is_some()).count(),
+        )
+    }
+}
+
+impl TryFrom<String> for IdPattern {
+    type Error = ChipDbError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        IdPattern::parse(&value)
+    }
+}
+
+impl From<IdPattern> for String {
+    fn from(pattern: IdPattern) -> Self {
+        pattern.to_string()
+    }
+}
+
+impl std::fmt::Display for IdPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut tokens: Vec<String> = self
+            .bytes
+            .iter()
+            .map(|b| match b {
+                Some(b) => format!("{:02X}", b),
+                None => "??".to_string(),
+            })
+            .collect();
+        if self.prefix {
+            tokens.push("*".to_string());
+        }
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+// ============================================================================
+// Database
+// ============================================================================
+
+/// Chip family a database file describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChipFamily {
+    Nand,
+    SpiNand,
+    SpiNor,
+    Emmc,
+    Ufs,
+}
+
+/// Database entry: ID patterns plus the chip parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChipRecord<T> {
+    pub ids: Vec<IdPattern>,
+    #[serde(flatten)]
+    pub info: T,
+}
+
+#[derive(Deserialize)]
+struct ChipDbFile {
+    version: u32,
+    family: ChipFamily,
+    chips: Vec<serde_json::Value>,
+}
+
+/// Loaded entry with the layer it came from (later layers take precedence)
+#[derive(Debug, Clone)]
+struct Entry<T> {
+    layer: usize,
+    record: ChipRecord<T>,
+}
+
+/// Chip database covering all supported families
+#[derive(Debug, Clone, Default)]
+pub struct ChipDatabase {
+    nand: Vec<Entry<NandChipInfo>>,
+    spi_nand: Vec<Entry<SpiNandChipInfo>>,
+    spi_nor: Vec<Entry<SpiNorChipInfo>>,
+    emmc: Vec<Entry<EmmcChipInfo>>,
+    ufs: Vec<Entry<UfsDeviceInfo>>,
+    layers: usize,
+}
+
+impl ChipDatabase {
+    /// Empty database
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Database compiled into the library
+    pub fn builtin() -> Self {
+        let mut db = Self::new();
+        for (i, contents) in BUILTIN_FILES.iter().enumerate() {
+            db.load_str(contents, &format!("builtin #{}", i))
+                .expect("built-in chip database is valid");
+        }
+        db
+    }
+
+    /// Built-in database with the user override directories layered on top
+    ///
+    /// Override files that fail to load are skipped; use [`load_dir`] to
+    /// report errors.
+    ///
+    /// [`load_dir`]: ChipDatabase::load_dir
+    pub fn with_user_overrides() -> Self {
+        let mut db = Self::builtin();
+        for dir in user_chipdb_dirs() {
+            let _ = db.load_dir(&dir);
+        }
+        db
+    }
+
+    /// Load one database file from a string, returning the number of chips
+    ///
+    /// Each load adds a layer whose entries take precedence over earlier ones.
+    pub fn load_str(&mut self, contents: &str, source: &str) -> Result<usize, ChipDbError> {
+        let parse_error = |e: serde_json::Error| ChipDbError::Parse {
+            source: source.to_string(),
+            message: e.to_string(),
+        };
+
+        let file: ChipDbFile = serde_json::from_str(contents).map_err(parse_error)?;
+        if file.version != CHIP_DB_VERSION {
+            return Err(ChipDbError::UnsupportedVersion {
+                source: source.to_string(),
+                version: file.version,
+            });
+        }
+
+        let layer = self.layers;
+        let count = match file.family {
+            ChipFamily::Nand => push_records(&mut self.nand, file.chips, layer),
+            ChipFamily::SpiNand => push_records(&mut self.spi_nand, file.chips, layer),
+            ChipFamily::SpiNor => push_records(&mut self.spi_nor, file.chips, layer),
+            ChipFamily::Emmc => push_records(&mut self.emmc, file.chips, layer),
+            ChipFamily::Ufs => push_records(&mut self.ufs, file.chips, layer),
+        }
+        .map_err(parse_error)?;
+
+        self.layers += 1;
+        Ok(count)
+    }
+
+    /// Load a database file from disk
+    pub fn load_file(&mut self, path: &Path) -> Result<usize, ChipDbError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ChipDbError::Io {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+        self.load_str(&contents, &path.display().to_string())
+    }
+
+    /// Load every `*.json` file in a directory, in file name order
+    ///
+    /// A missing directory is not an error.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize, ChipDbError> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => {
+                return Err(ChipDbError::Io {
+                    path: dir.display().to_string(),
+                    message: e.to_string(),
+                })
+            }
+        };
+
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        let mut count = 0;
+        for file in files {
+            count += self.load_file(&file)?;
+        }
+        Ok(count)
+    }
+
+    /// Number of chip entries per family
+    pub fn len(&self, family: ChipFamily) -> usize {
+        match family {
+            ChipFamily::Nand => self.nand.len(),
+            ChipFamily::SpiNand => self.spi_nand.len(),
+            ChipFamily::SpiNor => self.spi_nor.len(),
+            ChipFamily::Emmc => self.emmc.len(),
+            ChipFamily::Ufs => self.ufs.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers == 0
+    }
+
+    /// Look up a parallel NAND chip by its READ ID bytes
+    pub fn lookup_nand(&self, id: &[u8]) -> Option<NandChipInfo> {
+        best_match(&self.nand, id).cloned()
+    }
+
+    /// Look up a SPI NAND chip by manufacturer + device ID bytes
+    pub fn lookup_spi_nand(&self, id: &[u8]) -> Option<SpiNandChipInfo> {
+        best_match(&self.spi_nand, id).cloned()
+    }
+
+    /// Look up a SPI NOR chip by JEDEC ID
+    pub fn lookup_spi_nor(&self, jedec_id: &[u8; 3]) -> Option<SpiNorChipInfo> {
+        best_match(&self.spi_nor, jedec_id).map(|info| SpiNorChipInfo {
+            jedec_id: *jedec_id,
+            ..info.clone()
+        })
+    }
+
+    /// Look up an eMMC chip by CID manufacturer ID and product name
+    pub fn lookup_emmc(&self, mid: u8, product_name: &str) -> Option<EmmcChipInfo> {
+        let mut key = vec![mid];
+        key.extend_from_slice(product_name.as_bytes());
+        best_match(&self.emmc, &key).cloned()
+    }
+
+    /// Look up a UFS device by manufacturer ID and product name
+    pub fn lookup_ufs(&self, manufacturer_id: u16, product_name: &str) -> Option<UfsDeviceInfo> {
+        let mut key = manufacturer_id.to_be_bytes().to_vec();
+        key.extend_from_slice(product_name.as_bytes());
+        best_match(&self.ufs, &key).cloned()
+    }
+}
+
+fn push_records<T: DeserializeOwned>(
+    entries: &mut Vec<Entry<T>>,
+    chips: Vec<serde_json::Value>,
+    layer: usize,
+) -> Result<usize, serde_json::Error> {
+    let records = chips
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<Result<Vec<ChipRecord<T>>, _>>()?;
+    let count = records.len();
+    entries.extend(records.into_iter().map(|record| Entry { layer, record }));
+    Ok(count)
+}
+
+/// Best match: newest layer first, then most specific pattern, then file order
+fn best_match<'a, T>(entries: &'a [Entry<T>], id: &[u8]) -> Option<&'a T> {
+    entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| {
+            entry
+                .record
+                .ids
+                .iter()
+                .filter(|p| p.matches(id))
+                .map(|p| p.specificity())
+                .max()
+                .map(|specificity| ((entry.layer, specificity, std::cmp::Reverse(index)), entry))
+        })
+        .max_by_key(|(rank, _)| *rank)
+        .map(|(_, entry)| &entry.record.info)
+}
+
+/// User override directories, lowest precedence first
+///
+/// `$XDG_CONFIG_HOME/openflash/chipdb` (or `~/.config/openflash/chipdb`),
+/// followed by the directories listed in `OPENFLASH_CHIPDB_PATH`.
+pub fn user_chipdb_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+    if let Some(config_home) = config_home {
+        dirs.push(config_home.join("openflash").join("chipdb"));
+    }
+
+    if let Some(paths) = std::env::var_os(CHIP_DB_PATH_ENV) {
+        dirs.extend(std::env::split_paths(&paths));
+    }
+
+    dirs
+}
+
+/// Process-wide chip database (built-in plus user overrides), loaded on first use
+pub fn chip_database() -> &'static ChipDatabase {
+    static DATABASE: OnceLock<ChipDatabase> = OnceLock::new();
+    DATABASE.get_or_init(ChipDatabase::with_user_overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_id_pattern_parse() {
+        let p = IdPattern::parse("EC F1 00 95 40").unwrap();
+        assert!(p.matches(&[0xEC, 0xF1, 0x00, 0x95, 0x40]));
+        assert!(!p.matches(&[0xEC, 0xF1, 0x00, 0x95, 0x40, 0x00]));
+        assert_eq!(p.to_string(), "EC F1 00 95 40");
+
+        let p = IdPattern::parse("0xC8 ?? *").unwrap();
+        assert!(p.matches(&[0xC8, 0x12]));
+        assert!(p.matches(&[0xC8, 0x12, 0x34]));
+        assert!(!p.matches(&[0xC8]));
+        assert_eq!(p.to_string(), "C8 ?? *");
+
+        let p = IdPattern::parse("15 'BJTD4R'").unwrap();
+        assert!(p.matches(b"\x15BJTD4R"));
+
+        assert!(IdPattern::parse("EC * F1").is_err());
+        assert!(IdPattern::parse("ECF1").is_err());
+        assert!(IdPattern::parse("15 'open").is_err());
+        assert!(IdPattern::parse("").is_err());
+    }
+
+    #[test]
+    fn test_builtin_database() {
+        let db = ChipDatabase::builtin();
+        assert!(db.len(ChipFamily::Nand) > 0);
+        assert!(db.len(ChipFamily::SpiNand) > 0);
+        assert!(db.len(ChipFamily::SpiNor) > 0);
+        assert!(db.len(ChipFamily::Emmc) > 0);
+
+        let nand = db.lookup_nand(&[0xEC, 0xF1, 0x00, 0x95, 0x40]).unwrap();
+        assert_eq!(nand.model, "K9F1G08U0B");
+
+        let nor = db.lookup_spi_nor(&[0xEF, 0x40, 0x18]).unwrap();
+        assert_eq!(nor.jedec_id, [0xEF, 0x40, 0x18]);
+
+        assert!(db.lookup_emmc(0x15, "BJTD4R").is_some());
+        assert!(db.lookup_nand(&[0x00, 0x00, 0x00, 0x00, 0x00]).is_none());
+    }
+
+    #[test]
+    fn test_override_precedence_and_specificity() {
+        let mut db = ChipDatabase::builtin();
+        let override_file = r#"{
+            "version": 1,
+            "family": "spi_nor",
+            "chips": [
+                {
+                    "ids": ["EF 40 *"],
+                    "manufacturer": "Winbond",
+                    "model": "W25Q-wildcard",
+                    "size_bytes": 1048576,
+                    "page_size": 256,
+                    "sector_size": 4096,
+                    "block_size": 65536,
+                    "voltage": "3.3V",
+                    "max_clock_mhz": 50,
+                    "has_qspi": false,
+                    "has_dual": true,
+                    "address_bytes": 3
+                },
+                {
+                    "ids": ["EF 40 14"],
+                    "manufacturer": "Winbond",
+                    "model": "W25Q80-custom",
+                    "size_bytes": 1048576,
+                    "page_size": 256,
+                    "sector_size": 4096,
+                    "block_size": 65536,
+                    "voltage": "3.3V",
+                    "max_clock_mhz": 50,
+                    "has_qspi": false,
+                    "has_dual": true,
+                    "address_bytes": 3
+                }
+            ]
+        }"#;
+        assert_eq!(db.load_str(override_file, "test").unwrap(), 2);
+
+        // Exact ID beats the wildcard within the override layer
+        assert_eq!(
+            db.lookup_spi_nor(&[0xEF, 0x40, 0x14]).unwrap().model,
+            "W25Q80-custom"
+        );
+        // Override layer beats the built-in exact entry
+        assert_eq!(
+            db.lookup_spi_nor(&[0xEF, 0x40, 0x17]).unwrap().model,
+            "W25Q-wildcard"
+        );
+        // Other IDs still come from the built-in layer
+        assert_eq!(
+            db.lookup_spi_nor(&[0xEF, 0x60, 0x15]).unwrap().manufacturer,
+            "Winbond"
+        );
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let mut db = ChipDatabase::new();
+        assert!(matches!(
+            db.load_str(r#"{"version": 99, "family": "nand", "chips": []}"#, "v99"),
+            Err(ChipDbError::UnsupportedVersion { version: 99, .. })
+        ));
+        assert!(matches!(
+            db.load_str(
+                r#"{"version": 1, "family": "nand", "chips": [{"ids": ["XYZ"]}]}"#,
+                "bad"
+            ),
+            Err(ChipDbError::Parse { .. })
+        ));
+        assert!(db.is_empty());
+        assert_eq!(
+            db.load_dir(Path::new("/nonexistent/openflash/chipdb")),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn test_ufs_lookup() {
+        let mut db = ChipDatabase::new();
+        let file = r#"{
+            "version": 1,
+            "family": "ufs",
+            "chips": [{
+                "ids": ["01 CE 'KLUDG4U' *"],
+                "manufacturer": "Samsung",
+                "product_name": "KLUDG4U",
+                "serial_number": "",
+                "ufs_version": "Ufs31",
+                "capacity_bytes": 128000000000,
+                "logical_block_size": 4096,
+                "num_luns": 8,
+                "boot_lun_enabled": true
+            }]
+        }"#;
+        db.load_str(file, "ufs").unwrap();
+        let info = db.lookup_ufs(0x01CE, "KLUDG4UHDB-B2D1").unwrap();
+        assert_eq!(info.logical_block_size, 4096);
+        assert!(db.lookup_ufs(0x01AD, "KLUDG4UHDB-B2D1").is_none());
+    }
+}
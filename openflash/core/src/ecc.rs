@@ -1,14 +1,50 @@
 //! Error Correction Code implementations for NAND flash
-//! Supports Hamming and BCH algorithms
+//! Supports Hamming, BCH and quasi-cyclic LDPC algorithms
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// ECC algorithm selection
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum EccAlgorithm {
     None,
     Hamming,
-    Bch { t: u8 }, // t = number of correctable errors
+    Bch {
+        t: u8, // t = number of correctable errors
+    },
+    /// Codeword = 512-byte sector followed by its parity bytes
+    Ldpc {
+        code: LdpcCode,
+    },
+}
+
+impl EccAlgorithm {
+    /// Number of bit errors the algorithm can correct per sector
+    pub fn strength(&self) -> u32 {
+        match self {
+            EccAlgorithm::None => 0,
+            EccAlgorithm::Hamming => 1,
+            EccAlgorithm::Bch { t } => *t as u32,
+            // No fixed strength; roughly one correctable bit per 16 parity bits
+            EccAlgorithm::Ldpc { code } => (code.check_count() / 16) as u32,
+        }
+    }
+
+    /// Check the parameters are usable by the decode helpers
+    pub fn validate(&self) -> Result<(), EccError> {
+        match self {
+            EccAlgorithm::Ldpc { code } => {
+                code.validate()?;
+                let bits = code.codeword_bits();
+                if bits % 8 == 0 && bits > 512 * 8 {
+                    Ok(())
+                } else {
+                    Err(EccError::InvalidInput)
+                }
+            }
+            _ => Ok(()),
+        }
+    }
 }
 
 /// ECC processing result
@@ -19,6 +55,16 @@ pub struct EccResult {
     pub uncorrectable: bool,
 }
 
+/// Per-sector outcome of an ECC decode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorEccResult {
+    /// Bits corrected in this sector (bitflips for erased sectors)
+    pub corrected_bits: u32,
+    pub uncorrectable: bool,
+    /// Sector was recognised as erased and reset to 0xFF
+    pub erased: bool,
+}
+
 /// ECC error types
 #[derive(Debug, Clone)]
 pub enum EccError {
@@ -471,6 +517,334 @@ fn gf_mul_bit(a: u16, b: u16) -> u16 {
     }
 }
 
+// ============================================================================
+// LDPC ECC - Quasi-cyclic codes with min-sum soft-decision decoding
+// ============================================================================
+
+/// Quasi-cyclic LDPC parity-check matrix definition
+///
+/// The parity-check matrix H is built from a base matrix of circulant shift
+/// values: `-1` is an all-zero Z×Z block, `s >= 0` is the identity matrix
+/// cyclically shifted right by `s`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LdpcCode {
+    /// Circulant (expansion) size Z
+    pub circulant_size: usize,
+    /// Base matrix of circulant shifts, one row per block row
+    pub base_matrix: Vec<Vec<i32>>,
+}
+
+impl LdpcCode {
+    pub fn new(circulant_size: usize, base_matrix: Vec<Vec<i32>>) -> Result<Self, EccError> {
+        let code = Self {
+            circulant_size,
+            base_matrix,
+        };
+        code.validate()?;
+        Ok(code)
+    }
+
+    /// Load a code definition from JSON (`circulant_size`, `base_matrix`)
+    pub fn from_json(json: &str) -> Result<Self, EccError> {
+        let code: Self = serde_json::from_str(json).map_err(|_| EccError::InvalidInput)?;
+        code.validate()?;
+        Ok(code)
+    }
+
+    /// Load a base matrix from whitespace-separated text, one block row per line
+    ///
+    /// Empty lines and lines starting with `#` are ignored.
+    pub fn from_text(circulant_size: usize, text: &str) -> Result<Self, EccError> {
+        let base_matrix = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                line.split_whitespace()
+                    .map(|v| v.parse::<i32>().map_err(|_| EccError::InvalidInput))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(circulant_size, base_matrix)
+    }
+
+    fn validate(&self) -> Result<(), EccError> {
+        let cols = self.base_matrix.first().map(|r| r.len()).unwrap_or(0);
+        let valid = self.circulant_size > 0
+            && cols > 0
+            && self.base_matrix.iter().all(|row| {
+                row.len() == cols
+                    && row
+                        .iter()
+                        .all(|&s| s >= -1 && s < self.circulant_size as i32)
+            });
+        if valid {
+            Ok(())
+        } else {
+            Err(EccError::InvalidInput)
+        }
+    }
+
+    /// Codeword length in bits
+    pub fn codeword_bits(&self) -> usize {
+        self.base_matrix[0].len() * self.circulant_size
+    }
+
+    /// Number of parity checks (rows of H)
+    pub fn check_count(&self) -> usize {
+        self.base_matrix.len() * self.circulant_size
+    }
+
+    /// Variable (bit) indices taking part in each parity check
+    fn check_nodes(&self) -> Vec<Vec<usize>> {
+        let z = self.circulant_size;
+        let mut checks = Vec::with_capacity(self.check_count());
+        for row in &self.base_matrix {
+            for r in 0..z {
+                let vars = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &shift)| shift >= 0)
+                    .map(|(col, &shift)| col * z + (r + shift as usize) % z)
+                    .collect();
+                checks.push(vars);
+            }
+        }
+        checks
+    }
+}
+
+/// Result of a successful LDPC decode
+#[derive(Debug, Clone)]
+pub struct LdpcOutput {
+    /// Corrected codeword, MSB-first bit order
+    pub codeword: Vec<u8>,
+    /// Min-sum iterations used (0 if the input was already a codeword)
+    pub iterations: u32,
+}
+
+/// Normalized min-sum LDPC decoder
+///
+/// Bits are taken MSB-first from each byte, matching the BCH code above.
+/// Log-likelihood ratios are positive when a bit is more likely 0.
+pub struct LdpcDecoder {
+    code: LdpcCode,
+    checks: Vec<Vec<usize>>,
+    max_iterations: u32,
+    scaling: f32,
+}
+
+impl LdpcDecoder {
+    /// Fails with `InvalidInput` for a malformed code, e.g. one deserialized
+    /// or built field by field without going through `LdpcCode::new`
+    pub fn new(code: LdpcCode) -> Result<Self, EccError> {
+        code.validate()?;
+        let checks = code.check_nodes();
+        Ok(Self {
+            code,
+            checks,
+            max_iterations: 50,
+            scaling: 0.75,
+        })
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: u32) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// Set the min-sum normalization factor (1.0 = plain min-sum)
+    pub fn with_scaling(mut self, scaling: f32) -> Self {
+        self.scaling = scaling;
+        self
+    }
+
+    pub fn code(&self) -> &LdpcCode {
+        &self.code
+    }
+
+    /// Check whether `bits` (one entry per codeword bit) satisfies every parity check
+    fn is_codeword(&self, bits: &[bool]) -> bool {
+        self.checks
+            .iter()
+            .all(|vars| vars.iter().filter(|&&v| bits[v]).count() % 2 == 0)
+    }
+
+    /// Decode from per-bit log-likelihood ratios
+    pub fn decode_llr(&self, llrs: &[f32]) -> Result<LdpcOutput, EccError> {
+        let n = self.code.codeword_bits();
+        if llrs.len() != n {
+            return Err(EccError::InvalidInput);
+        }
+
+        let mut bits: Vec<bool> = llrs.iter().map(|&l| l < 0.0).collect();
+        if self.is_codeword(&bits) {
+            return Ok(LdpcOutput {
+                codeword: pack_bits(&bits),
+                iterations: 0,
+            });
+        }
+
+        // Edge messages, stored per check node in `checks` order
+        let mut var_to_check: Vec<Vec<f32>> = self
+            .checks
+            .iter()
+            .map(|vars| vars.iter().map(|&v| llrs[v]).collect())
+            .collect();
+        let mut check_to_var: Vec<Vec<f32>> = self
+            .checks
+            .iter()
+            .map(|vars| vec![0.0; vars.len()])
+            .collect();
+        let mut totals = vec![0f32; n];
+
+        for iteration in 1..=self.max_iterations {
+            // Check node update
+            for (messages, out) in var_to_check.iter().zip(check_to_var.iter_mut()) {
+                let mut sign_product = 1f32;
+                let mut min1 = f32::INFINITY;
+                let mut min2 = f32::INFINITY;
+                let mut min_idx = 0;
+                for (i, &m) in messages.iter().enumerate() {
+                    if m < 0.0 {
+                        sign_product = -sign_product;
+                    }
+                    let mag = m.abs();
+                    if mag < min1 {
+                        min2 = min1;
+                        min1 = mag;
+                        min_idx = i;
+                    } else if mag < min2 {
+                        min2 = mag;
+                    }
+                }
+                for (i, (&m, r)) in messages.iter().zip(out.iter_mut()).enumerate() {
+                    let sign = if m < 0.0 { -sign_product } else { sign_product };
+                    let mag = if i == min_idx { min2 } else { min1 };
+                    *r = self.scaling * sign * mag;
+                }
+            }
+
+            // Variable node update
+            totals.copy_from_slice(llrs);
+            for (vars, messages) in self.checks.iter().zip(check_to_var.iter()) {
+                for (&v, &r) in vars.iter().zip(messages.iter()) {
+                    totals[v] += r;
+                }
+            }
+            for ((vars, messages), out) in self
+                .checks
+                .iter()
+                .zip(check_to_var.iter())
+                .zip(var_to_check.iter_mut())
+            {
+                for ((&v, &r), q) in vars.iter().zip(messages.iter()).zip(out.iter_mut()) {
+                    *q = totals[v] - r;
+                }
+            }
+
+            for (bit, &total) in bits.iter_mut().zip(totals.iter()) {
+                *bit = total < 0.0;
+            }
+            if self.is_codeword(&bits) {
+                return Ok(LdpcOutput {
+                    codeword: pack_bits(&bits),
+                    iterations: iteration,
+                });
+            }
+        }
+
+        Err(EccError::UncorrectableError)
+    }
+
+    /// Hard-decision decode of a single read, correcting `codeword` in place
+    pub fn correct(&self, codeword: &mut [u8]) -> Result<u32, EccError> {
+        let n = self.code.codeword_bits();
+        if codeword.len() * 8 < n {
+            return Err(EccError::InvalidInput);
+        }
+
+        let llrs: Vec<f32> = (0..n)
+            .map(|i| if bit_at(codeword, i) { -1.0 } else { 1.0 })
+            .collect();
+        let output = self.decode_llr(&llrs)?;
+        Ok(apply_codeword(codeword, &output.codeword, n))
+    }
+
+    /// Soft-decision decode from several reads at shifted read-reference voltages
+    ///
+    /// `reads` are raw codewords read with different read thresholds. Each
+    /// bit's LLR comes from how many reads returned 0 for it; `llr_table`
+    /// maps that count (index 0..=reads.len()) to an LLR and defaults to a
+    /// linear ramp from -4.0 (all reads 1) to +4.0 (all reads 0).
+    pub fn correct_soft(
+        &self,
+        reads: &[Vec<u8>],
+        llr_table: Option<&[f32]>,
+        codeword: &mut [u8],
+    ) -> Result<u32, EccError> {
+        let llrs = llrs_from_reads(reads, self.code.codeword_bits(), llr_table)?;
+        if codeword.len() * 8 < llrs.len() {
+            return Err(EccError::InvalidInput);
+        }
+        let output = self.decode_llr(&llrs)?;
+        Ok(apply_codeword(codeword, &output.codeword, llrs.len()))
+    }
+}
+
+/// Build per-bit LLRs from multiple reads at shifted read-reference voltages
+pub fn llrs_from_reads(
+    reads: &[Vec<u8>],
+    n_bits: usize,
+    llr_table: Option<&[f32]>,
+) -> Result<Vec<f32>, EccError> {
+    if reads.is_empty() || reads.iter().any(|r| r.len() * 8 < n_bits) {
+        return Err(EccError::InvalidInput);
+    }
+    if llr_table.is_some_and(|t| t.len() != reads.len() + 1) {
+        return Err(EccError::InvalidInput);
+    }
+
+    let n_reads = reads.len() as f32;
+    Ok((0..n_bits)
+        .map(|i| {
+            let zeros = reads.iter().filter(|r| !bit_at(r, i)).count();
+            match llr_table {
+                Some(table) => table[zeros],
+                None => 4.0 * (2.0 * zeros as f32 - n_reads) / n_reads,
+            }
+        })
+        .collect())
+}
+
+#[inline]
+fn bit_at(bytes: &[u8], i: usize) -> bool {
+    bytes[i / 8] & (0x80 >> (i % 8)) != 0
+}
+
+fn pack_bits(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; (bits.len() + 7) / 8];
+    for (i, &bit) in bits.iter().enumerate() {
+        if bit {
+            bytes[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    bytes
+}
+
+/// Copy the first `n_bits` of `decoded` into `codeword`, returning bits changed
+fn apply_codeword(codeword: &mut [u8], decoded: &[u8], n_bits: usize) -> u32 {
+    let mut changed = 0u32;
+    for i in 0..n_bits {
+        let bit = bit_at(decoded, i);
+        if bit_at(codeword, i) != bit {
+            codeword[i / 8] ^= 0x80 >> (i % 8);
+            changed += 1;
+        }
+    }
+    changed
+}
+
 // ============================================================================
 // Public API
 // ============================================================================
@@ -499,52 +873,295 @@ pub fn encode_with_ecc(data: &[u8], algorithm: &EccAlgorithm) -> (Vec<u8>, Vec<u
             }
             (data.to_vec(), all_ecc)
         }
+        // LDPC parity comes from the controller; there is no encoder
+        EccAlgorithm::Ldpc { .. } => (data.to_vec(), vec![]),
+    }
+}
+
+/// Check whether a chunk is an erased chunk with a few bitflips
+///
+/// Mirrors Linux `nand_check_erased_ecc_chunk`: zero bits are counted across
+/// data and ECC bytes and, if the count stays within `bitflips_threshold`,
+/// the data is reset to 0xFF and the number of flipped bits is returned.
+pub fn check_erased_ecc_chunk(data: &mut [u8], ecc: &[u8], bitflips_threshold: u32) -> Option<u32> {
+    let mut bitflips = 0u32;
+    for &byte in data.iter().chain(ecc.iter()) {
+        bitflips += byte.count_zeros();
+        if bitflips > bitflips_threshold {
+            return None;
+        }
+    }
+
+    data.fill(0xFF);
+    Some(bitflips)
+}
+
+/// Sector codec used by the decode helpers
+trait SectorCodec {
+    fn ecc_bytes_per_sector(&self) -> usize;
+    fn correct_sector(&self, data: &mut [u8], ecc: &[u8]) -> Result<u32, EccError>;
+}
+
+impl SectorCodec for HammingEcc {
+    fn ecc_bytes_per_sector(&self) -> usize {
+        if self.sector_size == 256 {
+            3
+        } else {
+            4
+        }
+    }
+
+    fn correct_sector(&self, data: &mut [u8], ecc: &[u8]) -> Result<u32, EccError> {
+        self.correct(data, ecc)
+    }
+}
+
+impl SectorCodec for BchEcc {
+    fn ecc_bytes_per_sector(&self) -> usize {
+        self.generator.len() / 8 + 1
+    }
+
+    fn correct_sector(&self, data: &mut [u8], ecc: &[u8]) -> Result<u32, EccError> {
+        self.correct(data, ecc)
+    }
+}
+
+impl SectorCodec for LdpcDecoder {
+    fn ecc_bytes_per_sector(&self) -> usize {
+        self.code.codeword_bits() / 8 - 512
+    }
+
+    fn correct_sector(&self, data: &mut [u8], ecc: &[u8]) -> Result<u32, EccError> {
+        let mut codeword = data.to_vec();
+        codeword.extend_from_slice(ecc);
+        let changed = self.correct(&mut codeword)?;
+        data.copy_from_slice(&codeword[..data.len()]);
+        Ok(changed)
+    }
+}
+
+fn sector_codec(algorithm: &EccAlgorithm) -> Option<Box<dyn SectorCodec>> {
+    algorithm.validate().ok()?;
+    match algorithm {
+        EccAlgorithm::None => None,
+        EccAlgorithm::Hamming => Some(Box::new(HammingEcc::new(512))),
+        EccAlgorithm::Bch { t } => Some(Box::new(BchEcc::new(512, *t))),
+        EccAlgorithm::Ldpc { code } => Some(Box::new(LdpcDecoder::new(code.clone()).ok()?)),
+    }
+}
+
+/// Decode and correct data sector by sector, reporting each sector's outcome
+///
+/// A sector that decodes cleanly is kept as is. A sector that needs
+/// correction or fails to decode is checked with [`check_erased_ecc_chunk`]
+/// first, so erased sectors with up to `erased_threshold` bitflips are
+/// reported as erased instead of being "corrected" into garbage.
+pub fn decode_with_ecc_sectors(
+    data: &mut [u8],
+    ecc_data: &[u8],
+    algorithm: &EccAlgorithm,
+    erased_threshold: u32,
+) -> Vec<SectorEccResult> {
+    let Some(codec) = sector_codec(algorithm) else {
+        return Vec::new();
+    };
+    let ecc_per_sector = codec.ecc_bytes_per_sector();
+    let mut results = Vec::new();
+
+    for (i, chunk) in data.chunks_mut(512).enumerate() {
+        let ecc_start = i * ecc_per_sector;
+        let ecc_end = ecc_start + ecc_per_sector;
+        if chunk.len() != 512 || ecc_end > ecc_data.len() {
+            continue;
+        }
+        let ecc = &ecc_data[ecc_start..ecc_end];
+
+        let original = chunk.to_vec();
+        let outcome = codec.correct_sector(chunk, ecc);
+        let result = match outcome {
+            Ok(0) => SectorEccResult::default(),
+            _ => {
+                let mut erased = original;
+                match (
+                    check_erased_ecc_chunk(&mut erased, ecc, erased_threshold),
+                    outcome,
+                ) {
+                    (Some(bitflips), _) => {
+                        chunk.copy_from_slice(&erased);
+                        SectorEccResult {
+                            corrected_bits: bitflips,
+                            uncorrectable: false,
+                            erased: true,
+                        }
+                    }
+                    (None, Ok(corrected)) => SectorEccResult {
+                        corrected_bits: corrected,
+                        ..Default::default()
+                    },
+                    (None, Err(_)) => SectorEccResult {
+                        uncorrectable: true,
+                        ..Default::default()
+                    },
+                }
+            }
+        };
+        results.push(result);
     }
+
+    results
 }
 
 /// Decode and correct data using ECC
+///
+/// Erased sectors with no more bitflips than the algorithm's strength are
+/// restored to 0xFF; their bitflips are included in the returned count.
 pub fn decode_with_ecc(
     data: &mut [u8],
     ecc_data: &[u8],
     algorithm: &EccAlgorithm,
 ) -> Result<u32, EccError> {
-    match algorithm {
-        EccAlgorithm::None => Ok(0),
-        EccAlgorithm::Hamming => {
-            let ecc = HammingEcc::new(512);
-            let ecc_per_sector = 4; // 512-byte sector
-            let mut total_corrected = 0u32;
+    algorithm.validate()?;
+    let sectors = decode_with_ecc_sectors(data, ecc_data, algorithm, algorithm.strength());
 
-            for (i, chunk) in data.chunks_mut(512).enumerate() {
-                if chunk.len() == 512 {
-                    let ecc_start = i * ecc_per_sector;
-                    let ecc_end = ecc_start + ecc_per_sector;
-                    if ecc_end <= ecc_data.len() {
-                        total_corrected += ecc.correct(chunk, &ecc_data[ecc_start..ecc_end])?;
-                    }
-                }
-            }
-            Ok(total_corrected)
+    if sectors.iter().any(|s| s.uncorrectable) {
+        return Err(EccError::UncorrectableError);
+    }
+    Ok(sectors.iter().map(|s| s.corrected_bits).sum())
+}
+
+// ============================================================================
+// ECC Statistics Report
+// ============================================================================
+
+/// ECC outcome for one sector of a dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorEccRecord {
+    pub page: u32,
+    pub sector: u32,
+    pub corrected_bits: u32,
+    pub uncorrectable: bool,
+    pub erased: bool,
+}
+
+/// ECC roll-up for one erase block
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockEccSummary {
+    pub block: u32,
+    pub sectors: u32,
+    pub corrected_bits: u32,
+    pub max_corrected_bits: u32,
+    pub uncorrectable_sectors: u32,
+    pub erased_sectors: u32,
+    /// Sector count indexed by corrected bits (uncorrectable sectors excluded)
+    pub histogram: Vec<u32>,
+}
+
+/// Per-sector ECC statistics for a whole dump
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EccReport {
+    pub pages_per_block: u32,
+    pub sectors: Vec<SectorEccRecord>,
+}
+
+impl EccReport {
+    pub fn new(pages_per_block: u32) -> Self {
+        Self {
+            pages_per_block: pages_per_block.max(1),
+            sectors: Vec::new(),
         }
-        EccAlgorithm::Bch { t } => {
-            let ecc = BchEcc::new(512, *t);
-            let ecc_per_sector = ecc.generator.len() / 8 + 1;
-            let mut total_corrected = 0u32;
+    }
 
-            for (i, chunk) in data.chunks_mut(512).enumerate() {
-                if chunk.len() == 512 {
-                    let ecc_start = i * ecc_per_sector;
-                    let ecc_end = ecc_start + ecc_per_sector;
-                    if ecc_end <= ecc_data.len() {
-                        total_corrected += ecc.correct(chunk, &ecc_data[ecc_start..ecc_end])?;
-                    }
-                }
+    /// Record the sector results of one page
+    pub fn add_page(&mut self, page: u32, results: &[SectorEccResult]) {
+        for (sector, result) in results.iter().enumerate() {
+            self.sectors.push(SectorEccRecord {
+                page,
+                sector: sector as u32,
+                corrected_bits: result.corrected_bits,
+                uncorrectable: result.uncorrectable,
+                erased: result.erased,
+            });
+        }
+    }
+
+    pub fn total_corrected_bits(&self) -> u64 {
+        self.sectors.iter().map(|s| s.corrected_bits as u64).sum()
+    }
+
+    pub fn uncorrectable_sectors(&self) -> usize {
+        self.sectors.iter().filter(|s| s.uncorrectable).count()
+    }
+
+    pub fn erased_sectors(&self) -> usize {
+        self.sectors.iter().filter(|s| s.erased).count()
+    }
+
+    /// Whole-chip histogram: sector count indexed by corrected bits
+    pub fn histogram(&self) -> Vec<u32> {
+        let mut histogram = Vec::new();
+        for record in self.sectors.iter().filter(|s| !s.uncorrectable) {
+            add_to_histogram(&mut histogram, record.corrected_bits);
+        }
+        histogram
+    }
+
+    /// Roll sector records up into per-block summaries, ordered by block
+    pub fn block_summaries(&self) -> Vec<BlockEccSummary> {
+        let mut blocks: BTreeMap<u32, BlockEccSummary> = BTreeMap::new();
+
+        for record in &self.sectors {
+            let block = record.page / self.pages_per_block;
+            let summary = blocks.entry(block).or_insert_with(|| BlockEccSummary {
+                block,
+                ..Default::default()
+            });
+
+            summary.sectors += 1;
+            summary.corrected_bits += record.corrected_bits;
+            summary.max_corrected_bits = summary.max_corrected_bits.max(record.corrected_bits);
+            if record.uncorrectable {
+                summary.uncorrectable_sectors += 1;
+            } else {
+                add_to_histogram(&mut summary.histogram, record.corrected_bits);
+            }
+            if record.erased {
+                summary.erased_sectors += 1;
             }
-            Ok(total_corrected)
         }
+
+        blocks.into_values().collect()
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Export one CSV row per sector
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("block,page,sector,corrected_bits,uncorrectable,erased\n");
+        for s in &self.sectors {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                s.page / self.pages_per_block,
+                s.page,
+                s.sector,
+                s.corrected_bits,
+                s.uncorrectable,
+                s.erased
+            ));
+        }
+        csv
     }
 }
 
+fn add_to_histogram(histogram: &mut Vec<u32>, corrected_bits: u32) {
+    let bits = corrected_bits as usize;
+    if histogram.len() <= bits {
+        histogram.resize(bits + 1, 0);
+    }
+    histogram[bits] += 1;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -655,4 +1272,201 @@ mod tests {
         assert_eq!(encoded, data);
         assert!(!ecc.is_empty());
     }
+
+    #[test]
+    fn test_check_erased_chunk_with_bitflips() {
+        let mut data = vec![0xFFu8; 512];
+        data[10] = 0xFE;
+        data[300] = 0x7F;
+        let ecc = vec![0xFFu8, 0xEF];
+
+        assert_eq!(check_erased_ecc_chunk(&mut data, &ecc, 4), Some(3));
+        assert!(data.iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn test_check_erased_chunk_over_threshold() {
+        let mut data = vec![0xFFu8; 512];
+        data[0] = 0x00;
+        let ecc = vec![0xFFu8; 2];
+
+        assert_eq!(check_erased_ecc_chunk(&mut data, &ecc, 4), None);
+        assert_eq!(data[0], 0x00);
+    }
+
+    #[test]
+    fn test_decode_erased_page_with_bitflips() {
+        let mut data = vec![0xFFu8; 1024];
+        data[5] = 0xFB;
+        data[700] = 0xDF;
+        let ecc_data = vec![0xFFu8; 8];
+
+        let sectors = decode_with_ecc_sectors(&mut data, &ecc_data, &EccAlgorithm::Hamming, 1);
+        assert_eq!(sectors.len(), 2);
+        assert!(sectors.iter().all(|s| s.erased && !s.uncorrectable));
+        assert_eq!(sectors[0].corrected_bits, 1);
+        assert_eq!(sectors[1].corrected_bits, 1);
+        assert!(data.iter().all(|&b| b == 0xFF));
+
+        let mut data = vec![0xFFu8; 512];
+        data[42] = 0xF7;
+        let ecc_data = vec![0xFFu8; 16];
+        let result = decode_with_ecc(&mut data, &ecc_data, &EccAlgorithm::Bch { t: 4 });
+        assert_eq!(result.unwrap(), 1);
+        assert!(data.iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn test_ecc_report_rollup() {
+        let clean = SectorEccResult::default();
+        let flipped = SectorEccResult {
+            corrected_bits: 2,
+            ..Default::default()
+        };
+        let bad = SectorEccResult {
+            uncorrectable: true,
+            ..Default::default()
+        };
+        let erased = SectorEccResult {
+            corrected_bits: 1,
+            erased: true,
+            ..Default::default()
+        };
+
+        let mut report = EccReport::new(2);
+        report.add_page(0, &[clean, flipped]);
+        report.add_page(1, &[erased, clean]);
+        report.add_page(2, &[bad, flipped]);
+
+        assert_eq!(report.sectors.len(), 6);
+        assert_eq!(report.total_corrected_bits(), 5);
+        assert_eq!(report.uncorrectable_sectors(), 1);
+        assert_eq!(report.erased_sectors(), 1);
+        assert_eq!(report.histogram(), vec![2, 1, 2]);
+
+        let blocks = report.block_summaries();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].sectors, 4);
+        assert_eq!(blocks[0].max_corrected_bits, 2);
+        assert_eq!(blocks[0].erased_sectors, 1);
+        assert_eq!(blocks[1].uncorrectable_sectors, 1);
+        assert_eq!(blocks[1].histogram, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn test_ecc_report_export() {
+        let mut report = EccReport::new(64);
+        report.add_page(
+            65,
+            &[SectorEccResult {
+                corrected_bits: 3,
+                ..Default::default()
+            }],
+        );
+
+        let csv = report.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next(),
+            Some("block,page,sector,corrected_bits,uncorrectable,erased")
+        );
+        assert_eq!(lines.next(), Some("1,65,0,3,false,false"));
+
+        let json = report.to_json().unwrap();
+        let parsed: EccReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    /// Array LDPC code: Z = 31, shifts (i * j) mod Z, 3×6 base matrix
+    fn test_ldpc_code() -> LdpcCode {
+        let base = (0..3)
+            .map(|i| (0..6).map(|j| (i * j) % 31).collect())
+            .collect();
+        LdpcCode::new(31, base).unwrap()
+    }
+
+    #[test]
+    fn test_ldpc_code_loading() {
+        let code = LdpcCode::from_text(4, "# 2x3 base\n0 1 -1\n2 -1 3\n").unwrap();
+        assert_eq!(code.codeword_bits(), 12);
+        assert_eq!(code.check_count(), 8);
+
+        let json = r#"{"circulant_size": 4, "base_matrix": [[0, 1, -1], [2, -1, 3]]}"#;
+        assert_eq!(LdpcCode::from_json(json).unwrap(), code);
+
+        assert!(LdpcCode::from_text(4, "0 4\n").is_err());
+        assert!(LdpcCode::from_text(4, "0 1\n2\n").is_err());
+    }
+
+    #[test]
+    fn test_ldpc_hard_decision() {
+        let decoder = LdpcDecoder::new(test_ldpc_code()).unwrap();
+        let n_bytes = (decoder.code().codeword_bits() + 7) / 8;
+
+        // All rows have even weight, so all-ones is a codeword
+        let mut clean = vec![0xFFu8; n_bytes];
+        assert_eq!(decoder.correct(&mut clean).unwrap(), 0);
+
+        let mut corrupted = vec![0u8; n_bytes];
+        corrupted[3] ^= 0x10;
+        corrupted[17] ^= 0x02;
+        assert_eq!(decoder.correct(&mut corrupted).unwrap(), 2);
+        assert!(corrupted.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_ldpc_soft_decision() {
+        let decoder = LdpcDecoder::new(test_ldpc_code()).unwrap();
+        let n_bits = decoder.code().codeword_bits();
+        let n_bytes = (n_bits + 7) / 8;
+
+        // Three reads of an all-zero codeword; weak cells flip in some reads
+        let mut reads = vec![vec![0u8; n_bytes]; 3];
+        reads[0][1] = 0x81;
+        reads[1][1] = 0x80;
+        reads[1][9] = 0x04;
+        reads[2][9] = 0x04;
+        reads[2][20] = 0x40;
+
+        let llrs = llrs_from_reads(&reads, n_bits, None).unwrap();
+        assert_eq!(llrs[0], 4.0);
+        assert!(llrs[8] < 0.0 && llrs[8] > -4.0);
+
+        let mut codeword = reads[1].clone();
+        let changed = decoder.correct_soft(&reads, None, &mut codeword).unwrap();
+        assert_eq!(changed, 2);
+        assert!(codeword.iter().all(|&b| b == 0));
+
+        assert!(llrs_from_reads(&reads, n_bits, Some(&[1.0, -1.0])).is_err());
+    }
+
+    #[test]
+    fn test_ldpc_through_decode_with_ecc() {
+        // Z = 73, 64 block columns: 584-byte codeword = 512 data + 72 parity
+        let base = (0..3)
+            .map(|i| (0..64).map(|j| (i * j) % 73).collect())
+            .collect();
+        let algorithm = EccAlgorithm::Ldpc {
+            code: LdpcCode::new(73, base).unwrap(),
+        };
+
+        let mut data = vec![0u8; 512];
+        data[10] ^= 0x04;
+        data[300] ^= 0x80;
+        let corrected = decode_with_ecc(&mut data, &[0u8; 72], &algorithm).unwrap();
+        assert_eq!(corrected, 2);
+        assert!(data.iter().all(|&b| b == 0));
+
+        // A malformed code is rejected instead of panicking
+        let invalid = LdpcCode {
+            circulant_size: 0,
+            base_matrix: Vec::new(),
+        };
+        assert!(LdpcDecoder::new(invalid.clone()).is_err());
+        let algorithm = EccAlgorithm::Ldpc { code: invalid };
+        assert!(matches!(
+            decode_with_ecc(&mut data, &[], &algorithm),
+            Err(EccError::InvalidInput)
+        ));
+    }
 }
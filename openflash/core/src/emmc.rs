@@ -1,6 +1,8 @@
 //! eMMC Flash chip database and protocol
-//! Contains known eMMC chip parameters and command definitions
+//! Known chips are looked up in the chip database (see `chipdb`); this module
+//! holds protocol definitions and generic detection
 
+use crate::chipdb::chip_database;
 use serde::{Deserialize, Serialize};
 
 /// eMMC chip information
@@ -167,7 +169,7 @@ pub fn get_emmc_manufacturer_name(mid: u8) -> &'static str {
     }
 }
 
-/// Database of known eMMC chips
+/// Look up an eMMC chip in the chip database
 pub fn get_emmc_chip_info(cid: &[u8]) -> Option<EmmcChipInfo> {
     if cid.len() < 16 {
         return None;
@@ -183,408 +185,26 @@ pub fn get_emmc_chip_info(cid: &[u8]) -> Option<EmmcChipInfo> {
         .map(|&b| b as char)
         .collect();
 
-    // Try to match known chips
-    match (mid, pnm.as_str()) {
-        // Samsung
-        (0x15, "BJTD4R") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "KLMBG4JETD-B041".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x15, "AJTD4R") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "KLMAG1JETD-B041".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // Micron
-        (0x13, "Q2J54A") | (0xFE, "Q2J54A") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "MTFC4GACAJCN".into(),
-            size_gb: 4,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: false,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x13, "Q3J55A") | (0xFE, "Q3J55A") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "MTFC8GACAAAM".into(),
-            size_gb: 8,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // SanDisk
-        (0x02, "DA4032") | (0x45, "DA4032") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "SDINBDG4-32G".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // Toshiba
-        (0x11, "064G30") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "THGBMJG6C1LBAIL".into(),
-            size_gb: 8,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // Kingston
-        (0x70, "EMMC04G") => Some(EmmcChipInfo {
-            manufacturer,
-            model: "EMMC04G-M627".into(),
-            size_gb: 4,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: false,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: false,
-        }),
-        (0x70, "EMMC08G") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "EMMC08G-M627".into(),
-            size_gb: 8,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: false,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: false,
-        }),
-        (0x70, "EMMC16G") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "EMMC16G-M627".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x70, "EMMC32G") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "EMMC32G-M627".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // ============ Samsung eMMC 5.1 (v2.2) ============
-        (0x15, "CJNB4R") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "KLMCG2JETD-B041".into(),
-            size_gb: 64,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x15, "DJNB4R") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "KLMDG4UCTA-B041".into(),
-            size_gb: 128,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x15, "8GTF4R") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "KLMAG2GEND-B031".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // ============ Micron eMMC 5.1 (v2.2) ============
-        (0x13, "Q4J55A") | (0xFE, "Q4J55A") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "MTFC16GACAANA".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x13, "Q5J56A") | (0xFE, "Q5J56A") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "MTFC32GACAANA".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x13, "Q6J57A") | (0xFE, "Q6J57A") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "MTFC64GACAANA".into(),
-            size_gb: 64,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x13, "Q7J58A") | (0xFE, "Q7J58A") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "MTFC128GACAANA".into(),
-            size_gb: 128,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // ============ SK Hynix eMMC (v2.2) ============
-        (0x90, "hB8aP>") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "H26M41208HPR".into(),
-            size_gb: 8,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x90, "hC8aP>") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "H26M52208FPR".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x90, "hD8aP>") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "H26M64208EMR".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x90, "hE8aP>") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "H26M78208CMR".into(),
-            size_gb: 64,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // ============ SanDisk/WD eMMC (v2.2) ============
-        (0x02, "DA4064") | (0x45, "DA4064") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "SDINBDG4-64G".into(),
-            size_gb: 64,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x02, "DG4016") | (0x45, "DG4016") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "SDINBDG4-16G".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // ============ Foresee eMMC (v2.2) ============
-        (0x88, "NCEMAM") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "NCEMAM8G-08".into(),
-            size_gb: 8,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: true,
-            hs200_support: false,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: false,
-        }),
-        (0x88, "NCEMBM") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "NCEMBM8G-16".into(),
-            size_gb: 16,
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-        (0x88, "NCEMCM") => Some(EmmcChipInfo {
-            manufacturer: manufacturer.clone(),
-            model: "NCEMCM8G-32".into(),
-            size_gb: 32,
-            sector_size: 512,
-            erase_group_size: 1024,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 200,
-            ddr_support: true,
-            hs200_support: true,
-            hs400_support: true,
-            boot_partition: true,
-            rpmb_support: true,
-        }),
-
-        // Generic fallback
-        _ => Some(EmmcChipInfo {
-            manufacturer,
-            model: format!("Generic eMMC ({})", pnm),
-            size_gb: 0, // Will be determined from EXT_CSD
-            sector_size: 512,
-            erase_group_size: 512,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 52,
-            ddr_support: false,
-            hs200_support: false,
-            hs400_support: false,
-            boot_partition: true,
-            rpmb_support: false,
-        }),
+    // Try the chip database first
+    if let Some(info) = chip_database().lookup_emmc(mid, &pnm) {
+        return Some(info);
     }
+
+    // Generic fallback
+    Some(EmmcChipInfo {
+        manufacturer,
+        model: format!("Generic eMMC ({})", pnm),
+        size_gb: 0, // Will be determined from EXT_CSD
+        sector_size: 512,
+        erase_group_size: 512,
+        voltage: "3.3V".into(),
+        max_clock_mhz: 52,
+        ddr_support: false,
+        hs200_support: false,
+        hs400_support: false,
+        boot_partition: true,
+        rpmb_support: false,
+    })
 }
 
 /// Parse capacity from Extended CSD
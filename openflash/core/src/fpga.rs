@@ -0,0 +1,800 @@
+//! FPGA programmer backend (Lattice iCE40 / ECP5)
+//!
+//! NV-DDR2/3 and Toggle DDR buses run at hundreds of MT/s with data strobed
+//! by DQS, far beyond what MCU firmware can bit-bang. The FPGA gateware
+//! captures DDR data with a programmable DQS delay and exposes a small
+//! register file through the `FpgaReadReg` / `FpgaWriteReg` packets; the
+//! regular NAND packets drive the bus in whichever interface is selected.
+//!
+//! Scope: this module is the host driver. The gateware under
+//! `firmware/fpga` is limited to the register file (`openflash_regs.v`) and
+//! the DQS capture block (`nand_ddr_rx.v`); the top level, the NAND command
+//! engine, the USB/UART bridge and MCU forwarding of `FpgaReadReg` /
+//! `FpgaWriteReg` are not implemented, so the backend is only exercised
+//! against [`GatewareModel`], which simulates all of them.
+
+use crate::jedec::JedecParameterPage;
+use crate::onfi::{
+    set_timing_mode_packets, timing_mode_candidates, Onfi5Features, OnfiParameterPage,
+    ONFI_FEATURE_TIMING_MODE, SET_FEATURES_CMD,
+};
+use crate::protocol::{Command, Packet};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ============================================================================
+// Error Types
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FpgaError {
+    /// Packet transport failed
+    Transport(String),
+    /// ID register didn't read back the gateware ID
+    NotDetected(u32),
+    /// Interface not implemented by the gateware
+    Unsupported(DataInterface),
+    /// Response shorter than expected
+    BadResponse(usize),
+    /// No DQS delay tap read the reference data back
+    DqsTrainingFailed,
+    /// Reference data longer than one read packet can return
+    ReferenceTooLong(usize),
+}
+
+impl std::fmt::Display for FpgaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FpgaError::Transport(e) => write!(f, "Transport error: {}", e),
+            FpgaError::NotDetected(id) => write!(f, "FPGA gateware not detected (ID {:#010x})", id),
+            FpgaError::Unsupported(iface) => write!(f, "Interface {:?} not supported", iface),
+            FpgaError::BadResponse(len) => write!(f, "Bad response ({} bytes)", len),
+            FpgaError::DqsTrainingFailed => write!(f, "DQS training failed"),
+            FpgaError::ReferenceTooLong(len) => {
+                write!(f, "Training reference too long ({} bytes)", len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FpgaError {}
+
+pub type FpgaResult<T> = Result<T, FpgaError>;
+
+// ============================================================================
+// Register Map
+// ============================================================================
+
+/// Value of `FPGA_REG_ID` ("OFPG")
+pub const FPGA_GATEWARE_ID: u32 = 0x4F46_5047;
+
+/// Gateware ID, read-only
+pub const FPGA_REG_ID: u8 = 0x00;
+/// Bits 31:24 family (0 = iCE40, 1 = ECP5), bits 15:0 gateware version
+pub const FPGA_REG_VERSION: u8 = 0x01;
+/// Bits 3:0 NV-DDR/NV-DDR2/NV-DDR3/Toggle support, 15:8 DQS delay taps,
+/// 31:16 maximum data rate (MT/s)
+pub const FPGA_REG_CAPS: u8 = 0x02;
+/// Bits 7:0 `DataInterface`, bits 15:8 timing mode
+pub const FPGA_REG_INTERFACE: u8 = 0x03;
+/// Bus data rate (MT/s)
+pub const FPGA_REG_DATA_RATE: u8 = 0x04;
+/// DQS input delay tap
+pub const FPGA_REG_DQS_DELAY: u8 = 0x05;
+
+pub fn read_reg_packet(reg: u8) -> Packet {
+    Packet::new(Command::FpgaReadReg, &[reg])
+}
+
+pub fn write_reg_packet(reg: u8, value: u32) -> Packet {
+    let mut args = vec![reg];
+    args.extend_from_slice(&value.to_le_bytes());
+    Packet::new(Command::FpgaWriteReg, &args)
+}
+
+// ============================================================================
+// Data Interfaces
+// ============================================================================
+
+/// NAND data interface driven by the gateware
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DataInterface {
+    Sdr = 0,
+    NvDdr = 1,
+    NvDdr2 = 2,
+    NvDdr3 = 3,
+    ToggleDdr = 4,
+}
+
+impl DataInterface {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Sdr),
+            1 => Some(Self::NvDdr),
+            2 => Some(Self::NvDdr2),
+            3 => Some(Self::NvDdr3),
+            4 => Some(Self::ToggleDdr),
+            _ => None,
+        }
+    }
+
+    /// Data strobed on both DQS edges
+    pub fn is_ddr(&self) -> bool {
+        *self != Self::Sdr
+    }
+
+    /// Data rate (MT/s) of an ONFI timing mode
+    pub fn data_rate(&self, mode: u8) -> u16 {
+        let rates: &[u16] = match self {
+            Self::Sdr => &[10, 20, 28, 33, 40, 50],
+            Self::NvDdr => &[40, 66, 100, 133, 166, 200],
+            Self::NvDdr2 | Self::NvDdr3 | Self::ToggleDdr => &NV_DDR2_RATES,
+        };
+        rates[(mode as usize).min(rates.len() - 1)]
+    }
+}
+
+/// NV-DDR2/3 data rate (MT/s) per timing mode
+const NV_DDR2_RATES: [u16; 15] = [
+    66, 100, 133, 166, 200, 266, 333, 400, 533, 667, 800, 1066, 1200, 1333, 1600,
+];
+
+/// FPGA family the gateware runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FpgaFamily {
+    Ice40,
+    Ecp5,
+}
+
+/// What the loaded gateware can drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FpgaCapabilities {
+    pub family: FpgaFamily,
+    pub version: u16,
+    pub nv_ddr: bool,
+    pub nv_ddr2: bool,
+    pub nv_ddr3: bool,
+    pub toggle: bool,
+    pub dqs_taps: u8,
+    pub max_data_rate_mt_s: u16,
+}
+
+impl FpgaCapabilities {
+    pub fn from_regs(version: u32, caps: u32) -> Self {
+        Self {
+            family: if version >> 24 == 1 {
+                FpgaFamily::Ecp5
+            } else {
+                FpgaFamily::Ice40
+            },
+            version: version as u16,
+            nv_ddr: caps & (1 << 0) != 0,
+            nv_ddr2: caps & (1 << 1) != 0,
+            nv_ddr3: caps & (1 << 2) != 0,
+            toggle: caps & (1 << 3) != 0,
+            dqs_taps: (caps >> 8) as u8,
+            max_data_rate_mt_s: (caps >> 16) as u16,
+        }
+    }
+
+    pub fn supports(&self, iface: DataInterface) -> bool {
+        match iface {
+            DataInterface::Sdr => true,
+            DataInterface::NvDdr => self.nv_ddr,
+            DataInterface::NvDdr2 => self.nv_ddr2,
+            DataInterface::NvDdr3 => self.nv_ddr3,
+            DataInterface::ToggleDdr => self.toggle,
+        }
+    }
+}
+
+/// Selected interface, timing mode and data rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DataInterfaceConfig {
+    pub interface: DataInterface,
+    pub timing_mode: u8,
+    pub data_rate_mt_s: u16,
+}
+
+impl DataInterfaceConfig {
+    pub fn new(interface: DataInterface, timing_mode: u8) -> Self {
+        Self {
+            interface,
+            timing_mode,
+            data_rate_mt_s: interface.data_rate(timing_mode),
+        }
+    }
+
+    /// ONFI timing mode feature P1: interface in bits 5:4, mode in bits 3:0
+    pub fn feature_p1(&self) -> u8 {
+        ((self.interface as u8 & 0x03) << 4) | (self.timing_mode & 0x0F)
+    }
+
+    /// SET FEATURES switching the chip, then the gateware registers
+    ///
+    /// Toggle parts run DDR from power-up, so only the gateware is switched.
+    pub fn to_packets(&self) -> Vec<Packet> {
+        let mut packets = if self.interface == DataInterface::ToggleDdr {
+            Vec::new()
+        } else {
+            set_timing_mode_packets(self.feature_p1())
+        };
+        packets.push(write_reg_packet(
+            FPGA_REG_DATA_RATE,
+            self.data_rate_mt_s as u32,
+        ));
+        packets.push(write_reg_packet(
+            FPGA_REG_INTERFACE,
+            self.interface as u32 | (self.timing_mode as u32) << 8,
+        ));
+        packets
+    }
+}
+
+/// Fastest `modes` bit of `iface` within `limit` and the gateware's data rate
+fn fastest_mode(
+    caps: &FpgaCapabilities,
+    iface: DataInterface,
+    modes: u16,
+    limit: u16,
+) -> Option<DataInterfaceConfig> {
+    (0..16u8)
+        .rev()
+        .filter(|&mode| modes & (1 << mode) != 0)
+        .find(|&mode| iface.data_rate(mode) <= limit.min(caps.max_data_rate_mt_s))
+        .map(|mode| DataInterfaceConfig::new(iface, mode))
+}
+
+/// Fastest interface both the chip and the gateware support
+///
+/// NV-DDR3 comes from the ONFI 5.0 features (capped at the advertised data
+/// rate), NV-DDR2 and NV-DDR from the parameter page. Chips without a DDR
+/// interface stay in SDR at their fastest timing mode. Toggle DDR parts
+/// have no ONFI page; see [`select_jedec_data_interface`].
+pub fn select_data_interface(
+    caps: &FpgaCapabilities,
+    page: &OnfiParameterPage,
+    ext: Option<&Onfi5Features>,
+) -> DataInterfaceConfig {
+    let nv_ddr3 = ext
+        .filter(|f| f.nv_ddr3_support && caps.nv_ddr3)
+        .and_then(|f| {
+            let limit = f.nv_ddr3_timing.as_ref().map_or(800, |t| t.data_rate_mt_s);
+            fastest_mode(caps, DataInterface::NvDdr3, u16::MAX, limit)
+        });
+    let nv_ddr2 = (page.features.nv_ddr2 && caps.nv_ddr2)
+        .then(|| {
+            fastest_mode(
+                caps,
+                DataInterface::NvDdr2,
+                page.nv_ddr2_timing_modes as u16,
+                u16::MAX,
+            )
+        })
+        .flatten();
+    let nv_ddr = (page.features.nv_ddr && caps.nv_ddr)
+        .then(|| {
+            fastest_mode(
+                caps,
+                DataInterface::NvDdr,
+                page.nv_ddr_timing_modes as u16,
+                u16::MAX,
+            )
+        })
+        .flatten();
+
+    nv_ddr3.or(nv_ddr2).or(nv_ddr).unwrap_or_else(|| {
+        let mode = timing_mode_candidates(page.sdr_timing_modes, 5)[0];
+        DataInterfaceConfig::new(DataInterface::Sdr, mode)
+    })
+}
+
+/// Fastest interface for a chip that answered with a JEDEC parameter page
+///
+/// Toggle DDR is picked from `toggle_ddr_timing_modes` when the chip and the
+/// gateware both support it; otherwise the chip stays in SDR.
+pub fn select_jedec_data_interface(
+    caps: &FpgaCapabilities,
+    page: &JedecParameterPage,
+) -> DataInterfaceConfig {
+    (page.features.toggle_ddr && caps.toggle)
+        .then(|| {
+            fastest_mode(
+                caps,
+                DataInterface::ToggleDdr,
+                page.toggle_ddr_timing_modes,
+                u16::MAX,
+            )
+        })
+        .flatten()
+        .unwrap_or_else(|| {
+            let mode = timing_mode_candidates(page.async_sdr_timing_modes, 5)[0];
+            DataInterfaceConfig::new(DataInterface::Sdr, mode)
+        })
+}
+
+// ============================================================================
+// Host Driver
+// ============================================================================
+
+/// Host side of the FPGA backend
+///
+/// `transact` sends one packet and returns the response bytes (empty for
+/// packets without a response).
+#[derive(Debug, Clone)]
+pub struct FpgaBackend {
+    caps: FpgaCapabilities,
+    config: DataInterfaceConfig,
+    dqs_delay: u8,
+}
+
+impl FpgaBackend {
+    /// Check the gateware ID and read its capabilities
+    pub fn probe<T>(transact: &mut T) -> FpgaResult<Self>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        let id = read_reg(transact, FPGA_REG_ID)?;
+        if id != FPGA_GATEWARE_ID {
+            return Err(FpgaError::NotDetected(id));
+        }
+        let version = read_reg(transact, FPGA_REG_VERSION)?;
+        let caps = read_reg(transact, FPGA_REG_CAPS)?;
+        Ok(Self {
+            caps: FpgaCapabilities::from_regs(version, caps),
+            config: DataInterfaceConfig::new(DataInterface::Sdr, 0),
+            dqs_delay: 0,
+        })
+    }
+
+    pub fn capabilities(&self) -> &FpgaCapabilities {
+        &self.caps
+    }
+
+    pub fn config(&self) -> &DataInterfaceConfig {
+        &self.config
+    }
+
+    pub fn dqs_delay(&self) -> u8 {
+        self.dqs_delay
+    }
+
+    /// Switch the chip and the gateware to `config`
+    pub fn apply<T>(&mut self, transact: &mut T, config: DataInterfaceConfig) -> FpgaResult<()>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        if !self.caps.supports(config.interface) {
+            return Err(FpgaError::Unsupported(config.interface));
+        }
+        for packet in config.to_packets() {
+            transact(&packet)?;
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    /// Select the fastest common interface and switch to it
+    pub fn configure<T>(
+        &mut self,
+        transact: &mut T,
+        page: &OnfiParameterPage,
+        ext: Option<&Onfi5Features>,
+    ) -> FpgaResult<DataInterfaceConfig>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        let config = select_data_interface(&self.caps, page, ext);
+        self.apply(transact, config)?;
+        Ok(config)
+    }
+
+    /// Same as [`configure`](Self::configure) for chips with a JEDEC page
+    pub fn configure_jedec<T>(
+        &mut self,
+        transact: &mut T,
+        page: &JedecParameterPage,
+    ) -> FpgaResult<DataInterfaceConfig>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        let config = select_jedec_data_interface(&self.caps, page);
+        self.apply(transact, config)?;
+        Ok(config)
+    }
+
+    pub fn read_page<T>(&self, transact: &mut T, row: u32, len: u16) -> FpgaResult<Vec<u8>>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        let mut args = row.to_le_bytes().to_vec();
+        args.extend_from_slice(&len.to_le_bytes());
+        let data = transact(&Packet::new(Command::NandReadPage, &args))?;
+        if data.len() < len as usize {
+            return Err(FpgaError::BadResponse(data.len()));
+        }
+        Ok(data)
+    }
+
+    /// Center the DQS delay in the window that reads `expected` back
+    ///
+    /// `expected` is the page at `row` as read in SDR mode. Every tap is
+    /// tried and the middle of the longest passing run is kept.
+    pub fn train_dqs<T>(&mut self, transact: &mut T, row: u32, expected: &[u8]) -> FpgaResult<u8>
+    where
+        T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+    {
+        let len = u16::try_from(expected.len())
+            .map_err(|_| FpgaError::ReferenceTooLong(expected.len()))?;
+        let mut best: Option<(u8, u8)> = None;
+        let mut run_start = None;
+        for tap in 0..=self.caps.dqs_taps {
+            let pass = tap < self.caps.dqs_taps && {
+                transact(&write_reg_packet(FPGA_REG_DQS_DELAY, tap as u32))?;
+                self.read_page(transact, row, len)?[..expected.len()] == *expected
+            };
+            match (pass, run_start) {
+                (true, None) => run_start = Some(tap),
+                (false, Some(start)) => {
+                    if best.map_or(true, |(s, e)| tap - start > e - s) {
+                        best = Some((start, tap));
+                    }
+                    run_start = None;
+                }
+                _ => {}
+            }
+        }
+
+        let (start, end) = best.ok_or(FpgaError::DqsTrainingFailed)?;
+        let tap = start + (end - start - 1) / 2;
+        transact(&write_reg_packet(FPGA_REG_DQS_DELAY, tap as u32))?;
+        self.dqs_delay = tap;
+        Ok(tap)
+    }
+}
+
+fn read_reg<T>(transact: &mut T, reg: u8) -> FpgaResult<u32>
+where
+    T: FnMut(&Packet) -> FpgaResult<Vec<u8>>,
+{
+    let data = transact(&read_reg_packet(reg))?;
+    let bytes: [u8; 4] = data
+        .get(..4)
+        .and_then(|b| b.try_into().ok())
+        .ok_or(FpgaError::BadResponse(data.len()))?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+// ============================================================================
+// Simulated Gateware
+// ============================================================================
+
+/// Behavioral model of the gateware and an attached NAND chip
+///
+/// DDR reads return correct data only when the chip and the gateware agree
+/// on the interface and the DQS delay is inside `dqs_window`; otherwise the
+/// data looks like it was captured on the wrong strobe edge.
+#[derive(Debug, Clone)]
+pub struct GatewareModel {
+    pub caps: FpgaCapabilities,
+    /// DQS delay taps that capture correctly
+    pub dqs_window: std::ops::RangeInclusive<u8>,
+    pages: HashMap<u32, Vec<u8>>,
+    interface: u32,
+    data_rate: u32,
+    dqs_delay: u32,
+    /// Timing mode feature P1 last set on the chip
+    chip_p1: u8,
+    nand_cmd: u8,
+    nand_addr: u8,
+    feature_data: Vec<u8>,
+}
+
+impl GatewareModel {
+    pub fn new(caps: FpgaCapabilities) -> Self {
+        Self {
+            caps,
+            dqs_window: 0..=caps.dqs_taps.saturating_sub(1),
+            pages: HashMap::new(),
+            interface: 0,
+            data_rate: 0,
+            dqs_delay: 0,
+            chip_p1: 0,
+            nand_cmd: 0,
+            nand_addr: 0,
+            feature_data: Vec::new(),
+        }
+    }
+
+    pub fn with_page(mut self, row: u32, data: Vec<u8>) -> Self {
+        self.pages.insert(row, data);
+        self
+    }
+
+    /// Interface the gateware is driving
+    pub fn interface(&self) -> Option<DataInterface> {
+        DataInterface::from_u8(self.interface as u8)
+    }
+
+    /// Interface the chip was switched to with SET FEATURES
+    pub fn chip_interface(&self) -> DataInterface {
+        DataInterface::from_u8(self.chip_p1 >> 4).unwrap_or(DataInterface::Sdr)
+    }
+
+    pub fn transact(&mut self, packet: &Packet) -> FpgaResult<Vec<u8>> {
+        let args = &packet.args;
+        match packet.cmd {
+            Command::FpgaReadReg => {
+                let value = match args[0] {
+                    FPGA_REG_ID => FPGA_GATEWARE_ID,
+                    FPGA_REG_VERSION => self.version_reg(),
+                    FPGA_REG_CAPS => self.caps_reg(),
+                    FPGA_REG_INTERFACE => self.interface,
+                    FPGA_REG_DATA_RATE => self.data_rate,
+                    FPGA_REG_DQS_DELAY => self.dqs_delay,
+                    _ => 0,
+                };
+                Ok(value.to_le_bytes().to_vec())
+            }
+            Command::FpgaWriteReg => {
+                let value = u32::from_le_bytes([args[1], args[2], args[3], args[4]]);
+                match args[0] {
+                    FPGA_REG_INTERFACE => self.interface = value,
+                    FPGA_REG_DATA_RATE => {
+                        self.data_rate = value.min(self.caps.max_data_rate_mt_s as u32)
+                    }
+                    FPGA_REG_DQS_DELAY => self.dqs_delay = value,
+                    _ => {}
+                }
+                Ok(Vec::new())
+            }
+            Command::NandCmd => {
+                self.nand_cmd = args[0];
+                self.feature_data.clear();
+                Ok(Vec::new())
+            }
+            Command::NandAddr => {
+                self.nand_addr = args[0];
+                Ok(Vec::new())
+            }
+            Command::NandWriteData => {
+                self.feature_data.push(args[0]);
+                if self.nand_cmd == SET_FEATURES_CMD
+                    && self.nand_addr == ONFI_FEATURE_TIMING_MODE
+                    && self.feature_data.len() == 4
+                {
+                    self.chip_p1 = self.feature_data[0];
+                }
+                Ok(Vec::new())
+            }
+            Command::NandReadPage => {
+                let row = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+                let len = u16::from_le_bytes([args[4], args[5]]) as usize;
+                let mut data = self.pages.get(&row).cloned().unwrap_or_default();
+                data.resize(len, 0xFF);
+                Ok(self.capture(data))
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn version_reg(&self) -> u32 {
+        let family = match self.caps.family {
+            FpgaFamily::Ice40 => 0,
+            FpgaFamily::Ecp5 => 1,
+        };
+        (family << 24) | self.caps.version as u32
+    }
+
+    fn caps_reg(&self) -> u32 {
+        let caps = &self.caps;
+        (caps.nv_ddr as u32)
+            | (caps.nv_ddr2 as u32) << 1
+            | (caps.nv_ddr3 as u32) << 2
+            | (caps.toggle as u32) << 3
+            | (caps.dqs_taps as u32) << 8
+            | (caps.max_data_rate_mt_s as u32) << 16
+    }
+
+    fn capture(&self, mut data: Vec<u8>) -> Vec<u8> {
+        let Some(iface) = self.interface() else {
+            return data;
+        };
+        let chip = self.chip_interface();
+        let chip_matches = iface == DataInterface::ToggleDdr || iface == chip;
+        if !iface.is_ddr() && chip == DataInterface::Sdr {
+            return data;
+        }
+        let in_window =
+            u8::try_from(self.dqs_delay).is_ok_and(|tap| self.dqs_window.contains(&tap));
+        if !chip_matches || !in_window {
+            // Sampled on the opposite DQS edge
+            for pair in data.chunks_exact_mut(2) {
+                pair.swap(0, 1);
+            }
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jedec::tests::make_jedec_page;
+    use crate::onfi::{onfi_crc16, OnfiFeatures, OnfiNvDdr3Timing};
+
+    fn ecp5_caps() -> FpgaCapabilities {
+        FpgaCapabilities {
+            family: FpgaFamily::Ecp5,
+            version: 0x0100,
+            nv_ddr: true,
+            nv_ddr2: true,
+            nv_ddr3: true,
+            toggle: true,
+            dqs_taps: 128,
+            max_data_rate_mt_s: 800,
+        }
+    }
+
+    fn param_page(features: u16) -> OnfiParameterPage {
+        let mut raw = [0u8; 256];
+        raw[0..4].copy_from_slice(b"ONFI");
+        let crc = onfi_crc16(&raw[..254]);
+        raw[254..].copy_from_slice(&crc.to_le_bytes());
+        let mut page = OnfiParameterPage::parse(&raw).unwrap();
+        page.features = OnfiFeatures::from_bits(features);
+        page.sdr_timing_modes = 0x003F;
+        page.nv_ddr_timing_modes = 0x3F;
+        page.nv_ddr2_timing_modes = 0xFF;
+        page
+    }
+
+    fn onfi5(rate: u16) -> Onfi5Features {
+        Onfi5Features {
+            nv_ddr3_support: true,
+            zq_calibration: false,
+            dcc_training: false,
+            multi_plane_ops: false,
+            max_planes: 1,
+            extended_ecc_info: None,
+            nv_ddr3_timing: Some(OnfiNvDdr3Timing {
+                data_rate_mt_s: rate,
+                ..OnfiNvDdr3Timing::default()
+            }),
+        }
+    }
+
+    #[test]
+    fn test_select_data_interface() {
+        let caps = ecp5_caps();
+
+        // NV-DDR3 at 1600 MT/s is capped by the gateware's 800 MT/s
+        let page = param_page((1 << 5) | (1 << 10) | (1 << 13));
+        let config = select_data_interface(&caps, &page, Some(&onfi5(1600)));
+        assert_eq!(config.interface, DataInterface::NvDdr3);
+        assert_eq!(config.data_rate_mt_s, 800);
+        assert_eq!(config.feature_p1(), 0x30 | 10);
+
+        // Without ONFI 5.0 features NV-DDR2 mode 7 is the fastest
+        let config = select_data_interface(&caps, &page, None);
+        assert_eq!(config, DataInterfaceConfig::new(DataInterface::NvDdr2, 7));
+
+        let ice40 = FpgaCapabilities {
+            family: FpgaFamily::Ice40,
+            nv_ddr2: false,
+            nv_ddr3: false,
+            dqs_taps: 4,
+            max_data_rate_mt_s: 100,
+            ..caps
+        };
+        let config = select_data_interface(&ice40, &page, Some(&onfi5(800)));
+        assert_eq!(config, DataInterfaceConfig::new(DataInterface::NvDdr, 2));
+
+        let sdr_only = select_data_interface(&caps, &param_page(0), None);
+        assert_eq!(sdr_only, DataInterfaceConfig::new(DataInterface::Sdr, 5));
+    }
+
+    #[test]
+    fn test_select_jedec_data_interface() {
+        let caps = ecp5_caps();
+        let mut page = JedecParameterPage::parse(&make_jedec_page()).unwrap();
+        page.toggle_ddr_timing_modes = 0x00FF;
+
+        let config = select_jedec_data_interface(&caps, &page);
+        assert_eq!(
+            config,
+            DataInterfaceConfig::new(DataInterface::ToggleDdr, 7)
+        );
+        assert_eq!(config.data_rate_mt_s, 400);
+
+        let ice40 = FpgaCapabilities {
+            family: FpgaFamily::Ice40,
+            max_data_rate_mt_s: 100,
+            ..caps
+        };
+        let config = select_jedec_data_interface(&ice40, &page);
+        assert_eq!(
+            config,
+            DataInterfaceConfig::new(DataInterface::ToggleDdr, 1)
+        );
+
+        let no_toggle = FpgaCapabilities {
+            toggle: false,
+            ..caps
+        };
+        let config = select_jedec_data_interface(&no_toggle, &page);
+        assert_eq!(config, DataInterfaceConfig::new(DataInterface::Sdr, 5));
+    }
+
+    #[test]
+    fn test_probe_and_configure_model() {
+        let mut model = GatewareModel::new(ecp5_caps());
+        let mut transact = |p: &Packet| model.transact(p);
+
+        let mut backend = FpgaBackend::probe(&mut transact).unwrap();
+        assert_eq!(*backend.capabilities(), ecp5_caps());
+
+        let page = param_page((1 << 5) | (1 << 10));
+        let config = backend.configure(&mut transact, &page, None).unwrap();
+        assert_eq!(config.interface, DataInterface::NvDdr2);
+        assert_eq!(model.interface(), Some(DataInterface::NvDdr2));
+        assert_eq!(model.chip_interface(), DataInterface::NvDdr2);
+
+        let mut jedec = JedecParameterPage::parse(&make_jedec_page()).unwrap();
+        jedec.toggle_ddr_timing_modes = 0x001F;
+        let mut transact = |p: &Packet| model.transact(p);
+        let config = backend.configure_jedec(&mut transact, &jedec).unwrap();
+        assert_eq!(
+            config,
+            DataInterfaceConfig::new(DataInterface::ToggleDdr, 4)
+        );
+        assert_eq!(model.interface(), Some(DataInterface::ToggleDdr));
+    }
+
+    #[test]
+    fn test_dqs_training_and_ddr_read() {
+        let data: Vec<u8> = (0..64).collect();
+        let mut model = GatewareModel::new(ecp5_caps()).with_page(0x40, data.clone());
+        model.dqs_window = 10..=17;
+        let mut transact = |p: &Packet| model.transact(p);
+
+        let mut backend = FpgaBackend::probe(&mut transact).unwrap();
+        assert_eq!(backend.read_page(&mut transact, 0x40, 64).unwrap(), data);
+
+        let ddr = DataInterfaceConfig::new(DataInterface::NvDdr2, 5);
+        backend.apply(&mut transact, ddr).unwrap();
+        assert_ne!(backend.read_page(&mut transact, 0x40, 64).unwrap(), data);
+
+        assert_eq!(backend.train_dqs(&mut transact, 0x40, &data).unwrap(), 13);
+        assert_eq!(backend.read_page(&mut transact, 0x40, 64).unwrap(), data);
+
+        let oversized = vec![0u8; 0x1_0000];
+        assert_eq!(
+            backend.train_dqs(&mut transact, 0x40, &oversized),
+            Err(FpgaError::ReferenceTooLong(0x1_0000))
+        );
+    }
+
+    #[test]
+    fn test_probe_rejects_other_devices() {
+        let mut transact = |_: &Packet| Ok(vec![0u8; 4]);
+        assert_eq!(
+            FpgaBackend::probe(&mut transact).unwrap_err(),
+            FpgaError::NotDetected(0)
+        );
+
+        let caps = FpgaCapabilities {
+            toggle: false,
+            ..ecp5_caps()
+        };
+        let mut model = GatewareModel::new(caps);
+        let mut transact = |p: &Packet| model.transact(p);
+        let mut backend = FpgaBackend::probe(&mut transact).unwrap();
+        let toggle = DataInterfaceConfig::new(DataInterface::ToggleDdr, 4);
+        assert_eq!(
+            backend.apply(&mut transact, toggle),
+            Err(FpgaError::Unsupported(DataInterface::ToggleDdr))
+        );
+    }
+}
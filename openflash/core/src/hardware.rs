@@ -242,11 +242,13 @@ pub struct Tsop48PinMapping {
     pub io8_15: [u8; 8], // Data pins D8-D15 (x16 mode)
     pub cle: u8,         // Command Latch Enable
     pub ale: u8,         // Address Latch Enable
-    pub ce: u8,          // Chip Enable
+    pub ce: u8,          // Chip Enable (CE#)
+    pub ce2: u8,         // Second Chip Enable (CE2#) on dual-die packages
     pub re: u8,          // Read Enable
     pub we: u8,          // Write Enable
     pub wp: u8,          // Write Protect
     pub rb: u8,          // Ready/Busy
+    pub rb2: u8,         // Second Ready/Busy (R/B2#)
 }
 
 impl Tsop48PinMapping {
@@ -260,6 +262,11 @@ impl Tsop48PinMapping {
         }
     }
 
+    /// Chip enable pins, CE# first
+    pub fn chip_enables(&self) -> [u8; 2] {
+        [self.ce, self.ce2]
+    }
+
     fn standard() -> Self {
         Self {
             io0_7: [29, 30, 31, 32, 41, 42, 43, 44],
@@ -267,10 +274,12 @@ impl Tsop48PinMapping {
             cle: 17,
             ale: 18,
             ce: 9,
+            ce2: 10,
             re: 8,
             we: 19,
             wp: 20,
             rb: 7,
+            rb2: 6,
         }
     }
 
@@ -281,10 +290,12 @@ impl Tsop48PinMapping {
             cle: 17,
             ale: 18,
             ce: 9,
+            ce2: 10,
             re: 8,
             we: 19,
             wp: 20,
             rb: 7,
+            rb2: 6,
         }
     }
 
@@ -295,10 +306,12 @@ impl Tsop48PinMapping {
             cle: 17,
             ale: 18,
             ce: 9,
+            ce2: 10,
             re: 8,
             we: 19,
             wp: 22,
             rb: 7,
+            rb2: 6,
         }
     }
 }
@@ -1060,6 +1073,7 @@ mod tests {
         assert_eq!(mapping.cle, 17);
         assert_eq!(mapping.ale, 18);
         assert_eq!(mapping.ce, 9);
+        assert_eq!(mapping.chip_enables(), [9, 10]);
     }
 
     #[test]
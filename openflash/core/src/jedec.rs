@@ -0,0 +1,333 @@
+//! JEDEC (JESD230) NAND parameter page support
+//! Used by Samsung, Toshiba/Kioxia and other parts that answer READ PARAMETER
+//! PAGE with a "JESD" signature instead of "ONFI"
+
+use crate::onfi::{
+    onfi_crc16, onfi_sdr_timing, CellType, NandChipInfo, NandEccRequirement, NandTiming,
+};
+use serde::{Deserialize, Serialize};
+
+/// READ PARAMETER PAGE address for the JEDEC page
+pub const JEDEC_PARAM_PAGE_ADDR: u8 = 0x40;
+
+/// Size of one JEDEC parameter page copy (CRC in bytes 510-511)
+pub const JEDEC_PARAM_PAGE_SIZE: usize = 512;
+
+/// Feature support flags (parameter page bytes 6-7)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct JedecFeatures {
+    pub x16: bool,
+    pub multi_lun: bool,
+    pub non_sequential_program: bool,
+    pub multi_plane: bool,
+    pub multi_plane_read: bool,
+    pub sync_ddr: bool,
+    pub toggle_ddr: bool,
+}
+
+impl JedecFeatures {
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            x16: bits & (1 << 0) != 0,
+            multi_lun: bits & (1 << 1) != 0,
+            non_sequential_program: bits & (1 << 2) != 0,
+            multi_plane: bits & (1 << 3) != 0,
+            multi_plane_read: bits & (1 << 4) != 0,
+            sync_ddr: bits & (1 << 5) != 0,
+            toggle_ddr: bits & (1 << 6) != 0,
+        }
+    }
+}
+
+/// One ECC information block (parameter page bytes 211-242)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct JedecEccInfo {
+    /// Correctable bits per codeword
+    pub ecc_bits: u8,
+    /// Codeword size in bytes
+    pub codeword_size: u32,
+    pub max_bad_blocks_per_lun: u16,
+    /// Program/erase cycles per block at this ECC level
+    pub block_endurance: u32,
+}
+
+/// Decoded JEDEC parameter page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JedecParameterPage {
+    /// Copy (0, 1, 2, ...) that passed the CRC check
+    pub copy: usize,
+    /// Revision bits (bit 2 = JESD230 1.0)
+    pub revision: u16,
+    pub features: JedecFeatures,
+    pub manufacturer: String,
+    pub model: String,
+    pub jedec_id: [u8; 6],
+    pub page_size: u32,
+    pub oob_size: u32,
+    pub pages_per_block: u32,
+    pub blocks_per_lun: u32,
+    pub luns: u8,
+    pub row_address_cycles: u8,
+    pub column_address_cycles: u8,
+    pub bits_per_cell: u8,
+    pub programs_per_page: u8,
+    pub planes: u8,
+    /// Supported asynchronous SDR speed grades (bit N = mode N)
+    pub async_sdr_timing_modes: u16,
+    /// Supported Toggle DDR speed grades (bit N = grade N)
+    pub toggle_ddr_timing_modes: u16,
+    /// Supported synchronous DDR speed grades (bit N = grade N)
+    pub sync_ddr_timing_modes: u16,
+    /// Maximum page program time (microseconds)
+    pub t_prog_us: u16,
+    /// Maximum block erase time (microseconds)
+    pub t_bers_us: u16,
+    /// Maximum page read time (microseconds)
+    pub t_r_us: u16,
+    /// Maximum multi-plane page read time (microseconds)
+    pub t_r_multi_plane_us: u16,
+    /// Minimum change column setup time (nanoseconds)
+    pub t_ccs_ns: u16,
+    /// Blocks at the start of the target guaranteed valid when shipped
+    pub guaranteed_good_blocks: u8,
+    /// Program/erase cycles for the guaranteed good blocks
+    pub guaranteed_block_endurance: u32,
+    /// ECC information blocks in the order given by the chip
+    pub ecc: Vec<JedecEccInfo>,
+}
+
+impl JedecParameterPage {
+    /// Parse a parameter page read, trying each redundant copy in turn
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        data.chunks_exact(JEDEC_PARAM_PAGE_SIZE)
+            .enumerate()
+            .find_map(|(copy, page)| Self::parse_copy(page, copy))
+    }
+
+    fn parse_copy(data: &[u8], copy: usize) -> Option<Self> {
+        // Check "JESD" signature and CRC over bytes 0-509
+        if &data[0..4] != b"JESD" {
+            return None;
+        }
+        if onfi_crc16(&data[..510]) != u16::from_le_bytes([data[510], data[511]]) {
+            return None;
+        }
+
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let text_at = |range: std::ops::Range<usize>| {
+            String::from_utf8_lossy(&data[range]).trim().to_string()
+        };
+        // Endurance is stored as a value byte and a power-of-ten exponent byte
+        let endurance_at =
+            |i: usize| (data[i] as u32).saturating_mul(10u32.saturating_pow(data[i + 1] as u32));
+
+        let ecc = data[211..243]
+            .chunks_exact(8)
+            .enumerate()
+            .filter(|(_, block)| block[0] != 0)
+            .map(|(i, block)| JedecEccInfo {
+                ecc_bits: block[0],
+                codeword_size: 1u32.checked_shl(block[1] as u32).unwrap_or(0),
+                max_bad_blocks_per_lun: u16_at(211 + i * 8 + 2),
+                block_endurance: endurance_at(211 + i * 8 + 4),
+            })
+            .collect();
+
+        let mut jedec_id = [0u8; 6];
+        jedec_id.copy_from_slice(&data[64..70]);
+
+        Some(Self {
+            copy,
+            revision: u16_at(4),
+            features: JedecFeatures::from_bits(u16_at(6)),
+            manufacturer: text_at(32..44),
+            model: text_at(44..64),
+            jedec_id,
+            page_size: u32_at(80),
+            oob_size: u16_at(84) as u32,
+            pages_per_block: u32_at(92),
+            blocks_per_lun: u32_at(96),
+            luns: data[100],
+            row_address_cycles: data[101] & 0x0F,
+            column_address_cycles: data[101] >> 4,
+            bits_per_cell: data[102],
+            programs_per_page: data[103],
+            planes: 1u8.checked_shl((data[104] & 0x0F) as u32).unwrap_or(1),
+            async_sdr_timing_modes: u16_at(144),
+            toggle_ddr_timing_modes: u16_at(146),
+            sync_ddr_timing_modes: u16_at(148),
+            t_prog_us: u16_at(153),
+            t_bers_us: u16_at(155),
+            t_r_us: u16_at(157),
+            t_r_multi_plane_us: u16_at(159),
+            t_ccs_ns: u16_at(161),
+            guaranteed_good_blocks: data[208],
+            guaranteed_block_endurance: endurance_at(209),
+            ecc,
+        })
+    }
+
+    /// Total capacity across all LUNs in bytes
+    pub fn size_bytes(&self) -> u64 {
+        self.blocks_per_lun as u64
+            * self.luns as u64
+            * self.pages_per_block as u64
+            * self.page_size as u64
+    }
+
+    /// Fastest supported asynchronous SDR timing mode (0-5)
+    pub fn max_sdr_timing_mode(&self) -> u8 {
+        (0..=5u8)
+            .rev()
+            .find(|&mode| self.async_sdr_timing_modes & (1 << mode) != 0)
+            .unwrap_or(0)
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self.bits_per_cell {
+            2 => CellType::MLC,
+            3 => CellType::TLC,
+            4 => CellType::QLC,
+            _ => CellType::SLC,
+        }
+    }
+
+    /// ECC requirement from the first (preferred) ECC information block
+    pub fn ecc_requirement(&self) -> Option<NandEccRequirement> {
+        self.ecc.first().map(|ecc| NandEccRequirement {
+            bits: ecc.ecc_bits,
+            codeword_size: ecc.codeword_size,
+        })
+    }
+
+    pub fn to_chip_info(&self) -> NandChipInfo {
+        NandChipInfo {
+            manufacturer: self.manufacturer.clone(),
+            model: self.model.clone(),
+            size_mb: (self.size_bytes() / 1024 / 1024) as u32,
+            page_size: self.page_size,
+            block_size: self.pages_per_block,
+            oob_size: self.oob_size,
+            // Toggle DDR 2.0 parts run their I/O at 1.8V
+            voltage: if self.features.toggle_ddr {
+                "3.3V/1.8V".into()
+            } else {
+                "3.3V".into()
+            },
+            timing: NandTiming {
+                tR: self.t_r_us.min(255) as u8,
+                ..onfi_sdr_timing(self.max_sdr_timing_mode())
+            },
+            bus_width: if self.features.x16 { 16 } else { 8 },
+            cell_type: self.cell_type(),
+            ecc_requirement: self.ecc_requirement(),
+            planes: self.planes,
+            luns: self.luns.max(1),
+            chip_enables: 1,
+        }
+    }
+}
+
+/// Parse JEDEC parameter page (one or more 256-byte copies)
+pub fn parse_jedec_parameter_page(data: &[u8]) -> Option<NandChipInfo> {
+    JedecParameterPage::parse(data).map(|page| page.to_chip_info())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Build a parameter page copy with a valid CRC
+    pub(crate) fn make_jedec_page() -> Vec<u8> {
+        let mut page = vec![0u8; JEDEC_PARAM_PAGE_SIZE];
+        page[0..4].copy_from_slice(b"JESD");
+        page[4..6].copy_from_slice(&(1u16 << 2).to_le_bytes());
+        page[6..8].copy_from_slice(&0x0058u16.to_le_bytes()); // multi-plane, toggle DDR
+        page[32..44].copy_from_slice(b"TOSHIBA     ");
+        page[44..64].copy_from_slice(b"TC58TEG6DDKTA00     ");
+        page[64..70].copy_from_slice(&[0x98, 0xDE, 0x94, 0x93, 0x76, 0x50]);
+        page[80..84].copy_from_slice(&16384u32.to_le_bytes());
+        page[84..86].copy_from_slice(&1280u16.to_le_bytes());
+        page[92..96].copy_from_slice(&256u32.to_le_bytes());
+        page[96..100].copy_from_slice(&2092u32.to_le_bytes());
+        page[100] = 1;
+        page[101] = 0x23;
+        page[102] = 2;
+        page[103] = 1;
+        page[104] = 1; // 2 planes
+        page[144..146].copy_from_slice(&0x003Fu16.to_le_bytes()); // modes 0-5
+        page[153..155].copy_from_slice(&1600u16.to_le_bytes());
+        page[155..157].copy_from_slice(&5000u16.to_le_bytes());
+        page[157..159].copy_from_slice(&90u16.to_le_bytes());
+        page[208] = 1;
+        page[209] = 1;
+        page[210] = 3; // 1000 cycles
+
+        // ECC block 0: 40 bits per 1KB, 80 bad blocks, 3000 cycles
+        page[211] = 40;
+        page[212] = 10;
+        page[213..215].copy_from_slice(&80u16.to_le_bytes());
+        page[215] = 3;
+        page[216] = 3;
+        let crc = onfi_crc16(&page[..510]);
+        page[510..512].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    #[test]
+    fn test_jedec_parameter_page_decoding() {
+        let page = JedecParameterPage::parse(&make_jedec_page()).unwrap();
+
+        assert_eq!(page.copy, 0);
+        assert_eq!(page.manufacturer, "TOSHIBA");
+        assert_eq!(page.model, "TC58TEG6DDKTA00");
+        assert_eq!(page.jedec_id[0], 0x98);
+        assert!(page.features.multi_plane && page.features.toggle_ddr);
+        assert!(!page.features.x16);
+        assert_eq!(page.planes, 2);
+        assert_eq!(page.max_sdr_timing_mode(), 5);
+        assert_eq!(page.guaranteed_good_blocks, 1);
+        assert_eq!(page.guaranteed_block_endurance, 1000);
+        assert_eq!(
+            page.ecc,
+            vec![JedecEccInfo {
+                ecc_bits: 40,
+                codeword_size: 1024,
+                max_bad_blocks_per_lun: 80,
+                block_endurance: 3000,
+            }]
+        );
+
+        let info = page.to_chip_info();
+        assert_eq!(info.page_size, 16384);
+        assert_eq!(info.size_mb, 2092 * 4);
+        assert_eq!(info.cell_type, CellType::MLC);
+        assert_eq!(info.timing.tR, 90);
+        assert_eq!(info.planes, 2);
+        assert_eq!(
+            info.ecc_requirement,
+            Some(NandEccRequirement {
+                bits: 40,
+                codeword_size: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_jedec_parameter_page_redundant_copies() {
+        let good = make_jedec_page();
+        let mut corrupted = good.clone();
+        corrupted[100] = 4;
+
+        let data = [corrupted.clone(), corrupted.clone(), good].concat();
+        assert_eq!(JedecParameterPage::parse(&data).unwrap().copy, 2);
+
+        assert!(parse_jedec_parameter_page(&corrupted).is_none());
+        // ONFI pages are not accepted
+        let mut onfi = make_jedec_page();
+        onfi[0..4].copy_from_slice(b"ONFI");
+        assert!(parse_jedec_parameter_page(&onfi).is_none());
+    }
+}
@@ -1,16 +1,29 @@
 pub mod ai;
 pub mod ai_advanced;
 pub mod analysis;
+pub mod bbm;
+pub mod chipdb;
 pub mod cloud;
 pub mod ecc;
 pub mod emmc;
+pub mod fpga;
 pub mod hardware;
+pub mod jedec;
+pub mod nand_ops;
 pub mod onfi;
+pub mod pairing;
 pub mod protocol;
+pub mod randomizer;
 pub mod scripting;
 pub mod server;
+pub mod sfdp;
 pub mod spi_nand;
 pub mod spi_nor;
+pub mod spi_nor_addr;
+pub mod spi_nor_io;
+pub mod spi_nor_otp;
+pub mod spi_nor_plan;
+pub mod spi_nor_protect;
 pub mod ufs;
 pub mod write_ops;
 
@@ -54,10 +67,19 @@ pub use ai_advanced::{
     VulnScanner,
     Vulnerability,
 };
+pub use bbm::{BbmPolicy, MarkerCheck, MarkerPage};
+pub use chipdb::{
+    chip_database, user_chipdb_dirs, ChipDatabase, ChipDbError, ChipFamily, ChipRecord, IdPattern,
+    CHIP_DB_PATH_ENV, CHIP_DB_VERSION,
+};
 pub use emmc::{
     crc16, crc7, get_emmc_chip_info, get_emmc_manufacturer_name, parse_boot_size_from_ext_csd,
     parse_capacity_from_ext_csd, CardState, EmmcChipInfo, EmmcReadResult, ResponseType,
 };
+pub use fpga::{
+    select_data_interface, select_jedec_data_interface, DataInterface, DataInterfaceConfig,
+    FpgaBackend, FpgaCapabilities, FpgaError, FpgaFamily, FpgaResult, GatewareModel,
+};
 pub use hardware::{
     BgaProfile,
     BgaReworkStation,
@@ -94,6 +116,15 @@ pub use hardware::{
     Tsop48Pinout,
     VoltageLevel,
 };
+pub use jedec::{
+    parse_jedec_parameter_page, JedecEccInfo, JedecFeatures, JedecParameterPage,
+    JEDEC_PARAM_PAGE_ADDR, JEDEC_PARAM_PAGE_SIZE,
+};
+pub use nand_ops::{NandOp, NandOpCapabilities, NandOpScheduler};
+pub use pairing::{PagePairing, PageRole, SlcMode};
+pub use randomizer::{
+    detect_randomizer, known_lfsrs, Lfsr, Randomizer, RandomizerDetection, SeedStrategy,
+};
 pub use scripting::{
     AnalysisOptions, AnomalyInfo, BatchJob, BatchJobConfig, BatchJobResult, BatchJobStatus,
     BatchJobType, BatchProcessor, ChipDetectionResult, CiArtifact, CiArtifactType, CiJobConfig,
@@ -155,13 +186,36 @@ pub use server::{
     // WebSocket
     WsMessage,
 };
+pub use sfdp::{
+    AddressMode, Bfpt, BusyPolling, CommandExtension, ConfigDetect, DeepPowerDown, EraseOp,
+    EraseType, FastRead, FourByteEntry, FourByteExit, FourByteInstructions, GigaDeviceParams,
+    MacronixParams, OctalDdrSequences, ReadProtocol, SectorMap, SectorMapConfig, SectorRegion,
+    Sfdp, SoftReset, SpansionCfi, SuspendResume, VendorParams, VendorTable, XspiProfile1,
+};
 pub use spi_nand::{
-    calculate_column_address, calculate_row_address, get_spi_nand_chip_info,
-    get_spi_nand_manufacturer_name, EccStatus, SpiNandCellType, SpiNandChipInfo, SpiNandReadResult,
+    calculate_column_address, calculate_row_address, continuous_read_packet,
+    get_spi_nand_chip_info, get_spi_nand_manufacturer_name, EccStatus, EccStatusSource, OobRegion,
+    SpiNandCellType, SpiNandChipInfo, SpiNandDieSelect, SpiNandEccCapture, SpiNandEccDecoder,
+    SpiNandEccLayout, SpiNandEccReport, SpiNandPageAddress, SpiNandPageCapture, SpiNandReadMode,
+    SpiNandReadResult, SpiNandReader,
 };
 pub use spi_nor::{
-    get_spi_nor_chip_info, get_spi_nor_manufacturer_name, FastReadSupport, ProtectionStatus,
-    QuadEnableMethod, SfdpInfo, SfdpParser, SpiNorChipInfo, SpiNorError,
+    get_spi_nor_chip_info, get_spi_nor_chip_info_with_sfdp, get_spi_nor_manufacturer_name,
+    FastReadSupport, OctalEnable, ProtectionStatus, QuadEnableMethod, SfdpInfo, SfdpParser,
+    SpiNorChipInfo, SpiNorError, SpiNorIoMode,
+};
+pub use spi_nor_addr::{FourByteMode, SpiNorAddressing};
+pub use spi_nor_io::SpiNorIo;
+pub use spi_nor_otp::{
+    OtpDump, OtpLockOutcome, OtpLockRequest, OtpRegion, OtpRegionDump, OtpScheme, SpiNorOtp,
+    UniqueIdSource,
+};
+pub use spi_nor_plan::{
+    NorContents, NorErase, NorEraseSize, NorGeometry, NorPlanOp, NorPlanner, NorProgramPlan,
+};
+pub use spi_nor_protect::{
+    ProtectedRange, Protection, ProtectionFamily, ProtectionMap, RegisterWrite, StatusRegisters,
+    UnprotectPlan,
 };
 pub use ufs::{
     get_ufs_manufacturer_name, select_read_command, DeviceDescriptor, GeometryDescriptor,
@@ -169,9 +223,9 @@ pub use ufs::{
 };
 pub use write_ops::{
     BackupMetadata, BadBlockEntry, BadBlockReason, BadBlockTable, BlockWearInfo, ChangeTracker,
-    ChipCloner, ChipProgrammer, CloneMode, CloneOptions, ClonePhase, CloneProgress,
-    ProgramOperation, ProgramOptions, ProgramProgress, WearLevelingManager, WearStatistics,
-    WriteError, WriteResult,
+    ChipCloner, ChipProgrammer, CloneMode, CloneOptions, ClonePhase, CloneProgress, NorPlanReport,
+    PageRecovery, ProgramOperation, ProgramOptions, ProgramProgress, ReadRetryMethod,
+    RecoveryOptions, RetryOp, WearLevelingManager, WearStatistics, WriteError, WriteResult,
 };
 // Cloud & Pro features (v3.0)
 pub use cloud::{
@@ -0,0 +1,488 @@
+//! Cache and multi-plane operations for parallel NAND
+//!
+//! Sequential cache reads (31h/3Fh) overlap the array read (tR) with the
+//! data transfer, cache program (15h) overlaps tPROG the same way, and
+//! multi-plane operations run one array operation across several planes.
+//! The host schedules the operations; the firmware runs each one as a
+//! single packet.
+
+use crate::onfi::{address_bits, Onfi5Features, OnfiParameterPage};
+use crate::protocol::{Command, Packet};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Capabilities
+// ============================================================================
+
+/// Optional array operations a chip supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NandOpCapabilities {
+    pub cache_read: bool,
+    pub cache_program: bool,
+    /// Planes per multi-plane read (1 = no multi-plane read)
+    pub read_planes: u8,
+    /// Planes per multi-plane program/erase (1 = no multi-plane program)
+    pub program_planes: u8,
+}
+
+impl Default for NandOpCapabilities {
+    fn default() -> Self {
+        Self {
+            cache_read: false,
+            cache_program: false,
+            read_planes: 1,
+            program_planes: 1,
+        }
+    }
+}
+
+impl NandOpCapabilities {
+    /// Capabilities advertised by an ONFI parameter page
+    ///
+    /// The ONFI 5.0 extended page, when present, overrides the plane count.
+    /// Multi-plane reads also need CHANGE READ COLUMN ENHANCED to pull each
+    /// plane's data out of its page register.
+    pub fn from_onfi(page: &OnfiParameterPage, ext: Option<&Onfi5Features>) -> Self {
+        let planes = ext
+            .filter(|f| f.multi_plane_ops)
+            .map_or(page.planes, |f| f.max_planes)
+            .max(1);
+        let commands = page.optional_commands;
+        Self {
+            cache_read: commands.cache_read,
+            cache_program: commands.cache_program,
+            read_planes: if page.features.multi_plane_read && commands.change_read_column_enhanced {
+                planes
+            } else {
+                1
+            },
+            program_planes: if page.features.multi_plane { planes } else { 1 },
+        }
+    }
+}
+
+// ============================================================================
+// Operations
+// ============================================================================
+
+/// One firmware-level NAND operation
+///
+/// Rows are ONFI row addresses (page, block and LUN bits).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NandOp {
+    ReadPage {
+        row: u32,
+    },
+    /// `count` consecutive pages starting at `row`
+    CacheRead {
+        row: u32,
+        count: u16,
+    },
+    /// Same page in one block per plane, returned in plane order
+    MultiPlaneRead {
+        rows: Vec<u32>,
+    },
+    ProgramPage {
+        row: u32,
+    },
+    /// `last` ends the cache sequence with 10h
+    CacheProgram {
+        row: u32,
+        last: bool,
+    },
+    /// `last` ends the plane group with 10h
+    MultiPlaneProgram {
+        row: u32,
+        last: bool,
+    },
+    EraseBlock {
+        row: u32,
+    },
+    MultiPlaneErase {
+        rows: Vec<u32>,
+    },
+}
+
+impl NandOp {
+    /// Encode as a firmware packet; programs send `page_len` bytes of data after it
+    pub fn to_packet(&self, page_len: u16) -> Packet {
+        let len = page_len.to_le_bytes();
+        match self {
+            NandOp::ReadPage { row } => page_packet(Command::NandReadPage, *row, len, &[]),
+            NandOp::CacheRead { row, count } => {
+                page_packet(Command::NandCacheRead, *row, len, &count.to_le_bytes())
+            }
+            NandOp::MultiPlaneRead { rows } => {
+                let mut args = vec![len[0], len[1], rows.len() as u8];
+                args.extend(rows.iter().flat_map(|row| row.to_le_bytes()));
+                Packet::new(Command::NandMultiPlaneRead, &args)
+            }
+            NandOp::ProgramPage { row } => page_packet(Command::NandWritePage, *row, len, &[]),
+            NandOp::CacheProgram { row, last } => {
+                page_packet(Command::NandCacheProgram, *row, len, &[*last as u8])
+            }
+            NandOp::MultiPlaneProgram { row, last } => {
+                page_packet(Command::NandMultiPlaneProgram, *row, len, &[*last as u8])
+            }
+            NandOp::EraseBlock { row } => Packet::new(Command::NandErase, &row.to_le_bytes()),
+            NandOp::MultiPlaneErase { rows } => {
+                let mut args = vec![rows.len() as u8];
+                args.extend(rows.iter().flat_map(|row| row.to_le_bytes()));
+                Packet::new(Command::NandMultiPlaneErase, &args)
+            }
+        }
+    }
+
+    /// Rows whose page data crosses the bus, in transfer order
+    pub fn data_rows(&self) -> Vec<u32> {
+        match self {
+            NandOp::ReadPage { row }
+            | NandOp::ProgramPage { row }
+            | NandOp::CacheProgram { row, .. }
+            | NandOp::MultiPlaneProgram { row, .. } => vec![*row],
+            NandOp::CacheRead { row, count } => (0..*count as u32).map(|i| row + i).collect(),
+            NandOp::MultiPlaneRead { rows } => rows.clone(),
+            NandOp::EraseBlock { .. } | NandOp::MultiPlaneErase { .. } => Vec::new(),
+        }
+    }
+}
+
+fn page_packet(cmd: Command, row: u32, len: [u8; 2], extra: &[u8]) -> Packet {
+    let mut args = row.to_le_bytes().to_vec();
+    args.extend_from_slice(&len);
+    args.extend_from_slice(extra);
+    Packet::new(cmd, &args)
+}
+
+// ============================================================================
+// Scheduler
+// ============================================================================
+
+/// Plans reads, programs and erases of a block range within one LUN
+///
+/// Planes are selected by the low block address bits, so a plane group is
+/// `planes` consecutive blocks starting at a multiple of `planes`. Ranges
+/// that don't cover a whole group fall back to single-plane operations.
+#[derive(Debug, Clone)]
+pub struct NandOpScheduler {
+    caps: NandOpCapabilities,
+    pages_per_block: u32,
+}
+
+impl NandOpScheduler {
+    pub fn new(caps: NandOpCapabilities, pages_per_block: u32) -> Self {
+        Self {
+            caps,
+            pages_per_block,
+        }
+    }
+
+    /// Row address of a page
+    pub fn row(&self, block: u32, page: u32) -> u32 {
+        (block << address_bits(self.pages_per_block)) | page
+    }
+
+    /// Cache reads hide tR entirely, so they win over multi-plane reads
+    pub fn schedule_read(&self, first_block: u32, block_count: u32) -> Vec<NandOp> {
+        let mut ops = Vec::new();
+        if self.caps.cache_read && self.pages_per_block <= u16::MAX as u32 {
+            for block in first_block..first_block + block_count {
+                ops.push(NandOp::CacheRead {
+                    row: self.row(block, 0),
+                    count: self.pages_per_block as u16,
+                });
+            }
+            return ops;
+        }
+
+        for group in self.plane_groups(first_block, block_count, self.caps.read_planes) {
+            for page in 0..self.pages_per_block {
+                if group.len() > 1 {
+                    ops.push(NandOp::MultiPlaneRead {
+                        rows: group.iter().map(|&b| self.row(b, page)).collect(),
+                    });
+                } else {
+                    ops.push(NandOp::ReadPage {
+                        row: self.row(group[0], page),
+                    });
+                }
+            }
+        }
+        ops
+    }
+
+    /// Multi-plane program halves tPROG per page; cache program is the fallback
+    pub fn schedule_program(&self, first_block: u32, block_count: u32) -> Vec<NandOp> {
+        let mut ops = Vec::new();
+        for group in self.plane_groups(first_block, block_count, self.caps.program_planes) {
+            for page in 0..self.pages_per_block {
+                if group.len() > 1 {
+                    for (i, &block) in group.iter().enumerate() {
+                        ops.push(NandOp::MultiPlaneProgram {
+                            row: self.row(block, page),
+                            last: i == group.len() - 1,
+                        });
+                    }
+                } else if self.caps.cache_program {
+                    ops.push(NandOp::CacheProgram {
+                        row: self.row(group[0], page),
+                        last: page == self.pages_per_block - 1,
+                    });
+                } else {
+                    ops.push(NandOp::ProgramPage {
+                        row: self.row(group[0], page),
+                    });
+                }
+            }
+        }
+        ops
+    }
+
+    pub fn schedule_erase(&self, first_block: u32, block_count: u32) -> Vec<NandOp> {
+        self.plane_groups(first_block, block_count, self.caps.program_planes)
+            .into_iter()
+            .map(|group| {
+                if group.len() > 1 {
+                    NandOp::MultiPlaneErase {
+                        rows: group.iter().map(|&b| self.row(b, 0)).collect(),
+                    }
+                } else {
+                    NandOp::EraseBlock {
+                        row: self.row(group[0], 0),
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Read `block_count` blocks, returned block by block in page order
+    ///
+    /// `transfer` sends a packet with any data that follows it and returns
+    /// the `read_len` bytes a read streams back (0 for status-only replies).
+    pub fn read_blocks<E, T>(
+        &self,
+        first_block: u32,
+        block_count: u32,
+        page_len: u16,
+        mut transfer: T,
+    ) -> Result<Vec<u8>, E>
+    where
+        T: FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, E>,
+    {
+        let len = page_len as usize;
+        let mut data = vec![0xFF; (block_count * self.pages_per_block) as usize * len];
+        for op in self.schedule_read(first_block, block_count) {
+            let rows = op.data_rows();
+            let response = transfer(&op.to_packet(page_len), &[], rows.len() * len)?;
+            // Multi-plane reads interleave blocks, so place each page by its row
+            for (&row, page) in rows.iter().zip(response.chunks(len)) {
+                let offset = self.page_index(first_block, row) * len;
+                data[offset..offset + page.len()].copy_from_slice(page);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Program `data` (whole blocks of `page_len` pages) starting at `first_block`
+    pub fn program_blocks<E, T>(
+        &self,
+        first_block: u32,
+        page_len: u16,
+        data: &[u8],
+        mut transfer: T,
+    ) -> Result<(), E>
+    where
+        T: FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, E>,
+    {
+        let len = page_len as usize;
+        let block_bytes = self.pages_per_block as usize * len;
+        if block_bytes == 0 {
+            return Ok(());
+        }
+        let block_count = (data.len() / block_bytes) as u32;
+        for op in self.schedule_program(first_block, block_count) {
+            for row in op.data_rows() {
+                let offset = self.page_index(first_block, row) * len;
+                transfer(&op.to_packet(page_len), &data[offset..offset + len], 0)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn erase_blocks<E, T>(
+        &self,
+        first_block: u32,
+        block_count: u32,
+        mut transfer: T,
+    ) -> Result<(), E>
+    where
+        T: FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, E>,
+    {
+        for op in self.schedule_erase(first_block, block_count) {
+            transfer(&op.to_packet(0), &[], 0)?;
+        }
+        Ok(())
+    }
+
+    /// Index of a row's page within a range starting at `first_block`
+    fn page_index(&self, first_block: u32, row: u32) -> usize {
+        let bits = address_bits(self.pages_per_block);
+        let block = (row >> bits) - first_block;
+        let page = row & ((1 << bits) - 1);
+        (block * self.pages_per_block + page) as usize
+    }
+
+    fn plane_groups(&self, first_block: u32, block_count: u32, planes: u8) -> Vec<Vec<u32>> {
+        let planes = planes.max(1) as u32;
+        let end = first_block + block_count;
+        let mut groups = Vec::new();
+        let mut block = first_block;
+        while block < end {
+            if planes > 1 && block % planes == 0 && block + planes <= end {
+                groups.push((block..block + planes).collect());
+                block += planes;
+            } else {
+                groups.push(vec![block]);
+                block += 1;
+            }
+        }
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onfi::{onfi_crc16, OnfiFeatures, OnfiOptionalCommands};
+
+    fn caps(cache: bool, planes: u8) -> NandOpCapabilities {
+        NandOpCapabilities {
+            cache_read: cache,
+            cache_program: cache,
+            read_planes: planes,
+            program_planes: planes,
+        }
+    }
+
+    #[test]
+    fn test_cache_read_schedule() {
+        let sched = NandOpScheduler::new(caps(true, 2), 128);
+        let ops = sched.schedule_read(3, 2);
+        assert_eq!(
+            ops,
+            vec![
+                NandOp::CacheRead {
+                    row: 3 << 7,
+                    count: 128
+                },
+                NandOp::CacheRead {
+                    row: 4 << 7,
+                    count: 128
+                },
+            ]
+        );
+        assert_eq!(ops[0].data_rows().len(), 128);
+
+        let bytes = ops[0].to_packet(2112).to_bytes();
+        assert_eq!(bytes[0], Command::NandCacheRead as u8);
+        assert_eq!(&bytes[1..5], &(3u32 << 7).to_le_bytes());
+        assert_eq!(&bytes[5..9], &[0x40, 0x08, 128, 0]);
+    }
+
+    #[test]
+    fn test_multi_plane_groups() {
+        let sched = NandOpScheduler::new(caps(false, 2), 64);
+
+        // Block 1 has no partner, 2+3 pair up, 4 is cut off by the range end
+        let erase = sched.schedule_erase(1, 4);
+        assert_eq!(
+            erase,
+            vec![
+                NandOp::EraseBlock { row: 1 << 6 },
+                NandOp::MultiPlaneErase {
+                    rows: vec![2 << 6, 3 << 6]
+                },
+                NandOp::EraseBlock { row: 4 << 6 },
+            ]
+        );
+
+        let reads = sched.schedule_read(2, 2);
+        assert_eq!(reads.len(), 64);
+        assert_eq!(reads[1].data_rows(), vec![(2 << 6) | 1, (3 << 6) | 1]);
+
+        let programs = sched.schedule_program(2, 2);
+        assert_eq!(programs.len(), 128);
+        assert_eq!(
+            programs[0],
+            NandOp::MultiPlaneProgram {
+                row: 2 << 6,
+                last: false
+            }
+        );
+        assert_eq!(
+            programs[1],
+            NandOp::MultiPlaneProgram {
+                row: 3 << 6,
+                last: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_and_program_blocks() {
+        let sched = NandOpScheduler::new(caps(false, 2), 4);
+        let mut reads = 0;
+
+        // Each page reads back as its row, two bytes per page
+        let data = sched
+            .read_blocks::<(), _>(2, 2, 2, |packet, _, read_len| {
+                reads += 1;
+                assert_eq!(packet.cmd, Command::NandMultiPlaneRead);
+                assert_eq!(read_len, 4);
+                let rows = packet.args[3..11].chunks(4);
+                Ok(rows.flat_map(|r| [r[0], r[1]]).collect())
+            })
+            .unwrap();
+        assert_eq!(reads, 4);
+        // Block-major order even though each read returned both planes
+        assert_eq!(&data[..8], &[8, 0, 9, 0, 10, 0, 11, 0]);
+        assert_eq!(&data[8..10], &[12, 0]);
+
+        let mut programmed = Vec::new();
+        sched
+            .program_blocks::<(), _>(2, 2, &data, |packet, page, _| {
+                let row = u32::from_le_bytes(packet.args[..4].try_into().unwrap());
+                programmed.push((row, page[0]));
+                Ok(Vec::new())
+            })
+            .unwrap();
+        assert_eq!(programmed.len(), 8);
+        assert_eq!(&programmed[..2], &[(8, 8), (12, 12)]);
+
+        let mut erased = Vec::new();
+        sched
+            .erase_blocks::<(), _>(2, 2, |packet, _, _| {
+                erased.push(packet.cmd);
+                Ok(Vec::new())
+            })
+            .unwrap();
+        assert_eq!(erased, vec![Command::NandMultiPlaneErase]);
+    }
+
+    #[test]
+    fn test_capabilities_from_onfi() {
+        let mut raw = [0u8; 256];
+        raw[0..4].copy_from_slice(b"ONFI");
+        let crc = onfi_crc16(&raw[..254]);
+        raw[254..].copy_from_slice(&crc.to_le_bytes());
+        let mut page = OnfiParameterPage::parse(&raw).unwrap();
+        page.planes = 2;
+        page.features = OnfiFeatures::from_bits(1 << 3);
+        page.optional_commands = OnfiOptionalCommands::from_bits(0x0003);
+
+        let caps = NandOpCapabilities::from_onfi(&page, None);
+        assert!(caps.cache_read && caps.cache_program);
+        // Multi-plane read is not advertised, program/erase is
+        assert_eq!((caps.read_planes, caps.program_planes), (1, 2));
+    }
+}
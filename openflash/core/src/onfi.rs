@@ -1,7 +1,11 @@
 //! ONFI NAND Flash chip database and detection
-//! Contains known chip parameters and auto-detection logic
+//! Chip parameters come from the chip database (see `chipdb`); this module
+//! holds auto-detection logic
 //! Supports ONFI 1.0 through 5.0 specifications
 
+use crate::chipdb::chip_database;
+use crate::jedec::{parse_jedec_parameter_page, JEDEC_PARAM_PAGE_ADDR, JEDEC_PARAM_PAGE_SIZE};
+use crate::protocol::{Command, Packet};
 use serde::{Deserialize, Serialize};
 
 // ============================================================================
@@ -227,7 +231,7 @@ pub struct Onfi5Features {
 pub struct NandChipInfo {
     pub manufacturer: String,
     pub model: String,
-    pub size_mb: u32,
+    pub size_mb: u32, // per chip enable (all LUNs behind one CE#)
     pub page_size: u32,
     pub block_size: u32, // pages per block
     pub oob_size: u32,   // spare/OOB bytes per page
@@ -235,6 +239,31 @@ pub struct NandChipInfo {
     pub timing: NandTiming,
     pub bus_width: u8, // 8 or 16 bit
     pub cell_type: CellType,
+    /// Minimum ECC requirement, when the chip reports one
+    #[serde(default)]
+    pub ecc_requirement: Option<NandEccRequirement>,
+    /// Planes per LUN
+    #[serde(default = "default_one")]
+    pub planes: u8,
+    /// LUNs (dies) behind each chip enable
+    #[serde(default = "default_one")]
+    pub luns: u8,
+    /// Populated chip enables (CE#)
+    #[serde(default = "default_one")]
+    pub chip_enables: u8,
+}
+
+pub(crate) fn default_one() -> u8 {
+    1
+}
+
+/// Minimum ECC strength required by a NAND chip
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct NandEccRequirement {
+    /// Correctable bits per codeword
+    pub bits: u8,
+    /// Codeword size in bytes
+    pub codeword_size: u32,
 }
 
 impl NandChipInfo {
@@ -251,6 +280,20 @@ impl NandChipInfo {
             NandBusWidth::X8
         }
     }
+
+    /// Erase blocks in each LUN
+    pub fn blocks_per_lun(&self) -> u32 {
+        let block_bytes = self.page_size as u64 * self.block_size as u64;
+        if block_bytes == 0 {
+            return 0;
+        }
+        (self.size_mb as u64 * 1024 * 1024 / block_bytes / self.luns.max(1) as u64) as u32
+    }
+
+    /// Capacity across every chip enable
+    pub fn total_size_mb(&self) -> u64 {
+        self.size_mb as u64 * self.chip_enables.max(1) as u64
+    }
 }
 
 /// NAND cell type
@@ -343,1028 +386,1091 @@ pub fn get_manufacturer_name(id: u8) -> &'static str {
     }
 }
 
-/// Database of known NAND flash chips
+/// Look up a NAND flash chip in the chip database
 /// Returns chip info based on 5-byte chip ID
 pub fn get_chip_info(chip_id: &[u8]) -> Option<NandChipInfo> {
     if chip_id.len() < 2 {
         return None;
     }
 
-    let mfr = chip_id[0];
-    let device = chip_id[1];
-
-    // Try exact match first
+    // Try the chip database first
     if chip_id.len() >= 5 {
-        if let Some(info) = get_chip_info_exact(chip_id) {
+        if let Some(info) = chip_database().lookup_nand(chip_id) {
             return Some(info);
         }
     }
 
-    // Fall back to generic detection based on device ID
-    get_chip_info_generic(mfr, device)
+    // Fall back to generic detection based on the ID bytes
+    get_chip_info_generic(chip_id)
 }
 
-fn get_chip_info_exact(chip_id: &[u8]) -> Option<NandChipInfo> {
-    match chip_id {
-        // ============ Samsung ============
-        // K9F1G08U0B - 128MB SLC
-        [0xEC, 0xF1, 0x00, 0x95, 0x40] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F1G08U0B".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9F2G08U0C - 256MB SLC
-        [0xEC, 0xDA, 0x10, 0x95, 0x44] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F2G08U0C".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9F4G08U0D - 512MB SLC
-        [0xEC, 0xDC, 0x10, 0x95, 0x54] | [0xEC, 0xDC, 0x10, 0x95, 0x50] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F4G08U0D".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9F8G08U0M - 1GB SLC (4KB page)
-        [0xEC, 0xD3, 0x51, 0x95, 0x58] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F8G08U0M".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9K8G08U0M - 1GB SLC
-        [0xEC, 0xD7, 0x10, 0x95, 0x44] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9K8G08U0M".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9GAG08U0E - 2GB MLC
-        [0xEC, 0xD5, 0x84, 0x72, 0x50] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9GAG08U0E".into(),
-            size_mb: 2048,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 436,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // K9LBG08U0M - 4GB MLC
-        [0xEC, 0xD7, 0xD5, 0x29, 0x38] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9LBG08U0M".into(),
-            size_mb: 4096,
-            page_size: 4096,
-            block_size: 128,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-
-        // ============ Hynix ============
-        // HY27UF081G2A - 128MB SLC
-        [0xAD, 0xF1, 0x80, 0x1D, ..] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "HY27UF081G2A".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // HY27UF082G2A - 256MB SLC
-        [0xAD, 0xDA, 0x10, 0x95, 0x44] | [0xAD, 0xDC, 0x10, 0x95, 0x50] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "HY27UF082G2A".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // H27U4G8F2DTR - 512MB SLC
-        [0xAD, 0xDC, 0x90, 0x95, 0x54] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27U4G8F2DTR".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // H27UAG8T2BTR - 2GB MLC
-        [0xAD, 0xD5, 0x94, 0x25, 0x44] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27UAG8T2BTR".into(),
-            size_mb: 2048,
-            page_size: 4096,
-            block_size: 128,
-            oob_size: 224,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-
-        // ============ Micron ============
-        // MT29F1G08ABADAWP - 128MB SLC
-        [0x2C, 0xF1, 0x80, 0x95, 0x04] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F1G08ABADAWP".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F2G08ABAEAWP - 256MB SLC
-        [0x2C, 0xDA, 0x90, 0x95, 0x06] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F2G08ABAEAWP".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F4G08ABADAWP - 512MB SLC
-        [0x2C, 0xDC, 0x90, 0x95, 0x56] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F4G08ABADAWP".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F8G08ADBDAWP - 1GB SLC
-        [0x2C, 0xD3, 0xD1, 0x95, 0xA6] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F8G08ADBDAWP".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 224,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F16G08CBACAWP - 2GB MLC
-        [0x2C, 0x48, 0x04, 0x46, 0x85] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F16G08CBACAWP".into(),
-            size_mb: 2048,
-            page_size: 4096,
-            block_size: 256,
-            oob_size: 224,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-
-        // ============ Toshiba/Kioxia ============
-        // TC58NVG0S3ETA00 - 128MB SLC
-        [0x98, 0xF1, 0x80, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG0S3ETA00".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // TC58NVG1S3ETA00 - 256MB SLC
-        [0x98, 0xDA, 0x90, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG1S3ETA00".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // TC58NVG2S3ETA00 - 512MB SLC
-        [0x98, 0xDC, 0x90, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG2S3ETA00".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+/// Generic chip detection based on device ID byte
+///
+/// Capacity comes from the device ID byte; when the extended ID bytes are
+/// present they override the geometry (see [`decode_extended_id`]).
+fn get_chip_info_generic(chip_id: &[u8]) -> Option<NandChipInfo> {
+    let mfr = chip_id[0];
+    let device = chip_id[1];
+    let manufacturer = get_manufacturer_name(mfr).to_string();
 
-        // ============ Macronix ============
-        // MX30LF1G08AA - 128MB SLC
-        [0xC2, 0xF1, 0x80, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30LF1G08AA".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MX30UF2G28AD - 256MB SLC 1.8V
-        [0xC2, 0xDA, 0x90, 0x95, 0x46] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30UF2G28AD".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+    // Decode device ID according to ONFI conventions
+    // x8 devices use 0xFx, 0xDx series
+    // x16 devices use 0xAx, 0xBx, 0xCx series (same capacity, different bus width)
+    let (size_mb, page_size, block_size, cell_type, bus_width) = match device {
+        // ============ x8 devices ============
+        // 128MB class x8
+        0xF1 => (128, 2048, 64, CellType::SLC, 8),
+        // 256MB class x8
+        0xDA => (256, 2048, 64, CellType::SLC, 8),
+        // 512MB class x8
+        0xDC => (512, 2048, 64, CellType::SLC, 8),
+        // 1GB class x8
+        0xD3 => (1024, 4096, 64, CellType::SLC, 8),
+        // 2GB class x8 (often MLC)
+        0xD5 => (2048, 4096, 128, CellType::MLC, 8),
+        // 4GB class x8
+        0xD7 => (4096, 4096, 128, CellType::MLC, 8),
+        // 8GB class x8
+        0xDE => (8192, 8192, 256, CellType::MLC, 8),
+        // 16GB+ class x8
+        0x48 => (2048, 4096, 256, CellType::MLC, 8),
+        0x68 => (4096, 8192, 256, CellType::MLC, 8),
+        0x88 => (8192, 8192, 256, CellType::TLC, 8),
 
-        // ============ Winbond ============
-        // W29N01GVSIAA - 128MB SLC
-        [0xEF, 0xF1, 0x00, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W29N01GVSIAA".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+        // ============ x16 devices ============
+        // 128MB class x16
+        0xA1 => (128, 2048, 64, CellType::SLC, 16),
+        // 256MB class x16
+        0xCA => (256, 2048, 64, CellType::SLC, 16),
+        // 512MB class x16
+        0xCC => (512, 2048, 64, CellType::SLC, 16),
+        // 1GB class x16
+        0xB3 => (1024, 4096, 64, CellType::SLC, 16),
+        // 2GB class x16 (often MLC)
+        0xC5 => (2048, 4096, 128, CellType::MLC, 16),
+        // 4GB class x16
+        0xB7 => (4096, 4096, 128, CellType::MLC, 16),
+        // 8GB class x16
+        0xBE => (8192, 8192, 256, CellType::MLC, 16),
 
-        // ============ GigaDevice ============
-        // GD9FU1G8F2A - 128MB SLC
-        [0xC8, 0xF1, 0x80, 0x1D, ..] => Some(NandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD9FU1G8F2A".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+        _ => return None,
+    };
 
-        // ============ 16-bit (x16) Chip Variants ============
+    let mut info = NandChipInfo {
+        manufacturer,
+        model: String::new(),
+        size_mb,
+        page_size,
+        block_size,
+        oob_size: page_size / 32, // Typical OOB ratio
+        voltage: "3.3V".into(),
+        timing: NandTiming::default(),
+        bus_width,
+        cell_type,
+        ecc_requirement: None,
+        planes: 1,
+        luns: 1,
+        chip_enables: 1,
+    };
 
-        // Samsung x16 variants
-        // K9F1G16U0B - 128MB SLC x16
-        [0xEC, 0xA1, 0x00, 0x95, 0x40] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F1G16U0B".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // K9F2G16U0C - 256MB SLC x16
-        [0xEC, 0xCA, 0x10, 0x95, 0x44] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F2G16U0C".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // K9F4G16U0D - 512MB SLC x16
-        [0xEC, 0xCC, 0x10, 0x95, 0x54] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F4G16U0D".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // K9GAG16U0E - 2GB MLC x16
-        [0xEC, 0xC5, 0x84, 0x72, 0x50] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9GAG16U0E".into(),
-            size_mb: 2048,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 436,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::MLC,
-        }),
-
-        // Hynix x16 variants
-        // HY27UF161G2A - 128MB SLC x16
-        [0xAD, 0xA1, 0x80, 0x1D, ..] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "HY27UF161G2A".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // HY27UF162G2A - 256MB SLC x16
-        [0xAD, 0xCA, 0x10, 0x95, 0x44] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "HY27UF162G2A".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // H27U4G16F2DTR - 512MB SLC x16
-        [0xAD, 0xCC, 0x90, 0x95, 0x54] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27U4G16F2DTR".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
+    if let Some(ext) = decode_extended_id(chip_id, false) {
+        info.page_size = ext.page_size;
+        info.oob_size = ext.oob_size;
+        info.block_size = ext.pages_per_block;
+        info.bus_width = ext.bus_width;
+        info.cell_type = ext.cell_type;
+        info.ecc_requirement = ext.ecc_requirement;
+    }
+    info.model = format!("Generic 0x{:02X} x{}", device, info.bus_width);
 
-        // Micron x16 variants
-        // MT29F1G16ABADAWP - 128MB SLC x16
-        [0x2C, 0xA1, 0x80, 0x95, 0x04] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F1G16ABADAWP".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F2G16ABAEAWP - 256MB SLC x16
-        [0x2C, 0xCA, 0x90, 0x95, 0x06] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F2G16ABAEAWP".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F4G16ABADAWP - 512MB SLC x16
-        [0x2C, 0xCC, 0x90, 0x95, 0x56] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F4G16ABADAWP".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // MT29F8G16ADBDAWP - 1GB SLC x16
-        [0x2C, 0xB3, 0xD1, 0x95, 0xA6] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F8G16ADBDAWP".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 224,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
+    Some(info)
+}
 
-        // Toshiba x16 variants
-        // TC58NVG0S3HTA00 - 128MB SLC x16
-        [0x98, 0xA1, 0x80, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG0S3HTA00".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // TC58NVG1S3HTA00 - 256MB SLC x16
-        [0x98, 0xCA, 0x90, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG1S3HTA00".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // TC58NVG2S3HTA00 - 512MB SLC x16
-        [0x98, 0xCC, 0x90, 0x15, ..] => Some(NandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58NVG2S3HTA00".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
+// ============================================================================
+// Extended READ ID Decoding
+// ============================================================================
 
-        // Macronix x16 variants
-        // MX30LF1G18AC - 128MB SLC x16
-        [0xC2, 0xA1, 0x80, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30LF1G18AC".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
-        // MX30LF2G18AC-TI - 256MB SLC x16
-        [0xC2, 0xCA, 0x90, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30LF2G18AC-TI".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 16,
-            cell_type: CellType::SLC,
-        }),
+/// Geometry decoded from the 3rd-6th READ ID bytes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ExtendedIdInfo {
+    pub page_size: u32,
+    pub oob_size: u32,
+    pub pages_per_block: u32,
+    pub bus_width: u8,
+    pub cell_type: CellType,
+    pub ecc_requirement: Option<NandEccRequirement>,
+    /// Data must be randomized before programming (Hynix MLC/TLC)
+    pub needs_scrambling: bool,
+}
 
-        // ============ New Chips v2.2 ============
+/// Significant length of a READ ID response
+///
+/// Chips repeat their ID when more bytes are clocked out than they have, or
+/// return zeros. The repeat period (or the position of the last non-zero
+/// byte) gives the real ID length.
+pub fn nand_id_len(id: &[u8]) -> usize {
+    let last_nonzero = match id.iter().rposition(|&b| b != 0) {
+        Some(pos) => pos,
+        None => return 0,
+    };
 
-        // Samsung K9F Series (new models)
-        // K9F1G08U0E - 128MB SLC (new revision)
-        [0xEC, 0xF1, 0x00, 0x95, 0x42] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F1G08U0E".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9F2G08U0E - 256MB SLC (new revision)
-        [0xEC, 0xDA, 0x10, 0x95, 0x46] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9F2G08U0E".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // K9GBG08U0A - 4GB MLC
-        [0xEC, 0xD7, 0x94, 0x7A, 0x54] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9GBG08U0A".into(),
-            size_mb: 4096,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 640,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // K9GBG08U0B - 4GB MLC (new revision)
-        [0xEC, 0xD7, 0x94, 0x7E, 0x64] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9GBG08U0B".into(),
-            size_mb: 4096,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 1024,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // K9LCG08U0A - 8GB MLC
-        [0xEC, 0xDE, 0xD5, 0x7A, 0x58] => Some(NandChipInfo {
-            manufacturer: "Samsung".into(),
-            model: "K9LCG08U0A".into(),
-            size_mb: 8192,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 640,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-
-        // Micron MT29F Series (new models)
-        // MT29F32G08CBADAWP - 4GB MLC
-        [0x2C, 0x44, 0x44, 0x4B, 0xA9] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F32G08CBADAWP".into(),
-            size_mb: 4096,
-            page_size: 8192,
-            block_size: 256,
-            oob_size: 744,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // MT29F64G08CBABAWP - 8GB MLC
-        [0x2C, 0x64, 0x44, 0x4B, 0xA9] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F64G08CBABAWP".into(),
-            size_mb: 8192,
-            page_size: 8192,
-            block_size: 256,
-            oob_size: 744,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // MT29F128G08CFABAWP - 16GB MLC
-        [0x2C, 0x88, 0x04, 0x4B, 0xA9] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F128G08CFABAWP".into(),
-            size_mb: 16384,
-            page_size: 8192,
-            block_size: 256,
-            oob_size: 744,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // MT29F256G08CJAABWP - 32GB TLC
-        [0x2C, 0xA8, 0x05, 0xCB, 0xA9] => Some(NandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F256G08CJAABWP".into(),
-            size_mb: 32768,
-            page_size: 16384,
-            block_size: 512,
-            oob_size: 1872,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::TLC,
-        }),
-
-        // SK Hynix H27 Series (new models)
-        // H27U8G8T2BTR - 1GB SLC
-        [0xAD, 0xD3, 0x90, 0x2D, 0x64] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27U8G8T2BTR".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 224,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // H27UBG8T2ATR - 4GB MLC
-        [0xAD, 0xD7, 0x94, 0x91, 0x60] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27UBG8T2ATR".into(),
-            size_mb: 4096,
-            page_size: 8192,
-            block_size: 256,
-            oob_size: 640,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // H27UCG8T2ETR - 8GB MLC
-        [0xAD, 0xDE, 0x94, 0xEB, 0x74] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27UCG8T2ETR".into(),
-            size_mb: 8192,
-            page_size: 16384,
-            block_size: 256,
-            oob_size: 1664,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // H27QDG8VEBIR - 16GB TLC
-        [0xAD, 0x3A, 0x14, 0xAB, 0x42] => Some(NandChipInfo {
-            manufacturer: "SK Hynix".into(),
-            model: "H27QDG8VEBIR".into(),
-            size_mb: 16384,
-            page_size: 16384,
-            block_size: 512,
-            oob_size: 1872,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::TLC,
-        }),
-
-        // Kioxia/Toshiba TC58 Series (new models)
-        // TC58NVG3S0FTA00 - 1GB SLC
-        [0x98, 0xD3, 0x90, 0x26, 0x76] => Some(NandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58NVG3S0FTA00".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 232,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // TC58NVG4D2FTA00 - 2GB MLC
-        [0x98, 0xD5, 0x94, 0x32, 0x76] => Some(NandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58NVG4D2FTA00".into(),
-            size_mb: 2048,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 448,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // TC58NVG5D2HTA00 - 4GB MLC
-        [0x98, 0xD7, 0x94, 0x32, 0x76] => Some(NandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58NVG5D2HTA00".into(),
-            size_mb: 4096,
-            page_size: 8192,
-            block_size: 128,
-            oob_size: 640,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // TC58TEG6DDKTA00 - 8GB MLC
-        [0x98, 0xDE, 0x94, 0x93, 0x76] => Some(NandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58TEG6DDKTA00".into(),
-            size_mb: 8192,
-            page_size: 16384,
-            block_size: 256,
-            oob_size: 1280,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::MLC,
-        }),
-        // TC58TFG7DDLTA0D - 16GB TLC (BiCS)
-        [0x98, 0x3A, 0x94, 0x93, 0x76] => Some(NandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58TFG7DDLTA0D".into(),
-            size_mb: 16384,
-            page_size: 16384,
-            block_size: 384,
-            oob_size: 1872,
-            voltage: "3.3V".into(),
-            timing: fast_timing(),
-            bus_width: 8,
-            cell_type: CellType::TLC,
-        }),
-
-        // Macronix MX30LF Series (new models)
-        // MX30LF4G28AD - 512MB SLC
-        [0xC2, 0xDC, 0x90, 0xA6, 0x54] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30LF4G28AD".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 256,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // MX30UF1G28AD - 128MB SLC 1.8V
-        [0xC2, 0xF1, 0x80, 0x1D, 0x42] => Some(NandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX30UF1G28AD".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-
-        // Winbond W29N Series (new models)
-        // W29N02GVSIAA - 256MB SLC
-        [0xEF, 0xDA, 0x10, 0x95, 0x44] => Some(NandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W29N02GVSIAA".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // W29N04GVSIAA - 512MB SLC
-        [0xEF, 0xDC, 0x10, 0x95, 0x54] => Some(NandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W29N04GVSIAA".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // W29N08GVSIAA - 1GB SLC
-        [0xEF, 0xD3, 0x10, 0x95, 0x58] => Some(NandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W29N08GVSIAA".into(),
-            size_mb: 1024,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+    let has_period = |period: usize| (period..id.len()).all(|i| id[i] == id[i % period]);
+    if let Some(period) = (1..id.len()).find(|&p| has_period(p)) {
+        return period;
+    }
 
-        // ESMT F59L Series
-        // F59L1G81LA - 128MB SLC
-        [0x92, 0xF1, 0x80, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "ESMT".into(),
-            model: "F59L1G81LA".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // F59L2G81A - 256MB SLC
-        [0x92, 0xDA, 0x90, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "ESMT".into(),
-            model: "F59L2G81A".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
-            bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
-        // F59L4G81A - 512MB SLC
-        [0x92, 0xDC, 0x90, 0x95, ..] => Some(NandChipInfo {
-            manufacturer: "ESMT".into(),
-            model: "F59L4G81A".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            timing: NandTiming::default(),
+    last_nonzero + 1
+}
+
+fn bits_per_cell(cell_info: u8) -> u8 {
+    ((cell_info & 0x0C) >> 2) + 1
+}
+
+fn cell_type_from_bits(bits: u8) -> CellType {
+    match bits {
+        1 => CellType::SLC,
+        2 => CellType::MLC,
+        3 => CellType::TLC,
+        _ => CellType::QLC,
+    }
+}
+
+fn ecc_requirement(bits: u8, codeword_size: u32) -> Option<NandEccRequirement> {
+    Some(NandEccRequirement {
+        bits,
+        codeword_size,
+    })
+}
+
+/// Decode geometry from the extended READ ID bytes
+///
+/// Uses the vendor schemes for Samsung, SK Hynix and Toshiba and the common
+/// 4th-byte scheme for everyone else. `jedec_id_valid` tells the Hynix
+/// decoder whether the chip answered READ ID 0x40 with "JEDEC" (modern
+/// Toggle DDR parts use a different layout). Returns `None` if fewer than
+/// four significant ID bytes are present.
+pub fn decode_extended_id(chip_id: &[u8], jedec_id_valid: bool) -> Option<ExtendedIdInfo> {
+    let len = nand_id_len(chip_id);
+    if len < 4 {
+        return None;
+    }
+    let id = &chip_id[..len];
+
+    Some(match id[0] {
+        manufacturers::SAMSUNG => decode_samsung_id(id),
+        manufacturers::HYNIX => decode_hynix_id(id, jedec_id_valid),
+        manufacturers::TOSHIBA => decode_toshiba_id(id),
+        _ => decode_ext_id(id),
+    })
+}
+
+/// Common scheme: page, OOB, block size and bus width in the 4th byte
+fn decode_ext_id(id: &[u8]) -> ExtendedIdInfo {
+    let mut ext = id[3];
+
+    let page_size = 1024u32 << (ext & 0x03);
+    ext >>= 2;
+    let oob_size = (8u32 << (ext & 0x01)) * (page_size >> 9);
+    ext >>= 2;
+    // Block size is a multiple of 64KiB
+    let pages_per_block = ((64 * 1024u32) << (ext & 0x03)) / page_size;
+    ext >>= 2;
+    let bus_width = if ext & 0x01 != 0 { 16 } else { 8 };
+
+    ExtendedIdInfo {
+        page_size,
+        oob_size,
+        pages_per_block,
+        bus_width,
+        cell_type: cell_type_from_bits(bits_per_cell(id[2])),
+        ecc_requirement: None,
+        needs_scrambling: false,
+    }
+}
+
+fn decode_samsung_id(id: &[u8]) -> ExtendedIdInfo {
+    let slc = bits_per_cell(id[2]) == 1;
+
+    // New 6-byte ID scheme for MLC/TLC
+    if id.len() == 6 && !slc && id[5] != 0x00 {
+        let mut ext = id[3];
+        let page_size = 2048u32 << (ext & 0x03);
+        ext >>= 2;
+        let oob_size = match ((ext >> 2) & 0x04) | (ext & 0x03) {
+            1 => 128,
+            2 => 218,
+            3 => 400,
+            4 => 436,
+            5 => 512,
+            6 => 640,
+            _ => 1024, // Unlisted value, best guess
+        };
+        ext >>= 2;
+        let block_bytes = (128 * 1024u32) << (((ext >> 1) & 0x04) | (ext & 0x03));
+
+        // ECC requirement in the 5th byte
+        let ecc_level = (id[4] >> 4) & 0x07;
+        let ecc = match ecc_level {
+            0..=4 => ecc_requirement(1 << ecc_level, 512),
+            5 => ecc_requirement(24, 1024),
+            6 => ecc_requirement(40, 1024),
+            _ => ecc_requirement(60, 1024),
+        };
+
+        return ExtendedIdInfo {
+            page_size,
+            oob_size,
+            pages_per_block: block_bytes / page_size,
             bus_width: 8,
-            cell_type: CellType::SLC,
-        }),
+            cell_type: cell_type_from_bits(bits_per_cell(id[2])),
+            ecc_requirement: ecc,
+            needs_scrambling: false,
+        };
+    }
+
+    let mut info = decode_ext_id(id);
+    // K9F4G08U0D requires 1-bit ECC
+    if slc && id[1] == 0xDC {
+        info.ecc_requirement = ecc_requirement(1, 512);
+    }
+    info
+}
+
+fn decode_hynix_id(id: &[u8], jedec_id_valid: bool) -> ExtendedIdInfo {
+    let bits = bits_per_cell(id[2]);
+
+    // SLC parts and short IDs use the common scheme
+    if id.len() < 6 || bits == 1 {
+        return decode_ext_id(id);
+    }
+
+    let page_size = 2048u32 << (id[3] & 0x03);
+
+    // Bit 7 selects 1MiB or 128KiB base units, shifted by bits 4-5;
+    // bits 4-5 = 3 without bit 7 means 768KiB
+    let shift = (id[3] >> 4) & 0x03;
+    let block_bytes = if id[3] & 0x80 != 0 {
+        (1024 * 1024u32) << shift
+    } else if shift == 3 {
+        768 * 1024
+    } else {
+        (128 * 1024u32) << shift
+    };
+
+    let oob_code = ((id[3] >> 2) & 0x03) | ((id[3] >> 4) & 0x04);
+    let oob_size = if jedec_id_valid {
+        match oob_code {
+            0 => 2048,
+            1 => 1664,
+            2 => 1024,
+            _ => 640,
+        }
+    } else {
+        let oob = match oob_code {
+            0 => 128,
+            1 => 224,
+            2 => 448,
+            3 => 64,
+            4 => 32,
+            5 => 16,
+            _ => 640,
+        };
+        // H27UCG8T2BTR encodes the spare size per 8KiB of page
+        if id[1] == 0xDE {
+            oob * (page_size / 8192).max(1)
+        } else {
+            oob
+        }
+    };
+
+    let ecc_level = (id[4] >> 4) & 0x07;
+    let nand_tech = id[5] & 0x07;
+    let ecc = if jedec_id_valid {
+        match ecc_level {
+            0 => None,
+            1 => ecc_requirement(4, 1024),
+            2 => ecc_requirement(24, 1024),
+            3 => ecc_requirement(32, 1024),
+            4 => ecc_requirement(40, 1024),
+            5 => ecc_requirement(50, 1024),
+            _ => ecc_requirement(60, 1024),
+        }
+    } else if nand_tech < 3 {
+        // > 26nm
+        match ecc_level {
+            0..=4 => ecc_requirement(1 << ecc_level, 512),
+            5 => ecc_requirement(24, 2048),
+            6 => ecc_requirement(24, 1024),
+            _ => None,
+        }
+    } else {
+        // <= 26nm
+        match ecc_level {
+            0 => None,
+            1..=4 => ecc_requirement(1 << (ecc_level - 1), 512),
+            _ => ecc_requirement(24 + 8 * (ecc_level - 5), 1024),
+        }
+    };
+
+    // TLC always, MLC below 3x/32nm
+    let needs_scrambling = bits > 2
+        || if jedec_id_valid {
+            id[5] >> 4 > 0
+        } else {
+            nand_tech > 2
+        };
+
+    ExtendedIdInfo {
+        page_size,
+        oob_size,
+        pages_per_block: block_bytes / page_size,
+        bus_width: 8,
+        cell_type: cell_type_from_bits(bits),
+        ecc_requirement: ecc,
+        needs_scrambling,
+    }
+}
+
+fn decode_toshiba_id(id: &[u8]) -> ExtendedIdInfo {
+    let mut info = decode_ext_id(id);
+
+    if id.len() >= 6 && info.cell_type == CellType::SLC {
+        let process = id[5] & 0x07;
+        let benand = id[4] & 0x80 != 0;
+
+        // 24nm raw SLC (not BENAND) has 32 spare bytes per 512 bytes
+        if process == 0x06 && !benand {
+            info.oob_size = 32 * info.page_size / 512;
+        }
+
+        // 43nm: 1 bit, 32nm: 4 bits, 24nm: 8 bits per 512 bytes
+        info.ecc_requirement = match process {
+            0x04 => ecc_requirement(1, 512),
+            0x05 => ecc_requirement(4, 512),
+            0x06 => ecc_requirement(8, 512),
+            _ => None,
+        };
+    }
+    info
+}
+
+// ============================================================================
+// ONFI Parameter Page
+// ============================================================================
+
+/// Size of one parameter page copy
+pub const ONFI_PARAM_PAGE_SIZE: usize = 256;
+
+/// READ PARAMETER PAGE command
+pub const READ_PARAM_PAGE_CMD: u8 = 0xEC;
+
+/// READ PARAMETER PAGE address for the ONFI page
+pub const ONFI_PARAM_PAGE_ADDR: u8 = 0x00;
+
+/// CRC-16 seed defined by ONFI (ASCII "ON")
+const ONFI_CRC_BASE: u16 = 0x4F4E;
+
+/// ONFI CRC-16 (polynomial 0x8005, MSB first) over a parameter page
+pub fn onfi_crc16(data: &[u8]) -> u16 {
+    let mut crc = ONFI_CRC_BASE;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// Feature support flags (parameter page bytes 6-7)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct OnfiFeatures {
+    pub x16: bool,
+    pub multi_lun: bool,
+    pub non_sequential_program: bool,
+    pub multi_plane: bool,
+    pub nv_ddr: bool,
+    pub multi_plane_read: bool,
+    pub extended_param_page: bool,
+    pub nv_ddr2: bool,
+    pub nv_ddr3: bool,
+}
 
-        _ => None,
+impl OnfiFeatures {
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            x16: bits & (1 << 0) != 0,
+            multi_lun: bits & (1 << 1) != 0,
+            non_sequential_program: bits & (1 << 2) != 0,
+            multi_plane: bits & (1 << 3) != 0,
+            nv_ddr: bits & (1 << 5) != 0,
+            multi_plane_read: bits & (1 << 6) != 0,
+            extended_param_page: bits & (1 << 7) != 0,
+            nv_ddr2: bits & (1 << 10) != 0,
+            nv_ddr3: bits & (1 << 13) != 0,
+        }
     }
 }
 
-/// Generic chip detection based on device ID byte
-fn get_chip_info_generic(mfr: u8, device: u8) -> Option<NandChipInfo> {
-    let manufacturer = get_manufacturer_name(mfr).to_string();
+/// Optional command support (parameter page bytes 8-9)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct OnfiOptionalCommands {
+    pub cache_program: bool,
+    pub cache_read: bool,
+    pub get_set_features: bool,
+    pub read_status_enhanced: bool,
+    pub copyback: bool,
+    pub read_unique_id: bool,
+    pub change_read_column_enhanced: bool,
+}
 
-    // Decode device ID according to ONFI conventions
-    // x8 devices use 0xFx, 0xDx series
-    // x16 devices use 0xAx, 0xBx, 0xCx series (same capacity, different bus width)
-    let (size_mb, page_size, block_size, cell_type, bus_width) = match device {
-        // ============ x8 devices ============
-        // 128MB class x8
-        0xF1 => (128, 2048, 64, CellType::SLC, 8),
-        // 256MB class x8
-        0xDA => (256, 2048, 64, CellType::SLC, 8),
-        // 512MB class x8
-        0xDC => (512, 2048, 64, CellType::SLC, 8),
-        // 1GB class x8
-        0xD3 => (1024, 4096, 64, CellType::SLC, 8),
-        // 2GB class x8 (often MLC)
-        0xD5 => (2048, 4096, 128, CellType::MLC, 8),
-        // 4GB class x8
-        0xD7 => (4096, 4096, 128, CellType::MLC, 8),
-        // 8GB class x8
-        0xDE => (8192, 8192, 256, CellType::MLC, 8),
-        // 16GB+ class x8
-        0x48 => (2048, 4096, 256, CellType::MLC, 8),
-        0x68 => (4096, 8192, 256, CellType::MLC, 8),
-        0x88 => (8192, 8192, 256, CellType::TLC, 8),
+impl OnfiOptionalCommands {
+    pub fn from_bits(bits: u16) -> Self {
+        Self {
+            cache_program: bits & (1 << 0) != 0,
+            cache_read: bits & (1 << 1) != 0,
+            get_set_features: bits & (1 << 2) != 0,
+            read_status_enhanced: bits & (1 << 3) != 0,
+            copyback: bits & (1 << 4) != 0,
+            read_unique_id: bits & (1 << 5) != 0,
+            change_read_column_enhanced: bits & (1 << 6) != 0,
+        }
+    }
+}
 
-        // ============ x16 devices ============
-        // 128MB class x16
-        0xA1 => (128, 2048, 64, CellType::SLC, 16),
-        // 256MB class x16
-        0xCA => (256, 2048, 64, CellType::SLC, 16),
-        // 512MB class x16
-        0xCC => (512, 2048, 64, CellType::SLC, 16),
-        // 1GB class x16
-        0xB3 => (1024, 4096, 64, CellType::SLC, 16),
-        // 2GB class x16 (often MLC)
-        0xC5 => (2048, 4096, 128, CellType::MLC, 16),
-        // 4GB class x16
-        0xB7 => (4096, 4096, 128, CellType::MLC, 16),
-        // 8GB class x16
-        0xBE => (8192, 8192, 256, CellType::MLC, 16),
+/// Decoded ONFI parameter page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OnfiParameterPage {
+    /// Copy (0, 1, 2, ...) that passed the CRC check
+    pub copy: usize,
+    pub version: OnfiVersion,
+    pub features: OnfiFeatures,
+    pub optional_commands: OnfiOptionalCommands,
+    pub manufacturer: String,
+    pub model: String,
+    pub jedec_manufacturer_id: u8,
+    pub page_size: u32,
+    pub oob_size: u32,
+    pub pages_per_block: u32,
+    pub blocks_per_lun: u32,
+    pub luns: u8,
+    pub row_address_cycles: u8,
+    pub column_address_cycles: u8,
+    pub bits_per_cell: u8,
+    pub max_bad_blocks_per_lun: u16,
+    /// Program/erase cycles per block
+    pub block_endurance: u32,
+    /// Blocks at the start of the target guaranteed valid when shipped
+    pub guaranteed_valid_blocks: u8,
+    /// Program/erase cycles for the guaranteed valid blocks
+    pub guaranteed_block_endurance: u32,
+    pub programs_per_page: u8,
+    /// Required ECC correctability in bits (0xFF = see extended page)
+    pub ecc_bits: u8,
+    pub planes: u8,
+    /// Supported SDR timing modes (bit N = mode N)
+    pub sdr_timing_modes: u16,
+    /// Supported NV-DDR timing modes (bit N = mode N)
+    pub nv_ddr_timing_modes: u8,
+    /// Supported NV-DDR2 timing modes (bit N = mode N)
+    pub nv_ddr2_timing_modes: u8,
+    /// Maximum page program time (microseconds)
+    pub t_prog_us: u16,
+    /// Maximum block erase time (microseconds)
+    pub t_bers_us: u16,
+    /// Maximum page read time (microseconds)
+    pub t_r_us: u16,
+    /// Minimum change column setup time (nanoseconds)
+    pub t_ccs_ns: u16,
+}
 
-        _ => return None,
-    };
+impl OnfiParameterPage {
+    /// Parse a parameter page read, trying each redundant copy in turn
+    ///
+    /// `data` may hold several consecutive 256-byte copies (ONFI requires at
+    /// least three). The first copy with a valid signature and CRC is used.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        data.chunks_exact(ONFI_PARAM_PAGE_SIZE)
+            .enumerate()
+            .find_map(|(copy, page)| Self::parse_copy(page, copy))
+    }
+
+    fn parse_copy(data: &[u8], copy: usize) -> Option<Self> {
+        // Check ONFI signature "ONFI" and CRC over bytes 0-253
+        if &data[0..4] != b"ONFI" {
+            return None;
+        }
+        if onfi_crc16(&data[..254]) != u16::from_le_bytes([data[254], data[255]]) {
+            return None;
+        }
 
-    Some(NandChipInfo {
-        manufacturer,
-        model: format!("Generic 0x{:02X} x{}", device, bus_width),
-        size_mb,
-        page_size,
-        block_size,
-        oob_size: page_size / 32, // Typical OOB ratio
-        voltage: "3.3V".into(),
-        timing: NandTiming::default(),
-        bus_width,
-        cell_type,
-    })
+        let u16_at = |i: usize| u16::from_le_bytes([data[i], data[i + 1]]);
+        let u32_at =
+            |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+        let text_at = |range: std::ops::Range<usize>| {
+            String::from_utf8_lossy(&data[range]).trim().to_string()
+        };
+        // Endurance is stored as a value byte and a power-of-ten exponent byte
+        let endurance_at =
+            |i: usize| (data[i] as u32).saturating_mul(10u32.saturating_pow(data[i + 1] as u32));
+
+        Some(Self {
+            copy,
+            version: detect_onfi_version(data),
+            features: OnfiFeatures::from_bits(u16_at(6)),
+            optional_commands: OnfiOptionalCommands::from_bits(u16_at(8)),
+            manufacturer: text_at(32..44),
+            model: text_at(44..64),
+            jedec_manufacturer_id: data[64],
+            page_size: u32_at(80),
+            oob_size: u16_at(84) as u32,
+            pages_per_block: u32_at(92),
+            blocks_per_lun: u32_at(96),
+            luns: data[100],
+            row_address_cycles: data[101] & 0x0F,
+            column_address_cycles: data[101] >> 4,
+            bits_per_cell: data[102],
+            max_bad_blocks_per_lun: u16_at(103),
+            block_endurance: endurance_at(105),
+            guaranteed_valid_blocks: data[107],
+            guaranteed_block_endurance: endurance_at(108),
+            programs_per_page: data[110],
+            ecc_bits: data[112],
+            planes: 1u8.checked_shl((data[113] & 0x0F) as u32).unwrap_or(1),
+            sdr_timing_modes: u16_at(129),
+            nv_ddr_timing_modes: data[141],
+            nv_ddr2_timing_modes: data[142],
+            t_prog_us: u16_at(133),
+            t_bers_us: u16_at(135),
+            t_r_us: u16_at(137),
+            t_ccs_ns: u16_at(139),
+        })
+    }
+
+    /// Total capacity across all LUNs in bytes
+    pub fn size_bytes(&self) -> u64 {
+        self.blocks_per_lun as u64
+            * self.luns as u64
+            * self.pages_per_block as u64
+            * self.page_size as u64
+    }
+
+    /// Fastest supported SDR timing mode (0-5)
+    pub fn max_sdr_timing_mode(&self) -> u8 {
+        (0..=5u8)
+            .rev()
+            .find(|&mode| self.sdr_timing_modes & (1 << mode) != 0)
+            .unwrap_or(0)
+    }
+
+    pub fn cell_type(&self) -> CellType {
+        match self.bits_per_cell {
+            2 => CellType::MLC,
+            3 => CellType::TLC,
+            4 => CellType::QLC,
+            _ => CellType::SLC,
+        }
+    }
+
+    /// Supply voltages as "Vcc" or "Vcc/VccQ" for the fastest interface
+    ///
+    /// The parameter page has no Vcc field; NV-DDR3 implies 1.2V I/O and
+    /// NV-DDR2 implies 1.8V-capable I/O.
+    pub fn voltage(&self) -> String {
+        if self.features.nv_ddr3 {
+            "3.3V/1.2V".into()
+        } else if self.features.nv_ddr2 {
+            "3.3V/1.8V".into()
+        } else {
+            "3.3V".into()
+        }
+    }
+
+    /// ECC requirement; ONFI specifies it per 512 bytes, 0xFF defers to the extended page
+    pub fn ecc_requirement(&self) -> Option<NandEccRequirement> {
+        match self.ecc_bits {
+            0 | 0xFF => None,
+            bits => Some(NandEccRequirement {
+                bits,
+                codeword_size: 512,
+            }),
+        }
+    }
+
+    pub fn to_chip_info(&self) -> NandChipInfo {
+        NandChipInfo {
+            manufacturer: self.manufacturer.clone(),
+            model: self.model.clone(),
+            size_mb: (self.size_bytes() / 1024 / 1024) as u32,
+            page_size: self.page_size,
+            block_size: self.pages_per_block,
+            oob_size: self.oob_size,
+            voltage: self.voltage(),
+            timing: NandTiming {
+                tR: self.t_r_us.min(255) as u8,
+                ..onfi_sdr_timing(self.max_sdr_timing_mode())
+            },
+            bus_width: if self.features.x16 { 16 } else { 8 },
+            cell_type: self.cell_type(),
+            ecc_requirement: self.ecc_requirement(),
+            planes: self.planes,
+            luns: self.luns.max(1),
+            chip_enables: 1,
+        }
+    }
+}
+
+/// SDR timing parameters for an ONFI timing mode (0-5)
+pub fn onfi_sdr_timing(mode: u8) -> NandTiming {
+    let (t_rp, t_cls, t_rr, t_ar, t_rhw, t_whr) = match mode {
+        0 => (50, 50, 40, 25, 200, 120),
+        1 => (25, 25, 20, 10, 100, 80),
+        2 => (17, 15, 20, 10, 100, 80),
+        3 => (15, 10, 20, 10, 100, 60),
+        4 => (12, 10, 20, 10, 100, 60),
+        _ => (10, 10, 20, 10, 100, 60),
+    };
+    NandTiming {
+        tRP: t_rp,
+        tWP: t_rp,
+        tCLS: t_cls,
+        tALS: t_cls,
+        tRR: t_rr,
+        tAR: t_ar,
+        tCLR: if mode == 0 { 20 } else { 10 },
+        tRHW: t_rhw,
+        tWHR: t_whr,
+        ..NandTiming::default()
+    }
 }
 
-/// Parse ONFI parameter page (256 bytes)
+/// Parse ONFI parameter page (one or more 256-byte copies)
 pub fn parse_onfi_parameter_page(data: &[u8]) -> Option<NandChipInfo> {
-    if data.len() < 256 {
-        return None;
+    OnfiParameterPage::parse(data).map(|page| page.to_chip_info())
+}
+
+/// Where detected NAND chip information came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NandInfoSource {
+    ChipDatabase,
+    Onfi,
+    Jedec,
+    Generic,
+}
+
+/// Parameter page copies read during detection
+pub const PARAM_PAGE_COPIES: usize = 3;
+
+/// READ PARAMETER PAGE at `addr` followed by `len` data-out cycles
+pub fn read_parameter_page_packets(addr: u8, len: u16) -> [Packet; 3] {
+    [
+        Packet::new(Command::NandCmd, &[READ_PARAM_PAGE_CMD]),
+        Packet::new(Command::NandAddr, &[addr]),
+        Packet::new(Command::NandReadData, &len.to_le_bytes()),
+    ]
+}
+
+/// Read the parameter page at `addr` (`ONFI_PARAM_PAGE_ADDR` or
+/// `JEDEC_PARAM_PAGE_ADDR`), including its redundant copies
+///
+/// `transfer` sends a packet with the data that follows it and returns the
+/// requested number of bytes read back.
+pub fn read_parameter_page<E, T>(addr: u8, mut transfer: T) -> Result<Vec<u8>, E>
+where
+    T: FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, E>,
+{
+    let copy_size = if addr == JEDEC_PARAM_PAGE_ADDR {
+        JEDEC_PARAM_PAGE_SIZE
+    } else {
+        ONFI_PARAM_PAGE_SIZE
+    };
+    let len = copy_size * PARAM_PAGE_COPIES;
+    let [cmd, address, read] = read_parameter_page_packets(addr, len as u16);
+    transfer(&cmd, &[], 0)?;
+    transfer(&address, &[], 0)?;
+    transfer(&read, &[], len)
+}
+
+/// Detect a NAND chip from its ID and, if read, its parameter pages
+///
+/// Chip database entries take precedence, then the ONFI parameter page
+/// (READ PARAMETER PAGE at 00h), then the JEDEC one (at 40h), then device
+/// ID heuristics.
+pub fn detect_nand_chip(
+    chip_id: &[u8],
+    onfi_page: Option<&[u8]>,
+    jedec_page: Option<&[u8]>,
+) -> Option<(NandChipInfo, NandInfoSource)> {
+    if chip_id.len() >= 5 {
+        if let Some(info) = chip_database().lookup_nand(chip_id) {
+            return Some((info, NandInfoSource::ChipDatabase));
+        }
+    }
+
+    if let Some(info) = onfi_page.and_then(parse_onfi_parameter_page) {
+        return Some((info, NandInfoSource::Onfi));
+    }
+    if let Some(info) = jedec_page.and_then(parse_jedec_parameter_page) {
+        return Some((info, NandInfoSource::Jedec));
     }
 
-    // Check ONFI signature "ONFI"
-    if &data[0..4] != b"ONFI" {
+    if chip_id.len() < 2 {
         return None;
     }
+    get_chip_info_generic(chip_id).map(|info| (info, NandInfoSource::Generic))
+}
+
+// ============================================================================
+// Timing Mode Negotiation
+// ============================================================================
 
-    // Parse manufacturer (bytes 32-43)
-    let manufacturer = String::from_utf8_lossy(&data[32..44]).trim().to_string();
+/// ONFI SET FEATURES command
+pub const SET_FEATURES_CMD: u8 = 0xEF;
 
-    // Parse model (bytes 44-63)
-    let model = String::from_utf8_lossy(&data[44..64]).trim().to_string();
+/// ONFI feature address for the timing mode
+pub const ONFI_FEATURE_TIMING_MODE: u8 = 0x01;
 
-    // Parse geometry
-    let page_size = u32::from_le_bytes([data[80], data[81], data[82], data[83]]);
-    let oob_size = u16::from_le_bytes([data[84], data[85]]) as u32;
-    let pages_per_block = u32::from_le_bytes([data[92], data[93], data[94], data[95]]);
-    let blocks_per_lun = u32::from_le_bytes([data[96], data[97], data[98], data[99]]);
-    let luns = data[100];
+/// SDR tREA (RE# access time, ns) for an ONFI timing mode (0-5)
+pub fn onfi_sdr_trea(mode: u8) -> u8 {
+    match mode {
+        0 => 40,
+        1 => 30,
+        2 => 25,
+        3 | 4 => 20,
+        _ => 16,
+    }
+}
 
-    let total_blocks = blocks_per_lun * luns as u32;
-    let size_mb =
-        (total_blocks as u64 * pages_per_block as u64 * page_size as u64 / 1024 / 1024) as u32;
+/// Bus timing pushed to the firmware in a `BusConfig` packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusTimingConfig {
+    /// ONFI SDR timing mode (0-5)
+    pub mode: u8,
+    /// RE# pulse width (ns)
+    pub t_rp_ns: u8,
+    /// WE# pulse width (ns)
+    pub t_wp_ns: u8,
+    /// RE# access time (ns)
+    pub t_rea_ns: u8,
+}
 
-    // Parse timing (simplified)
-    let t_prog = u16::from_le_bytes([data[133], data[134]]);
-    let t_r = u16::from_le_bytes([data[139], data[140]]);
+impl BusTimingConfig {
+    pub fn for_mode(mode: u8) -> Self {
+        let mode = mode.min(5);
+        let timing = onfi_sdr_timing(mode);
+        Self {
+            mode,
+            t_rp_ns: timing.tRP,
+            t_wp_ns: timing.tWP,
+            t_rea_ns: onfi_sdr_trea(mode),
+        }
+    }
 
-    Some(NandChipInfo {
-        manufacturer,
-        model,
-        size_mb,
-        page_size,
-        block_size: pages_per_block,
-        oob_size,
-        voltage: "3.3V".into(),
-        timing: NandTiming {
-            tR: (t_r / 1000).min(255) as u8,
-            ..NandTiming::default()
-        },
-        bus_width: 8,
-        cell_type: CellType::SLC, // Would need to parse features byte
-    })
+    /// `BusConfig` packet: `[mode, tRP, tWP, tREA]`
+    pub fn to_packet(&self) -> Packet {
+        Packet::new(
+            Command::BusConfig,
+            &[self.mode, self.t_rp_ns, self.t_wp_ns, self.t_rea_ns],
+        )
+    }
+}
+
+/// SET FEATURES sequence selecting an ONFI timing mode on the chip
+pub fn set_timing_mode_packets(mode: u8) -> Vec<Packet> {
+    let mut packets = vec![
+        Packet::new(Command::NandCmd, &[SET_FEATURES_CMD]),
+        Packet::new(Command::NandAddr, &[ONFI_FEATURE_TIMING_MODE]),
+    ];
+    // P1 = timing mode, P2-P4 reserved
+    for param in [mode, 0, 0, 0] {
+        packets.push(Packet::new(Command::NandWriteData, &[param]));
+    }
+    packets
+}
+
+/// Timing modes to try, fastest first
+///
+/// Only modes set in the chip's `sdr_timing_modes` bitmap and no faster
+/// than `platform_max` are included. Mode 0 is always last since every
+/// ONFI chip powers up in it.
+pub fn timing_mode_candidates(sdr_timing_modes: u16, platform_max: u8) -> Vec<u8> {
+    let mut modes: Vec<u8> = (1..=platform_max.min(5))
+        .rev()
+        .filter(|&mode| sdr_timing_modes & (1 << mode) != 0)
+        .collect();
+    modes.push(0);
+    modes
+}
+
+/// Switch the chip and the programmer to the fastest working timing mode
+///
+/// For each candidate mode, `send` receives the SET FEATURES packets followed
+/// by the `BusConfig` packet, then `verify` checks the bus at that mode (e.g.
+/// by re-reading the parameter page). A mismatch drops back one mode; if
+/// nothing faster verifies, mode 0 is left applied.
+pub fn negotiate_timing_mode<E, S, V>(
+    page: &OnfiParameterPage,
+    platform_max: u8,
+    mut send: S,
+    mut verify: V,
+) -> Result<BusTimingConfig, E>
+where
+    S: FnMut(&[Packet]) -> Result<(), E>,
+    V: FnMut(&BusTimingConfig) -> Result<bool, E>,
+{
+    let mut config = BusTimingConfig::for_mode(0);
+    for mode in timing_mode_candidates(page.sdr_timing_modes, platform_max) {
+        config = BusTimingConfig::for_mode(mode);
+        let mut packets = set_timing_mode_packets(mode);
+        packets.push(config.to_packet());
+        send(&packets)?;
+        if verify(&config)? {
+            break;
+        }
+    }
+    Ok(config)
+}
+
+// ============================================================================
+// Multi-Die (CE# / LUN) Topology
+// ============================================================================
+
+/// Maximum CE# lines the programmer can drive
+pub const MAX_CHIP_ENABLES: u8 = 4;
+
+/// READ STATUS ENHANCED: status of the LUN selected by the row address
+pub const READ_STATUS_ENHANCED_CMD: u8 = 0x78;
+
+/// One die: a CE# line and a LUN behind it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NandTarget {
+    pub ce: u8,
+    pub lun: u8,
+}
+
+/// Page address within a multi-die package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NandAddress {
+    pub ce: u8,
+    pub lun: u8,
+    /// Block within the LUN
+    pub block: u32,
+    /// Page within the block
+    pub page: u32,
+}
+
+/// Consecutive blocks of a flat block range that sit on one die
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NandBlockRun {
+    pub ce: u8,
+    /// First block with the LUN bits above the block bits, so that
+    /// `(block << page_bits) | page` is the die's row address
+    pub first_block: u32,
+    pub block_count: u32,
+}
+
+/// Combined geometry of every die in a package
+///
+/// Blocks are numbered flat across targets in (ce, lun) order, so a
+/// `ChipProgrammer` sized with `total_blocks()` covers the whole package and
+/// `locate()` maps its block numbers back to a die.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NandTopology {
+    pub targets: Vec<NandTarget>,
+    pub blocks_per_lun: u32,
+    pub pages_per_block: u32,
+    pub page_size: u32,
+    pub oob_size: u32,
+}
+
+impl NandTopology {
+    /// Topology as described by the chip info, without probing
+    pub fn from_chip_info(info: &NandChipInfo) -> Self {
+        let targets = (0..info.chip_enables.max(1))
+            .flat_map(|ce| (0..info.luns.max(1)).map(move |lun| NandTarget { ce, lun }))
+            .collect();
+        Self {
+            targets,
+            blocks_per_lun: info.blocks_per_lun(),
+            pages_per_block: info.block_size,
+            page_size: info.page_size,
+            oob_size: info.oob_size,
+        }
+    }
+
+    /// Number of populated CE# lines
+    pub fn chip_enables(&self) -> u8 {
+        let mut ces: Vec<u8> = self.targets.iter().map(|t| t.ce).collect();
+        ces.dedup();
+        ces.len() as u8
+    }
+
+    pub fn total_blocks(&self) -> u32 {
+        self.targets.len() as u32 * self.blocks_per_lun
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.total_blocks() as u64 * self.pages_per_block as u64 * self.page_size as u64
+    }
+
+    /// Map a flat block number and page to a die address
+    pub fn locate(&self, block: u32, page: u32) -> Option<NandAddress> {
+        if self.blocks_per_lun == 0 || page >= self.pages_per_block {
+            return None;
+        }
+        let target = self.targets.get((block / self.blocks_per_lun) as usize)?;
+        Some(NandAddress {
+            ce: target.ce,
+            lun: target.lun,
+            block: block % self.blocks_per_lun,
+            page,
+        })
+    }
+
+    /// Flat block number for a die address
+    pub fn flat_block(&self, addr: &NandAddress) -> Option<u32> {
+        if addr.block >= self.blocks_per_lun {
+            return None;
+        }
+        let index = self
+            .targets
+            .iter()
+            .position(|t| t.ce == addr.ce && t.lun == addr.lun)?;
+        Some(index as u32 * self.blocks_per_lun + addr.block)
+    }
+
+    /// ONFI row address: page, then block, then LUN bits
+    pub fn row_address(&self, addr: &NandAddress) -> u32 {
+        let page_bits = address_bits(self.pages_per_block);
+        let block_bits = address_bits(self.blocks_per_lun);
+        ((addr.lun as u32) << (page_bits + block_bits)) | (addr.block << page_bits) | addr.page
+    }
+
+    /// `NandSelectCe` followed by a page command (`NandReadPage` or `NandWritePage`)
+    pub fn page_packets(&self, cmd: Command, addr: &NandAddress, len: u16) -> [Packet; 2] {
+        let row = self.row_address(addr).to_le_bytes();
+        let len = len.to_le_bytes();
+        [
+            select_ce_packet(addr.ce),
+            Packet::new(cmd, &[row[0], row[1], row[2], row[3], len[0], len[1]]),
+        ]
+    }
+
+    /// Split a flat block range into runs that each stay on one die
+    pub fn block_runs(&self, first_block: u32, block_count: u32) -> Option<Vec<NandBlockRun>> {
+        let end = first_block.checked_add(block_count)?;
+        let block_bits = address_bits(self.blocks_per_lun);
+        let mut runs = Vec::new();
+        let mut block = first_block;
+        while block < end {
+            let addr = self.locate(block, 0)?;
+            let count = (self.blocks_per_lun - addr.block).min(end - block);
+            runs.push(NandBlockRun {
+                ce: addr.ce,
+                first_block: ((addr.lun as u32) << block_bits) | addr.block,
+                block_count: count,
+            });
+            block += count;
+        }
+        Some(runs)
+    }
+
+    /// READ STATUS ENHANCED for a LUN on the currently selected CE#
+    ///
+    /// `transfer` sends a packet with the data that follows it and returns
+    /// the requested number of bytes read back.
+    pub fn read_status<E, T>(&self, target: NandTarget, mut transfer: T) -> Result<u8, E>
+    where
+        T: FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, E>,
+    {
+        let row = self
+            .row_address(&NandAddress {
+                ce: target.ce,
+                lun: target.lun,
+                block: 0,
+                page: 0,
+            })
+            .to_le_bytes();
+        let cmd = Packet::new(Command::NandCmd, &[READ_STATUS_ENHANCED_CMD]);
+        transfer(&cmd, &[], 0)?;
+        for cycle in &row[..3] {
+            transfer(&Packet::new(Command::NandAddr, &[*cycle]), &[], 0)?;
+        }
+        let read = Packet::new(Command::NandReadData, &1u16.to_le_bytes());
+        let status = transfer(&read, &[], 1)?;
+        // A missing byte reads as a floating bus
+        Ok(status.first().copied().unwrap_or(0xFF))
+    }
+
+    /// Chip info covering the whole package
+    pub fn combined_chip_info(&self, info: &NandChipInfo) -> NandChipInfo {
+        let chip_enables = self.chip_enables().max(1);
+        let luns = (self.targets.len() as u32 / chip_enables as u32).max(1) as u8;
+        NandChipInfo {
+            size_mb: (self.size_bytes() / chip_enables as u64 / 1024 / 1024) as u32,
+            luns,
+            chip_enables,
+            ..info.clone()
+        }
+    }
+}
+
+/// Row address bits needed to count up to `n`
+pub(crate) fn address_bits(n: u32) -> u32 {
+    n.max(1).next_power_of_two().trailing_zeros()
+}
+
+/// Make `ce` the active CE# for subsequent NAND commands
+pub fn select_ce_packet(ce: u8) -> Packet {
+    Packet::new(Command::NandSelectCe, &[ce])
+}
+
+/// A LUN answering READ STATUS ENHANCED reports ready with a sane value
+fn lun_status_valid(status: u8) -> bool {
+    status != 0xFF && status & 0x40 != 0
+}
+
+/// Probe each CE# and LUN and build the package topology
+///
+/// `read_id` returns the READ ID bytes seen on a CE#; targets whose ID
+/// differs from CE# 0 (or that float) are skipped. LUNs beyond 0 are probed
+/// with `read_status`, which issues READ STATUS ENHANCED for the target.
+pub fn enumerate_nand_targets<E, I, S>(
+    info: &NandChipInfo,
+    max_chip_enables: u8,
+    mut read_id: I,
+    mut read_status: S,
+) -> Result<NandTopology, E>
+where
+    I: FnMut(u8) -> Result<Vec<u8>, E>,
+    S: FnMut(NandTarget) -> Result<u8, E>,
+{
+    let reference = read_id(0)?;
+    let id_len = nand_id_len(&reference);
+    let mut topology = NandTopology::from_chip_info(info);
+    topology.targets.clear();
+
+    for ce in 0..max_chip_enables.clamp(1, MAX_CHIP_ENABLES) {
+        if ce > 0 {
+            let id = read_id(ce)?;
+            if id_len == 0 || id.len() < id_len || id[..id_len] != reference[..id_len] {
+                continue;
+            }
+        }
+        topology.targets.push(NandTarget { ce, lun: 0 });
+        for lun in 1..info.luns.max(1) {
+            let target = NandTarget { ce, lun };
+            if lun_status_valid(read_status(target)?) {
+                topology.targets.push(target);
+            }
+        }
+    }
+
+    Ok(topology)
 }
 
 // ============================================================================
@@ -1601,6 +1707,79 @@ mod tests {
         assert_eq!(chip_info.size_mb, 256);
     }
 
+    #[test]
+    fn test_nand_id_len() {
+        assert_eq!(
+            nand_id_len(&[0xAD, 0xD7, 0x94, 0x9A, 0x74, 0x42, 0xAD, 0xD7]),
+            6
+        );
+        assert_eq!(
+            nand_id_len(&[0xEC, 0xF1, 0x00, 0x95, 0x40, 0x00, 0x00, 0x00]),
+            5
+        );
+        assert_eq!(nand_id_len(&[0xEC, 0xD5, 0x84, 0x72, 0x50, 0x42]), 6);
+        assert_eq!(nand_id_len(&[0x00; 8]), 0);
+    }
+
+    #[test]
+    fn test_extended_id_samsung_mlc() {
+        // K9GAG08U0E: 8KiB page, 436 OOB, 1MiB block, 24 bits/1KiB
+        let ext = decode_extended_id(&[0xEC, 0xD5, 0x84, 0x72, 0x50, 0x42], false).unwrap();
+        assert_eq!(ext.page_size, 8192);
+        assert_eq!(ext.oob_size, 436);
+        assert_eq!(ext.pages_per_block, 128);
+        assert_eq!(ext.cell_type, CellType::MLC);
+        assert_eq!(
+            ext.ecc_requirement,
+            Some(NandEccRequirement {
+                bits: 24,
+                codeword_size: 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_extended_id_hynix_mlc() {
+        // 4KiB-page MLC with 224-byte OOB and 512KiB blocks
+        let chip_id = [0xAD, 0xD5, 0x94, 0x25, 0x44, 0x41, 0xAD, 0xD5];
+        let ext = decode_extended_id(&chip_id, false).unwrap();
+        assert_eq!(ext.page_size, 4096);
+        assert_eq!(ext.oob_size, 224);
+        assert_eq!(ext.pages_per_block, 128);
+        assert_eq!(ext.cell_type, CellType::MLC);
+        assert_eq!(ext.ecc_requirement.unwrap().bits, 16);
+        assert!(!ext.needs_scrambling);
+
+        // Unknown to the database, so geometry comes from the ID bytes
+        let info = get_chip_info(&chip_id).unwrap();
+        assert_eq!(info.page_size, 4096);
+        assert_eq!(info.oob_size, 224);
+        assert_eq!(info.block_size, 128);
+        assert_eq!(info.size_mb, 2048);
+    }
+
+    #[test]
+    fn test_extended_id_toshiba_slc() {
+        // TC58NVG2S0HTA00: 24nm SLC, 4KiB page with 256-byte OOB, 8 bits/512B
+        let ext = decode_extended_id(&[0x98, 0xDC, 0x90, 0x26, 0x76, 0x16], false).unwrap();
+        assert_eq!(ext.page_size, 4096);
+        assert_eq!(ext.oob_size, 256);
+        assert_eq!(ext.pages_per_block, 64);
+        assert_eq!(ext.cell_type, CellType::SLC);
+        assert_eq!(ext.ecc_requirement.unwrap().bits, 8);
+    }
+
+    #[test]
+    fn test_extended_id_common_scheme() {
+        // Micron 4th byte 0x95: 2KiB page, 64 OOB, 128KiB block, x8
+        let ext = decode_extended_id(&[0x2C, 0xDA, 0x90, 0x95, 0x06], false).unwrap();
+        assert_eq!(ext.page_size, 2048);
+        assert_eq!(ext.oob_size, 64);
+        assert_eq!(ext.pages_per_block, 64);
+        assert_eq!(ext.bus_width, 8);
+        assert!(decode_extended_id(&[0x2C, 0xDA], false).is_none());
+    }
+
     #[test]
     fn test_manufacturer_names() {
         assert_eq!(get_manufacturer_name(0xEC), "Samsung");
@@ -1834,6 +2013,10 @@ mod tests {
             timing: NandTiming::default(),
             bus_width: 8,
             cell_type: CellType::SLC,
+            ecc_requirement: None,
+            planes: 1,
+            luns: 1,
+            chip_enables: 1,
         };
 
         assert!(!chip.supports_x16());
@@ -2000,6 +2183,337 @@ mod tests {
         assert_eq!(chip_info.bus_width, 16);
         assert!(chip_info.supports_x16());
     }
+
+    /// Build a parameter page copy with a valid CRC
+    fn make_param_page() -> Vec<u8> {
+        let mut page = vec![0u8; ONFI_PARAM_PAGE_SIZE];
+        page[0..4].copy_from_slice(b"ONFI");
+        page[4..6].copy_from_slice(&0x03FEu16.to_le_bytes()); // up to ONFI 4.1
+        page[6..8].copy_from_slice(&((1u16 << 0) | (1 << 1) | (1 << 3) | (1 << 5)).to_le_bytes());
+        page[8..10].copy_from_slice(&0x004Fu16.to_le_bytes()); // cache, features, status enh., column enh.
+        page[32..44].copy_from_slice(b"MICRON      ");
+        page[44..64].copy_from_slice(b"MT29F64G08CBABA     ");
+        page[64] = 0x2C;
+        page[80..84].copy_from_slice(&8192u32.to_le_bytes());
+        page[84..86].copy_from_slice(&744u16.to_le_bytes());
+        page[92..96].copy_from_slice(&256u32.to_le_bytes());
+        page[96..100].copy_from_slice(&2048u32.to_le_bytes());
+        page[100] = 2;
+        page[101] = 0x23; // 2 column, 3 row cycles
+        page[102] = 2;
+        page[103..105].copy_from_slice(&50u16.to_le_bytes());
+        page[105] = 3;
+        page[106] = 3; // 3000 cycles
+        page[107] = 1;
+        page[108] = 1;
+        page[109] = 4; // 10000 cycles
+        page[110] = 1;
+        page[112] = 24;
+        page[113] = 1; // 2 planes
+        page[129..131].copy_from_slice(&0x001Fu16.to_le_bytes()); // modes 0-4
+        page[133..135].copy_from_slice(&2300u16.to_le_bytes());
+        page[135..137].copy_from_slice(&15000u16.to_le_bytes());
+        page[137..139].copy_from_slice(&75u16.to_le_bytes());
+        page[139..141].copy_from_slice(&200u16.to_le_bytes());
+        page[141] = 0x1F;
+        let crc = onfi_crc16(&page[..254]);
+        page[254..256].copy_from_slice(&crc.to_le_bytes());
+        page
+    }
+
+    #[test]
+    fn test_onfi_parameter_page_decoding() {
+        let page = OnfiParameterPage::parse(&make_param_page()).unwrap();
+
+        assert_eq!(page.copy, 0);
+        assert_eq!(page.version, OnfiVersion::Onfi40);
+        assert!(page.features.x16 && page.features.multi_lun && page.features.nv_ddr);
+        assert!(!page.features.nv_ddr2);
+        assert_eq!(page.manufacturer, "MICRON");
+        assert_eq!(page.model, "MT29F64G08CBABA");
+        assert_eq!(
+            (page.column_address_cycles, page.row_address_cycles),
+            (2, 3)
+        );
+        assert_eq!(page.block_endurance, 3000);
+        assert_eq!(page.guaranteed_valid_blocks, 1);
+        assert_eq!(page.guaranteed_block_endurance, 10000);
+        assert_eq!(page.planes, 2);
+        assert_eq!(page.max_sdr_timing_mode(), 4);
+        assert_eq!(
+            (page.t_prog_us, page.t_bers_us, page.t_r_us),
+            (2300, 15000, 75)
+        );
+        assert_eq!(page.t_ccs_ns, 200);
+
+        let info = page.to_chip_info();
+        assert_eq!(info.size_mb, 8192);
+        assert_eq!(info.bus_width, 16);
+        assert_eq!(info.cell_type, CellType::MLC);
+        assert_eq!(info.timing.tR, 75);
+        assert_eq!(info.timing.tRP, 12);
+        assert_eq!(info.planes, 2);
+        assert_eq!(info.ecc_requirement.unwrap().bits, 24);
+    }
+
+    #[test]
+    fn test_onfi_parameter_page_redundant_copies() {
+        let good = make_param_page();
+        let mut corrupted = good.clone();
+        corrupted[80] ^= 0x01;
+
+        // Bad first copy falls back to the second
+        let data = [corrupted.clone(), good.clone(), good].concat();
+        let page = OnfiParameterPage::parse(&data).unwrap();
+        assert_eq!(page.copy, 1);
+        assert_eq!(page.page_size, 8192);
+
+        // No valid copy at all
+        let data = [corrupted.clone(), corrupted.clone(), corrupted].concat();
+        assert!(parse_onfi_parameter_page(&data).is_none());
+        assert!(parse_onfi_parameter_page(&[0u8; 100]).is_none());
+    }
+
+    #[test]
+    fn test_detect_nand_chip_sources() {
+        // Known ID wins over the parameter page
+        let param_page = make_param_page();
+        let (info, source) =
+            detect_nand_chip(&[0xEC, 0xF1, 0x00, 0x95, 0x40], Some(&param_page), None).unwrap();
+        assert_eq!(source, NandInfoSource::ChipDatabase);
+        assert_eq!(info.model, "K9F1G08U0B");
+
+        // Unknown ID uses the ONFI page
+        let unknown_id = [0x2C, 0x68, 0x00, 0x27, 0xA9];
+        let (info, source) = detect_nand_chip(&unknown_id, Some(&param_page), None).unwrap();
+        assert_eq!(source, NandInfoSource::Onfi);
+        assert_eq!(info.model, "MT29F64G08CBABA");
+
+        // A blank ONFI read falls through to the JEDEC page
+        let jedec_page = crate::jedec::tests::make_jedec_page();
+        let (_, source) =
+            detect_nand_chip(&unknown_id, Some(&[0u8; 256]), Some(&jedec_page)).unwrap();
+        assert_eq!(source, NandInfoSource::Jedec);
+
+        // Without a usable page, fall back to ID heuristics
+        let (_, source) = detect_nand_chip(&unknown_id, Some(&[0u8; 256]), None).unwrap();
+        assert_eq!(source, NandInfoSource::Generic);
+        assert!(detect_nand_chip(&[0x2C], None, None).is_none());
+    }
+
+    #[test]
+    fn test_read_parameter_page() {
+        let mut sent = Vec::new();
+        let data = read_parameter_page(JEDEC_PARAM_PAGE_ADDR, |packet: &Packet, _: &[u8], len| {
+            sent.push((packet.cmd, packet.args[0], packet.args[1]));
+            Ok::<_, ()>(vec![0xA5; len])
+        })
+        .unwrap();
+
+        assert_eq!(data.len(), JEDEC_PARAM_PAGE_SIZE * PARAM_PAGE_COPIES);
+        assert_eq!(
+            sent,
+            vec![
+                (Command::NandCmd, READ_PARAM_PAGE_CMD, 0),
+                (Command::NandAddr, JEDEC_PARAM_PAGE_ADDR, 0),
+                (Command::NandReadData, 0x00, 0x06),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_timing_mode_candidates() {
+        // Chip supports modes 0-4, platform caps at 3
+        assert_eq!(timing_mode_candidates(0x001F, 3), vec![3, 2, 1, 0]);
+        // Sparse bitmap: only supported modes are tried
+        assert_eq!(timing_mode_candidates(0x0029, 5), vec![5, 3, 0]);
+        assert_eq!(timing_mode_candidates(0x0001, 5), vec![0]);
+    }
+
+    #[test]
+    fn test_bus_timing_packet() {
+        let config = BusTimingConfig::for_mode(4);
+        assert_eq!(
+            (config.t_rp_ns, config.t_wp_ns, config.t_rea_ns),
+            (12, 12, 20)
+        );
+
+        let bytes = config.to_packet().to_bytes();
+        assert_eq!(&bytes[..5], &[Command::BusConfig as u8, 4, 12, 12, 20]);
+
+        let packets = set_timing_mode_packets(4);
+        assert_eq!(packets.len(), 6);
+        assert_eq!(packets[0].args[0], SET_FEATURES_CMD);
+        assert_eq!(packets[1].args[0], ONFI_FEATURE_TIMING_MODE);
+        assert_eq!(packets[2].cmd, Command::NandWriteData);
+        assert_eq!(packets[2].args[0], 4);
+    }
+
+    #[test]
+    fn test_negotiate_timing_falls_back_on_mismatch() {
+        let page = OnfiParameterPage::parse(&make_param_page()).unwrap();
+        let mut sent = Vec::new();
+
+        let config = negotiate_timing_mode::<(), _, _>(
+            &page,
+            5,
+            |packets| {
+                sent.push(packets.last().unwrap().args[0]);
+                Ok(())
+            },
+            |config| Ok(config.mode <= 2),
+        )
+        .unwrap();
+
+        assert_eq!(config.mode, 2);
+        assert_eq!(sent, vec![4, 3, 2]);
+    }
+
+    fn dual_lun_chip() -> NandChipInfo {
+        let mut info = OnfiParameterPage::parse(&make_param_page())
+            .unwrap()
+            .to_chip_info();
+        info.luns = 2;
+        info
+    }
+
+    #[test]
+    fn test_topology_addressing() {
+        let info = NandChipInfo {
+            chip_enables: 2,
+            ..dual_lun_chip()
+        };
+        let topology = NandTopology::from_chip_info(&info);
+        assert_eq!(topology.targets.len(), 4);
+        assert_eq!(topology.blocks_per_lun, 2048);
+        assert_eq!(topology.total_blocks(), 8192);
+
+        let addr = topology.locate(2048 * 3 + 5, 7).unwrap();
+        assert_eq!(
+            addr,
+            NandAddress {
+                ce: 1,
+                lun: 1,
+                block: 5,
+                page: 7
+            }
+        );
+        assert_eq!(topology.flat_block(&addr), Some(2048 * 3 + 5));
+        assert!(topology.locate(8192, 0).is_none());
+
+        // 256 pages -> 8 bits, 2048 blocks -> 11 bits, LUN above them
+        assert_eq!(topology.row_address(&addr), (1 << 19) | (5 << 8) | 7);
+
+        let packets = topology.page_packets(Command::NandReadPage, &addr, 8936);
+        assert_eq!(packets[0].cmd, Command::NandSelectCe);
+        assert_eq!(packets[0].args[0], 1);
+        assert_eq!(
+            &packets[1].args[..4],
+            &((1u32 << 19) | (5 << 8) | 7).to_le_bytes()
+        );
+    }
+
+    #[test]
+    fn test_topology_block_runs() {
+        let info = NandChipInfo {
+            chip_enables: 2,
+            ..dual_lun_chip()
+        };
+        let topology = NandTopology::from_chip_info(&info);
+
+        // Blocks 2046..2052 cross from CE# 0 LUN 0 into LUN 1
+        let runs = topology.block_runs(2046, 6).unwrap();
+        assert_eq!(
+            runs,
+            vec![
+                NandBlockRun {
+                    ce: 0,
+                    first_block: 2046,
+                    block_count: 2
+                },
+                NandBlockRun {
+                    ce: 0,
+                    first_block: 1 << 11,
+                    block_count: 4
+                },
+            ]
+        );
+        // The run's block numbers already carry the LUN bits of the row address
+        let addr = topology.locate(2048 + 3, 9).unwrap();
+        assert_eq!(
+            topology.row_address(&addr),
+            ((runs[1].first_block + 3) << 8) | 9
+        );
+
+        let runs = topology.block_runs(4096, 2048).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].ce, 1);
+        assert!(topology.block_runs(8190, 4).is_none());
+    }
+
+    #[test]
+    fn test_topology_read_status() {
+        let topology = NandTopology::from_chip_info(&dual_lun_chip());
+        let mut sent = Vec::new();
+        let status = topology
+            .read_status(
+                NandTarget { ce: 0, lun: 1 },
+                |packet: &Packet, _: &[u8], len| {
+                    sent.push((packet.cmd, packet.args[0]));
+                    Ok::<_, ()>(vec![0xE0; len])
+                },
+            )
+            .unwrap();
+
+        assert_eq!(status, 0xE0);
+        // Row address 1 << 19: LUN bit in the third cycle
+        assert_eq!(
+            sent,
+            vec![
+                (Command::NandCmd, READ_STATUS_ENHANCED_CMD),
+                (Command::NandAddr, 0x00),
+                (Command::NandAddr, 0x00),
+                (Command::NandAddr, 0x08),
+                (Command::NandReadData, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enumerate_nand_targets() {
+        use crate::write_ops::ChipProgrammer;
+
+        let info = dual_lun_chip();
+        let id = vec![0x2C, 0x68, 0x00, 0x27, 0xA9];
+
+        // CE0 and CE2 populated, CE1 floats, CE3 is a different die
+        let topology = enumerate_nand_targets::<(), _, _>(
+            &info,
+            4,
+            |ce| {
+                Ok(match ce {
+                    0 | 2 => id.clone(),
+                    1 => vec![0xFF; 5],
+                    _ => vec![0xEC, 0xD3, 0x51, 0x95, 0x58],
+                })
+            },
+            |target| Ok(if target.ce == 2 { 0xFF } else { 0xE0 }),
+        )
+        .unwrap();
+
+        assert_eq!(
+            topology.targets,
+            vec![
+                NandTarget { ce: 0, lun: 0 },
+                NandTarget { ce: 0, lun: 1 },
+                NandTarget { ce: 2, lun: 0 },
+            ]
+        );
+        assert_eq!(topology.chip_enables(), 2);
+
+        let programmer = ChipProgrammer::for_topology(&topology, 3000);
+        assert_eq!(programmer.capacity(), topology.size_bytes());
+    }
 }
 
 #[cfg(test)]
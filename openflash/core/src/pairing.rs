@@ -0,0 +1,316 @@
+//! MLC/TLC page pairing and pseudo-SLC mode
+//!
+//! Multi-level cells store the lower (LSB), middle and upper (MSB) bits of
+//! a wordline in different pages, programmed in a vendor-specific staircase
+//! order. An interrupted upper-page program can destroy the already-written
+//! lower page, and boot ROMs often use only lower pages (pseudo-SLC mode).
+
+use crate::onfi::{CellType, NandChipInfo};
+use crate::write_ops::{RetryOp, WriteError, WriteResult};
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Page Pairing
+// ============================================================================
+
+/// Position of a page within its wordline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRole {
+    /// Wordline (for even/odd bitline parts, wordline half) within the block
+    pub wordline: u32,
+    /// Bit group: 0 = lower (LSB), 1 = upper (MLC) or middle (TLC), ...
+    pub group: u8,
+}
+
+/// Page-to-wordline mapping for one block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PagePairing {
+    bits_per_cell: u8,
+    roles: Vec<PageRole>,
+}
+
+impl PagePairing {
+    /// One page per wordline, nothing is paired
+    pub fn slc(pages_per_block: u32) -> Self {
+        Self {
+            bits_per_cell: 1,
+            roles: (0..pages_per_block)
+                .map(|wordline| PageRole { wordline, group: 0 })
+                .collect(),
+        }
+    }
+
+    /// Staircase program order
+    ///
+    /// Step `s` programs group `g` of wordline `s - g`, each wordline holding
+    /// `width` pages per group. MLC with `width` 1 gives the common
+    /// L0 L1 U0 L2 U1 ... order (upper pages 3 apart from their lower page);
+    /// `width` 2 gives the even/odd bitline L0 L0' L1 L1' U0 U0' ... order.
+    /// Fails unless `pages_per_block` is a whole number of wordlines.
+    pub fn staircase(bits_per_cell: u8, width: u8, pages_per_block: u32) -> WriteResult<Self> {
+        let bits = bits_per_cell.max(1) as u32;
+        let width = width.max(1) as u32;
+        if pages_per_block % (bits * width) != 0 {
+            return Err(WriteError::Unsupported(format!(
+                "{} pages per block is not a multiple of {} pages per wordline",
+                pages_per_block,
+                bits * width
+            )));
+        }
+        let wordlines = pages_per_block / (bits * width);
+
+        let mut roles = Vec::with_capacity(pages_per_block as usize);
+        for step in 0..wordlines + bits - 1 {
+            for group in 0..bits {
+                let Some(wordline) = step.checked_sub(group).filter(|&wl| wl < wordlines) else {
+                    continue;
+                };
+                for half in 0..width {
+                    roles.push(PageRole {
+                        wordline: wordline * width + half,
+                        group: group as u8,
+                    });
+                }
+            }
+        }
+
+        Ok(Self {
+            bits_per_cell: bits as u8,
+            roles,
+        })
+    }
+
+    /// Pairing used by the chip's vendor for its cell type
+    pub fn for_chip(info: &NandChipInfo) -> WriteResult<Self> {
+        let bits = match info.cell_type {
+            CellType::SLC => 1,
+            CellType::MLC => 2,
+            CellType::TLC => 3,
+            CellType::QLC => 4,
+        };
+        // Hynix MLC interleaves even and odd bitlines
+        let width = if bits == 2 && vendor_is(info, &["hynix"]) {
+            2
+        } else {
+            1
+        };
+        Self::staircase(bits, width, info.block_size)
+    }
+
+    pub fn bits_per_cell(&self) -> u8 {
+        self.bits_per_cell
+    }
+
+    pub fn pages_per_block(&self) -> u32 {
+        self.roles.len() as u32
+    }
+
+    pub fn role(&self, page: u32) -> Option<PageRole> {
+        self.roles.get(page as usize).copied()
+    }
+
+    pub fn is_lower(&self, page: u32) -> bool {
+        self.role(page).is_some_and(|r| r.group == 0)
+    }
+
+    /// Other pages sharing the wordline, in program order
+    pub fn paired_pages(&self, page: u32) -> Vec<u32> {
+        let Some(role) = self.role(page) else {
+            return Vec::new();
+        };
+        self.roles
+            .iter()
+            .enumerate()
+            .filter(|(p, r)| *p as u32 != page && r.wordline == role.wordline)
+            .map(|(p, _)| p as u32)
+            .collect()
+    }
+
+    /// Pages of the wordline programmed before `page` (at risk if it fails)
+    pub fn earlier_paired_pages(&self, page: u32) -> Vec<u32> {
+        self.paired_pages(page)
+            .into_iter()
+            .filter(|&p| p < page)
+            .collect()
+    }
+
+    /// Lower pages, the only ones written in pseudo-SLC mode
+    pub fn lower_pages(&self) -> Vec<u32> {
+        (0..self.pages_per_block())
+            .filter(|&p| self.is_lower(p))
+            .collect()
+    }
+}
+
+fn vendor_is(info: &NandChipInfo, names: &[&str]) -> bool {
+    let manufacturer = info.manufacturer.to_lowercase();
+    names.iter().any(|name| manufacturer.contains(name))
+}
+
+// ============================================================================
+// Pseudo-SLC Mode
+// ============================================================================
+
+/// Vendor command sequence for reading pseudo-SLC blocks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SlcMode {
+    /// No known SLC-mode command
+    Unsupported,
+    /// Mode entry/exit commands (Samsung, Hynix: DAh/DFh)
+    Command { enter: u8, exit: u8 },
+    /// Prefix issued before every read command (Toshiba/Kioxia: A2h)
+    ReadPrefix(u8),
+    /// SET FEATURES values switching a vendor feature address
+    SetFeature {
+        address: u8,
+        enable: u8,
+        disable: u8,
+    },
+}
+
+impl SlcMode {
+    pub fn for_chip(info: &NandChipInfo) -> Self {
+        if info.cell_type == CellType::SLC {
+            SlcMode::Unsupported
+        } else if vendor_is(info, &["samsung", "hynix"]) {
+            SlcMode::Command {
+                enter: 0xDA,
+                exit: 0xDF,
+            }
+        } else if vendor_is(info, &["toshiba", "kioxia", "sandisk"]) {
+            SlcMode::ReadPrefix(0xA2)
+        } else {
+            // Micron's feature 90h is array operation mode: 01h selects the
+            // OTP area and bit 3 is on-die ECC, so it is not an SLC switch
+            SlcMode::Unsupported
+        }
+    }
+
+    /// Bus operations entering SLC mode
+    pub fn enter_ops(&self) -> Vec<RetryOp> {
+        match *self {
+            SlcMode::Command { enter, .. } => vec![RetryOp::Command(enter)],
+            SlcMode::SetFeature {
+                address, enable, ..
+            } => set_feature_ops(address, enable),
+            SlcMode::Unsupported | SlcMode::ReadPrefix(_) => Vec::new(),
+        }
+    }
+
+    /// Bus operations issued before each page read
+    pub fn read_prefix_ops(&self) -> Vec<RetryOp> {
+        match *self {
+            SlcMode::ReadPrefix(cmd) => vec![RetryOp::Command(cmd)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Bus operations returning to normal mode
+    pub fn exit_ops(&self) -> Vec<RetryOp> {
+        match *self {
+            SlcMode::Command { exit, .. } => vec![RetryOp::Command(exit)],
+            SlcMode::SetFeature {
+                address, disable, ..
+            } => set_feature_ops(address, disable),
+            SlcMode::Unsupported | SlcMode::ReadPrefix(_) => Vec::new(),
+        }
+    }
+}
+
+fn set_feature_ops(address: u8, value: u8) -> Vec<RetryOp> {
+    vec![
+        RetryOp::Command(0xEF),
+        RetryOp::Address(address),
+        RetryOp::Data(value),
+        RetryOp::Data(0),
+        RetryOp::Data(0),
+        RetryOp::Data(0),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::onfi::get_chip_info;
+
+    #[test]
+    fn test_mlc_staircase_pairing() {
+        let pairing = PagePairing::staircase(2, 1, 128).unwrap();
+        assert_eq!(pairing.pages_per_block(), 128);
+
+        // L0 L1 U0 L2 U1 L3 U2 ...
+        assert_eq!(pairing.paired_pages(0), vec![2]);
+        assert_eq!(pairing.paired_pages(1), vec![4]);
+        assert_eq!(pairing.paired_pages(6), vec![3]);
+        assert_eq!(pairing.earlier_paired_pages(6), vec![3]);
+        assert!(pairing.earlier_paired_pages(3).is_empty());
+
+        // The block ends with the last two upper pages
+        assert_eq!(pairing.role(126).unwrap().group, 1);
+        assert_eq!(
+            pairing.role(127),
+            Some(PageRole {
+                wordline: 63,
+                group: 1
+            })
+        );
+        assert_eq!(pairing.lower_pages().len(), 64);
+    }
+
+    #[test]
+    fn test_hynix_even_odd_pairing() {
+        let pairing = PagePairing::staircase(2, 2, 256).unwrap();
+        // L0 L0' L1 L1' U0 U0' L2 L2' U1 U1' ...
+        assert_eq!(pairing.paired_pages(4), vec![0]);
+        assert_eq!(pairing.paired_pages(9), vec![3]);
+        assert!(pairing.is_lower(6));
+    }
+
+    #[test]
+    fn test_tlc_and_slc_pairing() {
+        let tlc = PagePairing::staircase(3, 1, 192).unwrap();
+        // L0 L1 M0 L2 M1 U0 ...
+        assert_eq!(tlc.paired_pages(0), vec![2, 5]);
+        assert_eq!(tlc.lower_pages().len(), 64);
+
+        let slc = PagePairing::slc(64);
+        assert!(slc.paired_pages(10).is_empty());
+        assert_eq!(slc.lower_pages().len(), 64);
+        assert_eq!(PagePairing::staircase(1, 1, 64).unwrap(), slc);
+
+        // 100 pages is not a whole number of TLC wordlines
+        assert!(matches!(
+            PagePairing::staircase(3, 1, 100),
+            Err(WriteError::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_slc_mode_for_vendor() {
+        let mut info = get_chip_info(&[0x2C, 0xDA]).unwrap();
+        info.cell_type = CellType::MLC;
+        // Micron's 90h would select OTP mode, not SLC
+        assert_eq!(SlcMode::for_chip(&info), SlcMode::Unsupported);
+
+        info.manufacturer = "Samsung".into();
+        let mode = SlcMode::for_chip(&info);
+        assert_eq!(mode.enter_ops(), vec![RetryOp::Command(0xDA)]);
+        assert_eq!(mode.exit_ops(), vec![RetryOp::Command(0xDF)]);
+
+        let mode = SlcMode::SetFeature {
+            address: 0x91,
+            enable: 0x01,
+            disable: 0x00,
+        };
+        assert_eq!(mode.enter_ops()[1], RetryOp::Address(0x91));
+        assert_eq!(mode.exit_ops()[2], RetryOp::Data(0x00));
+
+        info.manufacturer = "Toshiba/Kioxia".into();
+        let mode = SlcMode::for_chip(&info);
+        assert!(mode.enter_ops().is_empty());
+        assert_eq!(mode.read_prefix_ops(), vec![RetryOp::Command(0xA2)]);
+
+        info.cell_type = CellType::SLC;
+        assert_eq!(SlcMode::for_chip(&info), SlcMode::Unsupported);
+    }
+}
@@ -33,6 +33,16 @@ pub enum Command {
     NandReadId = 0x14,
     NandErase = 0x15,
     NandReadStatus = 0x16,
+    NandWriteData = 0x17, // Raw data-in cycles (SET FEATURES, vendor registers)
+    NandReadData = 0x0A,  // Raw data-out cycles: [len u16] (0x10-0x1F is full)
+    NandSelectCe = 0x18,  // Select the active CE# line
+    NandCacheRead = 0x19, // Sequential cache read (00h-30h, 31h..., 3Fh)
+    NandCacheProgram = 0x1A, // Cache program (80h-15h, last page 80h-10h)
+    NandMultiPlaneRead = 0x1B, // Multi-plane read (00h-32h ... 00h-30h)
+    NandMultiPlaneProgram = 0x1C, // Multi-plane program (80h-11h ... 80h-10h)
+    NandMultiPlaneErase = 0x1D, // Multi-plane erase (60h-D1h ... 60h-D0h)
+    FpgaReadReg = 0x1E,   // Read an FPGA gateware register
+    FpgaWriteReg = 0x1F,  // Write an FPGA gateware register
 
     // SPI NAND commands (0x20-0x3F)
     SpiNandReadId = 0x20,
@@ -48,6 +58,10 @@ pub enum Command {
     SpiNandBlockErase = 0x2A,
     SpiNandWriteEnable = 0x2B,
     SpiNandWriteDisable = 0x2C,
+    SpiNandReadEccStatus = 0x2D, // Vendor read-ECC-status command (Macronix 7Ch)
+    SpiNandDieSelect = 0x2E,     // Software die select (C2h) on stacked packages
+    SpiNandReadCacheContinuous = 0x2F, // Continuous read (BUF=0), main data only
+    SpiNandReadCacheContinuousX4 = 0x30, // Continuous read (Quad)
 
     // eMMC commands (0x40-0x5F)
     EmmcInit = 0x40,          // Initialize eMMC card
@@ -63,26 +77,37 @@ pub enum Command {
     EmmcSetPartition = 0x4A,  // Select partition (user/boot/rpmb)
 
     // SPI NOR commands (0x60-0x7F)
-    SpiNorReadJedecId = 0x60,   // Read JEDEC ID
-    SpiNorReadSfdp = 0x61,      // Read SFDP data
-    SpiNorRead = 0x62,          // Standard read
-    SpiNorFastRead = 0x63,      // Fast read with dummy cycle
-    SpiNorDualRead = 0x64,      // Dual SPI read
-    SpiNorQuadRead = 0x65,      // Quad SPI read
-    SpiNorPageProgram = 0x66,   // Page program (256 bytes)
-    SpiNorSectorErase = 0x67,   // Sector erase (4KB)
-    SpiNorBlockErase32K = 0x68, // Block erase (32KB)
-    SpiNorBlockErase64K = 0x69, // Block erase (64KB)
-    SpiNorChipErase = 0x6A,     // Chip erase
-    SpiNorReadStatus1 = 0x6B,   // Read status register 1
-    SpiNorReadStatus2 = 0x6C,   // Read status register 2
-    SpiNorReadStatus3 = 0x6D,   // Read status register 3
-    SpiNorWriteStatus1 = 0x6E,  // Write status register 1
-    SpiNorWriteStatus2 = 0x6F,  // Write status register 2
-    SpiNorWriteStatus3 = 0x70,  // Write status register 3
-    SpiNorWriteEnable = 0x71,   // Write enable
-    SpiNorWriteDisable = 0x72,  // Write disable
-    SpiNorReset = 0x73,         // Software reset
+    SpiNorReadJedecId = 0x60,        // Read JEDEC ID [addr, dummy, len]
+    SpiNorReadSfdp = 0x61,           // Read SFDP data
+    SpiNorRead = 0x62,               // Standard read
+    SpiNorFastRead = 0x63,           // Fast read with dummy cycle
+    SpiNorDualRead = 0x64,           // Dual SPI read
+    SpiNorQuadRead = 0x65,           // Quad SPI read
+    SpiNorPageProgram = 0x66,        // Page program (256 bytes)
+    SpiNorSectorErase = 0x67,        // Sector erase (4KB)
+    SpiNorBlockErase32K = 0x68,      // Block erase (32KB)
+    SpiNorBlockErase64K = 0x69,      // Block erase (64KB)
+    SpiNorChipErase = 0x6A,          // Chip erase
+    SpiNorReadStatus1 = 0x6B,        // Read status register 1
+    SpiNorReadStatus2 = 0x6C,        // Read status register 2
+    SpiNorReadStatus3 = 0x6D,        // Read status register 3
+    SpiNorWriteStatus1 = 0x6E,       // Write status register 1
+    SpiNorWriteStatus2 = 0x6F,       // Write status register 2
+    SpiNorWriteStatus3 = 0x70,       // Write status register 3
+    SpiNorWriteEnable = 0x71,        // Write enable
+    SpiNorWriteDisable = 0x72,       // Write disable
+    SpiNorReset = 0x73,              // Software reset
+    SpiNorReadUniqueId = 0x74,       // Read unique ID (4Bh)
+    SpiNorReadSecurityReg = 0x75,    // Read security register (48h)
+    SpiNorProgramSecurityReg = 0x76, // Program security register (42h)
+    SpiNorEraseSecurityReg = 0x77,   // Erase security register (44h)
+    SpiNorEnterSecuredOtp = 0x78,    // Enter secured OTP (ENSO, B1h)
+    SpiNorExitSecuredOtp = 0x79,     // Exit secured OTP (EXSO, C1h)
+    SpiNorReadSecurityScur = 0x7A,   // Read security register (RDSCUR, 2Bh)
+    SpiNorLockSecuredOtp = 0x7B,     // Lock secured OTP (WRSCUR, 2Fh) - irreversible
+    SpiNorSetIoMode = 0x7C,          // Switch bus lanes/DTR: mode, read opcode, dummy, cmd ext
+    SpiNorTransfer = 0x7D,           // Raw command in the current I/O mode: [len, bytes]
+    SpiNorSetAddressMode = 0x7E,     // 4-byte strategy: 3B, 4B opcodes, B7h, EAR, bank, always
 
     // UFS commands (0x80-0x9F)
     UfsInit = 0x80,           // Initialize UFS device
@@ -207,6 +232,16 @@ impl Command {
             0x07 | 0x14 => Some(Command::NandReadId),
             0x15 => Some(Command::NandErase),
             0x16 => Some(Command::NandReadStatus),
+            0x17 => Some(Command::NandWriteData),
+            0x0A => Some(Command::NandReadData),
+            0x18 => Some(Command::NandSelectCe),
+            0x19 => Some(Command::NandCacheRead),
+            0x1A => Some(Command::NandCacheProgram),
+            0x1B => Some(Command::NandMultiPlaneRead),
+            0x1C => Some(Command::NandMultiPlaneProgram),
+            0x1D => Some(Command::NandMultiPlaneErase),
+            0x1E => Some(Command::FpgaReadReg),
+            0x1F => Some(Command::FpgaWriteReg),
 
             // SPI NAND
             0x20 => Some(Command::SpiNandReadId),
@@ -222,6 +257,10 @@ impl Command {
             0x2A => Some(Command::SpiNandBlockErase),
             0x2B => Some(Command::SpiNandWriteEnable),
             0x2C => Some(Command::SpiNandWriteDisable),
+            0x2D => Some(Command::SpiNandReadEccStatus),
+            0x2E => Some(Command::SpiNandDieSelect),
+            0x2F => Some(Command::SpiNandReadCacheContinuous),
+            0x30 => Some(Command::SpiNandReadCacheContinuousX4),
 
             // eMMC
             0x40 => Some(Command::EmmcInit),
@@ -257,6 +296,17 @@ impl Command {
             0x71 => Some(Command::SpiNorWriteEnable),
             0x72 => Some(Command::SpiNorWriteDisable),
             0x73 => Some(Command::SpiNorReset),
+            0x74 => Some(Command::SpiNorReadUniqueId),
+            0x75 => Some(Command::SpiNorReadSecurityReg),
+            0x76 => Some(Command::SpiNorProgramSecurityReg),
+            0x77 => Some(Command::SpiNorEraseSecurityReg),
+            0x78 => Some(Command::SpiNorEnterSecuredOtp),
+            0x79 => Some(Command::SpiNorExitSecuredOtp),
+            0x7A => Some(Command::SpiNorReadSecurityScur),
+            0x7B => Some(Command::SpiNorLockSecuredOtp),
+            0x7C => Some(Command::SpiNorSetIoMode),
+            0x7D => Some(Command::SpiNorTransfer),
+            0x7E => Some(Command::SpiNorSetAddressMode),
 
             // UFS
             0x80 => Some(Command::UfsInit),
@@ -384,6 +434,10 @@ impl Command {
                 | Command::SpiNandBlockErase
                 | Command::SpiNandWriteEnable
                 | Command::SpiNandWriteDisable
+                | Command::SpiNandReadEccStatus
+                | Command::SpiNandDieSelect
+                | Command::SpiNandReadCacheContinuous
+                | Command::SpiNandReadCacheContinuousX4
         )
     }
 
@@ -429,6 +483,17 @@ impl Command {
                 | Command::SpiNorWriteEnable
                 | Command::SpiNorWriteDisable
                 | Command::SpiNorReset
+                | Command::SpiNorReadUniqueId
+                | Command::SpiNorReadSecurityReg
+                | Command::SpiNorProgramSecurityReg
+                | Command::SpiNorEraseSecurityReg
+                | Command::SpiNorEnterSecuredOtp
+                | Command::SpiNorExitSecuredOtp
+                | Command::SpiNorReadSecurityScur
+                | Command::SpiNorLockSecuredOtp
+                | Command::SpiNorSetIoMode
+                | Command::SpiNorTransfer
+                | Command::SpiNorSetAddressMode
         )
     }
 
@@ -623,6 +688,14 @@ pub mod nand_commands {
     pub const ERASESTART: u8 = 0xD0;
     pub const READSTATUS: u8 = 0x70;
     pub const RESET: u8 = 0xFF;
+    pub const CACHE_READ_SEQ: u8 = 0x31;
+    pub const CACHE_READ_END: u8 = 0x3F;
+    pub const CACHE_PROGRAM: u8 = 0x15;
+    pub const MULTI_PLANE_READ: u8 = 0x32;
+    pub const MULTI_PLANE_PROGRAM: u8 = 0x11;
+    pub const MULTI_PLANE_ERASE: u8 = 0xD1;
+    pub const CHANGE_READ_COLUMN_ENH: u8 = 0x06;
+    pub const CHANGE_READ_COLUMN_END: u8 = 0xE0;
 }
 
 /// SPI NAND flash commands (re-exported from spi_nand module)
@@ -0,0 +1,392 @@
+//! NAND data randomizer (scrambler) support
+//!
+//! Many NAND controllers XOR page data with an LFSR sequence seeded per page
+//! so that cells see balanced data. Scrambled dumps look like encrypted,
+//! high-entropy data until the sequence is removed. This module provides
+//! common LFSR definitions, per-page seed strategies, automatic detection
+//! from pages that hold scrambled padding, and descrambling.
+
+use serde::{Deserialize, Serialize};
+
+/// Number of keystream bytes checked when testing a page against an LFSR
+const DETECT_WINDOW: usize = 128;
+
+/// Minimum fraction of satisfied recurrence bits for a page to match
+const DETECT_THRESHOLD: f32 = 0.99;
+
+/// Maximum number of non-empty pages examined during detection
+const DETECT_MAX_PAGES: usize = 4096;
+
+/// Fibonacci LFSR definition
+///
+/// Each step outputs bit 0 of the state, shifts the state right and feeds
+/// the parity of `state & taps` into bit `width - 1`. Output bits are packed
+/// LSB-first into bytes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lfsr {
+    pub name: String,
+    /// State width in bits (2-32)
+    pub width: u8,
+    /// Feedback tap mask over the state bits
+    pub taps: u32,
+}
+
+impl Lfsr {
+    pub fn new(name: &str, width: u8, taps: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            taps,
+        }
+    }
+
+    fn mask(&self) -> u32 {
+        if self.width >= 32 {
+            u32::MAX
+        } else {
+            (1u32 << self.width) - 1
+        }
+    }
+
+    /// Generate `len` keystream bytes starting from `seed`
+    ///
+    /// A zero seed would lock the LFSR, so it is replaced by 1.
+    pub fn keystream(&self, seed: u32, len: usize) -> Vec<u8> {
+        let mask = self.mask();
+        let mut state = seed & mask;
+        if state == 0 {
+            state = 1;
+        }
+
+        let mut out = vec![0u8; len];
+        for byte in out.iter_mut() {
+            for bit in 0..8 {
+                *byte |= ((state & 1) as u8) << bit;
+                let feedback = (state & self.taps).count_ones() & 1;
+                state = (state >> 1) | (feedback << (self.width - 1));
+            }
+        }
+        out
+    }
+
+    /// Fraction of bits in `stream` that satisfy this LFSR's recurrence
+    ///
+    /// Independent of the seed: a raw keystream scores 1.0, unrelated data
+    /// scores around 0.5.
+    pub fn recurrence_score(&self, stream: &[u8]) -> f32 {
+        let width = self.width as usize;
+        let n_bits = stream.len() * 8;
+        if n_bits <= width {
+            return 0.0;
+        }
+
+        let bit = |i: usize| (stream[i / 8] >> (i % 8)) & 1;
+        let taps: Vec<usize> = (0..width).filter(|&i| self.taps & (1 << i) != 0).collect();
+
+        let checks = n_bits - width;
+        let satisfied = (0..checks)
+            .filter(|&k| taps.iter().fold(0, |acc, &i| acc ^ bit(k + i)) == bit(k + width))
+            .count();
+        satisfied as f32 / checks as f32
+    }
+
+    /// Recover the seed (initial state) that produced `stream`
+    pub fn recover_seed(&self, stream: &[u8]) -> Option<u32> {
+        let width = self.width as usize;
+        if stream.len() * 8 < width {
+            return None;
+        }
+        Some((0..width).fold(0u32, |seed, i| {
+            seed | ((((stream[i / 8] >> (i % 8)) & 1) as u32) << i)
+        }))
+    }
+}
+
+/// LFSR definitions used by common controller families
+pub fn known_lfsrs() -> Vec<Lfsr> {
+    vec![
+        Lfsr::new("Allwinner", 15, 0x0003),
+        Lfsr::new("MediaTek", 15, 0x4001),
+        Lfsr::new("Samsung", 17, 0x0009),
+        Lfsr::new("Toshiba", 23, 0x0021),
+    ]
+}
+
+/// How the LFSR seed is chosen for each page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SeedStrategy {
+    /// Same seed for every page
+    Fixed(u32),
+    /// Seed is the page number
+    PageNumber,
+    /// Seed is the page number modulo a period
+    PageModulo(u32),
+    /// Seed looked up by `page % table.len()`
+    PageTable(Vec<u32>),
+}
+
+impl SeedStrategy {
+    pub fn seed_for_page(&self, page: u32) -> u32 {
+        match self {
+            SeedStrategy::Fixed(seed) => *seed,
+            SeedStrategy::PageNumber => page,
+            SeedStrategy::PageModulo(period) => page % (*period).max(1),
+            SeedStrategy::PageTable(table) => {
+                if table.is_empty() {
+                    0
+                } else {
+                    table[page as usize % table.len()]
+                }
+            }
+        }
+    }
+}
+
+/// Complete randomizer configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Randomizer {
+    pub lfsr: Lfsr,
+    pub seed: SeedStrategy,
+    /// Restart the sequence every `step_size` bytes (per ECC step), if set
+    pub step_size: Option<usize>,
+}
+
+impl Randomizer {
+    pub fn new(lfsr: Lfsr, seed: SeedStrategy) -> Self {
+        Self {
+            lfsr,
+            seed,
+            step_size: None,
+        }
+    }
+
+    pub fn with_step_size(mut self, step_size: usize) -> Self {
+        self.step_size = Some(step_size);
+        self
+    }
+
+    /// Keystream covering one page
+    pub fn page_keystream(&self, page: u32, len: usize) -> Vec<u8> {
+        let seed = self.seed.seed_for_page(page) & self.lfsr.mask();
+        match self.step_size {
+            Some(step) if step > 0 && step < len => {
+                let stream = self.lfsr.keystream(seed, step);
+                stream.iter().copied().cycle().take(len).collect()
+            }
+            _ => self.lfsr.keystream(seed, len),
+        }
+    }
+
+    /// Scramble or descramble one page in place (the operation is symmetric)
+    pub fn descramble_page(&self, page: u32, data: &mut [u8]) {
+        let keystream = self.page_keystream(page, data.len());
+        for (byte, key) in data.iter_mut().zip(keystream) {
+            *byte ^= key;
+        }
+    }
+
+    /// Descramble a dump of consecutive pages; erased pages are left untouched
+    pub fn descramble_dump(&self, data: &[u8], page_size: usize) -> Vec<u8> {
+        let mut out = data.to_vec();
+        if page_size == 0 {
+            return out;
+        }
+        for (page, chunk) in out.chunks_mut(page_size).enumerate() {
+            if !chunk.iter().all(|&b| b == 0xFF) {
+                self.descramble_page(page as u32, chunk);
+            }
+        }
+        out
+    }
+}
+
+/// Result of automatic randomizer detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RandomizerDetection {
+    pub randomizer: Randomizer,
+    /// Pages whose content was recognised as scrambled padding
+    pub matched_pages: usize,
+    /// Non-empty pages examined
+    pub pages_tested: usize,
+}
+
+/// Detect the randomizer used in a dump
+///
+/// Pages that held 0xFF or 0x00 padding before scrambling contain the raw
+/// keystream (or its inverse). Each candidate LFSR is checked against those
+/// pages by its recurrence, the per-page seeds are recovered, and a seed
+/// strategy is inferred from them.
+pub fn detect_randomizer(
+    data: &[u8],
+    page_size: usize,
+    candidates: &[Lfsr],
+) -> Option<RandomizerDetection> {
+    if page_size == 0 || candidates.is_empty() {
+        return None;
+    }
+
+    let window = DETECT_WINDOW.min(page_size);
+    let mut pages_tested = 0;
+    // Per candidate: (page, seed) pairs from matching pages
+    let mut seeds: Vec<Vec<(u32, u32)>> = vec![Vec::new(); candidates.len()];
+
+    for (page, chunk) in data.chunks(page_size).enumerate() {
+        if pages_tested >= DETECT_MAX_PAGES {
+            break;
+        }
+        if chunk.len() < window || chunk.iter().all(|&b| b == 0xFF || b == 0x00) {
+            continue;
+        }
+        pages_tested += 1;
+
+        let raw = &chunk[..window];
+        let inverted: Vec<u8> = raw.iter().map(|b| !b).collect();
+
+        for (idx, lfsr) in candidates.iter().enumerate() {
+            for stream in [&inverted[..], raw] {
+                if lfsr.recurrence_score(stream) >= DETECT_THRESHOLD {
+                    if let Some(seed) = lfsr.recover_seed(stream) {
+                        seeds[idx].push((page as u32, seed));
+                    }
+                    break;
+                }
+            }
+        }
+    }
+
+    let (best, observed) = seeds
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, s)| s.len())
+        .filter(|(_, s)| !s.is_empty())?;
+
+    Some(RandomizerDetection {
+        randomizer: Randomizer::new(
+            candidates[best].clone(),
+            infer_seed_strategy(&candidates[best], observed),
+        ),
+        matched_pages: observed.len(),
+        pages_tested,
+    })
+}
+
+/// Infer a seed strategy from observed (page, seed) pairs
+fn infer_seed_strategy(lfsr: &Lfsr, observed: &[(u32, u32)]) -> SeedStrategy {
+    // Seed the LFSR actually starts from for a nominal seed value
+    let mask = lfsr.mask();
+    let effective = |seed: u32| match seed & mask {
+        0 => 1,
+        s => s,
+    };
+
+    let (_, first_seed) = observed[0];
+    if observed.iter().all(|&(_, seed)| seed == first_seed) {
+        return SeedStrategy::Fixed(first_seed);
+    }
+    if observed.iter().all(|&(page, seed)| effective(page) == seed) {
+        return SeedStrategy::PageNumber;
+    }
+    let last_page = observed.iter().map(|&(page, _)| page).max().unwrap_or(0);
+    if let Some(period) = (1..=last_page).find(|&p| {
+        observed
+            .iter()
+            .all(|&(page, seed)| effective(page % p) == seed)
+    }) {
+        return SeedStrategy::PageModulo(period);
+    }
+
+    // Fall back to a table indexed by page number
+    let mut table = vec![0u32; last_page as usize + 1];
+    for &(page, seed) in observed {
+        table[page as usize] = seed;
+    }
+    SeedStrategy::PageTable(table)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrambled_padding(randomizer: &Randomizer, pages: u32, page_size: usize) -> Vec<u8> {
+        let mut dump = Vec::new();
+        for page in 0..pages {
+            let mut data = vec![0xFFu8; page_size];
+            randomizer.descramble_page(page, &mut data);
+            dump.extend(data);
+        }
+        dump
+    }
+
+    #[test]
+    fn test_keystream_recurrence() {
+        for lfsr in known_lfsrs() {
+            let stream = lfsr.keystream(0x1234, 256);
+            assert_eq!(lfsr.recurrence_score(&stream), 1.0, "{}", lfsr.name);
+            assert_eq!(lfsr.recover_seed(&stream), Some(0x1234 & lfsr.mask()));
+        }
+
+        let allwinner = &known_lfsrs()[0];
+        let text = b"The quick brown fox jumps over the lazy dog, again and again and again.";
+        assert!(allwinner.recurrence_score(text) < 0.9);
+    }
+
+    #[test]
+    fn test_descramble_roundtrip() {
+        let randomizer = Randomizer::new(known_lfsrs()[2].clone(), SeedStrategy::PageNumber)
+            .with_step_size(1024);
+        let original: Vec<u8> = (0..4096u32).map(|i| (i % 251) as u8).collect();
+
+        let mut data = original.clone();
+        randomizer.descramble_page(7, &mut data);
+        assert_ne!(data, original);
+        // Keystream restarts every ECC step
+        let key0: Vec<u8> = data[..1024]
+            .iter()
+            .zip(&original[..1024])
+            .map(|(d, o)| d ^ o)
+            .collect();
+        let key1: Vec<u8> = data[1024..2048]
+            .iter()
+            .zip(&original[1024..2048])
+            .map(|(d, o)| d ^ o)
+            .collect();
+        assert_eq!(key0, key1);
+        randomizer.descramble_page(7, &mut data);
+        assert_eq!(data, original);
+    }
+
+    #[test]
+    fn test_detect_randomizer_page_number_seed() {
+        let randomizer = Randomizer::new(known_lfsrs()[1].clone(), SeedStrategy::PageNumber);
+        let mut dump = scrambled_padding(&randomizer, 8, 2048);
+        // Truly erased page is skipped
+        dump.extend(vec![0xFFu8; 2048]);
+
+        let detection = detect_randomizer(&dump, 2048, &known_lfsrs()).unwrap();
+        assert_eq!(detection.randomizer.lfsr.name, "MediaTek");
+        assert_eq!(detection.randomizer.seed, SeedStrategy::PageNumber);
+        assert_eq!(detection.matched_pages, 8);
+        assert_eq!(detection.pages_tested, 8);
+
+        let clean = detection.randomizer.descramble_dump(&dump, 2048);
+        assert!(clean[2048..].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn test_detect_randomizer_strategies() {
+        let fixed = Randomizer::new(known_lfsrs()[0].clone(), SeedStrategy::Fixed(0x2A5F));
+        let dump = scrambled_padding(&fixed, 4, 512);
+        let detection = detect_randomizer(&dump, 512, &known_lfsrs()).unwrap();
+        assert_eq!(detection.randomizer.lfsr.name, "Allwinner");
+        assert_eq!(detection.randomizer.seed, SeedStrategy::Fixed(0x2A5F));
+
+        let modulo = Randomizer::new(known_lfsrs()[3].clone(), SeedStrategy::PageModulo(4));
+        let dump = scrambled_padding(&modulo, 12, 512);
+        let detection = detect_randomizer(&dump, 512, &known_lfsrs()).unwrap();
+        assert_eq!(detection.randomizer.lfsr.name, "Toshiba");
+        assert_eq!(detection.randomizer.seed, SeedStrategy::PageModulo(4));
+
+        let plain: Vec<u8> = (0..4096u32).map(|i| (i * 7 % 256) as u8).collect();
+        assert!(detect_randomizer(&plain, 512, &known_lfsrs()).is_none());
+    }
+}
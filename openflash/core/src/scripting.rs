@@ -1,6 +1,9 @@
 //! Scripting & Automation module for OpenFlash v1.8
 //! Provides Python API bindings, CLI support, batch processing, and plugin system
 
+use crate::spi_nand::SpiNandEccCapture;
+use crate::spi_nor_otp::OtpDump;
+use crate::write_ops::{ChipProgrammer, PageRecovery, RecoveryOptions};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -166,6 +169,12 @@ pub struct ReadOptions {
     pub skip_bad_blocks: bool,
     /// Progress callback interval (bytes)
     pub progress_interval: u64,
+    /// SPI NAND on-die ECC: corrected, raw page + OOB, or both
+    #[serde(default)]
+    pub on_die_ecc: SpiNandEccCapture,
+    /// SPI NOR: also read the unique ID, security registers and OTP
+    #[serde(default)]
+    pub include_otp: bool,
 }
 
 impl Default for ReadOptions {
@@ -176,6 +185,8 @@ impl Default for ReadOptions {
             include_oob: false,
             skip_bad_blocks: true,
             progress_interval: 1024 * 1024, // 1MB
+            on_die_ecc: SpiNandEccCapture::Corrected,
+            include_otp: false,
         }
     }
 }
@@ -214,6 +225,10 @@ pub struct DumpResult {
     pub data: Vec<u8>,
     /// OOB data (if requested)
     pub oob_data: Option<Vec<u8>>,
+    /// Page + OOB dumps read with on-die ECC off (if requested)
+    pub raw_data: Option<Vec<u8>>,
+    /// SPI NOR unique ID and OTP areas (if requested)
+    pub otp: Option<OtpDump>,
     /// Bad blocks encountered
     pub bad_blocks: Vec<u32>,
     /// Read statistics
@@ -1121,6 +1136,18 @@ impl OpenFlash {
             } else {
                 None
             },
+            raw_data: if options.on_die_ecc == SpiNandEccCapture::Corrected {
+                None
+            } else {
+                let pages = length / chip.page_size as u64;
+                Some(vec![
+                    0xFF;
+                    (pages * (chip.page_size as u64 + chip.oob_size as u64))
+                        as usize
+                ])
+            },
+            // The mock chip is parallel NAND, which has no NOR OTP areas
+            otp: options.include_otp.then(OtpDump::default),
             bad_blocks: vec![],
             stats: ReadStats {
                 bytes_read: length,
@@ -1136,6 +1163,44 @@ impl OpenFlash {
         Ok(self.last_dump.as_ref().unwrap())
     }
 
+    /// Recover an uncorrectable page using re-reads and read-retry
+    pub fn recover_page(
+        &mut self,
+        block: u32,
+        page: u32,
+        options: &RecoveryOptions,
+    ) -> ScriptResult<PageRecovery> {
+        if !self.is_connected() {
+            return Err(ScriptError::NotConnected);
+        }
+
+        let chip = self.detect_chip()?;
+        let pages_per_block = chip.block_size / chip.page_size;
+        let total_blocks = (chip.capacity / chip.block_size as u64) as u32;
+        let programmer = ChipProgrammer::new(
+            chip.page_size,
+            pages_per_block,
+            total_blocks,
+            chip.oob_size as u32,
+            100_000,
+        );
+        let raw_size = (chip.page_size + chip.oob_size as u32) as usize;
+
+        // Mock implementation - real version sends RetryOp packets and page reads
+        programmer
+            .recover_page(
+                block,
+                page,
+                options,
+                |_ops| Ok(()),
+                |_, _| Ok(vec![0xFF; raw_size]),
+            )
+            .map_err(|e| ScriptError::ReadFailed {
+                address: programmer.block_page_to_address(block, page),
+                reason: e.to_string(),
+            })
+    }
+
     /// Get last dump
     pub fn last_dump(&self) -> Option<&DumpResult> {
         self.last_dump.as_ref()
@@ -1323,4 +1388,19 @@ mod tests {
         let result = of.read_full();
         assert!(matches!(result, Err(ScriptError::NotConnected)));
     }
+
+    #[test]
+    fn test_recover_page_mock() {
+        let mut of = OpenFlash::new();
+        assert!(matches!(
+            of.recover_page(0, 0, &RecoveryOptions::default()),
+            Err(ScriptError::NotConnected)
+        ));
+
+        of.connect().unwrap();
+        let result = of.recover_page(3, 7, &RecoveryOptions::default()).unwrap();
+        assert!(result.recovered);
+        assert_eq!(result.data.len(), 2048);
+        assert_eq!(result.retry_level, 0);
+    }
 }
@@ -92,6 +92,8 @@ pub enum DevicePlatform {
     STM32F4,
     ESP32,
     ESP32S3,
+    ICE40,
+    ECP5,
     Unknown,
 }
 
@@ -103,9 +105,28 @@ impl DevicePlatform {
             "STM32F4" | "STM32F401" | "STM32F411" | "STM32F446" => Self::STM32F4,
             "ESP32" => Self::ESP32,
             "ESP32S3" | "ESP32-S3" => Self::ESP32S3,
+            "ICE40" | "ICE40UP5K" | "ICE40HX8K" => Self::ICE40,
+            "ECP5" | "LFE5U" => Self::ECP5,
             _ => Self::Unknown,
         }
     }
+
+    /// Fastest ONFI SDR timing mode the platform's parallel NAND bus can drive
+    pub fn max_nand_timing_mode(&self) -> u8 {
+        match self {
+            Self::ICE40 | Self::ECP5 => 5,
+            Self::STM32F4 => 4,
+            Self::RP2040 => 3,
+            Self::ESP32 | Self::ESP32S3 => 2,
+            Self::STM32F1 => 1,
+            Self::Unknown => 0,
+        }
+    }
+
+    /// FPGA gateware, driven through `FpgaBackend` for DDR NAND interfaces
+    pub fn is_fpga(&self) -> bool {
+        matches!(self, Self::ICE40 | Self::ECP5)
+    }
 }
 
 /// Device capabilities
@@ -1953,6 +1974,8 @@ mod tests {
             DevicePlatform::from_str("ESP32-S3"),
             DevicePlatform::ESP32S3
         );
+        assert_eq!(DevicePlatform::from_str("ecp5"), DevicePlatform::ECP5);
+        assert!(DevicePlatform::ICE40.is_fpga() && !DevicePlatform::RP2040.is_fpga());
         assert_eq!(DevicePlatform::from_str("unknown"), DevicePlatform::Unknown);
     }
 
@@ -0,0 +1,1562 @@
+//! JESD216F Serial Flash Discoverable Parameters
+//!
+//! Decodes the Basic Flash Parameter Table (all 23 DWORDs), the 4-Byte
+//! Address Instruction table, Sector Map tables, the xSPI Profile 1.0 and
+//! octal DDR command sequence tables and vendor tables, and derives the
+//! read, erase and addressing commands a NOR driver needs for chips missing
+//! from the chip database.
+
+use crate::protocol::{Command, Packet};
+use crate::spi_nor::{
+    get_spi_nor_manufacturer_name, OctalEnable, QuadEnableMethod, SfdpParser, SpiNorChipInfo,
+    SpiNorIoMode,
+};
+use crate::spi_nor_addr::FourByteMode;
+use serde::{Deserialize, Serialize};
+
+/// Largest SpiNorReadSfdp the firmware answers
+pub const MAX_SFDP_READ: u32 = 256;
+
+/// Tables past this address are ignored when reading SFDP from a chip
+const SFDP_SPACE_LIMIT: usize = 0x1_0000;
+
+/// Parameter table IDs (MSB << 8 | LSB)
+pub const PARAM_ID_BFPT: u16 = 0xFF00;
+pub const PARAM_ID_XSPI_PROFILE1: u16 = 0xFF05;
+pub const PARAM_ID_OCTAL_DDR: u16 = 0xFF0A;
+pub const PARAM_ID_SECTOR_MAP: u16 = 0xFF81;
+pub const PARAM_ID_4BAIT: u16 = 0xFF84;
+
+fn bits(value: u32, hi: u32, lo: u32) -> u32 {
+    (value >> lo) & (u32::MAX >> (31 - (hi - lo)))
+}
+
+/// Variants whose bit is set in `mask`, bit 0 first
+fn decode_mask<T: Copy>(mask: u32, all: &[T]) -> Vec<T> {
+    all.iter()
+        .enumerate()
+        .filter(|(i, _)| mask & (1 << i) != 0)
+        .map(|(_, v)| *v)
+        .collect()
+}
+
+fn to_dwords(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .map(|d| u32::from_le_bytes([d[0], d[1], d[2], d[3]]))
+        .collect()
+}
+
+// ============================================================================
+// Basic Flash Parameter Table
+// ============================================================================
+
+/// Lanes and clocking of a read command (command-address-data)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadProtocol {
+    Fast111,
+    Dual112,
+    Dual122,
+    Dual222,
+    Quad114,
+    Quad144,
+    Quad444,
+    Octal118,
+    Octal188,
+    Octal888,
+    Dtr111,
+    Dtr122,
+    Dtr144,
+    Dtr444,
+    Dtr888,
+}
+
+impl ReadProtocol {
+    /// (command, address, data) lanes
+    pub fn lanes(&self) -> (u8, u8, u8) {
+        match self {
+            ReadProtocol::Fast111 | ReadProtocol::Dtr111 => (1, 1, 1),
+            ReadProtocol::Dual112 => (1, 1, 2),
+            ReadProtocol::Dual122 | ReadProtocol::Dtr122 => (1, 2, 2),
+            ReadProtocol::Dual222 => (2, 2, 2),
+            ReadProtocol::Quad114 => (1, 1, 4),
+            ReadProtocol::Quad144 | ReadProtocol::Dtr144 => (1, 4, 4),
+            ReadProtocol::Quad444 | ReadProtocol::Dtr444 => (4, 4, 4),
+            ReadProtocol::Octal118 => (1, 1, 8),
+            ReadProtocol::Octal188 => (1, 8, 8),
+            ReadProtocol::Octal888 | ReadProtocol::Dtr888 => (8, 8, 8),
+        }
+    }
+
+    pub fn is_dtr(&self) -> bool {
+        matches!(
+            self,
+            ReadProtocol::Dtr111
+                | ReadProtocol::Dtr122
+                | ReadProtocol::Dtr144
+                | ReadProtocol::Dtr444
+                | ReadProtocol::Dtr888
+        )
+    }
+}
+
+/// A fast read instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FastRead {
+    pub protocol: ReadProtocol,
+    pub opcode: u8,
+    pub mode_clocks: u8,
+    pub wait_states: u8,
+}
+
+impl FastRead {
+    /// Decode a 16-bit field: wait states 4:0, mode clocks 7:5, opcode 15:8
+    fn from_field(protocol: ReadProtocol, field: u32) -> Self {
+        Self {
+            protocol,
+            opcode: bits(field, 15, 8) as u8,
+            mode_clocks: bits(field, 7, 5) as u8,
+            wait_states: bits(field, 4, 0) as u8,
+        }
+    }
+
+    pub fn dummy_clocks(&self) -> u8 {
+        self.mode_clocks + self.wait_states
+    }
+}
+
+/// One of the four erase types (BFPT DWORDs 8-10)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EraseType {
+    pub size: u32,
+    pub opcode: u8,
+    /// 0 when the table has no timing DWORDs
+    pub typical_ms: u32,
+    pub max_ms: u32,
+}
+
+/// Address bytes accepted by the chip (DWORD 1 bits 18:17)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AddressMode {
+    ThreeByte,
+    ThreeOrFourByte,
+    FourByte,
+}
+
+/// Ways to enter 4-byte addressing (DWORD 16 bits 31:24)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FourByteEntry {
+    /// B7h
+    Command,
+    /// 06h, then B7h
+    WriteEnableCommand,
+    /// Extended address register (C5h write, C8h read)
+    ExtendedAddressRegister,
+    /// Bank register bit 7 (17h write, 16h read)
+    BankRegister,
+    /// 16-bit nonvolatile configuration register (B5h read, B1h write)
+    NonVolatileConfig,
+    /// Dedicated 4-byte instructions (see the 4-Byte Address Instruction table)
+    DedicatedInstructions,
+    /// Always in 4-byte mode
+    Always,
+}
+
+/// Ways to leave 4-byte addressing (DWORD 16 bits 23:14)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FourByteExit {
+    /// E9h
+    Command,
+    /// 06h, then E9h
+    WriteEnableCommand,
+    ExtendedAddressRegister,
+    BankRegister,
+    NonVolatileConfig,
+    HardwareReset,
+    SoftwareReset,
+    PowerCycle,
+}
+
+/// Soft reset sequences (DWORD 16 bits 13:8)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoftReset {
+    /// Drive Fh on all data lines for 8 clocks
+    DriveFh8Clocks,
+    /// Drive Fh for 10 clocks when in 4-4-4 or 8-8-8 mode
+    DriveFh10Clocks,
+    /// Drive Fh for 16 clocks
+    DriveFh16Clocks,
+    /// F0h
+    CommandF0,
+    /// 66h, then 99h
+    EnableAndReset,
+    /// Exit 0-4-4 mode before resetting
+    ExitContinuousRead,
+}
+
+/// How to poll for busy (DWORD 14 bits 7:2)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusyPolling {
+    /// WIP in status register 1 (05h bit 0)
+    pub status_wip: bool,
+    /// Ready in the flag status register (70h bit 7)
+    pub flag_status: bool,
+}
+
+/// Suspend/resume opcodes (DWORD 13)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SuspendResume {
+    pub program_suspend: u8,
+    pub program_resume: u8,
+    pub erase_suspend: u8,
+    pub erase_resume: u8,
+}
+
+/// Deep power-down opcodes (DWORD 14)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeepPowerDown {
+    pub enter: u8,
+    pub exit: u8,
+}
+
+/// Second command byte in 8D-8D-8D mode (DWORD 18 bits 30:29)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandExtension {
+    Repeat,
+    Invert,
+    SixteenBit,
+}
+
+/// Decoded Basic Flash Parameter Table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bfpt {
+    pub density_bits: u64,
+    pub address_mode: AddressMode,
+    pub page_size: u32,
+    /// 4 KB erase opcode, when 4 KB erase is uniformly available
+    pub erase_4k_opcode: Option<u8>,
+    /// Write enable for volatile status register writes (06h or 50h)
+    pub volatile_sr_write_enable: Option<u8>,
+    /// Erase types 1-4, indexed as in the Sector Map and 4BAIT tables
+    pub erase_types: [Option<EraseType>; 4],
+    pub page_program_typical_us: u32,
+    pub page_program_max_us: u32,
+    pub chip_erase_typical_ms: u32,
+    pub chip_erase_max_ms: u32,
+    pub fast_reads: Vec<FastRead>,
+    /// DTR clocking supported
+    pub dtr: bool,
+    pub quad_enable_method: QuadEnableMethod,
+    /// 4-4-4 mode enable opcode (38h or 35h)
+    pub qpi_enable: Option<u8>,
+    /// 4-4-4 mode disable opcode (FFh or F5h)
+    pub qpi_disable: Option<u8>,
+    pub four_byte_entry: Vec<FourByteEntry>,
+    pub four_byte_exit: Vec<FourByteExit>,
+    pub soft_reset: Vec<SoftReset>,
+    /// Status register 1 write modes, DWORD 16 bits 6:0
+    pub sr1_write_modes: u8,
+    pub busy_polling: BusyPolling,
+    pub suspend_resume: Option<SuspendResume>,
+    pub deep_power_down: Option<DeepPowerDown>,
+    pub octal_command_extension: Option<CommandExtension>,
+    /// Bytes are swapped within each 16-bit word in 8D-8D-8D mode
+    pub octal_dtr_byte_swap: bool,
+    /// Octal enable requirements, DWORD 19 bits 22:20
+    pub octal_enable: u8,
+    /// Characterized maximum clock per protocol, without data strobe
+    pub max_speeds_mhz: Vec<(ReadProtocol, u16)>,
+}
+
+/// DWORD 10 erase time units in ms
+const ERASE_UNITS_MS: [u32; 4] = [1, 16, 128, 1000];
+/// DWORD 11 chip erase time units in ms
+const CHIP_ERASE_UNITS_MS: [u32; 4] = [16, 256, 4000, 64000];
+/// DWORD 20 speed codes 1-12 in MHz
+const SPEED_CODES_MHZ: [u16; 12] = [33, 50, 66, 80, 100, 133, 166, 200, 250, 266, 333, 400];
+
+impl Bfpt {
+    /// Decode a BFPT of 9 (JESD216) to 23 (JESD216F) DWORDs
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let dw = to_dwords(data);
+        if dw.len() < 9 {
+            return None;
+        }
+        let get = |n: usize| dw.get(n - 1).copied();
+
+        let dw1 = dw[0];
+        let density_bits = if dw[1] & 0x8000_0000 != 0 {
+            1u64 << (dw[1] & 0x7FFF_FFFF).min(63)
+        } else {
+            dw[1] as u64 + 1
+        };
+
+        let mut fast_reads = Vec::new();
+        let mut push_read = |supported: bool, protocol, field| {
+            if supported {
+                fast_reads.push(FastRead::from_field(protocol, field));
+            }
+        };
+        push_read(dw1 & (1 << 16) != 0, ReadProtocol::Dual112, dw[3]);
+        push_read(dw1 & (1 << 20) != 0, ReadProtocol::Dual122, dw[3] >> 16);
+        push_read(dw1 & (1 << 22) != 0, ReadProtocol::Quad114, dw[2] >> 16);
+        push_read(dw1 & (1 << 21) != 0, ReadProtocol::Quad144, dw[2]);
+        push_read(dw[4] & 1 != 0, ReadProtocol::Dual222, dw[5] >> 16);
+        push_read(dw[4] & (1 << 4) != 0, ReadProtocol::Quad444, dw[6] >> 16);
+        if let Some(dw17) = get(17) {
+            push_read(bits(dw17, 15, 8) != 0, ReadProtocol::Octal118, dw17);
+            push_read(bits(dw17, 31, 24) != 0, ReadProtocol::Octal188, dw17 >> 16);
+        }
+        if let (Some(dw21), Some(dw22), Some(dw23)) = (get(21), get(22), get(23)) {
+            push_read(dw21 & 1 != 0, ReadProtocol::Dtr111, dw22);
+            push_read(dw21 & 2 != 0, ReadProtocol::Dtr122, dw22 >> 16);
+            push_read(dw21 & 4 != 0, ReadProtocol::Dtr144, dw23);
+            push_read(dw21 & 8 != 0, ReadProtocol::Dtr444, dw23 >> 16);
+        }
+
+        // Erase types and their timing
+        let dw10 = get(10);
+        let erase_max_multiplier = dw10.map_or(0, |d| 2 * (bits(d, 3, 0) + 1));
+        let mut erase_types = [None; 4];
+        for (i, erase_type) in erase_types.iter_mut().enumerate() {
+            let field = (dw[7 + i / 2] >> (16 * (i % 2))) & 0xFFFF;
+            let size_exp = bits(field, 7, 0);
+            if size_exp == 0 || size_exp > 31 {
+                continue;
+            }
+            let typical_ms = dw10.map_or(0, |d| {
+                let time = d >> (4 + 7 * i as u32);
+                (bits(time, 4, 0) + 1) * ERASE_UNITS_MS[bits(time, 6, 5) as usize]
+            });
+            *erase_type = Some(EraseType {
+                size: 1 << size_exp,
+                opcode: bits(field, 15, 8) as u8,
+                typical_ms,
+                max_ms: typical_ms * erase_max_multiplier,
+            });
+        }
+
+        // Program and chip erase timing, page size
+        let dw11 = get(11);
+        let program_max_multiplier = dw11.map_or(0, |d| 2 * (bits(d, 3, 0) + 1));
+        let page_program_typical_us = dw11.map_or(0, |d| {
+            (bits(d, 12, 8) + 1) * if d & (1 << 13) != 0 { 64 } else { 8 }
+        });
+        let chip_erase_typical_ms = dw11.map_or(0, |d| {
+            (bits(d, 28, 24) + 1) * CHIP_ERASE_UNITS_MS[bits(d, 30, 29) as usize]
+        });
+
+        let dw12 = get(12).unwrap_or(0x8000_0000);
+        let suspend_resume = match get(13) {
+            Some(dw13) if dw12 & 0x8000_0000 == 0 => Some(SuspendResume {
+                program_resume: bits(dw13, 7, 0) as u8,
+                program_suspend: bits(dw13, 15, 8) as u8,
+                erase_resume: bits(dw13, 23, 16) as u8,
+                erase_suspend: bits(dw13, 31, 24) as u8,
+            }),
+            _ => None,
+        };
+
+        let dw14 = get(14).unwrap_or(0x8000_0000);
+        let deep_power_down = (dw14 & 0x8000_0000 == 0).then(|| DeepPowerDown {
+            enter: bits(dw14, 30, 23) as u8,
+            exit: bits(dw14, 22, 15) as u8,
+        });
+        let busy_polling = if get(14).is_some() {
+            BusyPolling {
+                status_wip: dw14 & (1 << 2) != 0,
+                flag_status: dw14 & (1 << 3) != 0,
+            }
+        } else {
+            BusyPolling {
+                status_wip: true,
+                flag_status: false,
+            }
+        };
+
+        let dw15 = get(15).unwrap_or(0);
+        let qpi_enable_bits = bits(dw15, 8, 4);
+        let qpi_disable_bits = bits(dw15, 3, 0);
+
+        let dw16 = get(16).unwrap_or(0);
+        let entry_bits = bits(dw16, 31, 24);
+        let exit_bits = bits(dw16, 23, 14);
+        let reset_bits = bits(dw16, 13, 8);
+        let (octal_command_extension, octal_dtr_byte_swap) = match get(18) {
+            Some(dw18) => (
+                match bits(dw18, 30, 29) {
+                    0 => Some(CommandExtension::Repeat),
+                    1 => Some(CommandExtension::Invert),
+                    3 => Some(CommandExtension::SixteenBit),
+                    _ => None,
+                },
+                dw18 & 0x8000_0000 != 0,
+            ),
+            None => (None, false),
+        };
+
+        let max_speeds_mhz = get(20).map_or_else(Vec::new, |dw20| {
+            [
+                (0, ReadProtocol::Quad444),
+                (8, ReadProtocol::Dtr444),
+                (16, ReadProtocol::Octal888),
+                (24, ReadProtocol::Dtr888),
+            ]
+            .iter()
+            .filter_map(|&(shift, protocol)| {
+                let code = bits(dw20, shift + 3, shift) as usize;
+                (1..=12)
+                    .contains(&code)
+                    .then(|| (protocol, SPEED_CODES_MHZ[code - 1]))
+            })
+            .collect()
+        });
+
+        Some(Self {
+            density_bits,
+            address_mode: match bits(dw1, 18, 17) {
+                1 => AddressMode::ThreeOrFourByte,
+                2 => AddressMode::FourByte,
+                _ => AddressMode::ThreeByte,
+            },
+            page_size: dw11.map_or(256, |d| 1 << bits(d, 7, 4)),
+            erase_4k_opcode: (bits(dw1, 1, 0) == 0b01).then(|| bits(dw1, 15, 8) as u8),
+            volatile_sr_write_enable: (dw1 & (1 << 3) != 0).then_some(if dw1 & (1 << 4) != 0 {
+                0x06
+            } else {
+                0x50
+            }),
+            erase_types,
+            page_program_typical_us,
+            page_program_max_us: page_program_typical_us * program_max_multiplier,
+            chip_erase_typical_ms,
+            chip_erase_max_ms: chip_erase_typical_ms * program_max_multiplier,
+            fast_reads,
+            dtr: dw1 & (1 << 19) != 0,
+            quad_enable_method: QuadEnableMethod::from_qer(bits(dw15, 22, 20) as u8),
+            qpi_enable: if qpi_enable_bits & 0b00011 != 0 {
+                Some(0x38)
+            } else if qpi_enable_bits & 0b00100 != 0 {
+                Some(0x35)
+            } else {
+                None
+            },
+            qpi_disable: if qpi_disable_bits & 0b0001 != 0 {
+                Some(0xFF)
+            } else if qpi_disable_bits & 0b0010 != 0 {
+                Some(0xF5)
+            } else {
+                None
+            },
+            four_byte_entry: decode_mask(
+                entry_bits,
+                &[
+                    FourByteEntry::Command,
+                    FourByteEntry::WriteEnableCommand,
+                    FourByteEntry::ExtendedAddressRegister,
+                    FourByteEntry::BankRegister,
+                    FourByteEntry::NonVolatileConfig,
+                    FourByteEntry::DedicatedInstructions,
+                    FourByteEntry::Always,
+                ],
+            ),
+            four_byte_exit: decode_mask(
+                exit_bits,
+                &[
+                    FourByteExit::Command,
+                    FourByteExit::WriteEnableCommand,
+                    FourByteExit::ExtendedAddressRegister,
+                    FourByteExit::BankRegister,
+                    FourByteExit::NonVolatileConfig,
+                    FourByteExit::HardwareReset,
+                    FourByteExit::SoftwareReset,
+                    FourByteExit::PowerCycle,
+                ],
+            ),
+            soft_reset: decode_mask(
+                reset_bits,
+                &[
+                    SoftReset::DriveFh8Clocks,
+                    SoftReset::DriveFh10Clocks,
+                    SoftReset::DriveFh16Clocks,
+                    SoftReset::CommandF0,
+                    SoftReset::EnableAndReset,
+                    SoftReset::ExitContinuousRead,
+                ],
+            ),
+            sr1_write_modes: bits(dw16, 6, 0) as u8,
+            busy_polling,
+            suspend_resume,
+            deep_power_down,
+            octal_command_extension,
+            octal_dtr_byte_swap,
+            octal_enable: get(19).map_or(0, |d| bits(d, 22, 20) as u8),
+            max_speeds_mhz,
+        })
+    }
+
+    pub fn size_bytes(&self) -> u64 {
+        self.density_bits / 8
+    }
+
+    /// Address bytes to use for the whole array
+    pub fn address_bytes(&self) -> u8 {
+        match self.address_mode {
+            AddressMode::FourByte => 4,
+            _ if self.size_bytes() > 16 * 1024 * 1024 => 4,
+            _ => 3,
+        }
+    }
+
+    /// Erase types present, with their 1-based type number
+    pub fn erase_types(&self) -> impl Iterator<Item = (u8, &EraseType)> + '_ {
+        self.erase_types
+            .iter()
+            .enumerate()
+            .filter_map(|(i, t)| t.as_ref().map(|t| (i as u8 + 1, t)))
+    }
+
+    pub fn fast_read(&self, protocol: ReadProtocol) -> Option<&FastRead> {
+        self.fast_reads.iter().find(|r| r.protocol == protocol)
+    }
+}
+
+// ============================================================================
+// 4-Byte Address Instruction Table
+// ============================================================================
+
+/// Dedicated 4-byte address instructions (parameter ID FF84h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FourByteInstructions {
+    /// Support bits of DWORD 1
+    pub support: u32,
+    /// Opcodes for erase types 1-4 (DWORD 2)
+    pub erase_opcodes: [u8; 4],
+}
+
+impl FourByteInstructions {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let dw = to_dwords(data);
+        let (&support, &erase) = (dw.first()?, dw.get(1)?);
+        Some(Self {
+            support,
+            erase_opcodes: erase.to_le_bytes(),
+        })
+    }
+
+    fn supports(&self, bit: u32) -> bool {
+        self.support & (1 << bit) != 0
+    }
+
+    /// 4-byte opcode for a read protocol
+    pub fn read_opcode(&self, protocol: ReadProtocol) -> Option<u8> {
+        let (bit, opcode) = match protocol {
+            ReadProtocol::Fast111 => (1, 0x0C),
+            ReadProtocol::Dual112 => (2, 0x3C),
+            ReadProtocol::Dual122 => (3, 0xBC),
+            ReadProtocol::Quad114 => (4, 0x6C),
+            ReadProtocol::Quad144 => (5, 0xEC),
+            ReadProtocol::Dtr111 => (13, 0x0E),
+            ReadProtocol::Dtr122 => (14, 0xBE),
+            ReadProtocol::Dtr144 => (15, 0xEE),
+            ReadProtocol::Octal118 => (20, 0x7C),
+            ReadProtocol::Octal188 => (21, 0xCC),
+            _ => return None,
+        };
+        self.supports(bit).then_some(opcode)
+    }
+
+    /// 4-byte READ (13h)
+    pub fn read(&self) -> Option<u8> {
+        self.supports(0).then_some(0x13)
+    }
+
+    /// 4-byte page program opcode for 1, 4 or 8 data lanes
+    pub fn page_program(&self, lanes: u8) -> Option<u8> {
+        match lanes {
+            1 => self.supports(6).then_some(0x12),
+            4 => self.supports(7).then_some(0x34),
+            8 => self.supports(22).then_some(0x84),
+            _ => None,
+        }
+    }
+
+    /// 4-byte opcode for erase type 1-4
+    pub fn erase(&self, erase_type: u8) -> Option<u8> {
+        let index = erase_type.checked_sub(1)? as u32;
+        (index < 4 && self.supports(9 + index)).then(|| self.erase_opcodes[index as usize])
+    }
+}
+
+// ============================================================================
+// Sector Map
+// ============================================================================
+
+/// Command whose result selects a sector map configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigDetect {
+    pub opcode: u8,
+    /// 0, 3 or 4; `None` when variable (current address mode)
+    pub address_bytes: Option<u8>,
+    pub address: u32,
+    /// `None` when variable (current read latency)
+    pub dummy_cycles: Option<u8>,
+    /// Bit of the returned byte that contributes to the configuration ID
+    pub mask: u8,
+}
+
+/// Erase region of a non-uniform sector map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorRegion {
+    pub start: u32,
+    pub size: u32,
+    /// Erase types allowed in the region, bit n = type n + 1
+    pub erase_types: u8,
+}
+
+impl SectorRegion {
+    pub fn allows(&self, erase_type: u8) -> bool {
+        erase_type >= 1 && self.erase_types & (1 << (erase_type - 1)) != 0
+    }
+
+    pub fn contains(&self, addr: u32) -> bool {
+        addr >= self.start && (addr - self.start) < self.size
+    }
+}
+
+/// Regions of one configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorMapConfig {
+    pub id: u8,
+    pub regions: Vec<SectorRegion>,
+}
+
+impl SectorMapConfig {
+    pub fn region_at(&self, addr: u32) -> Option<&SectorRegion> {
+        self.regions.iter().find(|r| r.contains(addr))
+    }
+}
+
+/// Sector Map parameter table (parameter ID FF81h)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectorMap {
+    pub detect: Vec<ConfigDetect>,
+    pub configs: Vec<SectorMapConfig>,
+}
+
+impl SectorMap {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let dw = to_dwords(data);
+        let mut map = SectorMap::default();
+        let mut i = 0;
+        while i < dw.len() {
+            let header = dw[i];
+            let last = header & 1 != 0;
+            if header & 2 == 0 {
+                // Configuration detection command descriptor
+                let address = *dw.get(i + 1)?;
+                map.detect.push(ConfigDetect {
+                    opcode: bits(header, 15, 8) as u8,
+                    address_bytes: match bits(header, 23, 22) {
+                        0 => Some(0),
+                        1 => Some(3),
+                        2 => Some(4),
+                        _ => None,
+                    },
+                    address,
+                    dummy_cycles: match bits(header, 19, 16) {
+                        0xF => None,
+                        cycles => Some(cycles as u8),
+                    },
+                    mask: bits(header, 31, 24) as u8,
+                });
+                i += 2;
+            } else {
+                let count = bits(header, 23, 16) as usize + 1;
+                let mut start = 0u32;
+                let mut regions = Vec::with_capacity(count);
+                for &region in dw.get(i + 1..i + 1 + count)? {
+                    let size = (bits(region, 31, 8) + 1).saturating_mul(256);
+                    regions.push(SectorRegion {
+                        start,
+                        size,
+                        erase_types: bits(region, 3, 0) as u8,
+                    });
+                    start = start.saturating_add(size);
+                }
+                map.configs.push(SectorMapConfig {
+                    id: bits(header, 15, 8) as u8,
+                    regions,
+                });
+                i += 1 + count;
+            }
+            if last {
+                break;
+            }
+        }
+        Some(map)
+    }
+
+    /// Configuration ID from the detection results, first command as MSB
+    pub fn config_id(&self, results: &[u8]) -> u8 {
+        self.detect
+            .iter()
+            .zip(results)
+            .fold(0, |id, (cmd, &value)| {
+                (id << 1) | (value & cmd.mask != 0) as u8
+            })
+    }
+
+    /// Run the detection commands through `read` and pick the matching map
+    ///
+    /// Tables without detection commands have a single map.
+    pub fn detect_config<E, R>(&self, mut read: R) -> Result<Option<&SectorMapConfig>, E>
+    where
+        R: FnMut(&ConfigDetect) -> Result<u8, E>,
+    {
+        if self.detect.is_empty() {
+            return Ok(self.configs.first());
+        }
+        let results = self
+            .detect
+            .iter()
+            .map(&mut read)
+            .collect::<Result<Vec<u8>, E>>()?;
+        let id = self.config_id(&results);
+        Ok(self.configs.iter().find(|c| c.id == id))
+    }
+}
+
+// ============================================================================
+// xSPI Profile 1.0
+// ============================================================================
+
+/// xSPI Profile 1.0 table (parameter ID FF05h), 8D-8D-8D operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct XspiProfile1 {
+    /// Read Fast opcode
+    pub read_fast_opcode: u8,
+    /// Read Status needs a 4-byte address
+    pub read_status_address: bool,
+    /// Read Status dummy cycles (4 or 8)
+    pub read_status_dummy: u8,
+    /// Read dummy cycles at 100, 133, 166 and 200 MHz
+    pub read_dummy: [u8; 4],
+}
+
+impl XspiProfile1 {
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let dw = to_dwords(data);
+        let (dw1, dw4, dw5) = (*dw.first()?, *dw.get(3)?, *dw.get(4)?);
+        Some(Self {
+            read_fast_opcode: bits(dw1, 15, 8) as u8,
+            read_status_address: dw1 & (1 << 29) != 0,
+            read_status_dummy: if dw1 & (1 << 28) != 0 { 8 } else { 4 },
+            read_dummy: [
+                bits(dw5, 11, 7) as u8,
+                bits(dw5, 21, 17) as u8,
+                bits(dw5, 31, 27) as u8,
+                bits(dw4, 11, 7) as u8,
+            ],
+        })
+    }
+
+    /// Dummy cycles needed at `clock_mhz`
+    pub fn dummy_cycles(&self, clock_mhz: u16) -> u8 {
+        let index = match clock_mhz {
+            0..=100 => 0,
+            101..=133 => 1,
+            134..=166 => 2,
+            _ => 3,
+        };
+        self.read_dummy[index]
+    }
+}
+
+/// Command Sequences to Change to Octal DDR (8D-8D-8D) Mode table (FF0Ah)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OctalDdrSequences {
+    /// Commands sent in 1S-1S-1S, in order, including any write enable
+    pub commands: Vec<Vec<u8>>,
+}
+
+impl OctalDdrSequences {
+    /// Each command takes two DWORDs: length in bits 31:24 of the first,
+    /// then up to 7 bytes MSB first; a zero length ends the table
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let dw = to_dwords(data);
+        let commands: Vec<Vec<u8>> = dw
+            .chunks_exact(2)
+            .map(|pair| {
+                let bytes = [pair[0].to_be_bytes(), pair[1].to_be_bytes()].concat();
+                let len = (bytes[0] as usize).min(7);
+                bytes[1..1 + len].to_vec()
+            })
+            .take_while(|command| !command.is_empty())
+            .collect();
+        (!commands.is_empty()).then_some(Self { commands })
+    }
+}
+
+// ============================================================================
+// Vendor Tables
+// ============================================================================
+
+/// Macronix flash parameter table (manufacturer C2h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MacronixParams {
+    pub vcc_min_mv: u16,
+    pub vcc_max_mv: u16,
+    pub hw_reset_pin: bool,
+    pub hold_pin: bool,
+    pub deep_power_down: bool,
+    pub sw_reset_opcode: Option<u8>,
+    pub program_suspend: bool,
+    pub erase_suspend: bool,
+    /// Set Burst Length (C0h) for wrap-around reads
+    pub wrap_read_opcode: Option<u8>,
+    /// Individual block lock opcode (DWORD3)
+    pub block_lock_opcode: Option<u8>,
+    /// Secured OTP area (DWORD3)
+    pub secured_otp: bool,
+}
+
+impl MacronixParams {
+    fn parse(dw: &[u32]) -> Option<Self> {
+        let (dw1, dw2) = (*dw.first()?, *dw.get(1)?);
+        let dw3 = dw.get(2).copied().unwrap_or(0);
+        Some(Self {
+            vcc_max_mv: bcd_mv(bits(dw1, 15, 0)) * 10,
+            vcc_min_mv: bcd_mv(bits(dw1, 31, 16)) * 10,
+            hw_reset_pin: dw2 & 1 != 0,
+            hold_pin: dw2 & 2 != 0,
+            deep_power_down: dw2 & 4 != 0,
+            sw_reset_opcode: (dw2 & 8 != 0).then(|| bits(dw2, 11, 4) as u8),
+            program_suspend: dw2 & (1 << 12) != 0,
+            erase_suspend: dw2 & (1 << 13) != 0,
+            wrap_read_opcode: (dw2 & (1 << 15) != 0).then(|| bits(dw2, 23, 16) as u8),
+            block_lock_opcode: (dw3 & 1 != 0).then(|| bits(dw3, 9, 2) as u8),
+            secured_otp: dw3 & (1 << 11) != 0,
+        })
+    }
+}
+
+/// GigaDevice flash parameter table (manufacturer C8h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GigaDeviceParams {
+    pub vcc_min_mv: u16,
+    pub vcc_max_mv: u16,
+    pub hw_reset_pin: bool,
+    /// HOLD# doubles as RESET# on parts without a dedicated reset pin
+    pub hold_pin: bool,
+    pub deep_power_down: bool,
+    pub sw_reset_opcode: Option<u8>,
+    pub program_suspend: bool,
+    pub erase_suspend: bool,
+    /// Set Burst with Wrap (77h)
+    pub burst_wrap_opcode: Option<u8>,
+}
+
+impl GigaDeviceParams {
+    fn parse(dw: &[u32]) -> Option<Self> {
+        let (dw1, dw2) = (*dw.first()?, *dw.get(1)?);
+        Some(Self {
+            vcc_max_mv: bcd_mv(bits(dw1, 15, 0)) * 10,
+            vcc_min_mv: bcd_mv(bits(dw1, 31, 16)) * 10,
+            hw_reset_pin: dw2 & 1 != 0,
+            hold_pin: dw2 & 2 != 0,
+            deep_power_down: dw2 & 4 != 0,
+            sw_reset_opcode: (dw2 & 8 != 0).then(|| bits(dw2, 11, 4) as u8),
+            program_suspend: dw2 & (1 << 12) != 0,
+            erase_suspend: dw2 & (1 << 13) != 0,
+            burst_wrap_opcode: (dw2 & (1 << 15) != 0).then(|| bits(dw2, 23, 16) as u8),
+        })
+    }
+}
+
+/// Spansion/Cypress ID-CFI table (manufacturer 01h)
+///
+/// The table is the CFI query space: byte N is CFI address N.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpansionCfi {
+    pub vcc_min_mv: u16,
+    pub vcc_max_mv: u16,
+    pub size_bytes: u64,
+    pub page_size: u32,
+    pub page_program_typical_us: u32,
+    pub sector_erase_typical_ms: u32,
+}
+
+impl SpansionCfi {
+    fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(0x10..0x13)? != b"QRY" {
+            return None;
+        }
+        // Volts in bits 7:4, 100 mV in bits 3:0
+        let cfi_mv = |v: u8| (v >> 4) as u16 * 1000 + (v & 0xF) as u16 * 100;
+        let pow2 = |v: u8| 1u64.checked_shl(v as u32).unwrap_or(0);
+        Some(Self {
+            vcc_min_mv: cfi_mv(*data.get(0x1B)?),
+            vcc_max_mv: cfi_mv(*data.get(0x1C)?),
+            size_bytes: pow2(*data.get(0x27)?),
+            page_size: pow2(*data.get(0x2A)?) as u32,
+            page_program_typical_us: pow2(*data.get(0x20)?) as u32,
+            sector_erase_typical_ms: pow2(*data.get(0x21)?) as u32,
+        })
+    }
+}
+
+/// Vendor table decoded with its manufacturer's layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VendorParams {
+    Macronix(MacronixParams),
+    GigaDevice(GigaDeviceParams),
+    Spansion(SpansionCfi),
+}
+
+impl VendorParams {
+    /// Supply range as (min, max) millivolts
+    pub fn vcc_mv(&self) -> (u16, u16) {
+        match self {
+            Self::Macronix(p) => (p.vcc_min_mv, p.vcc_max_mv),
+            Self::GigaDevice(p) => (p.vcc_min_mv, p.vcc_max_mv),
+            Self::Spansion(p) => (p.vcc_min_mv, p.vcc_max_mv),
+        }
+    }
+}
+
+/// Vendor-specific parameter table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendorTable {
+    /// JEDEC manufacturer ID (parameter ID LSB)
+    pub manufacturer: u8,
+    pub major_rev: u8,
+    pub minor_rev: u8,
+    pub dwords: Vec<u32>,
+    /// Decoded for vendors with a known layout
+    pub params: Option<VendorParams>,
+}
+
+/// BCD millivolts (e.g. 0x3600 = 3.600 V)
+fn bcd_mv(value: u32) -> u16 {
+    (0..4).rev().fold(0, |mv, digit| {
+        mv * 10 + ((value >> (digit * 4)) & 0xF) as u16
+    }) / 10
+}
+
+impl VendorTable {
+    pub fn parse(manufacturer: u8, major_rev: u8, minor_rev: u8, data: &[u8]) -> Self {
+        let dwords = to_dwords(data);
+        // Winbond and others publish no documented layout; keep raw DWORDs
+        let params = match manufacturer {
+            0xC2 => MacronixParams::parse(&dwords).map(VendorParams::Macronix),
+            0xC8 => GigaDeviceParams::parse(&dwords).map(VendorParams::GigaDevice),
+            0x01 => SpansionCfi::parse(data).map(VendorParams::Spansion),
+            _ => None,
+        };
+        Self {
+            manufacturer,
+            major_rev,
+            minor_rev,
+            dwords,
+            params,
+        }
+    }
+
+    pub fn manufacturer_name(&self) -> &'static str {
+        get_spi_nor_manufacturer_name(self.manufacturer)
+    }
+}
+
+// ============================================================================
+// Complete SFDP
+// ============================================================================
+
+/// Erase command chosen for an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EraseOp {
+    pub opcode: u8,
+    pub size: u32,
+    pub address_bytes: u8,
+}
+
+/// All parameter tables of a chip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sfdp {
+    pub major_rev: u8,
+    pub minor_rev: u8,
+    pub bfpt: Bfpt,
+    pub four_byte: Option<FourByteInstructions>,
+    pub sector_map: Option<SectorMap>,
+    pub xspi: Option<XspiProfile1>,
+    pub octal_ddr: Option<OctalDdrSequences>,
+    pub vendor: Vec<VendorTable>,
+}
+
+impl Sfdp {
+    /// Parse the SFDP space starting at address 0
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let header = SfdpParser::parse_header(data)?;
+
+        let mut bfpt = None;
+        let mut four_byte = None;
+        let mut sector_map = None;
+        let mut xspi = None;
+        let mut octal_ddr = None;
+        let mut vendor = Vec::new();
+        for i in 0..header.num_param_headers as usize {
+            let Some(param) = data
+                .get(8 + i * 8..)
+                .and_then(SfdpParser::parse_param_header)
+            else {
+                break;
+            };
+            let start = param.table_pointer as usize;
+            let Some(table) = data.get(start..start + param.length_dwords as usize * 4) else {
+                continue;
+            };
+
+            match (param.id_msb as u16) << 8 | param.id_lsb as u16 {
+                // Later BFPT revisions supersede earlier ones
+                PARAM_ID_BFPT => bfpt = Bfpt::parse(table).or(bfpt),
+                PARAM_ID_4BAIT => four_byte = FourByteInstructions::parse(table),
+                PARAM_ID_SECTOR_MAP => sector_map = SectorMap::parse(table),
+                PARAM_ID_XSPI_PROFILE1 => xspi = XspiProfile1::parse(table),
+                PARAM_ID_OCTAL_DDR => octal_ddr = OctalDdrSequences::parse(table),
+                _ if param.id_msb != 0xFF => vendor.push(VendorTable::parse(
+                    param.id_lsb,
+                    param.major_rev,
+                    param.minor_rev,
+                    table,
+                )),
+                _ => {}
+            }
+        }
+
+        Some(Self {
+            major_rev: header.major_rev,
+            minor_rev: header.minor_rev,
+            bfpt: bfpt?,
+            four_byte,
+            sector_map,
+            xspi,
+            octal_ddr,
+            vendor,
+        })
+    }
+
+    /// Read the header, parameter headers and tables with SpiNorReadSfdp
+    /// and parse them; `None` when the chip has no usable SFDP
+    ///
+    /// `transfer` returns the response data of each packet.
+    pub fn read<E, T>(mut transfer: T) -> Result<Option<Self>, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let mut read = |address: usize, len: usize| -> Result<Vec<u8>, E> {
+            let mut data = Vec::with_capacity(len);
+            for offset in (0..len).step_by(MAX_SFDP_READ as usize) {
+                let chunk = (len - offset).min(MAX_SFDP_READ as usize);
+                let addr = ((address + offset) as u32).to_le_bytes();
+                let size = (chunk as u16).to_le_bytes();
+                let args = [addr[0], addr[1], addr[2], size[0], size[1]];
+                data.extend(transfer(&Packet::new(Command::SpiNorReadSfdp, &args), &[])?);
+            }
+            data.resize(len, 0xFF);
+            Ok(data)
+        };
+
+        let mut image = read(0, 8)?;
+        let Some(header) = SfdpParser::parse_header(&image) else {
+            return Ok(None);
+        };
+        let headers_len = header.num_param_headers as usize * 8;
+        image.extend(read(8, headers_len)?);
+
+        for i in 0..header.num_param_headers as usize {
+            let Some(param) = SfdpParser::parse_param_header(&image[8 + i * 8..]) else {
+                break;
+            };
+            let start = param.table_pointer as usize;
+            let end = start + param.length_dwords as usize * 4;
+            if end > SFDP_SPACE_LIMIT || start < 8 + headers_len {
+                continue;
+            }
+            let table = read(start, end - start)?;
+            if image.len() < end {
+                image.resize(end, 0xFF);
+            }
+            image[start..end].copy_from_slice(&table);
+        }
+        Ok(Self::parse(&image))
+    }
+
+    /// Address bytes to use for the whole array
+    pub fn address_bytes(&self) -> u8 {
+        self.bfpt.address_bytes()
+    }
+
+    fn vendor_params(&self) -> Option<&VendorParams> {
+        self.vendor.iter().find_map(|t| t.params.as_ref())
+    }
+
+    /// Modes beyond 1S-1S-1S from the fast read list and the xSPI profile
+    pub fn io_modes(&self) -> Vec<SpiNorIoMode> {
+        let mut modes: Vec<SpiNorIoMode> = Vec::new();
+        let xspi = self.xspi.map(|_| SpiNorIoMode::Dtr888);
+        for mode in self
+            .bfpt
+            .fast_reads
+            .iter()
+            .map(|r| r.protocol.into())
+            .chain(xspi)
+        {
+            if mode != SpiNorIoMode::Sdr111 && !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        modes
+    }
+
+    pub fn octal_enable(&self) -> OctalEnable {
+        if self.octal_ddr.is_some() {
+            OctalEnable::SfdpSequence
+        } else if self.bfpt.octal_enable == 1 {
+            OctalEnable::StatusRegister2
+        } else {
+            OctalEnable::None
+        }
+    }
+
+    /// Chip info for a chip missing from the database
+    pub fn chip_info(&self, jedec_id: [u8; 3]) -> SpiNorChipInfo {
+        let erase_sizes = || self.bfpt.erase_types().map(|(_, t)| t.size);
+        let sector_size = if self.bfpt.erase_4k_opcode.is_some() {
+            4096
+        } else {
+            erase_sizes().min().unwrap_or(4096)
+        };
+        let lanes = |n: u8| {
+            self.bfpt
+                .fast_reads
+                .iter()
+                .any(|r| r.protocol.lanes().2 == n && !r.protocol.is_dtr())
+        };
+
+        SpiNorChipInfo {
+            manufacturer: get_spi_nor_manufacturer_name(jedec_id[0]).to_string(),
+            model: format!(
+                "SFDP SPI NOR 0x{:02X}{:02X}{:02X}",
+                jedec_id[0], jedec_id[1], jedec_id[2]
+            ),
+            jedec_id,
+            size_bytes: self.bfpt.size_bytes().min(u32::MAX as u64) as u32,
+            page_size: self.bfpt.page_size,
+            sector_size,
+            block_size: erase_sizes().max().unwrap_or(65536),
+            voltage: match self.vendor_params().map(VendorParams::vcc_mv) {
+                Some((min, max)) => format!(
+                    "{}.{}-{}.{}V",
+                    min / 1000,
+                    min % 1000 / 100,
+                    max / 1000,
+                    max % 1000 / 100
+                ),
+                None => "3.3V".into(),
+            },
+            max_clock_mhz: 80,
+            has_qspi: lanes(4),
+            has_dual: lanes(2),
+            address_bytes: self.address_bytes(),
+            io_modes: self.io_modes(),
+            octal_enable: self.octal_enable(),
+            four_byte_mode: Some(FourByteMode::from_sfdp(self)),
+        }
+    }
+
+    /// Fastest single-lane-command read using at most `max_lanes` data lanes
+    ///
+    /// 4-byte opcodes are used when the chip needs 4-byte addresses and has
+    /// them; falls back to FAST READ (0Bh).
+    pub fn read_command(&self, max_lanes: u8) -> FastRead {
+        let four_byte = self.address_bytes() == 4;
+        let mut read = self
+            .bfpt
+            .fast_reads
+            .iter()
+            .filter(|r| {
+                let (cmd, _, data) = r.protocol.lanes();
+                cmd == 1 && data <= max_lanes && !r.protocol.is_dtr()
+            })
+            .max_by_key(|r| {
+                let (_, addr, data) = r.protocol.lanes();
+                (data, addr)
+            })
+            .copied()
+            .unwrap_or(FastRead {
+                protocol: ReadProtocol::Fast111,
+                opcode: 0x0B,
+                mode_clocks: 0,
+                wait_states: 8,
+            });
+        if four_byte {
+            if let Some(opcode) = self.four_byte.and_then(|t| t.read_opcode(read.protocol)) {
+                read.opcode = opcode;
+            }
+        }
+        read
+    }
+
+    /// Largest erase that starts at `addr`, fits in `len` and is allowed
+    /// by the sector map configuration (if the chip has one)
+    pub fn erase_command(
+        &self,
+        addr: u32,
+        len: u32,
+        config: Option<&SectorMapConfig>,
+    ) -> Option<EraseOp> {
+        let region = config.and_then(|c| c.region_at(addr));
+        let address_bytes = self.address_bytes();
+        self.bfpt
+            .erase_types()
+            .filter(|(n, t)| {
+                addr % t.size == 0
+                    && t.size <= len
+                    && region.map_or(true, |r| {
+                        r.allows(*n)
+                            && addr as u64 + t.size as u64 <= r.start as u64 + r.size as u64
+                    })
+            })
+            .max_by_key(|(_, t)| t.size)
+            .map(|(n, t)| EraseOp {
+                opcode: match address_bytes {
+                    4 => self.four_byte.and_then(|f| f.erase(n)).unwrap_or(t.opcode),
+                    _ => t.opcode,
+                },
+                size: t.size,
+                address_bytes,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(table: &mut [u8], dword: usize, value: u32) {
+        table[(dword - 1) * 4..dword * 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// 256 Mbit chip, JESD216F BFPT with 4 KB / 32 KB / 64 KB / 256 KB erase
+    fn bfpt() -> Vec<u8> {
+        let mut t = vec![0u8; 23 * 4];
+        // 4 KB erase 20h, 3- or 4-byte, 1-1-2, 1-2-2, 1-4-4, 1-1-4, DTR
+        put(
+            &mut t,
+            1,
+            0x0001 | (0x20 << 8) | (1 << 17) | (0xF << 19) | (1 << 16),
+        );
+        put(&mut t, 2, 256 * 1024 * 1024 - 1);
+        // 1-4-4 EBh 4 wait + 2 mode, 1-1-4 6Bh 8 wait
+        put(&mut t, 3, 0x6B08_EB44);
+        // 1-1-2 3Bh 8 wait, 1-2-2 BBh 4 mode
+        put(&mut t, 4, 0xBB80_3B08);
+        put(&mut t, 8, 0x520F_200C);
+        put(&mut t, 9, 0xDC12_D810);
+        // Erase type 1: 48 ms typical, max multiplier 2 * (2 + 1)
+        put(&mut t, 10, 0x0000_0222);
+        // Page 256 bytes, 64 us typical page program
+        put(&mut t, 11, 0x0000_2181);
+        put(&mut t, 13, 0x30B0_7A75);
+        // Deep power-down: enter B9h, exit ABh
+        put(&mut t, 14, 0x0000_0004 | (0xB9 << 23) | (0xAB << 15));
+        put(&mut t, 15, 0x0020_0000);
+        // Enter: B7h, 06h+B7h, dedicated; exit: E9h, 06h+E9h; reset 66h/99h
+        put(&mut t, 16, (0x23 << 24) | (0x03 << 14) | (0x10 << 8));
+        put(&mut t, 20, 0xFFFF_F6F6);
+        t
+    }
+
+    fn sfdp_image() -> Vec<u8> {
+        let mut data = vec![0xFFu8; 0x200];
+        data[..8].copy_from_slice(b"SFDP\x06\x01\x03\xFF");
+        let headers: [(u16, u8, u32); 4] = [
+            (PARAM_ID_BFPT, 23, 0x30),
+            (PARAM_ID_4BAIT, 2, 0x90),
+            (PARAM_ID_SECTOR_MAP, 7, 0xA0),
+            (0x00C2, 4, 0xD0),
+        ];
+        for (i, &(id, len, ptr)) in headers.iter().enumerate() {
+            let h = 8 + i * 8;
+            data[h] = id as u8;
+            data[h + 1..h + 3].copy_from_slice(&[0x00, 0x01]);
+            data[h + 3] = len;
+            data[h + 4..h + 7].copy_from_slice(&ptr.to_le_bytes()[..3]);
+            data[h + 7] = (id >> 8) as u8;
+        }
+        data[0x30..0x30 + 92].copy_from_slice(&bfpt());
+
+        // 4BAIT: 0Ch, ECh, 12h, erase types 1-4
+        let mut t = vec![0u8; 8];
+        put(&mut t, 1, (1 << 1) | (1 << 5) | (1 << 6) | (0xF << 9));
+        put(&mut t, 2, 0xDCDC_5C21);
+        data[0x90..0x98].copy_from_slice(&t);
+
+        // Sector map: one detect command (65h, 3-byte address 800004h,
+        // 8 dummy, bit 1) and two maps: 4 KB sectors on top for ID 1
+        let mut t = vec![0u8; 7 * 4];
+        put(&mut t, 1, 0x0208_6500 | (1 << 22));
+        put(&mut t, 2, 0x0080_0004);
+        put(&mut t, 3, 0x0000_0002);
+        put(&mut t, 4, ((0x200_0000 / 256 - 1) << 8) | 0x0F);
+        put(&mut t, 5, 0x0101_0103);
+        put(&mut t, 6, ((0x1FF_0000 / 256 - 1) << 8) | 0x0C);
+        put(&mut t, 7, ((0x1_0000 / 256 - 1) << 8) | 0x01);
+        data[0xA0..0xA0 + 28].copy_from_slice(&t);
+
+        // Macronix: 2.70-3.60 V, S/W reset 99h, suspend
+        let mut t = vec![0u8; 16];
+        put(&mut t, 1, 0x2700_3600);
+        put(&mut t, 2, 0x0000_3998);
+        data[0xD0..0xE0].copy_from_slice(&t);
+        data
+    }
+
+    #[test]
+    fn test_full_bfpt_decode() {
+        let bfpt = Bfpt::parse(&bfpt()).unwrap();
+        assert_eq!(bfpt.size_bytes(), 32 * 1024 * 1024);
+        assert_eq!(bfpt.address_mode, AddressMode::ThreeOrFourByte);
+        assert_eq!(bfpt.erase_4k_opcode, Some(0x20));
+        assert!(bfpt.dtr);
+
+        let quad_io = bfpt.fast_read(ReadProtocol::Quad144).unwrap();
+        assert_eq!((quad_io.opcode, quad_io.dummy_clocks()), (0xEB, 6));
+        assert_eq!(
+            bfpt.fast_read(ReadProtocol::Dual122).unwrap().mode_clocks,
+            4
+        );
+        assert!(bfpt.fast_read(ReadProtocol::Quad444).is_none());
+
+        let types: Vec<(u8, u32, u8)> = bfpt
+            .erase_types()
+            .map(|(n, t)| (n, t.size, t.opcode))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                (1, 4096, 0x20),
+                (2, 32768, 0x52),
+                (3, 65536, 0xD8),
+                (4, 262144, 0xDC)
+            ]
+        );
+        let type1 = bfpt.erase_types[0].unwrap();
+        assert_eq!((type1.typical_ms, type1.max_ms), (48, 48 * 6));
+        assert_eq!(bfpt.page_size, 256);
+        assert_eq!(bfpt.page_program_typical_us, 128);
+
+        assert_eq!(
+            bfpt.suspend_resume.unwrap(),
+            SuspendResume {
+                program_resume: 0x75,
+                program_suspend: 0x7A,
+                erase_resume: 0xB0,
+                erase_suspend: 0x30,
+            }
+        );
+        assert_eq!(
+            bfpt.deep_power_down,
+            Some(DeepPowerDown {
+                enter: 0xB9,
+                exit: 0xAB
+            })
+        );
+        assert!(bfpt.busy_polling.status_wip && !bfpt.busy_polling.flag_status);
+        assert_eq!(bfpt.quad_enable_method, QuadEnableMethod::StatusReg1Bit6);
+        assert_eq!(
+            bfpt.four_byte_entry,
+            vec![
+                FourByteEntry::Command,
+                FourByteEntry::WriteEnableCommand,
+                FourByteEntry::DedicatedInstructions
+            ]
+        );
+        assert_eq!(
+            bfpt.four_byte_exit,
+            vec![FourByteExit::Command, FourByteExit::WriteEnableCommand]
+        );
+        assert_eq!(bfpt.soft_reset, vec![SoftReset::EnableAndReset]);
+        assert_eq!(
+            bfpt.max_speeds_mhz,
+            vec![(ReadProtocol::Quad444, 133), (ReadProtocol::Dtr444, 133)]
+        );
+    }
+
+    #[test]
+    fn test_sfdp_tables_drive_nor_commands() {
+        let sfdp = Sfdp::parse(&sfdp_image()).unwrap();
+        assert_eq!(sfdp.address_bytes(), 4);
+
+        // 1-4-4 read with its 4-byte opcode; 1-1-2 has no 4-byte variant
+        let read = sfdp.read_command(4);
+        assert_eq!((read.protocol, read.opcode), (ReadProtocol::Quad144, 0xEC));
+        let read = sfdp.read_command(2);
+        assert_eq!((read.protocol, read.opcode), (ReadProtocol::Dual122, 0xBB));
+
+        let vendor = &sfdp.vendor[0];
+        assert_eq!(vendor.manufacturer_name(), "Macronix");
+        let Some(VendorParams::Macronix(params)) = vendor.params else {
+            panic!("Macronix table not decoded");
+        };
+        assert_eq!((params.vcc_min_mv, params.vcc_max_mv), (2700, 3600));
+        assert_eq!(params.sw_reset_opcode, Some(0x99));
+        assert!(params.program_suspend && params.erase_suspend);
+
+        let info = sfdp.chip_info([0xC2, 0x20, 0x19]);
+        assert_eq!(info.size_bytes, 32 * 1024 * 1024);
+        assert_eq!((info.sector_size, info.block_size), (4096, 262144));
+        assert_eq!(info.voltage, "2.7-3.6V");
+        assert!(info.has_qspi && info.has_dual);
+    }
+
+    #[test]
+    fn test_sector_map_erase_selection() {
+        let sfdp = Sfdp::parse(&sfdp_image()).unwrap();
+        let map = sfdp.sector_map.as_ref().unwrap();
+        assert_eq!(map.detect[0].opcode, 0x65);
+        assert_eq!(map.detect[0].address_bytes, Some(3));
+        assert_eq!(map.detect[0].dummy_cycles, Some(8));
+
+        // Detect bit set: configuration 1, 4 KB sectors in the top 64 KB
+        let config = map.detect_config::<(), _>(|_| Ok(0x02)).unwrap().unwrap();
+        assert_eq!(config.id, 1);
+        assert_eq!(config.regions[1].start, 0x1FF_0000);
+
+        // Uniform region: 256 KB erase, with the 4-byte opcode
+        let op = sfdp.erase_command(0, 0x10_0000, Some(config)).unwrap();
+        assert_eq!((op.size, op.opcode), (262144, 0xDC));
+        // Only 4 KB erase in the top region
+        let op = sfdp
+            .erase_command(0x1FF_0000, 0x1_0000, Some(config))
+            .unwrap();
+        assert_eq!((op.size, op.opcode), (4096, 0x21));
+        // 64 KB erase must not cross into the top region
+        let op = sfdp
+            .erase_command(0x1FE_0000, 0x2_0000, Some(config))
+            .unwrap();
+        assert_eq!(op.size, 65536);
+
+        let default = map.detect_config::<(), _>(|_| Ok(0x00)).unwrap().unwrap();
+        assert_eq!(default.regions.len(), 1);
+    }
+
+    #[test]
+    fn test_xspi_profile() {
+        let mut t = vec![0u8; 6 * 4];
+        put(&mut t, 1, 0xEE00 | (1 << 28) | (1 << 29));
+        put(&mut t, 4, 20 << 7);
+        put(&mut t, 5, (16 << 27) | (14 << 17) | (10 << 7));
+        let xspi = XspiProfile1::parse(&t).unwrap();
+        assert_eq!(xspi.read_fast_opcode, 0xEE);
+        assert!(xspi.read_status_address);
+        assert_eq!(xspi.read_status_dummy, 8);
+        assert_eq!(xspi.dummy_cycles(100), 10);
+        assert_eq!(xspi.dummy_cycles(166), 16);
+        assert_eq!(xspi.dummy_cycles(200), 20);
+    }
+
+    #[test]
+    fn test_octal_ddr_sequences() {
+        let mut t = vec![0u8; 6 * 4];
+        // WREN, then WRCR2 72h 00000000h 02h
+        put(&mut t, 1, 0x0106_0000);
+        put(&mut t, 3, 0x0672_0000);
+        put(&mut t, 4, 0x0000_0200);
+        let seq = OctalDdrSequences::parse(&t).unwrap();
+        assert_eq!(
+            seq.commands,
+            vec![vec![0x06], vec![0x72, 0x00, 0x00, 0x00, 0x00, 0x02]]
+        );
+        assert!(OctalDdrSequences::parse(&[0u8; 8]).is_none());
+    }
+
+    #[test]
+    fn test_read_over_protocol() {
+        let image = sfdp_image();
+        let mut reads = 0;
+        let sfdp = Sfdp::read::<(), _>(|packet, _| {
+            assert_eq!(packet.cmd, Command::SpiNorReadSfdp);
+            let a = &packet.args;
+            let start = u32::from_le_bytes([a[0], a[1], a[2], 0]) as usize;
+            let len = u16::from_le_bytes([a[3], a[4]]) as usize;
+            assert!(len <= MAX_SFDP_READ as usize);
+            reads += 1;
+            Ok(image[start..start + len].to_vec())
+        })
+        .unwrap();
+        // Header, parameter headers and four tables
+        assert_eq!(reads, 6);
+        assert_eq!(sfdp, Sfdp::parse(&image));
+
+        let none = Sfdp::read::<(), _>(|_, _| Ok(vec![0xFF; 8])).unwrap();
+        assert!(none.is_none());
+    }
+
+    #[test]
+    fn test_vendor_tables() {
+        // GD25Q128: 2.70-3.60 V, S/W reset 99h, suspend, Set Burst with Wrap 77h
+        let mut t = vec![0u8; 12];
+        put(&mut t, 1, 0x2700_3600);
+        put(&mut t, 2, 0x6477_B99E);
+        let table = VendorTable::parse(0xC8, 1, 0, &t);
+        let Some(VendorParams::GigaDevice(gd)) = table.params else {
+            panic!("GigaDevice table not decoded");
+        };
+        assert_eq!((gd.vcc_min_mv, gd.vcc_max_mv), (2700, 3600));
+        assert_eq!(gd.sw_reset_opcode, Some(0x99));
+        assert_eq!(gd.burst_wrap_opcode, Some(0x77));
+        assert!(gd.deep_power_down && gd.hold_pin && !gd.hw_reset_pin);
+
+        // Macronix DWORD3: block lock 36h, secured OTP
+        put(&mut t, 3, (1 << 11) | (0x36 << 2) | 1);
+        let table = VendorTable::parse(0xC2, 1, 0, &t);
+        let Some(VendorParams::Macronix(mx)) = table.params else {
+            panic!("Macronix table not decoded");
+        };
+        assert_eq!(mx.block_lock_opcode, Some(0x36));
+        assert!(mx.secured_otp);
+
+        // Winbond has no vendor layout of its own
+        let table = VendorTable::parse(0xEF, 1, 0, &t);
+        assert!(table.params.is_none());
+        assert_eq!(table.dwords.len(), 3);
+
+        // Spansion ID-CFI: 2.7-3.6 V, 32 MB, 256 B pages
+        let mut cfi = vec![0u8; 0x50];
+        cfi[..3].copy_from_slice(&[0x01, 0x02, 0x19]);
+        cfi[0x10..0x13].copy_from_slice(b"QRY");
+        cfi[0x1B] = 0x27;
+        cfi[0x1C] = 0x36;
+        cfi[0x20] = 8;
+        cfi[0x21] = 8;
+        cfi[0x27] = 25;
+        cfi[0x2A] = 8;
+        let table = VendorTable::parse(0x01, 1, 0, &cfi);
+        assert_eq!(table.manufacturer_name(), "Spansion/Cypress");
+        let Some(VendorParams::Spansion(cfi_params)) = table.params else {
+            panic!("Spansion table not decoded");
+        };
+        assert_eq!(table.params.unwrap().vcc_mv(), (2700, 3600));
+        assert_eq!(cfi_params.size_bytes, 32 * 1024 * 1024);
+        assert_eq!(cfi_params.page_size, 256);
+        assert_eq!(cfi_params.sector_erase_typical_ms, 256);
+        assert!(VendorTable::parse(0x01, 1, 0, &[0u8; 0x50])
+            .params
+            .is_none());
+
+        // A 5-DWORD table ends right after "QRY"
+        let short = VendorTable::parse(0x01, 1, 0, &cfi[..20]);
+        assert!(short.params.is_none());
+        assert_eq!(short.dwords.len(), 5);
+    }
+}
@@ -1,6 +1,11 @@
 //! SPI NAND Flash chip database and protocol
-//! Contains known SPI NAND chip parameters and command definitions
+//! Known chips are looked up in the chip database (see `chipdb`); this module
+//! holds protocol definitions and generic detection
 
+use crate::bbm::BbmPolicy;
+use crate::chipdb::chip_database;
+use crate::onfi::default_one;
+use crate::protocol::{Command, Packet};
 use serde::{Deserialize, Serialize};
 
 /// SPI NAND chip information
@@ -18,6 +23,22 @@ pub struct SpiNandChipInfo {
     pub has_ecc: bool,     // Internal ECC
     pub cell_type: SpiNandCellType,
     pub planes: u8, // Number of planes (1, 2, or 4)
+    #[serde(default)]
+    pub ecc_decoder: SpiNandEccDecoder,
+    /// OOB bytes holding on-die ECC parity
+    #[serde(default)]
+    pub ecc_layout: SpiNandEccLayout,
+    /// Plane select bit in the column address for odd blocks
+    #[serde(default)]
+    pub plane_select: bool,
+    /// Stacked dies; `size_mb` covers all of them
+    #[serde(default = "default_one")]
+    pub dies: u8,
+    #[serde(default)]
+    pub die_select: SpiNandDieSelect,
+    /// Continuous read (BUF=0) streams pages without a PAGE READ each
+    #[serde(default)]
+    pub continuous_read: bool,
 }
 
 /// SPI NAND cell type
@@ -59,6 +80,9 @@ pub mod commands {
     // Erase operations
     pub const BLOCK_ERASE: u8 = 0xD8;
 
+    // Stacked dies (Winbond W25M)
+    pub const SOFTWARE_DIE_SELECT: u8 = 0xC2;
+
     // Protection
     pub const READ_STATUS: u8 = 0x05; // Alias for GET_FEATURE(0xC0)
 }
@@ -86,6 +110,7 @@ pub mod feature_bits {
     pub const QE: u8 = 0x01; // Quad Enable
     pub const ECC_EN: u8 = 0x10; // ECC Enable
     pub const BUF: u8 = 0x08; // Buffer mode
+    pub const DIE_SELECT_SHIFT: u8 = 6; // Die number in the die select register (0xD0)
 }
 
 /// Get manufacturer name from ID
@@ -108,737 +133,23 @@ pub fn get_spi_nand_manufacturer_name(id: u8) -> &'static str {
     }
 }
 
-/// Database of known SPI NAND flash chips
+/// Look up a SPI NAND flash chip in the chip database
 /// Returns chip info based on manufacturer ID and device ID
 pub fn get_spi_nand_chip_info(chip_id: &[u8]) -> Option<SpiNandChipInfo> {
     if chip_id.len() < 2 {
         return None;
     }
 
-    let mfr = chip_id[0];
-    let device = if chip_id.len() >= 3 {
-        chip_id[1..3].to_vec()
-    } else {
-        vec![chip_id[1]]
-    };
+    // Manufacturer ID plus up to two device ID bytes
+    let id = &chip_id[..chip_id.len().min(3)];
 
-    // Try exact match first
-    if let Some(info) = get_spi_nand_chip_info_exact(mfr, &device) {
+    // Try the chip database first
+    if let Some(info) = chip_database().lookup_spi_nand(id) {
         return Some(info);
     }
 
     // Fall back to generic detection
-    get_spi_nand_chip_info_generic(mfr, &device)
-}
-
-fn get_spi_nand_chip_info_exact(mfr: u8, device: &[u8]) -> Option<SpiNandChipInfo> {
-    match (mfr, device) {
-        // ============ GigaDevice ============
-        // GD5F1GQ4UBxIG - 128MB SLC
-        (0xC8, [0xD1, ..]) | (0xC8, [0xB1, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F1GQ4UBxIG".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F2GQ4UBxIG - 256MB SLC
-        (0xC8, [0xD2, ..]) | (0xC8, [0xB2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F2GQ4UBxIG".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F4GQ4UBxIG - 512MB SLC
-        (0xC8, [0xD4, ..]) | (0xC8, [0xB4, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F4GQ4UBxIG".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F1GQ5UExxG - 128MB 1.8V
-        (0xC8, [0x51, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F1GQ5UExxG".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Winbond ============
-        // W25N01GV - 128MB SLC
-        (0xEF, [0xAA, 0x21]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N01GV".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // W25N02KV - 256MB SLC
-        (0xEF, [0xAA, 0x22]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N02KV".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 2,
-        }),
-        // W25N04KV - 512MB SLC
-        (0xEF, [0xAA, 0x23]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N04KV".into(),
-            size_mb: 512,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 4,
-        }),
-        // W25N01JW - 128MB 1.8V
-        (0xEF, [0xBC, 0x21]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N01JW".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Macronix ============
-        // MX35LF1GE4AB - 128MB SLC
-        (0xC2, [0x12, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35LF1GE4AB".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // MX35LF2GE4AB - 256MB SLC
-        (0xC2, [0x22, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35LF2GE4AB".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // MX35LF4GE4AD - 512MB SLC
-        (0xC2, [0x37, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35LF4GE4AD".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Micron ============
-        // MT29F1G01ABAFD - 128MB SLC
-        (0x2C, [0x14, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F1G01ABAFD".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // MT29F2G01ABAGD - 256MB SLC
-        (0x2C, [0x24, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F2G01ABAGD".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 2,
-        }),
-        // MT29F4G01ABAFD - 512MB SLC
-        (0x2C, [0x34, ..]) | (0x2C, [0x36, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F4G01ABAFD".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 256,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Toshiba/Kioxia ============
-        // TC58CVG0S3HRAIG - 128MB SLC
-        (0x98, [0xC2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58CVG0S3HRAIG".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // TC58CVG1S3HRAIG - 256MB SLC
-        (0x98, [0xCB, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58CVG1S3HRAIG".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // TC58CVG2S0HRAIG - 512MB SLC
-        (0x98, [0xCD, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Toshiba".into(),
-            model: "TC58CVG2S0HRAIG".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ XTX ============
-        // XT26G01A - 128MB SLC
-        (0x0B, [0xE1, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "XTX".into(),
-            model: "XT26G01A".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // XT26G02A - 256MB SLC
-        (0x0B, [0xE2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "XTX".into(),
-            model: "XT26G02A".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // XT26G04A - 512MB SLC (v2.2)
-        (0x0B, [0xE4, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "XTX".into(),
-            model: "XT26G04A".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // XT26G01C - 128MB SLC 1.8V (v2.2)
-        (0x0B, [0x11, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "XTX".into(),
-            model: "XT26G01C".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // XT26G02C - 256MB SLC 1.8V (v2.2)
-        (0x0B, [0x12, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "XTX".into(),
-            model: "XT26G02C".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Winbond W25N Series (v2.2) ============
-        // W25N512GV - 64MB SLC
-        (0xEF, [0xAA, 0x20]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N512GV".into(),
-            size_mb: 64,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // W25N01KV - 128MB SLC (new series)
-        (0xEF, [0xAE, 0x21]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N01KV".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // W25N02GV - 256MB SLC
-        (0xEF, [0xAA, 0x24]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N02GV".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 2,
-        }),
-        // W25N01JW - 128MB 1.8V (already exists, adding W25N02JW)
-        // W25N02JW - 256MB 1.8V
-        (0xEF, [0xBC, 0x22]) => Some(SpiNandChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25N02JW".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 2,
-        }),
-
-        // ============ GigaDevice GD5F1GM9 Series (v2.2) ============
-        // GD5F1GM9UxxG - 128MB High-Speed QSPI NAND
-        (0xC8, [0x91, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F1GM9UxxG".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 166,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F2GM9UxxG - 256MB High-Speed
-        (0xC8, [0x92, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F2GM9UxxG".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 166,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F4GM9UxxG - 512MB High-Speed
-        (0xC8, [0x94, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F4GM9UxxG".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 256,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 166,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F1GQ5UExxH - 128MB 1.8V
-        (0xC8, [0x31, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F1GQ5UExxH".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // GD5F2GQ5UExxH - 256MB 1.8V
-        (0xC8, [0x32, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD5F2GQ5UExxH".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Foresee/Longsys (v2.2) ============
-        // FS35ND01G - 128MB SLC
-        (0xCD, [0xB1, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Foresee".into(),
-            model: "FS35ND01G".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // FS35ND02G - 256MB SLC
-        (0xCD, [0xB2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Foresee".into(),
-            model: "FS35ND02G".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // FS35ND04G - 512MB SLC
-        (0xCD, [0xB4, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Foresee".into(),
-            model: "FS35ND04G".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Dosilicon (v2.2) ============
-        // DS35Q1GA - 128MB SLC
-        (0xE5, [0x71, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Dosilicon".into(),
-            model: "DS35Q1GA".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // DS35Q2GA - 256MB SLC
-        (0xE5, [0x72, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Dosilicon".into(),
-            model: "DS35Q2GA".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Zetta (v2.2) ============
-        // ZD35Q1GA - 128MB SLC
-        (0xBA, [0x21, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Zetta".into(),
-            model: "ZD35Q1GA".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // ZD35Q2GA - 256MB SLC
-        (0xBA, [0x22, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Zetta".into(),
-            model: "ZD35Q2GA".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Micron MT29F SPI NAND (v2.2) ============
-        // MT29F8G01ADAFD - 1GB SLC
-        (0x2C, [0x46, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT29F8G01ADAFD".into(),
-            size_mb: 1024,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 256,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 2,
-        }),
-
-        // ============ Macronix MX35UF Series 1.8V (v2.2) ============
-        // MX35UF1GE4AD - 128MB 1.8V
-        (0xC2, [0x92, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35UF1GE4AD".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // MX35UF2GE4AD - 256MB 1.8V
-        (0xC2, [0xA2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35UF2GE4AD".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // MX35UF4GE4AD - 512MB 1.8V
-        (0xC2, [0xB7, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX35UF4GE4AD".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        // ============ Toshiba/Kioxia TC58 Series (v2.2) ============
-        // TC58CVG2S0HRAIJ - 512MB SLC (new revision)
-        (0x98, [0xED, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58CVG2S0HRAIJ".into(),
-            size_mb: 512,
-            page_size: 4096,
-            block_size: 64,
-            oob_size: 128,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // TC58CYG0S3HRAIJ - 128MB 1.8V
-        (0x98, [0xD2, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58CYG0S3HRAIJ".into(),
-            size_mb: 128,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-        // TC58CYG1S3HRAIJ - 256MB 1.8V
-        (0x98, [0xDB, ..]) => Some(SpiNandChipInfo {
-            manufacturer: "Kioxia".into(),
-            model: "TC58CYG1S3HRAIJ".into(),
-            size_mb: 256,
-            page_size: 2048,
-            block_size: 64,
-            oob_size: 64,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_ecc: true,
-            cell_type: SpiNandCellType::SLC,
-            planes: 1,
-        }),
-
-        _ => None,
-    }
+    get_spi_nand_chip_info_generic(id[0], &id[1..])
 }
 
 /// Generic SPI NAND chip detection based on device ID patterns
@@ -883,6 +194,12 @@ fn get_spi_nand_chip_info_generic(mfr: u8, device: &[u8]) -> Option<SpiNandChipI
         has_ecc: true,
         cell_type: SpiNandCellType::SLC,
         planes: 1,
+        ecc_decoder: SpiNandEccDecoder::Generic,
+        ecc_layout: SpiNandEccLayout::UpperHalf,
+        plane_select: false,
+        dies: 1,
+        die_select: SpiNandDieSelect::None,
+        continuous_read: false,
     })
 }
 
@@ -920,6 +237,201 @@ impl EccStatus {
     }
 }
 
+/// Where a decoder reads the exact bitflip count from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum EccStatusSource {
+    /// GET FEATURE of a vendor register
+    Feature(u8),
+    /// Vendor command returning one status byte
+    Command(u8),
+}
+
+impl EccStatusSource {
+    pub fn to_packet(&self) -> Packet {
+        match *self {
+            EccStatusSource::Feature(addr) => Packet::new(Command::SpiNandGetFeature, &[addr]),
+            EccStatusSource::Command(cmd) => Packet::new(Command::SpiNandReadEccStatus, &[cmd]),
+        }
+    }
+}
+
+/// Vendor layout of the on-die ECC status
+///
+/// Where a chip only reports a range of corrected bits, the upper bound is
+/// returned so reports never understate wear.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SpiNandEccDecoder {
+    /// Status bits 5:4: 01 = 1-3 bits, 10 = 4+ bits, 11 = uncorrectable
+    #[default]
+    Generic,
+    /// 1-bit ECC (Winbond W25N01GV): 01 = 1 bit, 10/11 = uncorrectable
+    SingleBit,
+    /// GigaDevice GD5FxGQ4xA: 01 = 1-7 bits, 11 = 8 bits, 10 = uncorrectable
+    GigaDeviceQ4A,
+    /// GigaDevice GD5FxGQ4UB/UE: 01 refined by ECCSE (feature F0h bits 5:4) to 4-7 bits
+    GigaDeviceQ4Ue,
+    /// GigaDevice GD5FxGQ5: 01 = 1 + ECCSE (feature F0h bits 5:4), 11 = 4 bits
+    GigaDeviceQ5,
+    /// GigaDevice GD5FxGM9: 3-bit field in bits 6:4, 111 = uncorrectable
+    GigaDeviceM9,
+    /// Macronix: 01 = READ ECC STATUS (7Ch) returns the count
+    Macronix,
+    /// Micron: bits 6:4, 001 = 1-3, 011 = 4-6, 101 = 7-8, 010 = uncorrectable
+    Micron,
+    /// Toshiba/Kioxia, Winbond W25NxxKV: 01/11 = count in feature 30h bits 7:4
+    BitflipFeature,
+    /// XTX XT26GxxA: 4-bit field in bits 5:2, 1000 = uncorrectable, 1100 = 8 bits
+    XtxA,
+    /// XTX XT26GxxC: 4-bit field in bits 7:4 (0-8 bits), 1111 = uncorrectable
+    XtxC,
+}
+
+/// GigaDevice ECC status 2 register
+pub const GIGADEVICE_FEATURE_STATUS2: u8 = 0xF0;
+
+/// Toshiba/Kioxia and Winbond maximum bitflip register
+pub const FEATURE_BITFLIP_COUNT: u8 = 0x30;
+
+/// Macronix READ ECC STATUS command
+pub const MACRONIX_READ_ECC_STATUS: u8 = 0x7C;
+
+impl SpiNandEccDecoder {
+    /// Extra register needed to decode `status`, if any
+    pub fn extra_source(&self, status: u8) -> Option<EccStatusSource> {
+        let field = (status >> 4) & 0x03;
+        match self {
+            Self::GigaDeviceQ4Ue | Self::GigaDeviceQ5 if field == 0b01 => {
+                Some(EccStatusSource::Feature(GIGADEVICE_FEATURE_STATUS2))
+            }
+            Self::Macronix if field == 0b01 => {
+                Some(EccStatusSource::Command(MACRONIX_READ_ECC_STATUS))
+            }
+            Self::BitflipFeature if field == 0b01 || field == 0b11 => {
+                Some(EccStatusSource::Feature(FEATURE_BITFLIP_COUNT))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decode the status register (C0h); `extra` is the value read from
+    /// `extra_source`, without it the vendor's upper bound is reported
+    pub fn decode(&self, status: u8, extra: Option<u8>) -> EccStatus {
+        let field = (status >> 4) & 0x03;
+        let corrected = |bits: u8| {
+            if bits == 0 {
+                EccStatus::NoError
+            } else {
+                EccStatus::Corrected(bits)
+            }
+        };
+
+        match self {
+            Self::Generic => EccStatus::from_status_register(status),
+            Self::SingleBit => match field {
+                0b00 => EccStatus::NoError,
+                0b01 => EccStatus::Corrected(1),
+                _ => EccStatus::Uncorrectable,
+            },
+            Self::GigaDeviceQ4A | Self::GigaDeviceQ4Ue => match field {
+                0b00 => EccStatus::NoError,
+                0b01 if *self == Self::GigaDeviceQ4Ue => {
+                    EccStatus::Corrected(4 | extra.map_or(0x03, |s2| (s2 >> 4) & 0x03))
+                }
+                0b01 => EccStatus::Corrected(7),
+                0b10 => EccStatus::Uncorrectable,
+                _ => EccStatus::Corrected(8),
+            },
+            Self::GigaDeviceQ5 => match field {
+                0b00 => EccStatus::NoError,
+                0b01 => EccStatus::Corrected(1 + extra.map_or(0x03, |s2| (s2 >> 4) & 0x03)),
+                0b10 => EccStatus::Uncorrectable,
+                _ => EccStatus::Corrected(4),
+            },
+            Self::GigaDeviceM9 => match (status >> 4) & 0x07 {
+                0b000 => EccStatus::NoError,
+                0b111 => EccStatus::Uncorrectable,
+                bits => EccStatus::Corrected(bits + 2),
+            },
+            Self::Macronix => match field {
+                0b00 => EccStatus::NoError,
+                0b01 => corrected(extra.map_or(4, |eccsr| (eccsr & 0x0F).clamp(1, 4))),
+                0b10 => EccStatus::Uncorrectable,
+                _ => EccStatus::Corrected(4),
+            },
+            Self::Micron => match (status >> 4) & 0x07 {
+                0b000 => EccStatus::NoError,
+                0b001 => EccStatus::Corrected(3),
+                0b011 => EccStatus::Corrected(6),
+                0b101 => EccStatus::Corrected(8),
+                _ => EccStatus::Uncorrectable,
+            },
+            Self::BitflipFeature => match field {
+                0b00 => EccStatus::NoError,
+                0b10 => EccStatus::Uncorrectable,
+                _ => corrected(extra.map_or(8, |mbf| (mbf >> 4).max(1))),
+            },
+            Self::XtxA => match (status >> 2) & 0x0F {
+                0b1000 => EccStatus::Uncorrectable,
+                0b1100 => EccStatus::Corrected(8),
+                bits if bits < 0b1000 => corrected(bits),
+                _ => EccStatus::Uncorrectable,
+            },
+            Self::XtxC => match status >> 4 {
+                bits if bits <= 8 => corrected(bits),
+                _ => EccStatus::Uncorrectable,
+            },
+        }
+    }
+
+    /// Decode `status`, reading the extra register through `read` when needed
+    pub fn decode_with<E, R>(&self, status: u8, mut read: R) -> Result<EccStatus, E>
+    where
+        R: FnMut(EccStatusSource) -> Result<u8, E>,
+    {
+        let extra = match self.extra_source(status) {
+            Some(source) => Some(read(source)?),
+            None => None,
+        };
+        Ok(self.decode(status, extra))
+    }
+}
+
+/// Per-page ECC results of a dump
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SpiNandEccReport {
+    pub pages_read: u32,
+    /// (page, corrected bits) for every page that needed correction
+    pub corrected: Vec<(u32, u8)>,
+    pub uncorrectable: Vec<u32>,
+}
+
+impl SpiNandEccReport {
+    pub fn record(&mut self, page: u32, status: EccStatus) {
+        self.pages_read += 1;
+        match status {
+            EccStatus::Corrected(bits) => self.corrected.push((page, bits)),
+            EccStatus::Uncorrectable => self.uncorrectable.push(page),
+            EccStatus::NoError | EccStatus::Disabled => {}
+        }
+    }
+
+    pub fn total_corrected_bits(&self) -> u32 {
+        self.corrected.iter().map(|&(_, bits)| bits as u32).sum()
+    }
+
+    pub fn max_bitflips(&self) -> u8 {
+        self.corrected
+            .iter()
+            .map(|&(_, bits)| bits)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.corrected.is_empty() && self.uncorrectable.is_empty()
+    }
+}
+
 /// Calculate page address for SPI NAND
 /// SPI NAND uses row address (block + page within block)
 pub fn calculate_row_address(block: u32, page_in_block: u32, pages_per_block: u32) -> u32 {
@@ -935,6 +447,518 @@ pub fn calculate_column_address(offset: u16, include_oob: bool, page_size: u16)
     }
 }
 
+// ============================================================================
+// On-Die ECC Control
+// ============================================================================
+
+/// ECC_EN and BUF settings of the feature register (B0h)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiNandReadMode {
+    /// On-die ECC corrects the page on PAGE READ and reports its status
+    pub ecc: bool,
+    /// Buffer read mode; clearing it selects continuous read where supported
+    pub buf: bool,
+}
+
+impl SpiNandReadMode {
+    /// Corrected data, parity bytes may read back as 0xFF
+    pub const CORRECTED: Self = Self {
+        ecc: true,
+        buf: true,
+    };
+    /// Page + OOB exactly as stored, vendor parity included
+    pub const RAW: Self = Self {
+        ecc: false,
+        buf: true,
+    };
+
+    pub fn from_feature(value: u8) -> Self {
+        Self {
+            ecc: value & feature_bits::ECC_EN != 0,
+            buf: value & feature_bits::BUF != 0,
+        }
+    }
+
+    /// `current` with ECC_EN and BUF replaced, other bits (QE, ...) kept
+    pub fn apply(&self, current: u8) -> u8 {
+        let mut value = current & !(feature_bits::ECC_EN | feature_bits::BUF);
+        if self.ecc {
+            value |= feature_bits::ECC_EN;
+        }
+        if self.buf {
+            value |= feature_bits::BUF;
+        }
+        value
+    }
+
+    pub fn set_packet(&self, current: u8) -> Packet {
+        Packet::new(
+            Command::SpiNandSetFeature,
+            &[features::FEATURE, self.apply(current)],
+        )
+    }
+}
+
+/// Page variants captured by a read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpiNandEccCapture {
+    /// On-die ECC on
+    #[default]
+    Corrected,
+    /// On-die ECC off
+    Raw,
+    /// Both, each from its own PAGE READ (ECC is applied on the array read)
+    Both,
+}
+
+impl SpiNandEccCapture {
+    pub fn modes(&self) -> &'static [SpiNandReadMode] {
+        match self {
+            SpiNandEccCapture::Corrected => &[SpiNandReadMode::CORRECTED],
+            SpiNandEccCapture::Raw => &[SpiNandReadMode::RAW],
+            SpiNandEccCapture::Both => &[SpiNandReadMode::RAW, SpiNandReadMode::CORRECTED],
+        }
+    }
+}
+
+/// Byte range within the OOB area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OobRegion {
+    pub offset: u32,
+    pub len: u32,
+}
+
+/// Where the on-die ECC stores its parity in the OOB area
+///
+/// The first two OOB bytes hold the bad-block marker and are never free.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SpiNandEccLayout {
+    /// Upper half of the OOB area (Micron, GigaDevice Q5/M9)
+    #[default]
+    UpperHalf,
+    /// Last 8 bytes of each 16-byte OOB section (Winbond W25N GV/JW)
+    Interleaved16,
+    /// Last N bytes of the OOB area (XTX XT26GxxA)
+    Tail(u32),
+    /// Outside the addressable OOB area (Macronix, Toshiba/Kioxia)
+    Hidden,
+}
+
+impl SpiNandEccLayout {
+    pub fn parity_regions(&self, oob_size: u32) -> Vec<OobRegion> {
+        match *self {
+            SpiNandEccLayout::UpperHalf => vec![OobRegion {
+                offset: oob_size / 2,
+                len: oob_size / 2,
+            }],
+            SpiNandEccLayout::Interleaved16 => (0..oob_size / 16)
+                .map(|section| OobRegion {
+                    offset: section * 16 + 8,
+                    len: 8,
+                })
+                .collect(),
+            SpiNandEccLayout::Tail(len) => {
+                let len = len.min(oob_size);
+                vec![OobRegion {
+                    offset: oob_size - len,
+                    len,
+                }]
+            }
+            SpiNandEccLayout::Hidden => Vec::new(),
+        }
+    }
+
+    /// User bytes: everything but the marker and the parity
+    pub fn free_regions(&self, oob_size: u32) -> Vec<OobRegion> {
+        let mut free = Vec::new();
+        let mut start = 2.min(oob_size);
+        for parity in self.parity_regions(oob_size) {
+            if parity.offset > start {
+                free.push(OobRegion {
+                    offset: start,
+                    len: parity.offset - start,
+                });
+            }
+            start = start.max(parity.offset + parity.len);
+        }
+        if oob_size > start {
+            free.push(OobRegion {
+                offset: start,
+                len: oob_size - start,
+            });
+        }
+        free
+    }
+
+    /// Parity bytes of one raw OOB area, concatenated in layout order
+    pub fn extract_parity(&self, oob: &[u8]) -> Vec<u8> {
+        self.parity_regions(oob.len() as u32)
+            .iter()
+            .flat_map(|r| &oob[r.offset as usize..(r.offset + r.len) as usize])
+            .copied()
+            .collect()
+    }
+}
+
+/// One page read with on-die ECC off, on, or both
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpiNandPageCapture {
+    /// Page + OOB with ECC off
+    pub raw: Option<Vec<u8>>,
+    /// Page + OOB with ECC on
+    pub corrected: Option<Vec<u8>>,
+    /// Status of the corrected read, `Disabled` for raw-only captures
+    pub ecc_status: EccStatus,
+}
+
+// ============================================================================
+// Multi-Die and Plane Addressing
+// ============================================================================
+
+/// How a stacked package switches between dies
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SpiNandDieSelect {
+    /// Single die
+    #[default]
+    None,
+    /// Software die select command C2h (Winbond W25M)
+    SoftwareCommand,
+    /// Die number in feature register D0h bit 6 (Micron)
+    Feature,
+}
+
+impl SpiNandDieSelect {
+    pub fn packets(&self, die: u8) -> Vec<Packet> {
+        match self {
+            SpiNandDieSelect::None => Vec::new(),
+            SpiNandDieSelect::SoftwareCommand => {
+                vec![Packet::new(Command::SpiNandDieSelect, &[die])]
+            }
+            SpiNandDieSelect::Feature => vec![Packet::new(
+                Command::SpiNandSetFeature,
+                &[features::DIE_SELECT, die << feature_bits::DIE_SELECT_SHIFT],
+            )],
+        }
+    }
+}
+
+/// Location of a page within a multi-die, multi-plane package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiNandPageAddress {
+    pub die: u8,
+    /// Row address within the die
+    pub row: u32,
+    pub plane: u8,
+}
+
+impl SpiNandPageAddress {
+    /// Column address of `offset`; the plane bit sits just above the page + OOB range
+    pub fn column(&self, offset: u16, page_size: u32) -> u16 {
+        let plane_shift = 32 - page_size.leading_zeros();
+        offset | ((self.plane as u16) << plane_shift)
+    }
+}
+
+impl SpiNandChipInfo {
+    pub fn blocks_per_die(&self) -> u32 {
+        let block_bytes = self.page_size as u64 * self.block_size as u64;
+        if block_bytes == 0 {
+            return 0;
+        }
+        (self.size_mb as u64 * 1024 * 1024 / block_bytes / self.dies.max(1) as u64) as u32
+    }
+
+    pub fn total_pages(&self) -> u32 {
+        self.blocks_per_die() * self.block_size * self.dies.max(1) as u32
+    }
+
+    /// Address of a page, with blocks numbered across all dies
+    pub fn page_address(&self, block: u32, page_in_block: u32) -> SpiNandPageAddress {
+        let blocks_per_die = self.blocks_per_die().max(1);
+        let block_in_die = block % blocks_per_die;
+        SpiNandPageAddress {
+            die: (block / blocks_per_die) as u8,
+            row: calculate_row_address(block_in_die, page_in_block, self.block_size),
+            plane: if self.plane_select && self.planes > 1 {
+                (block_in_die % self.planes as u32) as u8
+            } else {
+                0
+            },
+        }
+    }
+}
+
+/// Reads pages across every die of a package
+///
+/// Die select and feature register packets are only emitted when the die or
+/// read mode changes. Each die has its own feature register, so the read
+/// mode is set again after a die switch.
+#[derive(Debug, Clone)]
+pub struct SpiNandReader<'a> {
+    info: &'a SpiNandChipInfo,
+    selected_die: Option<u8>,
+    /// Feature register value the read mode is merged into
+    feature: u8,
+    read_mode: Option<SpiNandReadMode>,
+    applied_mode: Option<SpiNandReadMode>,
+}
+
+impl<'a> SpiNandReader<'a> {
+    pub fn new(info: &'a SpiNandChipInfo) -> Self {
+        Self {
+            info,
+            selected_die: None,
+            feature: feature_bits::ECC_EN | feature_bits::BUF,
+            read_mode: None,
+            applied_mode: None,
+        }
+    }
+
+    /// Feature register value read from the chip, so QE and vendor bits survive
+    pub fn with_feature_register(mut self, value: u8) -> Self {
+        self.feature = value;
+        self
+    }
+
+    /// Read mode set before page reads; without one the chip's mode is left alone
+    pub fn with_read_mode(mut self, mode: SpiNandReadMode) -> Self {
+        self.read_mode = Some(mode);
+        self
+    }
+
+    /// Die select (if needed), PAGE READ and READ FROM CACHE of page + OOB
+    pub fn page_packets(&mut self, block: u32, page_in_block: u32) -> Vec<Packet> {
+        let mode = self.read_mode;
+        self.page_packets_in(block, page_in_block, mode)
+    }
+
+    /// `page_packets` with an explicit read mode
+    pub fn page_packets_in(
+        &mut self,
+        block: u32,
+        page_in_block: u32,
+        mode: Option<SpiNandReadMode>,
+    ) -> Vec<Packet> {
+        let addr = self.info.page_address(block, page_in_block);
+        let mut packets = Vec::new();
+        if self.info.dies > 1 && self.selected_die != Some(addr.die) {
+            packets.extend(self.info.die_select.packets(addr.die));
+            self.selected_die = Some(addr.die);
+            self.applied_mode = None;
+        }
+        if let Some(mode) = mode.filter(|&m| self.applied_mode != Some(m)) {
+            packets.push(mode.set_packet(self.feature));
+            self.applied_mode = Some(mode);
+        }
+
+        packets.push(Packet::new(
+            Command::SpiNandPageRead,
+            &addr.row.to_le_bytes(),
+        ));
+        let column = addr.column(0, self.info.page_size).to_le_bytes();
+        let len = ((self.info.page_size + self.info.oob_size) as u16).to_le_bytes();
+        packets.push(Packet::new(
+            Command::SpiNandReadCache,
+            &[column[0], column[1], len[0], len[1]],
+        ));
+        packets
+    }
+
+    /// Read one page in the variants of `capture`
+    ///
+    /// `transact` sends a packet and returns its response: page + OOB for
+    /// READ FROM CACHE, the register value for GET FEATURE.
+    pub fn capture_page<E, T>(
+        &mut self,
+        block: u32,
+        page_in_block: u32,
+        capture: SpiNandEccCapture,
+        mut transact: T,
+    ) -> Result<SpiNandPageCapture, E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        let mut result = SpiNandPageCapture {
+            raw: None,
+            corrected: None,
+            ecc_status: EccStatus::Disabled,
+        };
+        for &mode in capture.modes() {
+            let mut data = Vec::new();
+            for packet in self.page_packets_in(block, page_in_block, Some(mode)) {
+                data = transact(&packet)?;
+            }
+
+            if mode.ecc {
+                let status_packet = Packet::new(Command::SpiNandGetFeature, &[features::STATUS]);
+                let status = transact(&status_packet)?.first().copied().unwrap_or(0);
+                result.ecc_status = self.info.ecc_decoder.decode_with(status, |source| {
+                    Ok(transact(&source.to_packet())?.first().copied().unwrap_or(0))
+                })?;
+                result.corrected = Some(data);
+            } else {
+                result.raw = Some(data);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Read the whole package; `read` sends one page's packets (the page
+    /// number is chip-wide) and stores the data
+    pub fn read_chip<E, R>(&mut self, mut read: R) -> Result<(), E>
+    where
+        R: FnMut(u32, &[Packet]) -> Result<(), E>,
+    {
+        let blocks = self.info.blocks_per_die() * self.info.dies.max(1) as u32;
+        for block in 0..blocks {
+            for page in 0..self.info.block_size {
+                let packets = self.page_packets(block, page);
+                read(block * self.info.block_size + page, &packets)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Continuous Read
+// ============================================================================
+
+/// Stream `pages` pages of main data starting at `row` (BUF=0 must be set)
+pub fn continuous_read_packet(row: u32, pages: u32, quad: bool) -> Packet {
+    let cmd = if quad {
+        Command::SpiNandReadCacheContinuousX4
+    } else {
+        Command::SpiNandReadCacheContinuous
+    };
+    let mut args = row.to_le_bytes().to_vec();
+    args.extend_from_slice(&pages.to_le_bytes());
+    Packet::new(cmd, &args)
+}
+
+impl SpiNandChipInfo {
+    /// Drop `bad_blocks` from a main-data dump (no OOB), as skip-bad reads do
+    pub fn strip_bad_blocks(&self, data: &[u8], bad_blocks: &[u32]) -> Vec<u8> {
+        let block_bytes = (self.page_size * self.block_size) as usize;
+        if block_bytes == 0 {
+            return data.to_vec();
+        }
+        data.chunks(block_bytes)
+            .enumerate()
+            .filter(|(block, _)| !bad_blocks.contains(&(*block as u32)))
+            .flat_map(|(_, chunk)| chunk.iter().copied())
+            .collect()
+    }
+}
+
+impl<'a> SpiNandReader<'a> {
+    /// Continuous read of whole dies, one stream per die
+    ///
+    /// Each die is switched to BUF=0 for its stream and back to buffer
+    /// mode afterwards. The chip only reports the worst ECC status of the
+    /// stream, so per-page status needs a buffer-mode read.
+    pub fn continuous_packets(&mut self, quad: bool) -> Vec<Packet> {
+        let ecc = self.read_mode.map_or(true, |m| m.ecc);
+        let stream_mode = SpiNandReadMode { ecc, buf: false };
+        let buffer_mode = SpiNandReadMode { ecc, buf: true };
+        let pages_per_die = self.info.blocks_per_die() * self.info.block_size;
+
+        let mut packets = Vec::new();
+        for die in 0..self.info.dies.max(1) {
+            if self.info.dies > 1 && self.selected_die != Some(die) {
+                packets.extend(self.info.die_select.packets(die));
+                self.selected_die = Some(die);
+            }
+            packets.push(stream_mode.set_packet(self.feature));
+            packets.push(continuous_read_packet(0, pages_per_die, quad));
+            packets.push(buffer_mode.set_packet(self.feature));
+        }
+        self.applied_mode = Some(buffer_mode);
+        packets
+    }
+
+    /// Main data of the whole package
+    ///
+    /// Uses continuous read when the chip supports it, page reads with the
+    /// OOB dropped otherwise. Bad blocks are left in; find them afterwards
+    /// with `scan_bad_blocks` and drop them with `strip_bad_blocks`.
+    pub fn read_chip_data<E, T>(&mut self, quad: bool, mut transact: T) -> Result<Vec<u8>, E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        let page_size = self.info.page_size as usize;
+        let mut data = Vec::with_capacity(self.info.total_pages() as usize * page_size);
+        if self.info.continuous_read {
+            for packet in self.continuous_packets(quad) {
+                let response = transact(&packet)?;
+                if packet.cmd != Command::SpiNandSetFeature
+                    && packet.cmd != Command::SpiNandDieSelect
+                {
+                    data.extend_from_slice(&response);
+                }
+            }
+        } else {
+            let blocks = self.info.blocks_per_die() * self.info.dies.max(1) as u32;
+            for block in 0..blocks {
+                for page in 0..self.info.block_size {
+                    let mut response = Vec::new();
+                    for packet in self.page_packets(block, page) {
+                        response = transact(&packet)?;
+                    }
+                    response.truncate(page_size);
+                    data.extend_from_slice(&response);
+                }
+            }
+        }
+        Ok(data)
+    }
+
+    /// Page + OOB of the whole package in buffer mode, with the on-die ECC
+    /// status of every page decoded through the chip's `ecc_decoder`
+    pub fn read_chip_with_ecc<E, T>(
+        &mut self,
+        mut transact: T,
+    ) -> Result<(Vec<u8>, SpiNandEccReport), E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        let page_bytes = (self.info.page_size + self.info.oob_size) as usize;
+        let mut data = Vec::with_capacity(self.info.total_pages() as usize * page_bytes);
+        let mut report = SpiNandEccReport::default();
+        let blocks = self.info.blocks_per_die() * self.info.dies.max(1) as u32;
+        for block in 0..blocks {
+            for page in 0..self.info.block_size {
+                let capture =
+                    self.capture_page(block, page, SpiNandEccCapture::Corrected, &mut transact)?;
+                report.record(block * self.info.block_size + page, capture.ecc_status);
+                data.extend(capture.corrected.unwrap_or_default());
+            }
+        }
+        Ok((data, report))
+    }
+
+    /// Factory bad-block scan in buffer mode, reading only the marker pages
+    pub fn scan_bad_blocks<E, T>(
+        &mut self,
+        policy: &BbmPolicy,
+        mut transact: T,
+    ) -> Result<Vec<u32>, E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        let page_size = self.info.page_size as usize;
+        let blocks = self.info.blocks_per_die() * self.info.dies.max(1) as u32;
+        let block_size = self.info.block_size;
+        policy.scan_chip(blocks, block_size, |block, page| {
+            let mut response = Vec::new();
+            for packet in self.page_packets(block, page) {
+                response = transact(&packet)?;
+            }
+            Ok(response.get(page_size..).unwrap_or_default().to_vec())
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1004,6 +1028,306 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vendor_ecc_decoders() {
+        // GigaDevice GQ5: ECCS 01, ECCSE 10 -> 3 bits
+        let gd = SpiNandEccDecoder::GigaDeviceQ5;
+        assert_eq!(
+            gd.extra_source(0x10),
+            Some(EccStatusSource::Feature(GIGADEVICE_FEATURE_STATUS2))
+        );
+        assert_eq!(gd.decode(0x10, Some(0x20)), EccStatus::Corrected(3));
+        assert_eq!(gd.decode(0x20, None), EccStatus::Uncorrectable);
+
+        let micron = SpiNandEccDecoder::Micron;
+        assert_eq!(micron.extra_source(0x30), None);
+        assert_eq!(micron.decode(0x30, None), EccStatus::Corrected(6));
+        assert_eq!(micron.decode(0x20, None), EccStatus::Uncorrectable);
+
+        let macronix = SpiNandEccDecoder::Macronix;
+        let status = macronix.decode_with::<(), _>(0x10, |source| {
+            assert_eq!(source, EccStatusSource::Command(MACRONIX_READ_ECC_STATUS));
+            Ok(0x02)
+        });
+        assert_eq!(status, Ok(EccStatus::Corrected(2)));
+
+        let toshiba = SpiNandEccDecoder::BitflipFeature;
+        assert_eq!(toshiba.decode(0x30, Some(0x50)), EccStatus::Corrected(5));
+        assert_eq!(toshiba.decode(0x10, None), EccStatus::Corrected(8));
+
+        assert_eq!(
+            SpiNandEccDecoder::XtxA.decode(0x0C, None),
+            EccStatus::Corrected(3)
+        );
+        assert_eq!(
+            SpiNandEccDecoder::XtxA.decode(0x20, None),
+            EccStatus::Uncorrectable
+        );
+        assert_eq!(
+            SpiNandEccDecoder::XtxC.decode(0x70, None),
+            EccStatus::Corrected(7)
+        );
+        assert_eq!(
+            SpiNandEccDecoder::GigaDeviceM9.decode(0x70, None),
+            EccStatus::Uncorrectable
+        );
+    }
+
+    #[test]
+    fn test_ecc_decoder_from_chipdb() {
+        let info = get_spi_nand_chip_info(&[0x2C, 0x24, 0x00]).unwrap();
+        assert_eq!(info.ecc_decoder, SpiNandEccDecoder::Micron);
+
+        let info = get_spi_nand_chip_info(&[0xC8, 0xD2]).unwrap();
+        assert_eq!(info.ecc_decoder, SpiNandEccDecoder::GigaDeviceQ4Ue);
+
+        // GD5FxGQ4UF shares the Q4 family name but reports ECC like the M9 parts
+        let info = get_spi_nand_chip_info(&[0xC8, 0xB1]).unwrap();
+        assert_eq!(info.ecc_decoder, SpiNandEccDecoder::GigaDeviceM9);
+        assert_eq!(info.oob_size, 128);
+    }
+
+    #[test]
+    fn test_ecc_report() {
+        let mut report = SpiNandEccReport::default();
+        report.record(0, EccStatus::NoError);
+        report.record(1, EccStatus::Corrected(3));
+        report.record(2, EccStatus::Uncorrectable);
+        report.record(3, EccStatus::Corrected(6));
+
+        assert_eq!(report.pages_read, 4);
+        assert_eq!(report.corrected, vec![(1, 3), (3, 6)]);
+        assert_eq!(report.uncorrectable, vec![2]);
+        assert_eq!(report.total_corrected_bits(), 9);
+        assert_eq!(report.max_bitflips(), 6);
+        assert!(!report.is_clean());
+    }
+
+    #[test]
+    fn test_read_chip_with_ecc() {
+        let mut info = get_spi_nand_chip_info(&[0xC8, 0xB1]).unwrap();
+        info.size_mb = 1;
+        let mut reader = SpiNandReader::new(&info);
+        let mut row = 0;
+
+        let (data, report) = reader
+            .read_chip_with_ecc::<(), _>(|packet| {
+                Ok(match packet.cmd {
+                    Command::SpiNandPageRead => {
+                        row = u32::from_le_bytes(packet.args[..4].try_into().unwrap());
+                        Vec::new()
+                    }
+                    // M9 encoding: 001 = 1-3 bits, 101 = 7 bits, 111 = uncorrectable
+                    Command::SpiNandGetFeature => vec![match row {
+                        5 => 0x10,
+                        9 => 0x50,
+                        70 => 0x70,
+                        _ => 0x00,
+                    }],
+                    Command::SpiNandReadCache => vec![0xA5; 2048 + 128],
+                    _ => Vec::new(),
+                })
+            })
+            .unwrap();
+        assert_eq!(data.len(), 512 * (2048 + 128));
+        assert_eq!(report.pages_read, 512);
+        assert_eq!(report.corrected, vec![(5, 3), (9, 7)]);
+        assert_eq!(report.uncorrectable, vec![70]);
+    }
+
+    #[test]
+    fn test_stacked_die_addressing() {
+        let info = get_spi_nand_chip_info(&[0xEF, 0xAB, 0x21]).unwrap();
+        assert_eq!(info.model, "W25M02GV");
+        assert_eq!(info.blocks_per_die(), 1024);
+        assert_eq!(info.total_pages(), 2048 * 64);
+
+        let addr = info.page_address(1025, 3);
+        assert_eq!((addr.die, addr.row), (1, 64 + 3));
+
+        let mut reader = SpiNandReader::new(&info);
+        let first = reader.page_packets(1023, 0);
+        assert_eq!(first[0].cmd, Command::SpiNandDieSelect);
+        assert_eq!(first[0].args[0], 0);
+        // Same die: no new die select
+        assert_eq!(
+            reader.page_packets(1023, 1)[0].cmd,
+            Command::SpiNandPageRead
+        );
+        let next = reader.page_packets(1024, 0);
+        assert_eq!(
+            (next[0].cmd, next[0].args[0]),
+            (Command::SpiNandDieSelect, 1)
+        );
+        assert_eq!(&next[1].args[..4], &[0, 0, 0, 0]);
+
+        let mut selects = 0;
+        let mut pages = 0;
+        SpiNandReader::new(&info)
+            .read_chip::<(), _>(|_, packets| {
+                pages += 1;
+                selects += packets
+                    .iter()
+                    .filter(|p| p.cmd == Command::SpiNandDieSelect)
+                    .count();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!((pages, selects), (info.total_pages(), 2));
+    }
+
+    #[test]
+    fn test_plane_and_feature_die_select() {
+        let info = get_spi_nand_chip_info(&[0x2C, 0x24, 0x00]).unwrap();
+        assert!(info.plane_select);
+        let odd = info.page_address(7, 0);
+        assert_eq!(odd.plane, 1);
+        // 2048-byte pages: plane bit is column bit 12
+        assert_eq!(odd.column(0x10, info.page_size), 0x1010);
+        assert_eq!(info.page_address(6, 0).column(0, info.page_size), 0);
+
+        let micron = get_spi_nand_chip_info(&[0x2C, 0x46]).unwrap();
+        assert_eq!(micron.dies, 2);
+        let packets = micron.die_select.packets(1);
+        assert_eq!(packets[0].cmd, Command::SpiNandSetFeature);
+        assert_eq!(&packets[0].args[..2], &[features::DIE_SELECT, 0x40]);
+    }
+
+    #[test]
+    fn test_read_mode_feature_bits() {
+        // QE and the other bits survive the read-modify-write
+        let current = feature_bits::QE | feature_bits::ECC_EN | feature_bits::BUF;
+        assert_eq!(SpiNandReadMode::RAW.apply(current), 0x09);
+        assert_eq!(SpiNandReadMode::CORRECTED.apply(0x01), 0x19);
+        assert_eq!(SpiNandReadMode::from_feature(0x09), SpiNandReadMode::RAW);
+
+        let packet = SpiNandReadMode::RAW.set_packet(current);
+        assert_eq!(packet.cmd, Command::SpiNandSetFeature);
+        assert_eq!(&packet.args[..2], &[features::FEATURE, 0x09]);
+    }
+
+    #[test]
+    fn test_ecc_layout_regions() {
+        let winbond = get_spi_nand_chip_info(&[0xEF, 0xAA, 0x21]).unwrap();
+        assert_eq!(winbond.ecc_layout, SpiNandEccLayout::Interleaved16);
+        let parity = winbond.ecc_layout.parity_regions(64);
+        assert_eq!(parity.len(), 4);
+        assert_eq!(parity[3], OobRegion { offset: 56, len: 8 });
+        let free = winbond.ecc_layout.free_regions(64);
+        assert_eq!(free[0], OobRegion { offset: 2, len: 6 });
+        assert_eq!(free[1], OobRegion { offset: 16, len: 8 });
+
+        let mut oob = [0u8; 64];
+        oob[8] = 0xAB;
+        oob[63] = 0xCD;
+        let bytes = winbond.ecc_layout.extract_parity(&oob);
+        assert_eq!((bytes.len(), bytes[0], bytes[31]), (32, 0xAB, 0xCD));
+
+        assert_eq!(
+            SpiNandEccLayout::UpperHalf.free_regions(128),
+            vec![OobRegion { offset: 2, len: 62 }]
+        );
+        assert_eq!(
+            SpiNandEccLayout::Tail(16).parity_regions(64),
+            vec![OobRegion {
+                offset: 48,
+                len: 16
+            }]
+        );
+        assert!(SpiNandEccLayout::Hidden.parity_regions(64).is_empty());
+    }
+
+    #[test]
+    fn test_capture_raw_and_corrected() {
+        let info = get_spi_nand_chip_info(&[0xEF, 0xAA, 0x21]).unwrap();
+        let mut reader = SpiNandReader::new(&info).with_feature_register(0x19);
+        let mut ecc_on = true;
+        let mut sent = Vec::new();
+
+        let capture = reader
+            .capture_page::<(), _>(3, 1, SpiNandEccCapture::Both, |packet| {
+                sent.push(packet.cmd);
+                Ok(match packet.cmd {
+                    Command::SpiNandSetFeature => {
+                        ecc_on = packet.args[1] & feature_bits::ECC_EN != 0;
+                        Vec::new()
+                    }
+                    Command::SpiNandGetFeature => vec![0x10],
+                    // Raw data shows the flipped bit that ECC corrects
+                    Command::SpiNandReadCache => vec![if ecc_on { 0x00 } else { 0x01 }; 2112],
+                    _ => Vec::new(),
+                })
+            })
+            .unwrap();
+        assert_eq!(capture.raw.unwrap()[0], 0x01);
+        assert_eq!(capture.corrected.unwrap()[0], 0x00);
+        assert_eq!(capture.ecc_status, EccStatus::Corrected(1));
+        assert_eq!(
+            sent.iter()
+                .filter(|&&c| c == Command::SpiNandSetFeature)
+                .count(),
+            2
+        );
+
+        // Same mode again: no feature write
+        let raw = reader
+            .capture_page::<(), _>(3, 2, SpiNandEccCapture::Raw, |_| Ok(vec![0xFF; 2112]))
+            .unwrap();
+        assert!(raw.corrected.is_none());
+        assert_eq!(raw.ecc_status, EccStatus::Disabled);
+        let packets = reader.page_packets_in(3, 3, Some(SpiNandReadMode::RAW));
+        assert_eq!(packets[0].cmd, Command::SpiNandPageRead);
+    }
+
+    #[test]
+    fn test_continuous_read_dump() {
+        let info = get_spi_nand_chip_info(&[0xEF, 0xAB, 0x21]).unwrap();
+        assert!(info.continuous_read);
+        let mut reader = SpiNandReader::new(&info);
+        let packets = reader.continuous_packets(false);
+        // Per die: select, BUF=0, stream, BUF=1
+        assert_eq!(packets.len(), 8);
+        assert_eq!(&packets[1].args[..2], &[features::FEATURE, 0x10]);
+        assert_eq!(packets[2].cmd, Command::SpiNandReadCacheContinuous);
+        assert_eq!(&packets[2].args[4..8], &(1024u32 * 64).to_le_bytes());
+        assert_eq!(&packets[3].args[..2], &[features::FEATURE, 0x18]);
+        assert_eq!(
+            (packets[4].cmd, packets[4].args[0]),
+            (Command::SpiNandDieSelect, 1)
+        );
+
+        // Chip streams 1 byte per page instead of 2048 to keep the test small
+        let mut die = 0u8;
+        let data = SpiNandReader::new(&info)
+            .read_chip_data::<(), _>(false, |packet| {
+                Ok(match packet.cmd {
+                    Command::SpiNandDieSelect => {
+                        die = packet.args[0];
+                        Vec::new()
+                    }
+                    Command::SpiNandReadCacheContinuous => vec![die; 1024 * 64],
+                    _ => Vec::new(),
+                })
+            })
+            .unwrap();
+        assert_eq!(data.len(), 2 * 1024 * 64);
+        assert_eq!((data[0], data[1024 * 64]), (0, 1));
+
+        // Bad blocks are found in buffer mode and dropped afterwards
+        let mut row = 0;
+        let bad = SpiNandReader::new(&info)
+            .scan_bad_blocks::<(), _>(&BbmPolicy::default(), |packet| {
+                if packet.cmd == Command::SpiNandPageRead {
+                    row = u32::from_le_bytes(packet.args[..4].try_into().unwrap());
+                }
+                Ok(vec![if row == 5 * 64 { 0x00 } else { 0xFF }; 2112])
+            })
+            .unwrap();
+        assert_eq!(bad, vec![5, 1029]);
+        let dump = vec![0u8; 8 * 2048 * 64];
+        assert_eq!(info.strip_bad_blocks(&dump, &bad).len(), 7 * 2048 * 64);
+    }
+
     #[test]
     fn test_row_address_calculation() {
         // Block 10, page 5, 64 pages per block
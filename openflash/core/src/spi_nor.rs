@@ -1,6 +1,10 @@
 //! SPI NOR Flash chip database and protocol
-//! Contains known SPI NOR chip parameters and command definitions
+//! Known chips are looked up in the chip database (see `chipdb`); this module
+//! holds protocol definitions and generic detection
 
+use crate::chipdb::chip_database;
+use crate::sfdp::{Bfpt, ReadProtocol, Sfdp};
+use crate::spi_nor_addr::FourByteMode;
 use serde::{Deserialize, Serialize};
 
 /// SPI NOR chip information
@@ -8,16 +12,211 @@ use serde::{Deserialize, Serialize};
 pub struct SpiNorChipInfo {
     pub manufacturer: String,
     pub model: String,
+    #[serde(default)]
     pub jedec_id: [u8; 3], // Manufacturer + Memory Type + Capacity
-    pub size_bytes: u32,   // Total size in bytes
-    pub page_size: u32,    // Page program size (typically 256)
-    pub sector_size: u32,  // Sector erase size (typically 4KB)
-    pub block_size: u32,   // Block erase size (typically 64KB)
+    pub size_bytes: u32,  // Total size in bytes
+    pub page_size: u32,   // Page program size (typically 256)
+    pub sector_size: u32, // Sector erase size (typically 4KB)
+    pub block_size: u32,  // Block erase size (typically 64KB)
     pub voltage: String,
     pub max_clock_mhz: u8,
     pub has_qspi: bool,    // Quad SPI support
     pub has_dual: bool,    // Dual SPI support
     pub address_bytes: u8, // 3 or 4 byte addressing
+    /// Octal and DTR modes, on top of those `has_dual`/`has_qspi` imply
+    #[serde(default)]
+    pub io_modes: Vec<SpiNorIoMode>,
+    #[serde(default)]
+    pub octal_enable: OctalEnable,
+    /// How to reach addresses above 16 MiB, when SFDP can't be trusted
+    #[serde(default)]
+    pub four_byte_mode: Option<FourByteMode>,
+}
+
+impl SpiNorChipInfo {
+    /// Every I/O mode the chip supports, 1S-1S-1S first
+    pub fn supported_io_modes(&self) -> Vec<SpiNorIoMode> {
+        let mut modes = vec![SpiNorIoMode::Sdr111];
+        if self.has_dual {
+            modes.extend([SpiNorIoMode::Sdr112, SpiNorIoMode::Sdr122]);
+        }
+        if self.has_qspi {
+            modes.extend([SpiNorIoMode::Sdr114, SpiNorIoMode::Sdr144]);
+        }
+        for mode in &self.io_modes {
+            if !modes.contains(mode) {
+                modes.push(*mode);
+            }
+        }
+        modes
+    }
+}
+
+/// Bus mode as command-address-data lanes, S for single and D for double
+/// transfer rate (JESD251 notation)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SpiNorIoMode {
+    #[default]
+    #[serde(rename = "1S-1S-1S")]
+    Sdr111,
+    #[serde(rename = "1S-1S-2S")]
+    Sdr112,
+    #[serde(rename = "1S-2S-2S")]
+    Sdr122,
+    #[serde(rename = "2S-2S-2S")]
+    Sdr222,
+    #[serde(rename = "1S-1S-4S")]
+    Sdr114,
+    #[serde(rename = "1S-4S-4S")]
+    Sdr144,
+    #[serde(rename = "4S-4S-4S")]
+    Sdr444,
+    #[serde(rename = "1S-1S-8S")]
+    Sdr118,
+    #[serde(rename = "1S-8S-8S")]
+    Sdr188,
+    #[serde(rename = "8S-8S-8S")]
+    Sdr888,
+    #[serde(rename = "1S-1D-1D")]
+    Dtr111,
+    #[serde(rename = "1S-2D-2D")]
+    Dtr122,
+    #[serde(rename = "1S-4D-4D")]
+    Dtr144,
+    #[serde(rename = "4S-4D-4D")]
+    Dtr444,
+    #[serde(rename = "8D-8D-8D")]
+    Dtr888,
+}
+
+impl SpiNorIoMode {
+    /// All modes, in protocol code order
+    pub const ALL: [SpiNorIoMode; 15] = [
+        SpiNorIoMode::Sdr111,
+        SpiNorIoMode::Sdr112,
+        SpiNorIoMode::Sdr122,
+        SpiNorIoMode::Sdr222,
+        SpiNorIoMode::Sdr114,
+        SpiNorIoMode::Sdr144,
+        SpiNorIoMode::Sdr444,
+        SpiNorIoMode::Sdr118,
+        SpiNorIoMode::Sdr188,
+        SpiNorIoMode::Sdr888,
+        SpiNorIoMode::Dtr111,
+        SpiNorIoMode::Dtr122,
+        SpiNorIoMode::Dtr144,
+        SpiNorIoMode::Dtr444,
+        SpiNorIoMode::Dtr888,
+    ];
+
+    /// Command, address and data lanes
+    pub fn lanes(&self) -> (u8, u8, u8) {
+        match self {
+            SpiNorIoMode::Sdr111 | SpiNorIoMode::Dtr111 => (1, 1, 1),
+            SpiNorIoMode::Sdr112 => (1, 1, 2),
+            SpiNorIoMode::Sdr122 | SpiNorIoMode::Dtr122 => (1, 2, 2),
+            SpiNorIoMode::Sdr222 => (2, 2, 2),
+            SpiNorIoMode::Sdr114 => (1, 1, 4),
+            SpiNorIoMode::Sdr144 | SpiNorIoMode::Dtr144 => (1, 4, 4),
+            SpiNorIoMode::Sdr444 | SpiNorIoMode::Dtr444 => (4, 4, 4),
+            SpiNorIoMode::Sdr118 => (1, 1, 8),
+            SpiNorIoMode::Sdr188 => (1, 8, 8),
+            SpiNorIoMode::Sdr888 | SpiNorIoMode::Dtr888 => (8, 8, 8),
+        }
+    }
+
+    pub fn is_dtr(&self) -> bool {
+        matches!(
+            self,
+            SpiNorIoMode::Dtr111
+                | SpiNorIoMode::Dtr122
+                | SpiNorIoMode::Dtr144
+                | SpiNorIoMode::Dtr444
+                | SpiNorIoMode::Dtr888
+        )
+    }
+
+    pub fn is_octal(&self) -> bool {
+        self.lanes().2 == 8
+    }
+
+    /// Bits per clock on the data lanes
+    pub fn data_rate(&self) -> u8 {
+        self.lanes().2 * if self.is_dtr() { 2 } else { 1 }
+    }
+
+    /// Code sent to the firmware
+    pub fn code(&self) -> u8 {
+        Self::ALL.iter().position(|m| m == self).unwrap_or(0) as u8
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        Self::ALL.get(code as usize).copied()
+    }
+
+    /// JESD251 notation, e.g. "8D-8D-8D"
+    pub fn notation(&self) -> &'static str {
+        match self {
+            SpiNorIoMode::Sdr111 => "1S-1S-1S",
+            SpiNorIoMode::Sdr112 => "1S-1S-2S",
+            SpiNorIoMode::Sdr122 => "1S-2S-2S",
+            SpiNorIoMode::Sdr222 => "2S-2S-2S",
+            SpiNorIoMode::Sdr114 => "1S-1S-4S",
+            SpiNorIoMode::Sdr144 => "1S-4S-4S",
+            SpiNorIoMode::Sdr444 => "4S-4S-4S",
+            SpiNorIoMode::Sdr118 => "1S-1S-8S",
+            SpiNorIoMode::Sdr188 => "1S-8S-8S",
+            SpiNorIoMode::Sdr888 => "8S-8S-8S",
+            SpiNorIoMode::Dtr111 => "1S-1D-1D",
+            SpiNorIoMode::Dtr122 => "1S-2D-2D",
+            SpiNorIoMode::Dtr144 => "1S-4D-4D",
+            SpiNorIoMode::Dtr444 => "4S-4D-4D",
+            SpiNorIoMode::Dtr888 => "8D-8D-8D",
+        }
+    }
+}
+
+impl From<ReadProtocol> for SpiNorIoMode {
+    fn from(protocol: ReadProtocol) -> Self {
+        match protocol {
+            ReadProtocol::Fast111 => SpiNorIoMode::Sdr111,
+            ReadProtocol::Dual112 => SpiNorIoMode::Sdr112,
+            ReadProtocol::Dual122 => SpiNorIoMode::Sdr122,
+            ReadProtocol::Dual222 => SpiNorIoMode::Sdr222,
+            ReadProtocol::Quad114 => SpiNorIoMode::Sdr114,
+            ReadProtocol::Quad144 => SpiNorIoMode::Sdr144,
+            ReadProtocol::Quad444 => SpiNorIoMode::Sdr444,
+            ReadProtocol::Octal118 => SpiNorIoMode::Sdr118,
+            ReadProtocol::Octal188 => SpiNorIoMode::Sdr188,
+            ReadProtocol::Octal888 => SpiNorIoMode::Sdr888,
+            ReadProtocol::Dtr111 => SpiNorIoMode::Dtr111,
+            ReadProtocol::Dtr122 => SpiNorIoMode::Dtr122,
+            ReadProtocol::Dtr144 => SpiNorIoMode::Dtr144,
+            ReadProtocol::Dtr444 => SpiNorIoMode::Dtr444,
+            ReadProtocol::Dtr888 => SpiNorIoMode::Dtr888,
+        }
+    }
+}
+
+/// How a chip is switched into octal mode
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OctalEnable {
+    /// No octal mode, or no known way to enter it
+    #[default]
+    None,
+    /// Octal enable bit 3 of SR2, written with 3Eh (SFDP requirement 001b)
+    StatusRegister2,
+    /// Macronix OctaFlash: WRCR2 (72h) to CR2 address 0, 01h = STR OPI,
+    /// 02h = DTR OPI
+    MacronixCr2,
+    /// Micron Xccela: write volatile configuration (81h) address 0,
+    /// B7h = octal SDR, E7h = octal DDR
+    MicronVolatileConfig,
+    /// Infineon Semper: write any register (71h) to CFR5V (800006h),
+    /// 41h = octal SDR, 43h = octal DDR
+    InfineonCfr5,
+    /// Command sequences from the SFDP octal DDR table (FF0Ah)
+    SfdpSequence,
 }
 
 /// SPI NOR standard commands
@@ -55,11 +254,53 @@ pub mod commands {
     pub const WRITE_STATUS_2: u8 = 0x31;
     pub const WRITE_STATUS_3: u8 = 0x11;
 
+    // Individual block lock (WPS=1)
+    pub const INDIVIDUAL_BLOCK_LOCK: u8 = 0x36;
+    pub const INDIVIDUAL_BLOCK_UNLOCK: u8 = 0x39;
+    pub const READ_BLOCK_LOCK: u8 = 0x3D;
+    pub const GLOBAL_BLOCK_LOCK: u8 = 0x7E;
+    pub const GLOBAL_BLOCK_UNLOCK: u8 = 0x98;
+
+    // Security registers and OTP
+    pub const READ_SECURITY_REG: u8 = 0x48;
+    pub const PROGRAM_SECURITY_REG: u8 = 0x42;
+    pub const ERASE_SECURITY_REG: u8 = 0x44;
+    pub const ENTER_SECURED_OTP: u8 = 0xB1; // Macronix ENSO
+    pub const EXIT_SECURED_OTP: u8 = 0xC1; // Macronix EXSO
+    pub const READ_SECURITY_SCUR: u8 = 0x2B; // Macronix RDSCUR
+    pub const WRITE_SECURITY_SCUR: u8 = 0x2F; // Macronix WRSCUR (sets LDSO)
+
+    // Octal, QPI and DTR
+    pub const OCTAL_READ: u8 = 0x8B; // 1S-1S-8S
+    pub const OCTAL_IO_READ: u8 = 0xCB; // 1S-8S-8S
+    pub const OCTA_READ: u8 = 0xEC; // Macronix 8S-8S-8S
+    pub const OCTA_DTR_READ: u8 = 0xEE; // 8D-8D-8D
+    pub const DTR_FAST_READ: u8 = 0x0D;
+    pub const DTR_DUAL_IO_READ: u8 = 0xBD;
+    pub const DTR_QUAD_IO_READ: u8 = 0xED;
+    pub const ENTER_QPI: u8 = 0x38;
+    pub const WRITE_STATUS_2_OCTAL: u8 = 0x3E; // SR2 bit 3 octal enable
+    pub const WRITE_CR2: u8 = 0x72; // Macronix WRCR2
+    pub const WRITE_VOLATILE_CONFIG: u8 = 0x81; // Micron
+    pub const WRITE_ANY_REGISTER: u8 = 0x71; // Infineon WRAR
+
     // Other
     pub const RESET_ENABLE: u8 = 0x66;
     pub const RESET: u8 = 0x99;
     pub const ENTER_4BYTE_MODE: u8 = 0xB7;
     pub const EXIT_4BYTE_MODE: u8 = 0xE9;
+    pub const WRITE_EXT_ADDR: u8 = 0xC5;
+    pub const READ_EXT_ADDR: u8 = 0xC8;
+    pub const WRITE_BANK_REG: u8 = 0x17; // Spansion BRWR
+    pub const READ_BANK_REG: u8 = 0x16; // Spansion BRRD
+
+    // Dedicated 4-byte address opcodes
+    pub const READ_4B: u8 = 0x13;
+    pub const FAST_READ_4B: u8 = 0x0C;
+    pub const PAGE_PROGRAM_4B: u8 = 0x12;
+    pub const SECTOR_ERASE_4B: u8 = 0x21;
+    pub const BLOCK_ERASE_32K_4B: u8 = 0x5C;
+    pub const BLOCK_ERASE_64K_4B: u8 = 0xDC;
     pub const READ_UNIQUE_ID: u8 = 0x4B;
     pub const POWER_DOWN: u8 = 0xB9;
     pub const RELEASE_POWER_DOWN: u8 = 0xAB;
@@ -95,6 +336,14 @@ pub mod status3 {
     pub const DRV1: u8 = 0x40;
 }
 
+/// Macronix security register (RDSCUR) bits
+pub mod scur {
+    /// Secured OTP factory locked
+    pub const FACTORY_LOCK: u8 = 0x01;
+    /// Secured OTP locked down by the user (LDSO)
+    pub const LDSO: u8 = 0x02;
+}
+
 /// SPI NOR manufacturer IDs
 pub mod manufacturers {
     pub const WINBOND: u8 = 0xEF;
@@ -129,8 +378,8 @@ pub fn get_spi_nor_manufacturer_name(mfr_id: u8) -> &'static str {
 pub fn get_spi_nor_chip_info(jedec_id: &[u8; 3]) -> Option<SpiNorChipInfo> {
     let mfr = jedec_id[0];
 
-    // Try exact match first
-    if let Some(info) = get_spi_nor_chip_info_exact(jedec_id) {
+    // Try the chip database first
+    if let Some(info) = chip_database().lookup_spi_nor(jedec_id) {
         return Some(info);
     }
 
@@ -138,1014 +387,18 @@ pub fn get_spi_nor_chip_info(jedec_id: &[u8; 3]) -> Option<SpiNorChipInfo> {
     get_spi_nor_chip_info_generic(mfr, jedec_id)
 }
 
-fn get_spi_nor_chip_info_exact(jedec_id: &[u8; 3]) -> Option<SpiNorChipInfo> {
-    match jedec_id {
-        // ============ Winbond W25Q Series ============
-        [0xEF, 0x40, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q80DV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024, // 1MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x40, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q16JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024, // 2MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x40, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q32JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024, // 4MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x40, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q64JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024, // 8MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x40, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q128JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024, // 16MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x40, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q256JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024, // 32MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0xEF, 0x40, 0x20] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q512JV".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 64 * 1024 * 1024, // 64MB
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        // Winbond 1.8V variants
-        [0xEF, 0x60, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q16JW".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x60, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q32JW".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x60, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q64JW".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x60, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q128JW".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ Macronix MX25L Series ============
-        [0xC2, 0x20, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L8035E".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x20, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L1606E".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 86,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x20, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L3233F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x20, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L6433F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x20, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L12835F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x20, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L25635F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0xC2, 0x20, 0x1A] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25L51245G".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 64 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        // Macronix 1.8V variants
-        [0xC2, 0x25, 0x36] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25U3235F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x25, 0x37] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25U6435F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC2, 0x25, 0x38] => Some(SpiNorChipInfo {
-            manufacturer: "Macronix".into(),
-            model: "MX25U12835F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ ISSI IS25LP Series ============
-        [0x9D, 0x60, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP080D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x60, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP016D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x60, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP032D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x60, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP064D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x60, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP128F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x60, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP256D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0x9D, 0x60, 0x1A] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25LP512M".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 64 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        // ISSI 1.8V variants (IS25WP series)
-        [0x9D, 0x70, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25WP016D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x70, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25WP032D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x70, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25WP064D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x9D, 0x70, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "ISSI".into(),
-            model: "IS25WP128F".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ GigaDevice GD25Q Series (v2.2) ============
-        [0xC8, 0x40, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q80C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x40, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q16C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x40, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q32C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x40, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q64C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x40, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q128C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x40, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q256D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0xC8, 0x40, 0x20] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25Q512MC".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 64 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        // GigaDevice 1.8V variants
-        [0xC8, 0x60, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25LQ16C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x60, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25LQ32D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x60, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25LQ64C".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x60, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25LQ128D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xC8, 0x60, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "GigaDevice".into(),
-            model: "GD25LQ256D".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 120,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-
-        // ============ EON EN25QH Series (v2.2) ============
-        [0x1C, 0x70, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH80A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x1C, 0x70, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH16A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x1C, 0x70, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH32B".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x1C, 0x70, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH64A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x1C, 0x70, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH128A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x1C, 0x70, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "EON".into(),
-            model: "EN25QH256A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-
-        // ============ Micron/Numonyx N25Q Series (v2.2) ============
-        [0x20, 0xBA, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q032A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0xBA, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q064A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0xBA, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q128A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0xBA, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q256A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0x20, 0xBA, 0x20] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q512A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 64 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        [0x20, 0xBA, 0x21] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "MT25QL01G".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 128 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-        // Micron 1.8V variants
-        [0x20, 0xBB, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q128A11".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0xBB, 0x19] => Some(SpiNorChipInfo {
-            manufacturer: "Micron".into(),
-            model: "N25Q256A11".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 32 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.8V".into(),
-            max_clock_mhz: 108,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 4,
-        }),
-
-        // ============ XMC/XTX XM25Q Series (v2.2) ============
-        [0x20, 0x40, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "XMC".into(),
-            model: "XM25QH32B".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0x40, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "XMC".into(),
-            model: "XM25QH64A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x20, 0x40, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "XMC".into(),
-            model: "XM25QH128A".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ Puya P25Q Series (v2.2) ============
-        [0x85, 0x60, 0x14] => Some(SpiNorChipInfo {
-            manufacturer: "Puya".into(),
-            model: "P25Q80H".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 1 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x85, 0x60, 0x15] => Some(SpiNorChipInfo {
-            manufacturer: "Puya".into(),
-            model: "P25Q16H".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 2 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x85, 0x60, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Puya".into(),
-            model: "P25Q32H".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ Boya BY25Q Series (v2.2) ============
-        [0x68, 0x40, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Boya".into(),
-            model: "BY25Q32BS".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x68, 0x40, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Boya".into(),
-            model: "BY25Q64AS".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0x68, 0x40, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Boya".into(),
-            model: "BY25Q128AS".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "3.3V".into(),
-            max_clock_mhz: 104,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        // ============ Winbond W25Q 1.2V Series (v2.2) ============
-        [0xEF, 0x80, 0x16] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q32ND".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 4 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.2V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x80, 0x17] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q64ND".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 8 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.2V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-        [0xEF, 0x80, 0x18] => Some(SpiNorChipInfo {
-            manufacturer: "Winbond".into(),
-            model: "W25Q128ND".into(),
-            jedec_id: *jedec_id,
-            size_bytes: 16 * 1024 * 1024,
-            page_size: 256,
-            sector_size: 4096,
-            block_size: 65536,
-            voltage: "1.2V".into(),
-            max_clock_mhz: 133,
-            has_qspi: true,
-            has_dual: true,
-            address_bytes: 3,
-        }),
-
-        _ => None,
+/// Get chip info from JEDEC ID, using SFDP for chips not in the database
+pub fn get_spi_nor_chip_info_with_sfdp(
+    jedec_id: &[u8; 3],
+    sfdp: Option<&Sfdp>,
+) -> Option<SpiNorChipInfo> {
+    if let Some(info) = chip_database().lookup_spi_nor(jedec_id) {
+        return Some(info);
+    }
+
+    match sfdp {
+        Some(sfdp) => Some(sfdp.chip_info(*jedec_id)),
+        None => get_spi_nor_chip_info_generic(jedec_id[0], jedec_id),
     }
 }
 
@@ -1185,6 +438,9 @@ fn get_spi_nor_chip_info_generic(mfr: u8, jedec_id: &[u8; 3]) -> Option<SpiNorCh
         has_qspi: true,
         has_dual: true,
         address_bytes,
+        io_modes: Vec::new(),
+        octal_enable: OctalEnable::None,
+        four_byte_mode: None,
     })
 }
 
@@ -1210,13 +466,45 @@ pub struct SfdpParamHeader {
 }
 
 /// Quad enable method from SFDP
-#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum QuadEnableMethod {
     None,
     StatusReg2Bit1,
     StatusReg1Bit6,
     StatusReg2Bit7,
     StatusReg1Bit1Volatile,
+    /// Bit 1 of SR2, read with 35h, written as the second byte of 01h
+    StatusReg2Bit1Read35,
+    /// Bit 1 of SR2, read with 35h, written with 31h
+    StatusReg2Bit1Write31,
+}
+
+impl QuadEnableMethod {
+    /// Decode the QER field (BFPT DWORD 15 bits 22:20)
+    pub fn from_qer(qer: u8) -> Self {
+        match qer {
+            1 => QuadEnableMethod::StatusReg2Bit1,
+            2 => QuadEnableMethod::StatusReg1Bit6,
+            3 => QuadEnableMethod::StatusReg2Bit7,
+            4 => QuadEnableMethod::StatusReg1Bit1Volatile,
+            5 => QuadEnableMethod::StatusReg2Bit1Read35,
+            6 => QuadEnableMethod::StatusReg2Bit1Write31,
+            _ => QuadEnableMethod::None,
+        }
+    }
+
+    /// QER field value
+    pub fn qer(&self) -> u8 {
+        match self {
+            QuadEnableMethod::None => 0,
+            QuadEnableMethod::StatusReg2Bit1 => 1,
+            QuadEnableMethod::StatusReg1Bit6 => 2,
+            QuadEnableMethod::StatusReg2Bit7 => 3,
+            QuadEnableMethod::StatusReg1Bit1Volatile => 4,
+            QuadEnableMethod::StatusReg2Bit1Read35 => 5,
+            QuadEnableMethod::StatusReg2Bit1Write31 => 6,
+        }
+    }
 }
 
 /// Fast read support flags
@@ -1286,136 +574,78 @@ impl SfdpParser {
 
     /// Parse JEDEC Basic Flash Parameter Table (BFPT)
     pub fn parse_bfpt(data: &[u8]) -> Option<SfdpInfo> {
-        if data.len() < 36 {
-            // Minimum 9 DWORDs
-            return None;
-        }
-
-        // DWORD 1: Block/Sector erase sizes
-        let dword1 = u32::from_le_bytes([data[0], data[1], data[2], data[3]]);
-        let supports_4kb_erase = (dword1 & 0x03) != 0x01; // Bits 1:0
-
-        // DWORD 2: Density
-        let dword2 = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
-        let density_bits = if dword2 & 0x80000000 != 0 {
-            // Bit 31 set: density is 2^N bits
-            1u64 << (dword2 & 0x7FFFFFFF)
-        } else {
-            // Bit 31 clear: density is N+1 bits
-            (dword2 as u64) + 1
-        };
-
-        // DWORD 3: Fast read support
-        let dword3 = u32::from_le_bytes([data[8], data[9], data[10], data[11]]);
-        let fast_read_support = FastReadSupport {
-            fast_read_112: (dword3 & (1 << 16)) != 0,
-            fast_read_122: (dword3 & (1 << 20)) != 0,
-            fast_read_114: (dword3 & (1 << 22)) != 0,
-            fast_read_144: (dword3 & (1 << 21)) != 0,
-        };
-
-        // DWORD 8: Erase type 1 & 2
-        let dword8 = u32::from_le_bytes([data[28], data[29], data[30], data[31]]);
-        let erase1_size = 1u32 << (dword8 & 0xFF);
-        let erase2_size = 1u32 << ((dword8 >> 16) & 0xFF);
-
-        let supports_32kb_erase = erase1_size == 32768 || erase2_size == 32768;
-        let supports_64kb_erase = erase1_size == 65536 || erase2_size == 65536;
-
-        // Determine sector size (smallest erase unit)
-        let sector_size = if supports_4kb_erase {
-            4096
-        } else {
-            erase1_size.min(erase2_size)
-        };
-
-        // DWORD 15 (if available): Quad enable method
-        let quad_enable_method = if data.len() >= 60 {
-            let dword15 = u32::from_le_bytes([data[56], data[57], data[58], data[59]]);
-            match (dword15 >> 20) & 0x07 {
-                0 => QuadEnableMethod::None,
-                1 => QuadEnableMethod::StatusReg2Bit1,
-                2 => QuadEnableMethod::StatusReg1Bit6,
-                3 => QuadEnableMethod::StatusReg2Bit7,
-                4 => QuadEnableMethod::StatusReg1Bit1Volatile,
-                _ => QuadEnableMethod::None,
-            }
-        } else {
-            QuadEnableMethod::None
-        };
-
-        // Address bytes
-        let address_bytes = if density_bits > 128 * 1024 * 1024 * 8 {
-            4
-        } else {
-            3
-        };
-
-        Some(SfdpInfo {
-            density_bits,
-            page_size: 256, // Standard page size
-            sector_size,
-            supports_4kb_erase,
-            supports_32kb_erase,
-            supports_64kb_erase,
-            quad_enable_method,
-            address_bytes,
-            fast_read_support,
-        })
+        Bfpt::parse(data).map(|bfpt| SfdpInfo::from_bfpt(&bfpt))
     }
 
     /// Parse complete SFDP data
+    ///
+    /// Use [`Sfdp::parse`] for the optional tables (4-byte instructions,
+    /// sector map, xSPI, vendor).
     pub fn parse(data: &[u8]) -> Option<SfdpInfo> {
-        let header = Self::parse_header(data)?;
-
-        if header.num_param_headers == 0 {
-            return None;
-        }
-
-        // Parse first parameter header (JEDEC BFPT)
-        let param_header = Self::parse_param_header(&data[8..])?;
-
-        // Read BFPT at specified offset
-        let bfpt_offset = param_header.table_pointer as usize;
-        let bfpt_len = (param_header.length_dwords as usize) * 4;
-
-        if data.len() < bfpt_offset + bfpt_len {
-            return None;
-        }
-
-        Self::parse_bfpt(&data[bfpt_offset..])
+        Sfdp::parse(data).map(|sfdp| SfdpInfo::from_bfpt(&sfdp.bfpt))
     }
 }
 
 impl SfdpInfo {
+    /// Summarize a fully decoded BFPT
+    pub fn from_bfpt(bfpt: &Bfpt) -> Self {
+        let has_erase = |size: u32| bfpt.erase_types().any(|(_, t)| t.size == size);
+        let has_read = |protocol: ReadProtocol| bfpt.fast_read(protocol).is_some();
+        let supports_4kb_erase = bfpt.erase_4k_opcode.is_some();
+
+        SfdpInfo {
+            density_bits: bfpt.density_bits,
+            page_size: bfpt.page_size,
+            sector_size: if supports_4kb_erase {
+                4096
+            } else {
+                bfpt.erase_types()
+                    .map(|(_, t)| t.size)
+                    .min()
+                    .unwrap_or(65536)
+            },
+            supports_4kb_erase,
+            supports_32kb_erase: has_erase(32768),
+            supports_64kb_erase: has_erase(65536),
+            quad_enable_method: bfpt.quad_enable_method,
+            address_bytes: bfpt.address_bytes(),
+            fast_read_support: FastReadSupport {
+                fast_read_112: has_read(ReadProtocol::Dual112),
+                fast_read_122: has_read(ReadProtocol::Dual122),
+                fast_read_114: has_read(ReadProtocol::Quad114),
+                fast_read_144: has_read(ReadProtocol::Quad144),
+            },
+        }
+    }
+
     /// Serialize SfdpInfo to BFPT bytes (for round-trip testing)
     /// Returns a 64-byte BFPT (16 DWORDs)
     pub fn to_bfpt_bytes(&self) -> Vec<u8> {
         let mut bfpt = vec![0u8; 64];
 
-        // DWORD 1: Block/Sector erase sizes
-        let dword1: u32 = if self.supports_4kb_erase { 0x00 } else { 0x01 };
-        bfpt[0..4].copy_from_slice(&dword1.to_le_bytes());
-
-        // DWORD 2: Density (use N+1 format for simplicity)
-        let dword2: u32 = (self.density_bits - 1) as u32;
-        bfpt[4..8].copy_from_slice(&dword2.to_le_bytes());
-
-        // DWORD 3: Fast read support
-        let mut dword3: u32 = 0;
+        // DWORD 1: 4KB erase (bits 1:0 = 01, opcode 20h) and fast read support
+        let mut dword1: u32 = if self.supports_4kb_erase {
+            0x01 | (0x20 << 8)
+        } else {
+            0x03
+        };
         if self.fast_read_support.fast_read_112 {
-            dword3 |= 1 << 16;
+            dword1 |= 1 << 16;
         }
         if self.fast_read_support.fast_read_122 {
-            dword3 |= 1 << 20;
+            dword1 |= 1 << 20;
         }
         if self.fast_read_support.fast_read_114 {
-            dword3 |= 1 << 22;
+            dword1 |= 1 << 22;
         }
         if self.fast_read_support.fast_read_144 {
-            dword3 |= 1 << 21;
+            dword1 |= 1 << 21;
         }
-        bfpt[8..12].copy_from_slice(&dword3.to_le_bytes());
+        bfpt[0..4].copy_from_slice(&dword1.to_le_bytes());
+
+        // DWORD 2: Density (use N+1 format for simplicity)
+        let dword2: u32 = (self.density_bits - 1) as u32;
+        bfpt[4..8].copy_from_slice(&dword2.to_le_bytes());
 
         // DWORD 8: Erase type 1 & 2
         let erase1_exp = if self.supports_4kb_erase { 12u8 } else { 16u8 }; // 4KB or 64KB
@@ -1430,13 +660,7 @@ impl SfdpInfo {
         bfpt[28..32].copy_from_slice(&dword8.to_le_bytes());
 
         // DWORD 15: Quad enable method
-        let qe_bits: u32 = match self.quad_enable_method {
-            QuadEnableMethod::None => 0,
-            QuadEnableMethod::StatusReg2Bit1 => 1,
-            QuadEnableMethod::StatusReg1Bit6 => 2,
-            QuadEnableMethod::StatusReg2Bit7 => 3,
-            QuadEnableMethod::StatusReg1Bit1Volatile => 4,
-        };
+        let qe_bits = self.quad_enable_method.qer() as u32;
         let dword15: u32 = qe_bits << 20;
         bfpt[56..60].copy_from_slice(&dword15.to_le_bytes());
 
@@ -1466,7 +690,7 @@ impl ProtectionStatus {
             bp0: (sr1 & status1::BP0) != 0,
             bp1: (sr1 & status1::BP1) != 0,
             bp2: (sr1 & status1::BP2) != 0,
-            bp3: false, // BP3 location varies by chip
+            bp3: false, // BP3 location varies by chip, see `ProtectionMap::status`
             bp4: false, // BP4 location varies by chip
             tb: (sr1 & status1::TB) != 0,
             sec: (sr1 & status1::SEC) != 0,
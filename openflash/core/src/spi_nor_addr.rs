@@ -0,0 +1,435 @@
+//! 4-byte addressing for SPI NOR chips over 16 MiB
+//!
+//! Chooses how addresses above 16 MiB are reached (dedicated 4-byte opcodes,
+//! B7h, the extended address register or the bank register) from SFDP or
+//! the chip table. Restoring puts the chip back into 3-byte mode and bank 0
+//! so the target SoC's boot ROM can still read it.
+
+use crate::protocol::{Command, Packet};
+use crate::sfdp::{AddressMode, FourByteEntry, ReadProtocol, Sfdp};
+use crate::spi_nor::{commands, SpiNorChipInfo};
+use serde::{Deserialize, Serialize};
+
+/// Bytes reachable with 3-byte addresses
+pub const THREE_BYTE_LIMIT: u32 = 1 << 24;
+
+/// How addresses above 16 MiB are reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FourByteMode {
+    /// 16 MiB or smaller
+    ThreeByte,
+    /// 13h/0Ch/12h/21h/5Ch/DCh; the chip itself stays in 3-byte mode
+    DedicatedOpcodes,
+    /// B7h to enter, E9h to leave
+    EnterCommand,
+    /// 06h + B7h to enter, 06h + E9h to leave (Micron)
+    EnterCommandWriteEnable,
+    /// 3-byte addresses, A31-A24 in the extended address register
+    /// (06h + C5h write, C8h read)
+    ExtendedAddressRegister,
+    /// 3-byte addresses, A31-A24 in the bank register (17h write, 16h read,
+    /// Spansion)
+    BankRegister,
+    /// The chip only accepts 4-byte addresses
+    Always,
+}
+
+impl FourByteMode {
+    /// Code sent to the firmware with SpiNorSetAddressMode
+    pub fn code(&self) -> u8 {
+        match self {
+            FourByteMode::ThreeByte => 0,
+            FourByteMode::DedicatedOpcodes => 1,
+            FourByteMode::EnterCommand => 2,
+            FourByteMode::EnterCommandWriteEnable => 3,
+            FourByteMode::ExtendedAddressRegister => 4,
+            FourByteMode::BankRegister => 5,
+            FourByteMode::Always => 6,
+        }
+    }
+
+    /// Address bytes on the bus
+    pub fn address_bytes(&self) -> u8 {
+        match self {
+            FourByteMode::ThreeByte
+            | FourByteMode::ExtendedAddressRegister
+            | FourByteMode::BankRegister => 3,
+            _ => 4,
+        }
+    }
+
+    /// A31-A24 live in a register, so accesses must not cross 16 MiB
+    pub fn is_banked(&self) -> bool {
+        matches!(
+            self,
+            FourByteMode::ExtendedAddressRegister | FourByteMode::BankRegister
+        )
+    }
+
+    /// Best mode the SFDP tables advertise
+    ///
+    /// Dedicated opcodes come first since they never change the chip's
+    /// address mode; nonvolatile configuration is never used.
+    pub fn from_sfdp(sfdp: &Sfdp) -> Self {
+        let bfpt = &sfdp.bfpt;
+        if bfpt.address_mode == AddressMode::ThreeByte
+            || bfpt.size_bytes() <= THREE_BYTE_LIMIT as u64
+        {
+            return FourByteMode::ThreeByte;
+        }
+        if bfpt.address_mode == AddressMode::FourByte
+            || bfpt.four_byte_entry.contains(&FourByteEntry::Always)
+        {
+            return FourByteMode::Always;
+        }
+
+        let dedicated = sfdp.four_byte.is_some_and(|t| {
+            (t.read().is_some() || t.read_opcode(ReadProtocol::Fast111).is_some())
+                && t.page_program(1).is_some()
+        });
+        let entry = |e| bfpt.four_byte_entry.contains(&e);
+        if dedicated || entry(FourByteEntry::DedicatedInstructions) {
+            FourByteMode::DedicatedOpcodes
+        } else if entry(FourByteEntry::Command) {
+            FourByteMode::EnterCommand
+        } else if entry(FourByteEntry::WriteEnableCommand) {
+            FourByteMode::EnterCommandWriteEnable
+        } else if entry(FourByteEntry::ExtendedAddressRegister) {
+            FourByteMode::ExtendedAddressRegister
+        } else if entry(FourByteEntry::BankRegister) {
+            FourByteMode::BankRegister
+        } else {
+            FourByteMode::EnterCommand
+        }
+    }
+
+    /// The chip table entry's mode, else SFDP, else B7h
+    ///
+    /// Without SFDP nothing says which dedicated opcodes exist
+    /// (MX25L25635E has none), while B7h is near universal.
+    pub fn for_chip(info: &SpiNorChipInfo, sfdp: Option<&Sfdp>) -> Self {
+        if info.size_bytes <= THREE_BYTE_LIMIT {
+            return FourByteMode::ThreeByte;
+        }
+        info.four_byte_mode
+            .or_else(|| sfdp.map(Self::from_sfdp))
+            .unwrap_or(FourByteMode::EnterCommand)
+    }
+
+    /// Opcode actually sent for a 3-byte address opcode
+    ///
+    /// `None` for 32K erase with dedicated opcodes: 5Ch is optional (the
+    /// W25Q256 lacks it), so only `SpiNorAddressing::opcode` issues it, and
+    /// only when 4BAIT lists it.
+    pub fn opcode(&self, opcode: u8) -> Option<u8> {
+        if *self != FourByteMode::DedicatedOpcodes {
+            return Some(opcode);
+        }
+        Some(match opcode {
+            commands::READ => commands::READ_4B,
+            commands::FAST_READ => commands::FAST_READ_4B,
+            commands::DUAL_READ => 0x3C,
+            commands::DUAL_IO_READ => 0xBC,
+            commands::QUAD_READ => 0x6C,
+            commands::QUAD_IO_READ => 0xEC,
+            commands::PAGE_PROGRAM => commands::PAGE_PROGRAM_4B,
+            commands::QUAD_PAGE_PROGRAM => 0x34,
+            commands::SECTOR_ERASE => commands::SECTOR_ERASE_4B,
+            commands::BLOCK_ERASE_32K => return None,
+            commands::BLOCK_ERASE_64K => commands::BLOCK_ERASE_64K_4B,
+            other => other,
+        })
+    }
+}
+
+/// Largest SpiNorRead the firmware answers (its page buffer)
+pub const MAX_READ_CHUNK: u32 = 4096;
+
+/// Address mode handling for one chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiNorAddressing {
+    pub mode: FourByteMode,
+    pub size_bytes: u32,
+    /// 4BAIT lists 5Ch for the 32K erase type
+    #[serde(default)]
+    pub erase_32k_4b: bool,
+}
+
+impl SpiNorAddressing {
+    pub fn for_chip(info: &SpiNorChipInfo, sfdp: Option<&Sfdp>) -> Self {
+        let erase_32k_4b = sfdp.is_some_and(|sfdp| {
+            sfdp.four_byte.is_some_and(|t| {
+                sfdp.bfpt.erase_types().any(|(erase_type, e)| {
+                    e.size == 32768 && t.erase(erase_type) == Some(commands::BLOCK_ERASE_32K_4B)
+                })
+            })
+        });
+        Self {
+            mode: FourByteMode::for_chip(info, sfdp),
+            size_bytes: info.size_bytes,
+            erase_32k_4b,
+        }
+    }
+
+    /// Opcode actually sent for a 3-byte address opcode, `None` when the
+    /// chip can't run it in this mode
+    pub fn opcode(&self, opcode: u8) -> Option<u8> {
+        if opcode == commands::BLOCK_ERASE_32K
+            && self.mode == FourByteMode::DedicatedOpcodes
+            && self.erase_32k_4b
+        {
+            return Some(commands::BLOCK_ERASE_32K_4B);
+        }
+        self.mode.opcode(opcode)
+    }
+
+    /// Tell the firmware to enter the mode before the first access
+    ///
+    /// Bit 0 of the second byte lets it send 5Ch for 32K erases.
+    pub fn setup_packet(&self) -> Packet {
+        Packet::new(
+            Command::SpiNorSetAddressMode,
+            &[self.mode.code(), self.erase_32k_4b as u8],
+        )
+    }
+
+    /// Leave the chip in 3-byte mode with bank 0 selected
+    ///
+    /// Send after the last access so the target can boot from the chip.
+    pub fn restore_packet(&self) -> Packet {
+        Packet::new(
+            Command::SpiNorSetAddressMode,
+            &[FourByteMode::ThreeByte.code()],
+        )
+    }
+
+    /// Run `f` between setup and restore, restoring even when `f` fails
+    pub fn with_mode<E, T, R, F>(&self, transfer: &mut T, f: F) -> Result<R, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+        F: FnOnce(&mut T) -> Result<R, E>,
+    {
+        if self.mode == FourByteMode::ThreeByte {
+            return f(transfer);
+        }
+        transfer(&self.setup_packet(), &[])?;
+        let result = f(transfer);
+        let restore = transfer(&self.restore_packet(), &[]);
+        let value = result?;
+        restore?;
+        Ok(value)
+    }
+
+    /// Split `start..start + len` so no piece crosses a 16 MiB bank in
+    /// banked modes, clipped to the chip size
+    pub fn split(&self, start: u32, len: u32) -> Vec<(u32, u32)> {
+        let end = (start as u64 + len as u64).min(self.size_bytes as u64);
+        let mut pieces = Vec::new();
+        let mut addr = start as u64;
+        while addr < end {
+            let next = if self.mode.is_banked() {
+                ((addr / THREE_BYTE_LIMIT as u64) + 1) * THREE_BYTE_LIMIT as u64
+            } else {
+                end
+            };
+            let piece_end = next.min(end);
+            pieces.push((addr as u32, (piece_end - addr) as u32));
+            addr = piece_end;
+        }
+        pieces
+    }
+
+    /// Read `start..start + len` in the chip's address mode
+    pub fn read<E, T>(&self, transfer: &mut T, start: u32, len: u32) -> Result<Vec<u8>, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        self.with_mode(transfer, |transfer| {
+            let mut data = Vec::with_capacity(len as usize);
+            for (address, piece) in self.split(start, len) {
+                for offset in (0..piece).step_by(MAX_READ_CHUNK as usize) {
+                    let chunk = (piece - offset).min(MAX_READ_CHUNK);
+                    let addr = (address + offset).to_le_bytes();
+                    let size = (chunk as u16).to_le_bytes();
+                    let args = [addr[0], addr[1], addr[2], addr[3], size[0], size[1]];
+                    data.extend(transfer(&Packet::new(Command::SpiNorRead, &args), &[])?);
+                }
+            }
+            Ok(data)
+        })
+    }
+
+    /// Program `data` at `start`, one page program per (partial) page
+    pub fn program<E, T>(
+        &self,
+        transfer: &mut T,
+        start: u32,
+        data: &[u8],
+        page_size: u32,
+    ) -> Result<(), E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        self.with_mode(transfer, |transfer| {
+            let mut offset = 0usize;
+            while offset < data.len() {
+                let address = start + offset as u32;
+                let room = (page_size - address % page_size) as usize;
+                let chunk = room.min(data.len() - offset);
+                let addr = address.to_le_bytes();
+                let size = (chunk as u16).to_le_bytes();
+                let args = [addr[0], addr[1], addr[2], addr[3], size[0], size[1]];
+                transfer(
+                    &Packet::new(Command::SpiNorPageProgram, &args),
+                    &data[offset..offset + chunk],
+                )?;
+                offset += chunk;
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi_nor::get_spi_nor_chip_info;
+
+    #[test]
+    fn test_mode_selection() {
+        let small = get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap();
+        assert_eq!(
+            FourByteMode::for_chip(&small, None),
+            FourByteMode::ThreeByte
+        );
+
+        // No SFDP: B7h, which every opcode works under
+        let w25q256 = get_spi_nor_chip_info(&[0xEF, 0x40, 0x19]).unwrap();
+        let addressing = SpiNorAddressing::for_chip(&w25q256, None);
+        assert_eq!(addressing.mode, FourByteMode::EnterCommand);
+        assert_eq!(
+            addressing.opcode(commands::BLOCK_ERASE_32K),
+            Some(commands::BLOCK_ERASE_32K)
+        );
+
+        let mode = FourByteMode::DedicatedOpcodes;
+        assert_eq!(mode.opcode(commands::READ), Some(0x13));
+        assert_eq!(mode.opcode(commands::FAST_READ), Some(0x0C));
+        assert_eq!(mode.opcode(commands::PAGE_PROGRAM), Some(0x12));
+        assert_eq!(mode.opcode(commands::SECTOR_ERASE), Some(0x21));
+        assert_eq!(mode.opcode(commands::BLOCK_ERASE_64K), Some(0xDC));
+        // 5Ch only when 4BAIT lists it
+        let dedicated = SpiNorAddressing {
+            mode,
+            size_bytes: 32 << 20,
+            erase_32k_4b: false,
+        };
+        assert_eq!(dedicated.opcode(commands::BLOCK_ERASE_32K), None);
+        let with_5c = SpiNorAddressing {
+            erase_32k_4b: true,
+            ..dedicated
+        };
+        assert_eq!(with_5c.opcode(commands::BLOCK_ERASE_32K), Some(0x5C));
+        assert_eq!(with_5c.setup_packet().args[..2], [1, 1]);
+
+        // Chip table override
+        let n25q256 = get_spi_nor_chip_info(&[0x20, 0xBA, 0x19]).unwrap();
+        let mode = FourByteMode::for_chip(&n25q256, None);
+        assert_eq!(mode, FourByteMode::EnterCommandWriteEnable);
+        assert_eq!(mode.opcode(commands::READ), Some(commands::READ));
+    }
+
+    #[test]
+    fn test_banked_split_and_restore() {
+        let addressing = SpiNorAddressing {
+            mode: FourByteMode::ExtendedAddressRegister,
+            size_bytes: 64 << 20,
+            erase_32k_4b: false,
+        };
+        assert_eq!(
+            addressing.split(0xFF_F000, 0x2000),
+            vec![(0xFF_F000, 0x1000), (0x100_0000, 0x1000)]
+        );
+        // Clipped to the chip
+        assert_eq!(
+            addressing.split(0x3FF_0000, 0x10_0000),
+            vec![(0x3FF_0000, 0x1_0000)]
+        );
+
+        let mut log = Vec::new();
+        let result = addressing.with_mode::<(), _, (), _>(
+            &mut |packet: &Packet, _: &[u8]| {
+                log.push((packet.cmd, packet.args[0]));
+                Ok(vec![])
+            },
+            |_| Err(()),
+        );
+        assert!(result.is_err());
+        assert_eq!(
+            log,
+            vec![
+                (Command::SpiNorSetAddressMode, 4),
+                (Command::SpiNorSetAddressMode, 0)
+            ]
+        );
+
+        let dedicated = SpiNorAddressing {
+            mode: FourByteMode::DedicatedOpcodes,
+            size_bytes: 64 << 20,
+            erase_32k_4b: false,
+        };
+        assert_eq!(dedicated.split(0, 64 << 20), vec![(0, 64 << 20)]);
+    }
+
+    #[test]
+    fn test_read_and_program_flow() {
+        let addressing = SpiNorAddressing {
+            mode: FourByteMode::BankRegister,
+            size_bytes: 32 << 20,
+            erase_32k_4b: false,
+        };
+        let log = std::cell::RefCell::new(Vec::new());
+        let mut transfer = |packet: &Packet, data: &[u8]| -> Result<Vec<u8>, ()> {
+            let address = u32::from_le_bytes(packet.args[..4].try_into().unwrap());
+            let len = u16::from_le_bytes([packet.args[4], packet.args[5]]);
+            log.borrow_mut()
+                .push((packet.cmd, address, len, data.len()));
+            Ok(match packet.cmd {
+                Command::SpiNorRead => vec![0xA5; len as usize],
+                _ => vec![],
+            })
+        };
+
+        // Crosses the 16 MiB bank, so two reads between setup and restore
+        let data = addressing.read(&mut transfer, 0xFF_FF00, 0x200).unwrap();
+        assert_eq!(data.len(), 0x200);
+        let reads: Vec<_> = log
+            .borrow()
+            .iter()
+            .filter(|l| l.0 == Command::SpiNorRead)
+            .map(|l| (l.1, l.2))
+            .collect();
+        assert_eq!(reads, vec![(0xFF_FF00, 0x100), (0x100_0000, 0x100)]);
+        assert_eq!(
+            log.borrow().first().unwrap().0,
+            Command::SpiNorSetAddressMode
+        );
+        assert_eq!(
+            log.borrow().last().unwrap().0,
+            Command::SpiNorSetAddressMode
+        );
+
+        // Unaligned start: partial first page
+        log.borrow_mut().clear();
+        addressing
+            .program(&mut transfer, 0x80, &[0u8; 0x200], 256)
+            .unwrap();
+        let programs: Vec<_> = log
+            .borrow()
+            .iter()
+            .filter(|l| l.0 == Command::SpiNorPageProgram)
+            .map(|l| (l.1, l.3))
+            .collect();
+        assert_eq!(programs, vec![(0x80, 0x80), (0x100, 0x100), (0x200, 0x80)]);
+    }
+}
@@ -0,0 +1,407 @@
+//! SPI NOR I/O mode negotiation and octal/DTR mode entry
+//!
+//! Picks the fastest mode both the chip and the programmer support, switches
+//! the chip into it and drops back to 1S-1S-1S when the chip no longer
+//! answers READ ID correctly.
+
+use crate::protocol::{Command, Packet};
+use crate::sfdp::{CommandExtension, Sfdp};
+use crate::spi_nor::{commands, OctalEnable, SpiNorChipInfo, SpiNorIoMode};
+use serde::{Deserialize, Serialize};
+
+/// Mode a chip runs in and how to get there
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiNorIo {
+    pub mode: SpiNorIoMode,
+    pub read_opcode: u8,
+    pub dummy_cycles: u8,
+    /// Second command byte when commands use 8 lanes
+    pub command_extension: Option<CommandExtension>,
+    /// Commands sent in 1S-1S-1S to enter `mode`
+    pub enter: Vec<Vec<u8>>,
+    /// How the chip was switched to 8 command lanes, which decides the
+    /// READ ID format
+    pub octal_enable: OctalEnable,
+}
+
+impl Default for SpiNorIo {
+    fn default() -> Self {
+        Self {
+            mode: SpiNorIoMode::Sdr111,
+            read_opcode: commands::FAST_READ,
+            dummy_cycles: 8,
+            command_extension: None,
+            enter: Vec::new(),
+            octal_enable: OctalEnable::None,
+        }
+    }
+}
+
+impl SpiNorIo {
+    /// Fastest mode supported by the chip and `programmer` that the chip
+    /// can be switched into, 1S-1S-1S if there is none
+    pub fn negotiate(
+        chip: &SpiNorChipInfo,
+        sfdp: Option<&Sfdp>,
+        programmer: &[SpiNorIoMode],
+        clock_mhz: u16,
+    ) -> Self {
+        chip.supported_io_modes()
+            .into_iter()
+            .filter(|mode| programmer.contains(mode))
+            .filter_map(|mode| Some((mode, enter_sequence(chip.octal_enable, mode, sfdp)?)))
+            .max_by_key(|(mode, _)| {
+                let (cmd, addr, _) = mode.lanes();
+                (mode.data_rate(), addr, cmd)
+            })
+            .map(|(mode, enter)| Self::for_mode(chip.octal_enable, sfdp, mode, enter, clock_mhz))
+            .unwrap_or_default()
+    }
+
+    fn for_mode(
+        enable: OctalEnable,
+        sfdp: Option<&Sfdp>,
+        mode: SpiNorIoMode,
+        enter: Vec<Vec<u8>>,
+        clock_mhz: u16,
+    ) -> Self {
+        let sfdp_read = sfdp.and_then(|s| {
+            s.bfpt
+                .fast_reads
+                .iter()
+                .find(|r| SpiNorIoMode::from(r.protocol) == mode)
+        });
+        let (read_opcode, dummy_cycles) = match (sfdp_read, sfdp.and_then(|s| s.xspi)) {
+            (Some(read), _) => (read.opcode, read.dummy_clocks()),
+            (None, Some(xspi)) if mode == SpiNorIoMode::Dtr888 => {
+                (xspi.read_fast_opcode, xspi.dummy_cycles(clock_mhz))
+            }
+            _ => default_read(enable, mode),
+        };
+        let command_extension = (mode.lanes().0 == 8).then(|| {
+            sfdp.and_then(|s| s.bfpt.octal_command_extension)
+                .unwrap_or(match enable {
+                    OctalEnable::MacronixCr2 => CommandExtension::Invert,
+                    _ => CommandExtension::Repeat,
+                })
+        });
+
+        Self {
+            mode,
+            read_opcode,
+            dummy_cycles,
+            command_extension,
+            enter,
+            octal_enable: if mode.lanes().0 == 8 {
+                enable
+            } else {
+                OctalEnable::None
+            },
+        }
+    }
+
+    /// SpiNorSetIoMode arguments: mode, read opcode, dummy cycles, command
+    /// extension (0 none, 1 repeat, 2 invert, 3 16-bit)
+    pub fn set_io_mode_packet(&self) -> Packet {
+        let extension = match self.command_extension {
+            None => 0,
+            Some(CommandExtension::Repeat) => 1,
+            Some(CommandExtension::Invert) => 2,
+            Some(CommandExtension::SixteenBit) => 3,
+        };
+        Packet::new(
+            Command::SpiNorSetIoMode,
+            &[
+                self.mode.code(),
+                self.read_opcode,
+                self.dummy_cycles,
+                extension,
+            ],
+        )
+    }
+
+    /// SpiNorTransfer arguments: byte count, then the command bytes
+    pub fn transfer_packet(command: &[u8]) -> Packet {
+        let len = command.len().min(62);
+        let mut args = Vec::with_capacity(len + 1);
+        args.push(len as u8);
+        args.extend_from_slice(&command[..len]);
+        Packet::new(Command::SpiNorTransfer, &args)
+    }
+
+    /// SpiNorReadJedecId in the current mode
+    ///
+    /// Macronix OctaFlash takes a 4-byte address and 4 dummy cycles in OPI;
+    /// arguments are address bytes, dummy cycles and the length to read.
+    pub fn read_id_packet(&self) -> Packet {
+        if self.octal_enable == OctalEnable::MacronixCr2 {
+            let len = if self.mode.is_dtr() { 6 } else { 3 };
+            Packet::new(Command::SpiNorReadJedecId, &[4, 4, len])
+        } else {
+            Packet::new(Command::SpiNorReadJedecId, &[])
+        }
+    }
+
+    /// JEDEC ID bytes from a READ ID response in the current mode
+    ///
+    /// Macronix 8D-8D-8D outputs every ID byte twice (C2 C2 80 80 ...).
+    pub fn jedec_id(&self, response: &[u8]) -> Vec<u8> {
+        if self.octal_enable == OctalEnable::MacronixCr2 && self.mode == SpiNorIoMode::Dtr888 {
+            response.iter().step_by(2).copied().collect()
+        } else {
+            response.to_vec()
+        }
+    }
+
+    /// Switch the chip into `mode` and check it still returns `jedec_id`
+    ///
+    /// Falls back to 1S-1S-1S when it does not; returns the mode in use.
+    pub fn enter<E, T>(&self, jedec_id: [u8; 3], mut transfer: T) -> Result<SpiNorIoMode, E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        if self.mode == SpiNorIoMode::Sdr111 {
+            return Ok(SpiNorIoMode::Sdr111);
+        }
+
+        for command in &self.enter {
+            transfer(&Self::transfer_packet(command))?;
+        }
+        transfer(&self.set_io_mode_packet())?;
+
+        let id = transfer(&self.read_id_packet())?;
+        if self.jedec_id(&id).starts_with(&jedec_id) {
+            return Ok(self.mode);
+        }
+        self.exit(&mut transfer)?;
+        Ok(SpiNorIoMode::Sdr111)
+    }
+
+    /// Return the chip and the programmer to 1S-1S-1S
+    ///
+    /// Every supported mode switch is volatile, so a software reset sent in
+    /// the current mode undoes it.
+    pub fn exit<E, T>(&self, mut transfer: T) -> Result<(), E>
+    where
+        T: FnMut(&Packet) -> Result<Vec<u8>, E>,
+    {
+        if self.enter.is_empty() && self.mode.lanes().0 == 1 {
+            return Ok(());
+        }
+        transfer(&Packet::new(Command::SpiNorReset, &[]))?;
+        transfer(&Self::default().set_io_mode_packet())?;
+        Ok(())
+    }
+}
+
+/// Commands switching the chip into `mode`, `None` when it can't be done
+fn enter_sequence(
+    enable: OctalEnable,
+    mode: SpiNorIoMode,
+    sfdp: Option<&Sfdp>,
+) -> Option<Vec<Vec<u8>>> {
+    let wren = vec![commands::WRITE_ENABLE];
+    let dtr = mode.is_dtr();
+    match mode.lanes() {
+        (8, _, _) => match enable {
+            OctalEnable::MacronixCr2 => Some(vec![
+                wren,
+                vec![
+                    commands::WRITE_CR2,
+                    0,
+                    0,
+                    0,
+                    0,
+                    if dtr { 0x02 } else { 0x01 },
+                ],
+            ]),
+            OctalEnable::MicronVolatileConfig => Some(vec![
+                wren,
+                vec![
+                    commands::WRITE_VOLATILE_CONFIG,
+                    0,
+                    0,
+                    0,
+                    if dtr { 0xE7 } else { 0xB7 },
+                ],
+            ]),
+            OctalEnable::InfineonCfr5 => Some(vec![
+                wren,
+                vec![
+                    commands::WRITE_ANY_REGISTER,
+                    0x80,
+                    0x00,
+                    0x06,
+                    if dtr { 0x43 } else { 0x41 },
+                ],
+            ]),
+            OctalEnable::SfdpSequence if dtr => Some(sfdp?.octal_ddr.as_ref()?.commands.clone()),
+            _ => None,
+        },
+        (1, _, 8) => match enable {
+            OctalEnable::StatusRegister2 => {
+                Some(vec![wren, vec![commands::WRITE_STATUS_2_OCTAL, 0x08]])
+            }
+            _ => Some(Vec::new()),
+        },
+        (4, _, _) => {
+            let qpi = sfdp.and_then(|s| s.bfpt.qpi_enable);
+            Some(vec![vec![qpi.unwrap_or(commands::ENTER_QPI)]])
+        }
+        // DPI entry is not standardized
+        (2, _, _) => None,
+        _ => Some(Vec::new()),
+    }
+}
+
+/// Read opcode and dummy cycles when SFDP doesn't say
+fn default_read(enable: OctalEnable, mode: SpiNorIoMode) -> (u8, u8) {
+    match mode {
+        SpiNorIoMode::Sdr111 => (commands::FAST_READ, 8),
+        SpiNorIoMode::Sdr112 => (commands::DUAL_READ, 8),
+        SpiNorIoMode::Sdr122 | SpiNorIoMode::Sdr222 => (commands::DUAL_IO_READ, 4),
+        SpiNorIoMode::Sdr114 => (commands::QUAD_READ, 8),
+        SpiNorIoMode::Sdr144 | SpiNorIoMode::Sdr444 => (commands::QUAD_IO_READ, 6),
+        SpiNorIoMode::Sdr118 => (commands::OCTAL_READ, 8),
+        SpiNorIoMode::Sdr188 => (commands::OCTAL_IO_READ, 16),
+        SpiNorIoMode::Dtr111 => (commands::DTR_FAST_READ, 6),
+        SpiNorIoMode::Dtr122 => (commands::DTR_DUAL_IO_READ, 6),
+        SpiNorIoMode::Dtr144 | SpiNorIoMode::Dtr444 => (commands::DTR_QUAD_IO_READ, 8),
+        // Micron Xccela keeps its 1S-8S-8S opcode in octal mode
+        SpiNorIoMode::Sdr888 | SpiNorIoMode::Dtr888
+            if enable == OctalEnable::MicronVolatileConfig =>
+        {
+            (commands::OCTAL_IO_READ, 16)
+        }
+        SpiNorIoMode::Sdr888 => (commands::OCTA_READ, 20),
+        SpiNorIoMode::Dtr888 => (commands::OCTA_DTR_READ, 20),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi_nor::get_spi_nor_chip_info;
+
+    const ALL_MODES: &[SpiNorIoMode] = &SpiNorIoMode::ALL;
+
+    #[test]
+    fn test_negotiate_octaflash() {
+        let chip = get_spi_nor_chip_info(&[0xC2, 0x80, 0x3A]).unwrap();
+        assert_eq!(chip.octal_enable, OctalEnable::MacronixCr2);
+
+        let io = SpiNorIo::negotiate(&chip, None, ALL_MODES, 200);
+        assert_eq!(io.mode, SpiNorIoMode::Dtr888);
+        assert_eq!((io.read_opcode, io.dummy_cycles), (0xEE, 20));
+        assert_eq!(io.command_extension, Some(CommandExtension::Invert));
+        assert_eq!(io.enter[1], vec![0x72, 0, 0, 0, 0, 0x02]);
+
+        // Programmer without DTR: octal STR
+        let io = SpiNorIo::negotiate(
+            &chip,
+            None,
+            &[SpiNorIoMode::Sdr111, SpiNorIoMode::Sdr888],
+            133,
+        );
+        assert_eq!(io.mode, SpiNorIoMode::Sdr888);
+        assert_eq!(io.enter[1][5], 0x01);
+
+        // 1-bit programmer
+        let io = SpiNorIo::negotiate(&chip, None, &[SpiNorIoMode::Sdr111], 50);
+        assert_eq!(io, SpiNorIo::default());
+    }
+
+    #[test]
+    fn test_negotiate_quad_chip() {
+        let chip = get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap();
+        let io = SpiNorIo::negotiate(&chip, None, ALL_MODES, 104);
+        assert_eq!(io.mode, SpiNorIoMode::Sdr144);
+        assert!(io.enter.is_empty());
+        assert_eq!(io.command_extension, None);
+    }
+
+    #[test]
+    fn test_enter_falls_back_to_single() {
+        let chip = get_spi_nor_chip_info(&[0x2C, 0x5B, 0x1A]).unwrap();
+        let io = SpiNorIo::negotiate(&chip, None, ALL_MODES, 200);
+        assert_eq!(io.mode, SpiNorIoMode::Dtr888);
+
+        // Chip answers in 8D-8D-8D
+        let mut log = Vec::new();
+        let mode = io
+            .enter::<(), _>([0x2C, 0x5B, 0x1A], |packet| {
+                log.push(packet.cmd);
+                Ok(match packet.cmd {
+                    Command::SpiNorReadJedecId => vec![0x2C, 0x5B, 0x1A],
+                    _ => vec![],
+                })
+            })
+            .unwrap();
+        assert_eq!(mode, SpiNorIoMode::Dtr888);
+        assert_eq!(
+            log,
+            vec![
+                Command::SpiNorTransfer,
+                Command::SpiNorTransfer,
+                Command::SpiNorSetIoMode,
+                Command::SpiNorReadJedecId
+            ]
+        );
+
+        // Garbage READ ID: reset and back to 1S-1S-1S
+        let mut modes = Vec::new();
+        let mode = io
+            .enter::<(), _>([0x2C, 0x5B, 0x1A], |packet| {
+                if packet.cmd == Command::SpiNorSetIoMode {
+                    modes.push(SpiNorIoMode::from_code(packet.args[0]).unwrap());
+                }
+                Ok(vec![0xFF; 3])
+            })
+            .unwrap();
+        assert_eq!(mode, SpiNorIoMode::Sdr111);
+        assert_eq!(modes, vec![SpiNorIoMode::Dtr888, SpiNorIoMode::Sdr111]);
+
+        // Macronix 8D-8D-8D: READ ID with address and dummy, bytes doubled
+        let chip = get_spi_nor_chip_info(&[0xC2, 0x80, 0x3A]).unwrap();
+        let io = SpiNorIo::negotiate(&chip, None, ALL_MODES, 200);
+        let mut id_args = Vec::new();
+        let mut transfers = Vec::new();
+        let mode = io
+            .enter::<(), _>([0xC2, 0x80, 0x3A], |packet| {
+                Ok(match packet.cmd {
+                    Command::SpiNorTransfer => {
+                        transfers.push(packet.args[..7].to_vec());
+                        vec![]
+                    }
+                    Command::SpiNorReadJedecId => {
+                        id_args = packet.args[..3].to_vec();
+                        vec![0xC2, 0xC2, 0x80, 0x80, 0x3A, 0x3A]
+                    }
+                    _ => vec![],
+                })
+            })
+            .unwrap();
+        assert_eq!(mode, SpiNorIoMode::Dtr888);
+        assert_eq!(id_args, vec![4, 4, 6]);
+        // Transfers carry their length: WREN, then WRCR2 with DTR OPI
+        assert_eq!(transfers[0][..2], [1, 0x06]);
+        assert_eq!(transfers[1], vec![6, 0x72, 0, 0, 0, 0, 0x02]);
+
+        // Same chip in octal STR returns each byte once
+        let io = SpiNorIo::negotiate(
+            &chip,
+            None,
+            &[SpiNorIoMode::Sdr111, SpiNorIoMode::Sdr888],
+            133,
+        );
+        let mode = io
+            .enter::<(), _>([0xC2, 0x80, 0x3A], |packet| {
+                Ok(match packet.cmd {
+                    Command::SpiNorReadJedecId => vec![0xC2, 0x80, 0x3A],
+                    _ => vec![],
+                })
+            })
+            .unwrap();
+        assert_eq!(mode, SpiNorIoMode::Sdr888);
+    }
+}
@@ -0,0 +1,507 @@
+//! SPI NOR unique ID, security registers and OTP areas
+//!
+//! Reads, programs and locks the one-time-programmable areas used for device
+//! authentication. Locks are irreversible, so every lock goes through a
+//! confirmation callback first.
+
+use crate::protocol::{Command, Packet};
+use crate::spi_nor::{manufacturers, scur, status2, SpiNorChipInfo};
+use serde::{Deserialize, Serialize};
+
+/// Program granularity of security registers and the secured OTP
+const OTP_PAGE_SIZE: u32 = 256;
+
+/// How a chip exposes its unique ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UniqueIdSource {
+    None,
+    /// READ UNIQUE ID (4Bh), `len` bytes after the dummy bytes
+    Command {
+        len: u8,
+    },
+    /// Factory-programmed serial number at the start of the secured OTP
+    SecuredOtp {
+        len: u8,
+    },
+}
+
+/// OTP layout of a chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OtpScheme {
+    None,
+    /// Registers 1..=count of `size` bytes at n << 12: read with 48h,
+    /// programmed with 42h, erased with 44h, locked by LB1.. in SR2
+    /// (Winbond, GigaDevice)
+    SecurityRegisters {
+        count: u8,
+        size: u32,
+    },
+    /// Separate array entered with ENSO (B1h) and left with EXSO (C1h),
+    /// locked by LDSO through WRSCUR (2Fh) (Macronix)
+    SecuredOtp {
+        size: u32,
+    },
+}
+
+/// One lockable OTP area
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtpRegion {
+    /// Security register number, 0 for the secured OTP
+    pub index: u8,
+    pub address: u32,
+    pub size: u32,
+}
+
+/// What an irreversible lock is about to do
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtpLockRequest {
+    pub region: OtpRegion,
+    pub description: String,
+}
+
+/// Result of a lock attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OtpLockOutcome {
+    Locked,
+    AlreadyLocked,
+    /// The confirmation callback refused
+    Declined,
+}
+
+/// Contents of one OTP area
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtpRegionDump {
+    pub region: OtpRegion,
+    pub locked: bool,
+    pub data: Vec<u8>,
+}
+
+/// Unique ID and OTP areas, saved next to a dump
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OtpDump {
+    pub unique_id: Option<Vec<u8>>,
+    pub regions: Vec<OtpRegionDump>,
+}
+
+/// Unique ID and OTP access for one chip
+///
+/// `transfer` sends a packet followed by `payload` (program data, usually
+/// empty) and returns the response data, mapping an error status to `E`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpiNorOtp {
+    pub unique_id: UniqueIdSource,
+    pub scheme: OtpScheme,
+}
+
+impl SpiNorOtp {
+    pub fn for_chip(info: &SpiNorChipInfo) -> Self {
+        let (unique_id, scheme) = match info.jedec_id[0] {
+            manufacturers::WINBOND => (
+                UniqueIdSource::Command { len: 8 },
+                OtpScheme::SecurityRegisters {
+                    count: 3,
+                    size: 256,
+                },
+            ),
+            manufacturers::GIGADEVICE => (
+                UniqueIdSource::Command { len: 16 },
+                OtpScheme::SecurityRegisters {
+                    count: 3,
+                    size: 256,
+                },
+            ),
+            manufacturers::MACRONIX => (
+                UniqueIdSource::SecuredOtp { len: 16 },
+                OtpScheme::SecuredOtp { size: 512 },
+            ),
+            manufacturers::ISSI => (UniqueIdSource::Command { len: 16 }, OtpScheme::None),
+            _ => (UniqueIdSource::None, OtpScheme::None),
+        };
+        Self { unique_id, scheme }
+    }
+
+    pub fn regions(&self) -> Vec<OtpRegion> {
+        match self.scheme {
+            OtpScheme::None => Vec::new(),
+            OtpScheme::SecurityRegisters { count, size } => (1..=count)
+                .map(|index| OtpRegion {
+                    index,
+                    address: (index as u32) << 12,
+                    size,
+                })
+                .collect(),
+            OtpScheme::SecuredOtp { size } => vec![OtpRegion {
+                index: 0,
+                address: 0,
+                size,
+            }],
+        }
+    }
+
+    /// Read the unique ID, `None` when the chip has none
+    pub fn read_unique_id<E, T>(&self, mut transfer: T) -> Result<Option<Vec<u8>>, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        match self.unique_id {
+            UniqueIdSource::None => Ok(None),
+            UniqueIdSource::Command { len } => {
+                let packet = Packet::new(Command::SpiNorReadUniqueId, &[len]);
+                Ok(Some(transfer(&packet, &[])?))
+            }
+            UniqueIdSource::SecuredOtp { len } => {
+                let region = OtpRegion {
+                    index: 0,
+                    address: 0,
+                    size: len as u32,
+                };
+                self.read_region(&region, transfer).map(Some)
+            }
+        }
+    }
+
+    /// Read a whole OTP area
+    pub fn read_region<E, T>(&self, region: &OtpRegion, mut transfer: T) -> Result<Vec<u8>, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let len = (region.size as u16).to_le_bytes();
+        match self.scheme {
+            OtpScheme::SecuredOtp { .. } => self.in_secured_otp(&mut transfer, |transfer| {
+                let addr = region.address.to_le_bytes();
+                let args = [addr[0], addr[1], addr[2], addr[3], len[0], len[1]];
+                transfer(&Packet::new(Command::SpiNorRead, &args), &[])
+            }),
+            _ => {
+                let addr = region.address.to_le_bytes();
+                let args = [addr[0], addr[1], addr[2], len[0], len[1]];
+                transfer(&Packet::new(Command::SpiNorReadSecurityReg, &args), &[])
+            }
+        }
+    }
+
+    /// Lock state of each region from `regions()`
+    pub fn lock_status<E, T>(&self, mut transfer: T) -> Result<Vec<bool>, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let regions = self.regions();
+        match self.scheme {
+            OtpScheme::None => Ok(Vec::new()),
+            OtpScheme::SecurityRegisters { .. } => {
+                let sr2 = Self::read_register(&mut transfer, Command::SpiNorReadStatus2)?;
+                Ok(regions
+                    .iter()
+                    .map(|r| sr2 & Self::lock_bit(r) != 0)
+                    .collect())
+            }
+            OtpScheme::SecuredOtp { .. } => {
+                let value = Self::read_register(&mut transfer, Command::SpiNorReadSecurityScur)?;
+                Ok(vec![value & scur::LDSO != 0])
+            }
+        }
+    }
+
+    /// Program `data` at `offset` within a region, one page at a time
+    pub fn program_region<E, T>(
+        &self,
+        region: &OtpRegion,
+        offset: u32,
+        data: &[u8],
+        mut transfer: T,
+    ) -> Result<(), E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let end = (offset as usize + data.len()).min(region.size as usize);
+        let data = &data[..end.saturating_sub(offset as usize)];
+        let command = match self.scheme {
+            OtpScheme::None => return Ok(()),
+            OtpScheme::SecurityRegisters { .. } => Command::SpiNorProgramSecurityReg,
+            OtpScheme::SecuredOtp { .. } => Command::SpiNorPageProgram,
+        };
+
+        let program = |transfer: &mut T| {
+            let mut done = 0usize;
+            while done < data.len() {
+                let address = region.address + offset + done as u32;
+                let room = (OTP_PAGE_SIZE - address % OTP_PAGE_SIZE) as usize;
+                let chunk = &data[done..done + room.min(data.len() - done)];
+                let addr = address.to_le_bytes();
+                let len = (chunk.len() as u16).to_le_bytes();
+                let args = match command {
+                    Command::SpiNorPageProgram => {
+                        vec![addr[0], addr[1], addr[2], addr[3], len[0], len[1]]
+                    }
+                    _ => vec![addr[0], addr[1], addr[2], len[0], len[1]],
+                };
+                transfer(&Packet::new(command, &args), chunk)?;
+                done += chunk.len();
+            }
+            Ok::<(), E>(())
+        };
+
+        match self.scheme {
+            OtpScheme::SecuredOtp { .. } => self.in_secured_otp(&mut transfer, program),
+            _ => program(&mut transfer),
+        }
+    }
+
+    /// Erase a security register; `false` for true OTP areas
+    pub fn erase_region<E, T>(&self, region: &OtpRegion, mut transfer: T) -> Result<bool, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        match self.scheme {
+            OtpScheme::SecurityRegisters { .. } => {
+                let addr = region.address.to_le_bytes();
+                let packet = Packet::new(Command::SpiNorEraseSecurityReg, &addr[..3]);
+                transfer(&packet, &[])?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Permanently lock a region once `confirm` agrees
+    ///
+    /// `confirm` is not called for regions that are already locked.
+    pub fn lock_region<E, T, C>(
+        &self,
+        region: &OtpRegion,
+        confirm: C,
+        mut transfer: T,
+    ) -> Result<OtpLockOutcome, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+        C: FnOnce(&OtpLockRequest) -> bool,
+    {
+        let Some(position) = self.regions().iter().position(|r| r == region) else {
+            return Ok(OtpLockOutcome::Declined);
+        };
+        if self.lock_status(&mut transfer)?[position] {
+            return Ok(OtpLockOutcome::AlreadyLocked);
+        }
+
+        let area = match self.scheme {
+            OtpScheme::SecuredOtp { .. } => "secured OTP".to_string(),
+            _ => format!("security register {}", region.index),
+        };
+        let request = OtpLockRequest {
+            region: *region,
+            description: format!(
+                "Permanently lock {} (0x{:04X}, {} bytes); it can never be programmed or erased again",
+                area, region.address, region.size
+            ),
+        };
+        if !confirm(&request) {
+            return Ok(OtpLockOutcome::Declined);
+        }
+
+        match self.scheme {
+            OtpScheme::SecuredOtp { .. } => {
+                transfer(&Packet::new(Command::SpiNorLockSecuredOtp, &[]), &[])?;
+            }
+            _ => {
+                let sr2 = Self::read_register(&mut transfer, Command::SpiNorReadStatus2)?;
+                let value = sr2 | Self::lock_bit(region);
+                transfer(&Packet::new(Command::SpiNorWriteStatus2, &[value]), &[])?;
+            }
+        }
+        Ok(OtpLockOutcome::Locked)
+    }
+
+    /// Unique ID and every OTP area with its lock state
+    pub fn dump<E, T>(&self, mut transfer: T) -> Result<OtpDump, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let unique_id = self.read_unique_id(&mut transfer)?;
+        let locks = self.lock_status(&mut transfer)?;
+        let regions = self
+            .regions()
+            .into_iter()
+            .zip(locks)
+            .map(|(region, locked)| {
+                Ok(OtpRegionDump {
+                    region,
+                    locked,
+                    data: self.read_region(&region, &mut transfer)?,
+                })
+            })
+            .collect::<Result<_, E>>()?;
+        Ok(OtpDump { unique_id, regions })
+    }
+
+    /// SR2 lock bit of a security register (LB1 for register 1)
+    fn lock_bit(region: &OtpRegion) -> u8 {
+        status2::LB1 << region.index.saturating_sub(1)
+    }
+
+    fn read_register<E, T>(transfer: &mut T, command: Command) -> Result<u8, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let response = transfer(&Packet::new(command, &[]), &[])?;
+        Ok(response.first().copied().unwrap_or(0))
+    }
+
+    /// Run `f` inside ENSO/EXSO, leaving OTP mode even when `f` fails
+    fn in_secured_otp<E, T, R, F>(&self, transfer: &mut T, f: F) -> Result<R, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+        F: FnOnce(&mut T) -> Result<R, E>,
+    {
+        transfer(&Packet::new(Command::SpiNorEnterSecuredOtp, &[]), &[])?;
+        let result = f(transfer);
+        let exit = transfer(&Packet::new(Command::SpiNorExitSecuredOtp, &[]), &[]);
+        let value = result?;
+        exit?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi_nor::get_spi_nor_chip_info;
+
+    /// Winbond-style chip: security registers at 0x1000.. and SR2
+    struct MockWinbond {
+        regs: [[u8; 256]; 3],
+        sr2: u8,
+    }
+
+    impl MockWinbond {
+        fn transfer(&mut self, packet: &Packet, payload: &[u8]) -> Result<Vec<u8>, ()> {
+            let a = &packet.args;
+            let addr = u32::from_le_bytes([a[0], a[1], a[2], 0]);
+            let (reg, offset) = (
+                ((addr >> 12) as usize).saturating_sub(1),
+                (addr & 0xFF) as usize,
+            );
+            match packet.cmd {
+                Command::SpiNorReadUniqueId => Ok(vec![0xD2; a[0] as usize]),
+                Command::SpiNorReadStatus2 => Ok(vec![self.sr2]),
+                Command::SpiNorWriteStatus2 => {
+                    self.sr2 = a[0];
+                    Ok(vec![])
+                }
+                Command::SpiNorReadSecurityReg => {
+                    let len = u16::from_le_bytes([a[3], a[4]]) as usize;
+                    Ok(self.regs[reg][offset..offset + len].to_vec())
+                }
+                Command::SpiNorProgramSecurityReg => {
+                    if self.sr2 & (status2::LB1 << reg) != 0 {
+                        return Err(());
+                    }
+                    self.regs[reg][offset..offset + payload.len()].copy_from_slice(payload);
+                    Ok(vec![])
+                }
+                _ => Err(()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_security_registers_program_and_lock() {
+        let info = get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap();
+        let otp = SpiNorOtp::for_chip(&info);
+        let regions = otp.regions();
+        assert_eq!(
+            regions.iter().map(|r| r.address).collect::<Vec<_>>(),
+            vec![0x1000, 0x2000, 0x3000]
+        );
+
+        let mut chip = MockWinbond {
+            regs: [[0xFF; 256]; 3],
+            sr2: 0x02, // QE
+        };
+        let key: Vec<u8> = (0..40).collect();
+        otp.program_region(&regions[1], 0xF0, &key, |p, d| chip.transfer(p, d))
+            .unwrap();
+        // Clipped to the 256-byte register
+        assert_eq!(&chip.regs[1][0xF0..], &key[..16]);
+
+        // Declined: nothing written
+        let outcome = otp
+            .lock_region(&regions[1], |_| false, |p, d| chip.transfer(p, d))
+            .unwrap();
+        assert_eq!(outcome, OtpLockOutcome::Declined);
+        assert_eq!(chip.sr2, 0x02);
+
+        let mut prompt = String::new();
+        let outcome = otp
+            .lock_region(
+                &regions[1],
+                |request| {
+                    prompt = request.description.clone();
+                    true
+                },
+                |p, d| chip.transfer(p, d),
+            )
+            .unwrap();
+        assert_eq!(outcome, OtpLockOutcome::Locked);
+        assert!(prompt.contains("security register 2"));
+        assert_eq!(chip.sr2, 0x02 | status2::LB2);
+
+        let outcome = otp
+            .lock_region(
+                &regions[1],
+                |_| panic!("no prompt"),
+                |p, d| chip.transfer(p, d),
+            )
+            .unwrap();
+        assert_eq!(outcome, OtpLockOutcome::AlreadyLocked);
+
+        let dump = otp.dump(|p, d| chip.transfer(p, d)).unwrap();
+        assert_eq!(dump.unique_id, Some(vec![0xD2; 8]));
+        assert_eq!(
+            dump.regions.iter().map(|r| r.locked).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+        assert_eq!(dump.regions[1].data[0xF0], 0);
+    }
+
+    #[test]
+    fn test_macronix_secured_otp() {
+        let info = get_spi_nor_chip_info(&[0xC2, 0x20, 0x18]).unwrap();
+        let otp = SpiNorOtp::for_chip(&info);
+        assert_eq!(otp.scheme, OtpScheme::SecuredOtp { size: 512 });
+
+        let mut otp_mode = false;
+        let mut log = Vec::new();
+        let mut transfer = |packet: &Packet, _: &[u8]| -> Result<Vec<u8>, ()> {
+            log.push(packet.cmd);
+            match packet.cmd {
+                Command::SpiNorEnterSecuredOtp => otp_mode = true,
+                Command::SpiNorExitSecuredOtp => otp_mode = false,
+                Command::SpiNorReadSecurityScur => return Ok(vec![scur::FACTORY_LOCK]),
+                Command::SpiNorRead if otp_mode => {
+                    let len = u16::from_le_bytes([packet.args[4], packet.args[5]]);
+                    return Ok((0..len).map(|i| i as u8).collect());
+                }
+                _ => return Err(()),
+            }
+            Ok(vec![])
+        };
+
+        let dump = otp.dump(&mut transfer).unwrap();
+        // OTP cannot be erased
+        let region = otp.regions()[0];
+        assert!(!otp.erase_region(&region, &mut transfer).unwrap());
+
+        assert_eq!(dump.unique_id, Some((0..16).collect()));
+        assert_eq!(dump.regions.len(), 1);
+        assert!(!dump.regions[0].locked);
+        assert_eq!(dump.regions[0].data.len(), 512);
+        assert_eq!(
+            log[..3],
+            [
+                Command::SpiNorEnterSecuredOtp,
+                Command::SpiNorRead,
+                Command::SpiNorExitSecuredOtp
+            ]
+        );
+    }
+}
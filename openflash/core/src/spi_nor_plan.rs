@@ -0,0 +1,639 @@
+//! Diff-based SPI NOR programming
+//!
+//! Compares an image with the chip's current contents (or per-sector hashes
+//! of them) and plans the cheapest mix of erase sizes. Sectors that already
+//! match are left alone, sectors that only need 1→0 bit changes are
+//! programmed without an erase, and pages that are blank after an erase are
+//! not programmed. `ChipProgrammer::execute_nor_plan` runs the plan with
+//! verify.
+
+use crate::protocol::{Command, Packet};
+use crate::sfdp::Bfpt;
+use crate::spi_nor::{commands, SfdpInfo, SpiNorChipInfo};
+use crate::spi_nor_addr::SpiNorAddressing;
+use crate::write_ops::{ChangeTracker, WriteError, WriteResult};
+use serde::{Deserialize, Serialize};
+
+/// Typical page program time when SFDP doesn't give one (µs)
+const DEFAULT_PAGE_PROGRAM_US: u32 = 700;
+
+/// Typical erase time for sizes SFDP gives no timing for (ms)
+fn default_erase_ms(size: u32) -> u32 {
+    match size {
+        0..=4096 => 45,
+        4097..=32768 => 120,
+        _ => 150 * (size / 65536).max(1),
+    }
+}
+
+/// Standard opcode for an erase size
+fn default_erase_opcode(size: u32) -> u8 {
+    match size {
+        32768 => commands::BLOCK_ERASE_32K,
+        65536 => commands::BLOCK_ERASE_64K,
+        _ => commands::SECTOR_ERASE,
+    }
+}
+
+/// One erase size the chip supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NorEraseSize {
+    pub size: u32,
+    pub opcode: u8,
+    pub typical_ms: u32,
+}
+
+impl NorEraseSize {
+    /// Erase size with the standard opcode and typical time
+    pub fn standard(size: u32) -> Self {
+        Self {
+            size,
+            opcode: default_erase_opcode(size),
+            typical_ms: default_erase_ms(size),
+        }
+    }
+}
+
+/// Page and erase geometry the planner works with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NorGeometry {
+    pub page_size: u32,
+    /// Smallest first; each size is a multiple of the one before
+    pub erase_sizes: Vec<NorEraseSize>,
+    pub page_program_us: u32,
+}
+
+impl NorGeometry {
+    /// Keep the erase sizes that nest (each a multiple of the previous one)
+    pub fn new(page_size: u32, mut erase_sizes: Vec<NorEraseSize>, page_program_us: u32) -> Self {
+        erase_sizes.sort_by_key(|e| e.size);
+        erase_sizes.dedup_by_key(|e| e.size);
+        let mut nested: Vec<NorEraseSize> = Vec::new();
+        for erase in erase_sizes {
+            let fits = match nested.last() {
+                Some(prev) => erase.size % prev.size == 0,
+                None => page_size > 0 && erase.size % page_size == 0,
+            };
+            if fits {
+                nested.push(erase);
+            }
+        }
+        Self {
+            page_size,
+            erase_sizes: nested,
+            page_program_us: if page_program_us == 0 {
+                DEFAULT_PAGE_PROGRAM_US
+            } else {
+                page_program_us
+            },
+        }
+    }
+
+    /// `sector_size` and `block_size` from the chip table
+    pub fn for_chip(info: &SpiNorChipInfo) -> Self {
+        Self::new(
+            info.page_size,
+            vec![
+                NorEraseSize::standard(info.sector_size),
+                NorEraseSize::standard(info.block_size),
+            ],
+            0,
+        )
+    }
+
+    /// 4K/32K/64K erase support from the SFDP summary
+    pub fn from_sfdp_info(info: &SfdpInfo) -> Self {
+        let sizes = [
+            (4096, info.supports_4kb_erase),
+            (32768, info.supports_32kb_erase),
+            (65536, info.supports_64kb_erase),
+        ];
+        let mut erase_sizes: Vec<NorEraseSize> = sizes
+            .iter()
+            .filter(|(_, supported)| *supported)
+            .map(|&(size, _)| NorEraseSize::standard(size))
+            .collect();
+        if erase_sizes.is_empty() {
+            erase_sizes.push(NorEraseSize::standard(info.sector_size));
+        }
+        Self::new(info.page_size, erase_sizes, 0)
+    }
+
+    /// Erase types, opcodes and typical times from the BFPT
+    pub fn from_bfpt(bfpt: &Bfpt) -> Self {
+        let erase_sizes = bfpt
+            .erase_types()
+            .map(|(_, t)| NorEraseSize {
+                size: t.size,
+                opcode: t.opcode,
+                typical_ms: if t.typical_ms == 0 {
+                    default_erase_ms(t.size)
+                } else {
+                    t.typical_ms
+                },
+            })
+            .collect();
+        Self::new(bfpt.page_size, erase_sizes, bfpt.page_program_typical_us)
+    }
+
+    /// Drop erase sizes the chip can't run in `addressing`'s mode, such
+    /// as 32K erase with 4-byte opcodes but no 5Ch
+    pub fn retain_supported(&mut self, addressing: &SpiNorAddressing) {
+        self.erase_sizes
+            .retain(|e| addressing.opcode(e.opcode).is_some());
+    }
+
+    fn sector_size(&self) -> u32 {
+        self.erase_sizes[0].size
+    }
+
+    fn erase_us(&self, level: usize) -> u64 {
+        self.erase_sizes[level].typical_ms as u64 * 1000
+    }
+}
+
+/// Current chip contents the image is compared against
+#[derive(Debug, Clone, Copy)]
+pub enum NorContents<'a> {
+    /// Read back over the same range as the image
+    Data(&'a [u8]),
+    /// `ChangeTracker::calculate_checksum` of each smallest erase unit
+    ///
+    /// A changed sector is always erased since its bits are unknown.
+    SectorHashes(&'a [u64]),
+}
+
+/// One erase in a plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NorErase {
+    pub address: u32,
+    pub size: u32,
+    pub opcode: u8,
+}
+
+impl NorErase {
+    /// Firmware packet for the standard 4K/32K/64K erases
+    pub fn packet(&self) -> Option<Packet> {
+        let cmd = match (self.size, self.opcode) {
+            (4096, commands::SECTOR_ERASE) => Command::SpiNorSectorErase,
+            (32768, commands::BLOCK_ERASE_32K) => Command::SpiNorBlockErase32K,
+            (65536, commands::BLOCK_ERASE_64K) => Command::SpiNorBlockErase64K,
+            _ => return None,
+        };
+        Some(Packet::new(cmd, &self.address.to_le_bytes()))
+    }
+}
+
+/// One step of a plan, in address order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum NorPlanOp {
+    Erase(NorErase),
+    /// Program `len` image bytes at `address`
+    Program {
+        address: u32,
+        len: u32,
+    },
+}
+
+/// Erase/program plan for one image
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NorProgramPlan {
+    pub base_address: u32,
+    pub ops: Vec<NorPlanOp>,
+    /// Ranges to read back: erased units and pages programmed without erase
+    pub verify: Vec<(u32, u32)>,
+    /// Image bytes in sectors that were left untouched
+    pub unchanged_bytes: u64,
+    pub estimated_ms: u64,
+    /// Estimate for erasing everything and programming every non-blank page
+    pub full_estimated_ms: u64,
+}
+
+impl NorProgramPlan {
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn erases(&self) -> impl Iterator<Item = &NorErase> + '_ {
+        self.ops.iter().filter_map(|op| match op {
+            NorPlanOp::Erase(erase) => Some(erase),
+            NorPlanOp::Program { .. } => None,
+        })
+    }
+
+    pub fn program_count(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, NorPlanOp::Program { .. }))
+            .count()
+    }
+
+    pub fn bytes_erased(&self) -> u64 {
+        self.erases().map(|e| e.size as u64).sum()
+    }
+}
+
+/// What the smallest erase unit needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SectorState {
+    Equal,
+    /// Only 1→0 changes; these page offsets differ
+    ProgramOnly(Vec<u32>),
+    NeedsErase,
+}
+
+/// Chosen operations for one unit and their cost
+struct Solution {
+    cost_us: u64,
+    ops: Vec<NorPlanOp>,
+    verify: Vec<(u32, u32)>,
+    unchanged_bytes: u64,
+}
+
+/// Plans diff-based erase/program sequences
+pub struct NorPlanner {
+    geometry: NorGeometry,
+}
+
+impl NorPlanner {
+    pub fn new(geometry: NorGeometry) -> Self {
+        Self { geometry }
+    }
+
+    pub fn geometry(&self) -> &NorGeometry {
+        &self.geometry
+    }
+
+    /// Plan writing `image` at `base_address`
+    ///
+    /// The image must start and end on the smallest erase size.
+    pub fn plan(
+        &self,
+        base_address: u32,
+        image: &[u8],
+        current: NorContents,
+    ) -> WriteResult<NorProgramPlan> {
+        let Some(smallest) = self.geometry.erase_sizes.first() else {
+            return Err(WriteError::Unsupported("no usable erase size".into()));
+        };
+        let sector = smallest.size as usize;
+        if base_address as usize % sector != 0 || image.len() % sector != 0 {
+            return Err(WriteError::Unsupported(format!(
+                "image not aligned to {} byte erase units",
+                sector
+            )));
+        }
+
+        let sectors = image.len() / sector;
+        let states = match current {
+            NorContents::Data(data) => {
+                if data.len() != image.len() {
+                    return Err(WriteError::DataSizeMismatch {
+                        expected: image.len(),
+                        actual: data.len(),
+                    });
+                }
+                (0..sectors)
+                    .map(|i| {
+                        let range = i * sector..(i + 1) * sector;
+                        self.compare_sector(&image[range.clone()], &data[range])
+                    })
+                    .collect::<Vec<_>>()
+            }
+            NorContents::SectorHashes(hashes) => {
+                if hashes.len() != sectors {
+                    return Err(WriteError::DataSizeMismatch {
+                        expected: sectors,
+                        actual: hashes.len(),
+                    });
+                }
+                image
+                    .chunks(sector)
+                    .zip(hashes)
+                    .map(|(chunk, &hash)| {
+                        if ChangeTracker::calculate_checksum(chunk) == hash {
+                            SectorState::Equal
+                        } else {
+                            SectorState::NeedsErase
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        let mut plan = NorProgramPlan {
+            base_address,
+            ops: Vec::new(),
+            verify: Vec::new(),
+            unchanged_bytes: 0,
+            estimated_ms: 0,
+            full_estimated_ms: 0,
+        };
+        let mut cost_us = 0u64;
+        let mut full_us = 0u64;
+        let mut offset = 0usize;
+        while offset < image.len() {
+            // Largest erase size aligned here that fits in the image
+            let address = base_address as usize + offset;
+            let level = (0..self.geometry.erase_sizes.len())
+                .rev()
+                .find(|&l| {
+                    let size = self.geometry.erase_sizes[l].size as usize;
+                    address % size == 0 && offset + size <= image.len()
+                })
+                .unwrap_or(0);
+            let size = self.geometry.erase_sizes[level].size as usize;
+
+            let solution = self.solve(base_address, image, &states, offset, level);
+            full_us += self.geometry.erase_us(level)
+                + self.blank_free_pages(&image[offset..offset + size]).len() as u64
+                    * self.geometry.page_program_us as u64;
+            cost_us += solution.cost_us;
+            plan.ops.extend(solution.ops);
+            plan.verify.extend(solution.verify);
+            plan.unchanged_bytes += solution.unchanged_bytes;
+            offset += size;
+        }
+
+        plan.verify = merge_ranges(plan.verify);
+        plan.estimated_ms = (cost_us + 999) / 1000;
+        plan.full_estimated_ms = (full_us + 999) / 1000;
+        Ok(plan)
+    }
+
+    fn compare_sector(&self, target: &[u8], current: &[u8]) -> SectorState {
+        if target == current {
+            return SectorState::Equal;
+        }
+        // Programming only clears bits
+        if target.iter().zip(current).any(|(&t, &c)| t & !c != 0) {
+            return SectorState::NeedsErase;
+        }
+        let page = self.geometry.page_size as usize;
+        let pages = target
+            .chunks(page)
+            .zip(current.chunks(page))
+            .enumerate()
+            .filter(|(_, (t, c))| t != c)
+            .map(|(i, _)| (i * page) as u32)
+            .collect();
+        SectorState::ProgramOnly(pages)
+    }
+
+    /// Offsets of pages in `target` that aren't all 0xFF
+    fn blank_free_pages(&self, target: &[u8]) -> Vec<u32> {
+        let page = self.geometry.page_size as usize;
+        target
+            .chunks(page)
+            .enumerate()
+            .filter(|(_, p)| p.iter().any(|&b| b != 0xFF))
+            .map(|(i, _)| (i * page) as u32)
+            .collect()
+    }
+
+    /// Erase the unit at `offset` with `level`, then program its non-blank pages
+    fn erase_unit(&self, base_address: u32, image: &[u8], offset: usize, level: usize) -> Solution {
+        let erase = self.geometry.erase_sizes[level];
+        let address = base_address + offset as u32;
+        let pages = self.blank_free_pages(&image[offset..offset + erase.size as usize]);
+        let mut ops = vec![NorPlanOp::Erase(NorErase {
+            address,
+            size: erase.size,
+            opcode: erase.opcode,
+        })];
+        ops.extend(pages.iter().map(|&p| NorPlanOp::Program {
+            address: address + p,
+            len: self.geometry.page_size,
+        }));
+        Solution {
+            cost_us: self.geometry.erase_us(level)
+                + pages.len() as u64 * self.geometry.page_program_us as u64,
+            ops,
+            verify: vec![(address, erase.size)],
+            unchanged_bytes: 0,
+        }
+    }
+
+    /// Cheapest way to bring the unit at `offset` of size `level` up to date
+    ///
+    /// Erasing the whole unit is weighed against the best choice for each of
+    /// its sub-units; ties keep the smaller erases to save wear.
+    fn solve(
+        &self,
+        base_address: u32,
+        image: &[u8],
+        states: &[SectorState],
+        offset: usize,
+        level: usize,
+    ) -> Solution {
+        let sector = self.geometry.sector_size() as usize;
+        if level == 0 {
+            let address = base_address + offset as u32;
+            return match &states[offset / sector] {
+                SectorState::Equal => Solution {
+                    cost_us: 0,
+                    ops: Vec::new(),
+                    verify: Vec::new(),
+                    unchanged_bytes: sector as u64,
+                },
+                SectorState::ProgramOnly(pages) => {
+                    let page_size = self.geometry.page_size;
+                    let program = Solution {
+                        cost_us: pages.len() as u64 * self.geometry.page_program_us as u64,
+                        ops: pages
+                            .iter()
+                            .map(|&p| NorPlanOp::Program {
+                                address: address + p,
+                                len: page_size,
+                            })
+                            .collect(),
+                        verify: pages.iter().map(|&p| (address + p, page_size)).collect(),
+                        unchanged_bytes: 0,
+                    };
+                    let erase = self.erase_unit(base_address, image, offset, 0);
+                    if erase.cost_us < program.cost_us {
+                        erase
+                    } else {
+                        program
+                    }
+                }
+                SectorState::NeedsErase => self.erase_unit(base_address, image, offset, 0),
+            };
+        }
+
+        let size = self.geometry.erase_sizes[level].size as usize;
+        let child = self.geometry.erase_sizes[level - 1].size as usize;
+        let mut split = Solution {
+            cost_us: 0,
+            ops: Vec::new(),
+            verify: Vec::new(),
+            unchanged_bytes: 0,
+        };
+        for child_offset in (offset..offset + size).step_by(child) {
+            let s = self.solve(base_address, image, states, child_offset, level - 1);
+            split.cost_us += s.cost_us;
+            split.ops.extend(s.ops);
+            split.verify.extend(s.verify);
+            split.unchanged_bytes += s.unchanged_bytes;
+        }
+        if split.ops.is_empty() {
+            return split;
+        }
+        let erase = self.erase_unit(base_address, image, offset, level);
+        if erase.cost_us < split.cost_us {
+            erase
+        } else {
+            split
+        }
+    }
+}
+
+/// Merge adjacent (address, len) ranges
+fn merge_ranges(ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    let mut merged: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (address, len) in ranges {
+        match merged.last_mut() {
+            Some((start, total)) if *start + *total == address => *total += len,
+            _ => merged.push((address, len)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry() -> NorGeometry {
+        NorGeometry::new(
+            256,
+            vec![
+                NorEraseSize::standard(4096),
+                NorEraseSize::standard(32768),
+                NorEraseSize::standard(65536),
+            ],
+            700,
+        )
+    }
+
+    #[test]
+    fn test_unchanged_and_program_only() {
+        let planner = NorPlanner::new(geometry());
+        let current = vec![0xFFu8; 65536];
+        let plan = planner
+            .plan(0, &current, NorContents::Data(&current))
+            .unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.unchanged_bytes, 65536);
+
+        // 1→0 only in one page: no erase
+        let mut image = current.clone();
+        image[0x1234] = 0x00;
+        let plan = planner
+            .plan(0, &image, NorContents::Data(&current))
+            .unwrap();
+        assert_eq!(
+            plan.ops,
+            vec![NorPlanOp::Program {
+                address: 0x1200,
+                len: 256
+            }]
+        );
+        assert_eq!(plan.verify, vec![(0x1200, 256)]);
+        assert!(plan.estimated_ms < plan.full_estimated_ms);
+    }
+
+    #[test]
+    fn test_cheapest_erase_mix() {
+        let planner = NorPlanner::new(geometry());
+        let current = vec![0x00u8; 131072];
+
+        // One dirty sector: a single 4K erase at 0x10000
+        let mut image = current.clone();
+        image[0x10010] = 0xFF;
+        let plan = planner
+            .plan(0, &image, NorContents::Data(&current))
+            .unwrap();
+        let erases: Vec<_> = plan.erases().map(|e| (e.address, e.size)).collect();
+        assert_eq!(erases, vec![(0x10000, 4096)]);
+        assert_eq!(plan.program_count(), 16);
+
+        // Everything blank in the image: 64K erases win, nothing programmed
+        let image = vec![0xFFu8; 131072];
+        let plan = planner
+            .plan(0, &image, NorContents::Data(&current))
+            .unwrap();
+        let erases: Vec<_> = plan.erases().map(|e| (e.address, e.opcode)).collect();
+        assert_eq!(
+            erases,
+            vec![
+                (0x00000, commands::BLOCK_ERASE_64K),
+                (0x10000, commands::BLOCK_ERASE_64K)
+            ]
+        );
+        assert_eq!(plan.program_count(), 0);
+        assert_eq!(plan.verify, vec![(0, 131072)]);
+    }
+
+    #[test]
+    fn test_sector_hashes_and_alignment() {
+        let planner = NorPlanner::new(geometry());
+        let image = vec![0x5Au8; 8192];
+        let hashes = [ChangeTracker::calculate_checksum(&image[..4096]), 0];
+        let plan = planner
+            .plan(0x2000, &image, NorContents::SectorHashes(&hashes))
+            .unwrap();
+        let erases: Vec<_> = plan.erases().map(|e| (e.address, e.size)).collect();
+        assert_eq!(erases, vec![(0x3000, 4096)]);
+        assert_eq!(plan.unchanged_bytes, 4096);
+
+        assert!(planner
+            .plan(0x100, &image, NorContents::Data(&image))
+            .is_err());
+    }
+
+    #[test]
+    fn test_geometry_sources() {
+        let chip = crate::spi_nor::get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap();
+        let geometry = NorGeometry::for_chip(&chip);
+        let sizes: Vec<_> = geometry.erase_sizes.iter().map(|e| e.size).collect();
+        assert_eq!(sizes, vec![4096, 65536]);
+
+        // Non-nesting sizes are dropped
+        let geometry = NorGeometry::new(
+            256,
+            vec![NorEraseSize::standard(4096), NorEraseSize::standard(6144)],
+            0,
+        );
+        assert_eq!(geometry.erase_sizes.len(), 1);
+        assert_eq!(geometry.page_program_us, DEFAULT_PAGE_PROGRAM_US);
+
+        let erase = NorErase {
+            address: 0x1000,
+            size: 4096,
+            opcode: commands::SECTOR_ERASE,
+        };
+        let packet = erase.packet().unwrap();
+        assert_eq!(packet.cmd, Command::SpiNorSectorErase);
+        assert_eq!(&packet.args[..4], &[0x00, 0x10, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_retain_supported_erase_sizes() {
+        let mut geometry = geometry();
+        geometry.retain_supported(&SpiNorAddressing::for_chip(
+            &crate::spi_nor::get_spi_nor_chip_info(&[0xEF, 0x40, 0x19]).unwrap(),
+            None,
+        ));
+        assert_eq!(geometry.erase_sizes.len(), 3);
+
+        // 4-byte opcodes without 5Ch have no 32K erase
+        geometry.retain_supported(&SpiNorAddressing {
+            mode: crate::spi_nor_addr::FourByteMode::DedicatedOpcodes,
+            size_bytes: 32 << 20,
+            erase_32k_4b: false,
+        });
+        let sizes: Vec<_> = geometry.erase_sizes.iter().map(|e| e.size).collect();
+        assert_eq!(sizes, vec![4096, 65536]);
+    }
+}
@@ -0,0 +1,705 @@
+//! SPI NOR block protection maps
+//!
+//! Decodes status register protection bits into the exact protected address
+//! range for each vendor scheme and plans the register changes needed to
+//! unprotect a range before write/erase.
+
+use crate::protocol::{Command, Packet};
+use crate::spi_nor::{
+    commands, manufacturers, status1, status2, status3, ProtectionStatus, SpiNorChipInfo,
+    SpiNorError,
+};
+use crate::spi_nor_io::SpiNorIo;
+use serde::{Deserialize, Serialize};
+
+const BLOCK_64K: u64 = 64 * 1024;
+const SECTOR_4K: u64 = 4 * 1024;
+
+/// Status register values as read from the chip
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusRegisters {
+    pub sr1: u8,
+    pub sr2: u8,
+    /// SR3 (15h), the configuration register on Macronix (also read with 15h)
+    pub sr3: u8,
+}
+
+impl StatusRegisters {
+    /// Read SR1, SR2 and SR3
+    pub fn read<E, T>(transfer: &mut T) -> Result<Self, E>
+    where
+        T: FnMut(&Packet, &[u8]) -> Result<Vec<u8>, E>,
+    {
+        let mut read = |command| {
+            transfer(&Packet::new(command, &[]), &[])
+                .map(|response| response.first().copied().unwrap_or(0))
+        };
+        Ok(Self {
+            sr1: read(Command::SpiNorReadStatus1)?,
+            sr2: read(Command::SpiNorReadStatus2)?,
+            sr3: read(Command::SpiNorReadStatus3)?,
+        })
+    }
+}
+
+/// Contiguous protected address range
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectedRange {
+    pub start: u32,
+    pub len: u32,
+}
+
+impl ProtectedRange {
+    pub fn end(&self) -> u64 {
+        self.start as u64 + self.len as u64
+    }
+
+    pub fn overlaps(&self, start: u32, len: u32) -> bool {
+        len > 0 && (start as u64) < self.end() && (self.start as u64) < start as u64 + len as u64
+    }
+}
+
+/// Current protection of the array
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Protection {
+    None,
+    Range(ProtectedRange),
+    /// WPS=1: per-block lock bits, BP bits are ignored
+    IndividualLocks,
+}
+
+/// Block protection bit layout of a chip family
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProtectionFamily {
+    /// Winbond W25Q/GigaDevice up to 128 Mbit: BP2:0, TB, SEC, CMP
+    WinbondSec,
+    /// Winbond W25Q256 and larger: BP3:0, TB in SR1 bit 6, CMP
+    WinbondLarge,
+    /// GigaDevice GD25Q256 and larger: BP4:0, BP4 (SR1 bit 6) selects the
+    /// bottom of the array, CMP
+    GigaDeviceLarge,
+    /// Macronix MX25: BP3:0, TB in configuration register bit 3 (OTP)
+    Macronix,
+    /// ISSI IS25: BP3:0, top only (TBS lives in the OTP function register)
+    Issi,
+    /// Micron N25Q/MT25Q: BP2:0, TB in SR1 bit 5, BP3 in SR1 bit 6
+    Micron,
+    /// BP2:0 only, fractions of the array from the top (SST, Atmel, EON, ...)
+    Legacy,
+}
+
+impl ProtectionFamily {
+    /// Pick the family from the JEDEC ID
+    pub fn for_jedec_id(jedec_id: &[u8; 3], size_bytes: u32) -> Self {
+        let large = size_bytes > 16 * 1024 * 1024;
+        match jedec_id[0] {
+            manufacturers::WINBOND if large => ProtectionFamily::WinbondLarge,
+            manufacturers::GIGADEVICE if large => ProtectionFamily::GigaDeviceLarge,
+            manufacturers::WINBOND | manufacturers::GIGADEVICE => ProtectionFamily::WinbondSec,
+            manufacturers::MACRONIX => ProtectionFamily::Macronix,
+            manufacturers::ISSI => ProtectionFamily::Issi,
+            // Micron and XMC share manufacturer ID 0x20
+            manufacturers::MICRON if matches!(jedec_id[1], 0xBA | 0xBB) => ProtectionFamily::Micron,
+            manufacturers::XMC => ProtectionFamily::WinbondSec,
+            _ => ProtectionFamily::Legacy,
+        }
+    }
+
+    /// SR1 masks of the BP bits, BP0 first
+    fn bp_masks(&self) -> &'static [u8] {
+        match self {
+            ProtectionFamily::WinbondSec | ProtectionFamily::Legacy => &[0x04, 0x08, 0x10],
+            ProtectionFamily::Micron => &[0x04, 0x08, 0x10, 0x40],
+            ProtectionFamily::GigaDeviceLarge => &[0x04, 0x08, 0x10, 0x20, 0x40],
+            _ => &[0x04, 0x08, 0x10, 0x20],
+        }
+    }
+
+    fn bp(&self, sr1: u8) -> u8 {
+        self.bp_masks()
+            .iter()
+            .enumerate()
+            .fold(0, |bp, (i, &mask)| bp | (((sr1 & mask != 0) as u8) << i))
+    }
+
+    fn with_bp(&self, sr1: u8, bp: u8) -> u8 {
+        self.bp_masks()
+            .iter()
+            .enumerate()
+            .fold(sr1, |sr1, (i, &mask)| {
+                if bp & (1 << i) != 0 {
+                    sr1 | mask
+                } else {
+                    sr1 & !mask
+                }
+            })
+    }
+
+    /// SR1 mask of a writable TB bit
+    fn tb_mask(&self) -> Option<u8> {
+        match self {
+            ProtectionFamily::WinbondSec | ProtectionFamily::Micron => Some(status1::TB),
+            ProtectionFamily::WinbondLarge => Some(0x40),
+            _ => None,
+        }
+    }
+
+    fn tb(&self, regs: &StatusRegisters) -> bool {
+        match self {
+            ProtectionFamily::Macronix => regs.sr3 & 0x08 != 0,
+            ProtectionFamily::GigaDeviceLarge => self.bp(regs.sr1) & 0x10 != 0,
+            _ => self.tb_mask().is_some_and(|mask| regs.sr1 & mask != 0),
+        }
+    }
+
+    fn has_sec(&self) -> bool {
+        *self == ProtectionFamily::WinbondSec
+    }
+
+    fn has_cmp(&self) -> bool {
+        matches!(
+            self,
+            ProtectionFamily::WinbondSec
+                | ProtectionFamily::WinbondLarge
+                | ProtectionFamily::GigaDeviceLarge
+        )
+    }
+
+    fn has_wps(&self) -> bool {
+        matches!(
+            self,
+            ProtectionFamily::WinbondSec | ProtectionFamily::WinbondLarge
+        )
+    }
+
+    /// Protected length for a BP value before TB/CMP are applied
+    fn bp_len(&self, size: u64, bp: u8, sec: bool) -> u64 {
+        let len = match self {
+            ProtectionFamily::WinbondSec | ProtectionFamily::Legacy => match bp {
+                0 => 0,
+                7 => size,
+                n if sec => SECTOR_4K << (n - 1).min(3),
+                n => size >> (7 - n),
+            },
+            // BP4 only picks the end of the array
+            ProtectionFamily::GigaDeviceLarge => match bp & 0x0F {
+                0 => 0,
+                n => BLOCK_64K << (n - 1),
+            },
+            _ if bp == 0 => 0,
+            _ => BLOCK_64K << (bp - 1),
+        };
+        len.min(size)
+    }
+}
+
+// ============================================================================
+// Protection Map
+// ============================================================================
+
+/// One status register write (preceded by WRITE ENABLE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterWrite {
+    pub opcode: u8,
+    pub value: u8,
+}
+
+/// What to do before writing or erasing a range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UnprotectPlan {
+    /// The range is not protected
+    Unprotected,
+    /// Rewrite the status registers
+    WriteRegisters {
+        writes: Vec<RegisterWrite>,
+        /// SRP0 set: /WP must be held high for the writes to take effect
+        requires_wp_high: bool,
+        /// Protection left after the writes
+        remaining: Option<ProtectedRange>,
+    },
+    /// WPS=1: unlock each block/sector containing the range
+    UnlockBlocks { opcode: u8, addresses: Vec<u32> },
+    /// WPS=1 and the range covers the whole array
+    GlobalUnlock { opcode: u8 },
+}
+
+impl UnprotectPlan {
+    /// Packets carrying out the plan
+    ///
+    /// Block unlocks go out as raw transfers, each after WRITE ENABLE, with
+    /// `address_bytes` address bytes (4 once the chip is in 4-byte mode).
+    pub fn packets(&self, address_bytes: u8) -> Vec<Packet> {
+        let write_enable = || Packet::new(Command::SpiNorWriteEnable, &[]);
+        match self {
+            UnprotectPlan::Unprotected => Vec::new(),
+            UnprotectPlan::WriteRegisters { writes, .. } => writes
+                .iter()
+                .map(|write| {
+                    let command = if write.opcode == commands::WRITE_STATUS_2 {
+                        Command::SpiNorWriteStatus2
+                    } else {
+                        Command::SpiNorWriteStatus1
+                    };
+                    Packet::new(command, &[write.value])
+                })
+                .collect(),
+            UnprotectPlan::UnlockBlocks { opcode, addresses } => addresses
+                .iter()
+                .flat_map(|address| {
+                    let mut command = vec![*opcode];
+                    command.extend_from_slice(
+                        &address.to_be_bytes()[4 - address_bytes.clamp(3, 4) as usize..],
+                    );
+                    [write_enable(), SpiNorIo::transfer_packet(&command)]
+                })
+                .collect(),
+            UnprotectPlan::GlobalUnlock { opcode } => {
+                vec![write_enable(), SpiNorIo::transfer_packet(&[*opcode])]
+            }
+        }
+    }
+}
+
+/// Protection map of a specific chip
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectionMap {
+    pub family: ProtectionFamily,
+    pub size_bytes: u32,
+}
+
+impl ProtectionMap {
+    pub fn new(family: ProtectionFamily, size_bytes: u32) -> Self {
+        Self { family, size_bytes }
+    }
+
+    pub fn for_chip(info: &SpiNorChipInfo) -> Self {
+        Self::new(
+            ProtectionFamily::for_jedec_id(&info.jedec_id, info.size_bytes),
+            info.size_bytes,
+        )
+    }
+
+    /// Decode protection bits with this family's layout
+    pub fn status(&self, regs: &StatusRegisters) -> ProtectionStatus {
+        let bp = self.family.bp(regs.sr1);
+        ProtectionStatus {
+            bp0: bp & 0x01 != 0,
+            bp1: bp & 0x02 != 0,
+            bp2: bp & 0x04 != 0,
+            bp3: bp & 0x08 != 0,
+            bp4: bp & 0x10 != 0,
+            tb: self.family.tb(regs),
+            sec: self.family.has_sec() && regs.sr1 & status1::SEC != 0,
+            cmp: self.family.has_cmp() && regs.sr2 & status2::CMP != 0,
+            srp0: regs.sr1 & status1::SRP0 != 0,
+            srp1: self.family.has_cmp() && regs.sr2 & status2::SRP1 != 0,
+        }
+    }
+
+    fn range(&self, bp: u8, tb: bool, sec: bool, cmp: bool) -> Option<ProtectedRange> {
+        let size = self.size_bytes as u64;
+        let len = self.family.bp_len(size, bp, sec);
+        let tb = match self.family {
+            ProtectionFamily::GigaDeviceLarge => bp & 0x10 != 0,
+            _ => tb,
+        };
+        // Range protected by BP/TB, then inverted by CMP
+        let (start, len) = match (cmp, tb) {
+            (false, false) => (size - len, len),
+            (false, true) => (0, len),
+            (true, false) => (0, size - len),
+            (true, true) => (len, size - len),
+        };
+        (len > 0).then_some(ProtectedRange {
+            start: start as u32,
+            len: len as u32,
+        })
+    }
+
+    /// Currently protected area
+    pub fn decode(&self, regs: &StatusRegisters) -> Protection {
+        if self.family.has_wps() && regs.sr3 & status3::WPS != 0 {
+            return Protection::IndividualLocks;
+        }
+        let status = self.status(regs);
+        match self.range(self.family.bp(regs.sr1), status.tb, status.sec, status.cmp) {
+            Some(range) => Protection::Range(range),
+            None => Protection::None,
+        }
+    }
+
+    /// Human-readable protection with exact addresses
+    pub fn description(&self, regs: &StatusRegisters) -> String {
+        match self.decode(regs) {
+            Protection::None => "Unprotected".to_string(),
+            Protection::IndividualLocks => "Individual block locks (WPS=1)".to_string(),
+            Protection::Range(range) => format!(
+                "Protected 0x{:08X}-0x{:08X} ({} KB)",
+                range.start,
+                range.end() - 1,
+                range.len / 1024
+            ),
+        }
+    }
+
+    /// Lock units covering a range in WPS mode: 4 KB sectors in the first
+    /// and last 64 KB blocks, 64 KB blocks elsewhere
+    pub fn lock_units(&self, start: u32, len: u32) -> Vec<u32> {
+        let size = self.size_bytes as u64;
+        let end = (start as u64 + len as u64).min(size);
+        let mut units = Vec::new();
+        let mut addr = start as u64;
+        while addr < end {
+            let unit = if addr < BLOCK_64K || addr >= size.saturating_sub(BLOCK_64K) {
+                SECTOR_4K
+            } else {
+                BLOCK_64K
+            };
+            let base = addr - addr % unit;
+            units.push(base as u32);
+            addr = base + unit;
+        }
+        units
+    }
+
+    /// Smallest register change that leaves `start..start + len` writable
+    ///
+    /// Fewest changed bits wins, then the setting that keeps the most of the
+    /// array protected. OTP bits (Macronix TB) are never changed.
+    pub fn plan_unprotect(
+        &self,
+        regs: &StatusRegisters,
+        start: u32,
+        len: u32,
+    ) -> Result<UnprotectPlan, SpiNorError> {
+        let end = start as u64 + len as u64;
+        if end > self.size_bytes as u64 {
+            return Err(SpiNorError::InvalidAddress {
+                address: (end - 1) as u32,
+                max: self.size_bytes.saturating_sub(1),
+            });
+        }
+
+        let status = self.status(regs);
+        let current = self.decode(regs);
+        match current {
+            Protection::None => return Ok(UnprotectPlan::Unprotected),
+            Protection::Range(range) if !range.overlaps(start, len) => {
+                return Ok(UnprotectPlan::Unprotected)
+            }
+            _ => {}
+        }
+        // SRP1 set: locked until power cycle, or permanently
+        if status.srp1 {
+            return Err(SpiNorError::WriteProtected);
+        }
+
+        if current == Protection::IndividualLocks {
+            return Ok(if start == 0 && len == self.size_bytes {
+                UnprotectPlan::GlobalUnlock {
+                    opcode: commands::GLOBAL_BLOCK_UNLOCK,
+                }
+            } else {
+                UnprotectPlan::UnlockBlocks {
+                    opcode: commands::INDIVIDUAL_BLOCK_UNLOCK,
+                    addresses: self.lock_units(start, len),
+                }
+            });
+        }
+
+        let family = self.family;
+        let bp_values = 1u8 << family.bp_masks().len();
+        let toggle = |value: bool, allowed: bool| {
+            if allowed {
+                vec![value, !value]
+            } else {
+                vec![value]
+            }
+        };
+
+        let mut best: Option<(u32, u64, u8, u8, Option<ProtectedRange>)> = None;
+        for bp in 0..bp_values {
+            for &tb in &toggle(status.tb, family.tb_mask().is_some()) {
+                for &sec in &toggle(status.sec, family.has_sec()) {
+                    for &cmp in &toggle(status.cmp, family.has_cmp()) {
+                        let range = self.range(bp, tb, sec, cmp);
+                        if range.is_some_and(|r| r.overlaps(start, len)) {
+                            continue;
+                        }
+
+                        let mut sr1 = family.with_bp(regs.sr1, bp);
+                        if let Some(mask) = family.tb_mask() {
+                            sr1 = if tb { sr1 | mask } else { sr1 & !mask };
+                        }
+                        if family.has_sec() {
+                            sr1 = if sec {
+                                sr1 | status1::SEC
+                            } else {
+                                sr1 & !status1::SEC
+                            };
+                        }
+                        let sr2 = if cmp {
+                            regs.sr2 | status2::CMP
+                        } else {
+                            regs.sr2 & !status2::CMP
+                        };
+
+                        let changed = (sr1 ^ regs.sr1).count_ones() + (sr2 ^ regs.sr2).count_ones();
+                        let kept = range.map_or(0, |r| r.len as u64);
+                        let better = best.as_ref().map_or(true, |&(c, k, ..)| {
+                            changed < c || (changed == c && kept > k)
+                        });
+                        if better {
+                            best = Some((changed, kept, sr1, sr2, range));
+                        }
+                    }
+                }
+            }
+        }
+
+        // bp = 0 without CMP is always a candidate
+        let (_, _, sr1, sr2, remaining) = best.ok_or(SpiNorError::WriteProtected)?;
+        let mut writes = Vec::new();
+        if sr1 != regs.sr1 {
+            writes.push(RegisterWrite {
+                opcode: commands::WRITE_STATUS_1,
+                value: sr1,
+            });
+        }
+        if sr2 != regs.sr2 {
+            writes.push(RegisterWrite {
+                opcode: commands::WRITE_STATUS_2,
+                value: sr2,
+            });
+        }
+
+        Ok(UnprotectPlan::WriteRegisters {
+            writes,
+            requires_wp_high: status.srp0,
+            remaining,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::spi_nor::get_spi_nor_chip_info;
+
+    const MB: u32 = 1024 * 1024;
+
+    fn regs(sr1: u8, sr2: u8, sr3: u8) -> StatusRegisters {
+        StatusRegisters { sr1, sr2, sr3 }
+    }
+
+    #[test]
+    fn test_winbond_protection_ranges() {
+        // W25Q128: 16 MB
+        let map = ProtectionMap::for_chip(&get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap());
+        assert_eq!(map.family, ProtectionFamily::WinbondSec);
+
+        // BP=1: upper 256 KB
+        let range = |sr1, sr2| match map.decode(&regs(sr1, sr2, 0)) {
+            Protection::Range(r) => Some((r.start, r.len)),
+            _ => None,
+        };
+        assert_eq!(range(0x04, 0), Some((16 * MB - 256 * 1024, 256 * 1024)));
+        // BP=1, TB: lower 256 KB
+        assert_eq!(range(0x24, 0), Some((0, 256 * 1024)));
+        // BP=2, SEC: upper 8 KB
+        assert_eq!(range(0x48, 0), Some((16 * MB - 8192, 8192)));
+        // BP=1 with CMP: all but the upper 256 KB
+        assert_eq!(range(0x04, status2::CMP), Some((0, 16 * MB - 256 * 1024)));
+        // BP=7: all, with CMP: none
+        assert_eq!(range(0x1C, 0), Some((0, 16 * MB)));
+        assert_eq!(range(0x1C, status2::CMP), None);
+        assert_eq!(
+            map.description(&regs(0x04, 0, 0)),
+            "Protected 0x00FC0000-0x00FFFFFF (256 KB)"
+        );
+    }
+
+    #[test]
+    fn test_bp3_families() {
+        // W25Q256 BP=0b1001 in SR1 bits 2-5: upper 16 MB, TB in bit 6
+        let map = ProtectionMap::new(ProtectionFamily::WinbondLarge, 32 * MB);
+        let r = regs(0x24 | 0x40, 0, 0);
+        assert_eq!(
+            map.decode(&r),
+            Protection::Range(ProtectedRange {
+                start: 0,
+                len: 16 * MB
+            })
+        );
+        assert!(map.status(&r).bp3);
+
+        // MT25Q: BP3 in bit 6, TB in bit 5
+        let map = ProtectionMap::new(ProtectionFamily::Micron, 64 * MB);
+        assert_eq!(
+            map.decode(&regs(0x40 | 0x04, 0, 0)),
+            Protection::Range(ProtectedRange {
+                start: 64 * MB - 16 * MB,
+                len: 16 * MB
+            })
+        );
+
+        // GD25Q256: BP4:0 = 0b11001 protects the lower 16 MB
+        let family = ProtectionFamily::for_jedec_id(&[0xC8, 0x40, 0x19], 32 * MB);
+        assert_eq!(family, ProtectionFamily::GigaDeviceLarge);
+        let map = ProtectionMap::new(family, 32 * MB);
+        let r = regs(0x40 | 0x20 | 0x04, 0, 0);
+        let status = map.status(&r);
+        assert!(status.bp4 && status.bp3 && status.bp0 && status.tb);
+        assert_eq!(
+            map.decode(&r),
+            Protection::Range(ProtectedRange {
+                start: 0,
+                len: 16 * MB
+            })
+        );
+        // Clearing BP4 alone moves the protection to the upper 16 MB
+        assert_eq!(
+            map.plan_unprotect(&r, 0, 4096).unwrap(),
+            UnprotectPlan::WriteRegisters {
+                writes: vec![RegisterWrite {
+                    opcode: commands::WRITE_STATUS_1,
+                    value: 0x24,
+                }],
+                requires_wp_high: false,
+                remaining: Some(ProtectedRange {
+                    start: 16 * MB,
+                    len: 16 * MB
+                }),
+            }
+        );
+
+        // Macronix: TB from the configuration register
+        let map = ProtectionMap::new(ProtectionFamily::Macronix, 16 * MB);
+        assert_eq!(
+            map.decode(&regs(0x04, 0, 0x08)),
+            Protection::Range(ProtectedRange {
+                start: 0,
+                len: 64 * 1024
+            })
+        );
+    }
+
+    #[test]
+    fn test_plan_unprotect_minimal_change() {
+        let map = ProtectionMap::new(ProtectionFamily::WinbondSec, 16 * MB);
+
+        // Upper 8 MB protected (BP=6); writing the bottom needs nothing
+        let r = regs(0x18, 0, 0);
+        assert_eq!(
+            map.plan_unprotect(&r, 0, MB).unwrap(),
+            UnprotectPlan::Unprotected
+        );
+
+        // Writing the top 4 KB: one bit (TB or CMP) moves the 8 MB to the bottom
+        match map.plan_unprotect(&r, 16 * MB - 4096, 4096).unwrap() {
+            UnprotectPlan::WriteRegisters {
+                writes, remaining, ..
+            } => {
+                assert_eq!(writes.len(), 1);
+                assert_eq!(
+                    remaining,
+                    Some(ProtectedRange {
+                        start: 0,
+                        len: 8 * MB
+                    })
+                );
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        // Macronix TB is OTP: shrink the protected range instead
+        let map = ProtectionMap::new(ProtectionFamily::Macronix, 16 * MB);
+        let r = regs(0x80 | 0x0C, 0, 0);
+        match map.plan_unprotect(&r, 16 * MB - 4096 * 20, 4096).unwrap() {
+            UnprotectPlan::WriteRegisters {
+                writes,
+                requires_wp_high,
+                remaining,
+            } => {
+                assert_eq!(writes.len(), 1);
+                assert!(requires_wp_high);
+                assert_eq!(
+                    remaining,
+                    Some(ProtectedRange {
+                        start: 16 * MB - 65536,
+                        len: 65536
+                    })
+                );
+            }
+            plan => panic!("unexpected plan {:?}", plan),
+        }
+
+        // SRP1: registers locked
+        let map = ProtectionMap::new(ProtectionFamily::WinbondSec, 16 * MB);
+        assert!(matches!(
+            map.plan_unprotect(&regs(0x1C, status2::SRP1, 0), 0, 4096),
+            Err(SpiNorError::WriteProtected)
+        ));
+    }
+
+    #[test]
+    fn test_individual_block_lock_plan() {
+        let map = ProtectionMap::new(ProtectionFamily::WinbondSec, 16 * MB);
+        let r = regs(0, 0, status3::WPS);
+        assert_eq!(map.decode(&r), Protection::IndividualLocks);
+
+        // 4 KB sectors in the first block, 64 KB blocks after it
+        let plan = map.plan_unprotect(&r, 0xF000, 0x20000).unwrap();
+        assert_eq!(
+            plan,
+            UnprotectPlan::UnlockBlocks {
+                opcode: commands::INDIVIDUAL_BLOCK_UNLOCK,
+                addresses: vec![0xF000, 0x10000, 0x20000],
+            }
+        );
+        assert_eq!(
+            map.lock_units(16 * MB - 0x11000, 0x3000),
+            vec![16 * MB - 0x20000, 16 * MB - 0x10000, 16 * MB - 0xF000]
+        );
+        assert_eq!(
+            map.plan_unprotect(&r, 0, 16 * MB).unwrap(),
+            UnprotectPlan::GlobalUnlock {
+                opcode: commands::GLOBAL_BLOCK_UNLOCK
+            }
+        );
+
+        // WREN, then 39h with a 3-byte address, per unit
+        let packets = plan.packets(3);
+        assert_eq!(packets.len(), 6);
+        assert_eq!(packets[0].cmd, Command::SpiNorWriteEnable);
+        assert_eq!(packets[1].cmd, Command::SpiNorTransfer);
+        assert_eq!(packets[3].args[..5], [4, 0x39, 0x01, 0x00, 0x00]);
+        assert_eq!(
+            plan.packets(4)[1].args[..6],
+            [5, 0x39, 0x00, 0x00, 0xF0, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_unprotect_packets() {
+        let mut transfer = |packet: &Packet, _: &[u8]| -> Result<Vec<u8>, ()> {
+            Ok(match packet.cmd {
+                Command::SpiNorReadStatus1 => vec![0x9C],
+                Command::SpiNorReadStatus2 => vec![0x02],
+                _ => vec![0x00],
+            })
+        };
+        let r = StatusRegisters::read(&mut transfer).unwrap();
+        assert_eq!(r, regs(0x9C, 0x02, 0x00));
+
+        // All protected: setting CMP is the one-bit change
+        let map = ProtectionMap::new(ProtectionFamily::WinbondSec, 16 * MB);
+        let plan = map.plan_unprotect(&r, 0, 16 * MB).unwrap();
+        let packets = plan.packets(3);
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].cmd, Command::SpiNorWriteStatus2);
+        assert_eq!(packets[0].args[0], 0x02 | status2::CMP);
+
+        assert!(UnprotectPlan::Unprotected.packets(3).is_empty());
+    }
+}
@@ -1,8 +1,16 @@
 //! Advanced Write Operations for OpenFlash v1.7
 //!
 //! Provides full chip programming, bad block management, wear leveling,
-//! incremental backup/restore, and chip-to-chip cloning.
-
+//! incremental backup/restore, chip-to-chip cloning, read-retry recovery
+//! of uncorrectable pages, and diff-based SPI NOR programming.
+
+use crate::bbm::MarkerCheck;
+use crate::ecc::{decode_with_ecc_sectors, EccAlgorithm};
+use crate::onfi::NandTopology;
+use crate::pairing::{PagePairing, SlcMode};
+use crate::protocol::{Command, Packet};
+use crate::spi_nor::SpiNorChipInfo;
+use crate::spi_nor_plan::{NorErase, NorPlanOp, NorProgramPlan};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
@@ -35,6 +43,8 @@ pub enum WriteError {
     Cancelled,
     /// I/O error
     IoError(String),
+    /// Chip does not support the requested operation
+    Unsupported(String),
 }
 
 impl std::fmt::Display for WriteError {
@@ -73,6 +83,7 @@ impl std::fmt::Display for WriteError {
             }
             WriteError::Cancelled => write!(f, "Operation cancelled"),
             WriteError::IoError(e) => write!(f, "I/O error: {}", e),
+            WriteError::Unsupported(op) => write!(f, "Unsupported operation: {}", op),
         }
     }
 }
@@ -219,6 +230,18 @@ impl BadBlockTable {
             }
         }
     }
+
+    /// Record trust-but-verify results; unmarked blocks that failed keep their test failure
+    pub fn apply_marker_checks(&mut self, checks: &[MarkerCheck]) {
+        for check in checks.iter().filter(|c| c.is_bad()) {
+            let reason = if check.marked_bad {
+                BadBlockReason::Factory
+            } else {
+                check.test_failure.unwrap_or(BadBlockReason::EraseFail)
+            };
+            let _ = self.mark_bad(check.block, reason);
+        }
+    }
 }
 
 // ============================================================================
@@ -502,6 +525,8 @@ pub struct ChipProgrammer {
     wear_manager: WearLevelingManager,
     /// Programming options
     options: ProgramOptions,
+    /// Lower/upper page pairing within a block
+    pairing: PagePairing,
 }
 
 impl ChipProgrammer {
@@ -521,14 +546,48 @@ impl ChipProgrammer {
             bbt: BadBlockTable::new(total_blocks, 2), // 2% spare
             wear_manager: WearLevelingManager::new(total_blocks, max_erase_cycles),
             options: ProgramOptions::default(),
+            pairing: PagePairing::slc(pages_per_block),
         }
     }
 
+    /// Create a programmer spanning every die in a multi-die package
+    ///
+    /// Block numbers are flat across targets; `NandTopology::locate` maps
+    /// them to (ce, lun, block, page).
+    pub fn for_topology(topology: &NandTopology, max_erase_cycles: u32) -> Self {
+        Self::new(
+            topology.page_size,
+            topology.pages_per_block,
+            topology.total_blocks(),
+            topology.oob_size,
+            max_erase_cycles,
+        )
+    }
+
     /// Set programming options
     pub fn set_options(&mut self, options: ProgramOptions) {
         self.options = options;
     }
 
+    /// Set the MLC/TLC page pairing (defaults to SLC)
+    pub fn set_pairing(&mut self, pairing: PagePairing) {
+        self.pairing = pairing;
+    }
+
+    pub fn pairing(&self) -> &PagePairing {
+        &self.pairing
+    }
+
+    /// Pages to verify after programming `page`
+    ///
+    /// A failed upper-page program can corrupt the lower pages already
+    /// written on the same wordline, so those are re-checked too.
+    pub fn pages_to_verify(&self, page: u32) -> Vec<u32> {
+        let mut pages = self.pairing.earlier_paired_pages(page);
+        pages.push(page);
+        pages
+    }
+
     /// Get bad block table reference
     pub fn bad_block_table(&self) -> &BadBlockTable {
         &self.bbt
@@ -609,6 +668,36 @@ impl ChipProgrammer {
         Ok(())
     }
 
+    /// Verify `page` after programming it, re-reading its earlier paired pages
+    ///
+    /// `block_data` is the image written to the block, one page after
+    /// another; `read` returns the main area of a page in `block`.
+    pub fn verify_programmed_page<R>(
+        &self,
+        block: u32,
+        page: u32,
+        block_data: &[u8],
+        mut read: R,
+    ) -> WriteResult<()>
+    where
+        R: FnMut(u32, u32) -> WriteResult<Vec<u8>>,
+    {
+        let page_size = self.page_size as usize;
+        for p in self.pages_to_verify(page) {
+            let start = p as usize * page_size;
+            let expected =
+                block_data
+                    .get(start..start + page_size)
+                    .ok_or(WriteError::DataSizeMismatch {
+                        expected: start + page_size,
+                        actual: block_data.len(),
+                    })?;
+            let actual = read(block, p)?;
+            self.verify_page(expected, &actual, block, p)?;
+        }
+        Ok(())
+    }
+
     /// Get chip capacity in bytes
     pub fn capacity(&self) -> u64 {
         self.total_blocks as u64 * self.pages_per_block as u64 * self.page_size as u64
@@ -621,6 +710,463 @@ impl ChipProgrammer {
     }
 }
 
+// ============================================================================
+// Smart NOR Programming
+// ============================================================================
+
+/// Outcome of running a `NorProgramPlan`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NorPlanReport {
+    pub erases: u32,
+    pub bytes_erased: u64,
+    pub pages_programmed: u32,
+    pub bytes_verified: u64,
+    /// Pages re-programmed after a verify mismatch
+    pub verify_retries: u32,
+}
+
+impl ChipProgrammer {
+    /// Create a programmer for a SPI NOR chip: sectors as blocks, no OOB
+    pub fn for_spi_nor(info: &SpiNorChipInfo) -> Self {
+        Self::new(
+            info.page_size,
+            info.sector_size / info.page_size,
+            info.size_bytes / info.sector_size,
+            0,
+            100_000,
+        )
+    }
+
+    /// Run a diff-based NOR plan
+    ///
+    /// `erase` and `program` get the plan's operations in address order.
+    /// With `verify` set, the plan's verify ranges are read back through
+    /// `read`; a mismatching page is re-programmed up to `retry_count`
+    /// times, while a non-blank byte in an erased, unprogrammed page fails
+    /// the erase.
+    pub fn execute_nor_plan<E, P, R>(
+        &mut self,
+        plan: &NorProgramPlan,
+        image: &[u8],
+        mut erase: E,
+        mut program: P,
+        mut read: R,
+    ) -> WriteResult<NorPlanReport>
+    where
+        E: FnMut(&NorErase) -> WriteResult<()>,
+        P: FnMut(u32, &[u8]) -> WriteResult<()>,
+        R: FnMut(u32, usize) -> WriteResult<Vec<u8>>,
+    {
+        let base = plan.base_address;
+        let (page_size, pages_per_block) = (self.page_size, self.pages_per_block);
+        let image_range = |address: u32, len: u32| -> WriteResult<std::ops::Range<usize>> {
+            let start = address.checked_sub(base).map(|o| o as usize);
+            match start {
+                Some(start) if start + len as usize <= image.len() => {
+                    Ok(start..start + len as usize)
+                }
+                _ => Err(WriteError::InvalidAddress {
+                    block: address / page_size / pages_per_block,
+                    page: address / page_size % pages_per_block,
+                }),
+            }
+        };
+
+        let mut report = NorPlanReport::default();
+        let mut programmed = HashSet::new();
+        for op in &plan.ops {
+            match *op {
+                NorPlanOp::Erase(ref e) => {
+                    erase(e)?;
+                    if self.options.wear_leveling {
+                        let sector = self.page_size * self.pages_per_block;
+                        for address in (e.address..e.address + e.size).step_by(sector as usize) {
+                            self.wear_manager.record_erase(address / sector)?;
+                        }
+                    }
+                    report.erases += 1;
+                    report.bytes_erased += e.size as u64;
+                }
+                NorPlanOp::Program { address, len } => {
+                    program(address, &image[image_range(address, len)?])?;
+                    programmed.insert(address);
+                    report.pages_programmed += 1;
+                }
+            }
+        }
+
+        if !self.options.verify {
+            return Ok(report);
+        }
+
+        for &(start, len) in &plan.verify {
+            for address in (start..start + len).step_by(page_size as usize) {
+                let chunk = page_size.min(start + len - address);
+                let expected = &image[image_range(address, chunk)?];
+                let mut attempts = 0;
+                loop {
+                    let actual = read(address, chunk as usize)?;
+                    let (block, page) = self.address_to_block_page(address as u64);
+                    match self.verify_page(expected, &actual, block, page) {
+                        Ok(()) => break,
+                        Err(WriteError::VerifyFailed { .. }) if !programmed.contains(&address) => {
+                            return Err(WriteError::EraseFailed(block));
+                        }
+                        Err(err) if attempts >= self.options.retry_count => return Err(err),
+                        Err(_) => {
+                            attempts += 1;
+                            report.verify_retries += 1;
+                            program(address, expected)?;
+                        }
+                    }
+                }
+                report.bytes_verified += chunk as u64;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+// ============================================================================
+// Read Retry Recovery
+// ============================================================================
+
+/// ONFI feature address holding the read-retry level
+pub const ONFI_FEATURE_READ_RETRY: u8 = 0x89;
+
+/// Toshiba 19nm/A19nm read-retry register values (registers 0x04-0x07)
+const TOSHIBA_RETRY_TABLE: [[u8; 4]; 7] = [
+    [0x04, 0x04, 0x7C, 0x7E],
+    [0x00, 0x7C, 0x78, 0x78],
+    [0x7C, 0x76, 0x74, 0x72],
+    [0x08, 0x08, 0x00, 0x00],
+    [0x0B, 0x7E, 0x76, 0x74],
+    [0x10, 0x76, 0x72, 0x70],
+    [0x02, 0x00, 0x7E, 0x7C],
+];
+
+/// Single low-level bus operation needed to change the read-retry level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RetryOp {
+    /// Parallel NAND command cycle
+    Command(u8),
+    /// Parallel NAND address cycle
+    Address(u8),
+    /// Parallel NAND data-in cycle
+    Data(u8),
+    /// SPI NAND SET FEATURE
+    SpiNandSetFeature { register: u8, value: u8 },
+}
+
+impl RetryOp {
+    /// Encode as a firmware packet
+    pub fn to_packet(&self) -> Packet {
+        match *self {
+            RetryOp::Command(cmd) => Packet::new(Command::NandCmd, &[cmd]),
+            RetryOp::Address(addr) => Packet::new(Command::NandAddr, &[addr]),
+            RetryOp::Data(data) => Packet::new(Command::NandWriteData, &[data]),
+            RetryOp::SpiNandSetFeature { register, value } => {
+                Packet::new(Command::SpiNandSetFeature, &[register, value])
+            }
+        }
+    }
+}
+
+/// Read-retry mechanism supported by the chip
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadRetryMethod {
+    /// No read-retry support: only repeated reads are used
+    None,
+    /// ONFI SET FEATURES on feature 0x89 (also used by Micron)
+    OnfiSetFeatures { levels: u8 },
+    /// Hynix vendor sequence: per-level register values, usually from the OTP table
+    Hynix {
+        registers: Vec<u8>,
+        table: Vec<Vec<u8>>,
+    },
+    /// Toshiba/Kioxia vendor sequence (0x55 register writes + 0x26/0x5D)
+    Toshiba,
+    /// SPI NAND vendor feature register taking one value per level
+    SpiNandFeature { register: u8, values: Vec<u8> },
+}
+
+impl ReadRetryMethod {
+    /// Highest retry level (level 0 is the default read threshold)
+    pub fn max_level(&self) -> u8 {
+        match self {
+            ReadRetryMethod::None => 0,
+            ReadRetryMethod::OnfiSetFeatures { levels } => levels.saturating_sub(1),
+            ReadRetryMethod::Hynix { table, .. } => table.len().saturating_sub(1) as u8,
+            ReadRetryMethod::Toshiba => TOSHIBA_RETRY_TABLE.len() as u8,
+            ReadRetryMethod::SpiNandFeature { values, .. } => values.len().saturating_sub(1) as u8,
+        }
+    }
+
+    /// Bus operations that switch the chip to `level`
+    pub fn level_ops(&self, level: u8) -> Vec<RetryOp> {
+        match self {
+            ReadRetryMethod::None => Vec::new(),
+            ReadRetryMethod::OnfiSetFeatures { .. } => vec![
+                RetryOp::Command(0xEF),
+                RetryOp::Address(ONFI_FEATURE_READ_RETRY),
+                RetryOp::Data(level),
+                RetryOp::Data(0),
+                RetryOp::Data(0),
+                RetryOp::Data(0),
+            ],
+            ReadRetryMethod::Hynix { registers, table } => {
+                let Some(values) = table.get(level as usize) else {
+                    return Vec::new();
+                };
+                let mut ops = vec![RetryOp::Command(0x36)];
+                for (&reg, &value) in registers.iter().zip(values.iter()) {
+                    ops.push(RetryOp::Address(reg));
+                    ops.push(RetryOp::Data(value));
+                }
+                ops.push(RetryOp::Command(0x16));
+                ops
+            }
+            ReadRetryMethod::Toshiba => {
+                if level == 0 {
+                    // Reset restores the default read thresholds
+                    return vec![RetryOp::Command(0xFF)];
+                }
+                let Some(values) = TOSHIBA_RETRY_TABLE.get(level as usize - 1) else {
+                    return Vec::new();
+                };
+                let mut ops = Vec::new();
+                if level == 1 {
+                    ops.push(RetryOp::Command(0x5C));
+                    ops.push(RetryOp::Command(0xC5));
+                }
+                for (reg, &value) in (0x04u8..).zip(values.iter()) {
+                    ops.push(RetryOp::Command(0x55));
+                    ops.push(RetryOp::Address(reg));
+                    ops.push(RetryOp::Data(value));
+                }
+                ops.push(RetryOp::Command(0x26));
+                ops.push(RetryOp::Command(0x5D));
+                ops
+            }
+            ReadRetryMethod::SpiNandFeature { register, values } => values
+                .get(level as usize)
+                .map(|&value| {
+                    vec![RetryOp::SpiNandSetFeature {
+                        register: *register,
+                        value,
+                    }]
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Read recovery options
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryOptions {
+    /// Read-retry mechanism to step through
+    pub method: ReadRetryMethod,
+    /// Reads per retry level used for majority voting
+    pub reads_per_level: u8,
+    /// ECC used to judge whether a voted page is recovered
+    pub ecc_algorithm: EccAlgorithm,
+}
+
+impl Default for RecoveryOptions {
+    fn default() -> Self {
+        Self {
+            method: ReadRetryMethod::None,
+            reads_per_level: 5,
+            ecc_algorithm: EccAlgorithm::Bch { t: 8 },
+        }
+    }
+}
+
+/// Outcome of a page recovery attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageRecovery {
+    pub block: u32,
+    pub page: u32,
+    /// Best page data found (ECC corrected when recovered)
+    pub data: Vec<u8>,
+    /// Voted OOB bytes
+    pub oob: Vec<u8>,
+    /// Whether every sector decoded after voting
+    pub recovered: bool,
+    /// Retry level that produced `data`
+    pub retry_level: u8,
+    /// Total page reads issued
+    pub reads: u32,
+    /// Bits corrected by ECC after voting
+    pub corrected_bits: u32,
+    /// Bits that differed between reads at the final level
+    pub unstable_bits: u32,
+}
+
+/// Majority-vote each bit across repeated reads of the same page
+///
+/// Returns the voted data and the number of bits that were not unanimous.
+pub fn majority_vote(reads: &[Vec<u8>]) -> (Vec<u8>, u32) {
+    let len = reads.iter().map(|r| r.len()).min().unwrap_or(0);
+    let mut voted = vec![0u8; len];
+    let mut unstable_bits = 0u32;
+
+    for (i, out) in voted.iter_mut().enumerate() {
+        for bit in 0..8 {
+            let ones = reads.iter().filter(|r| r[i] & (1 << bit) != 0).count();
+            if ones * 2 > reads.len() {
+                *out |= 1 << bit;
+            }
+            if ones != 0 && ones != reads.len() {
+                unstable_bits += 1;
+            }
+        }
+    }
+
+    (voted, unstable_bits)
+}
+
+impl ChipProgrammer {
+    /// Read a page of a pseudo-SLC block through the vendor SLC-mode commands
+    ///
+    /// `apply_ops` receives the mode entry, per-read prefix and exit
+    /// sequences; the chip is returned to normal mode before this returns.
+    pub fn read_slc_page<A, R>(
+        &self,
+        block: u32,
+        page: u32,
+        mode: &SlcMode,
+        mut apply_ops: A,
+        mut read_page: R,
+    ) -> WriteResult<Vec<u8>>
+    where
+        A: FnMut(&[RetryOp]) -> WriteResult<()>,
+        R: FnMut(u32, u32) -> WriteResult<Vec<u8>>,
+    {
+        if block >= self.total_blocks || page >= self.pages_per_block {
+            return Err(WriteError::InvalidAddress { block, page });
+        }
+        if *mode == SlcMode::Unsupported {
+            return Err(WriteError::Unsupported("SLC-mode read".into()));
+        }
+
+        let enter = mode.enter_ops();
+        if !enter.is_empty() {
+            apply_ops(&enter)?;
+        }
+        let prefix = mode.read_prefix_ops();
+        let result = if prefix.is_empty() {
+            read_page(block, page)
+        } else {
+            apply_ops(&prefix).and_then(|_| read_page(block, page))
+        };
+        let exit = mode.exit_ops();
+        if !exit.is_empty() {
+            apply_ops(&exit)?;
+        }
+        result
+    }
+
+    /// Try to recover an uncorrectable page by re-reading and read-retry
+    ///
+    /// For each retry level, `apply_ops` receives the bus operations that
+    /// select the level and `read_page` returns one raw page+OOB read. Reads
+    /// are majority-voted and fed back into ECC; the chip is returned to
+    /// level 0 before this returns.
+    pub fn recover_page<A, R>(
+        &self,
+        block: u32,
+        page: u32,
+        options: &RecoveryOptions,
+        mut apply_ops: A,
+        mut read_page: R,
+    ) -> WriteResult<PageRecovery>
+    where
+        A: FnMut(&[RetryOp]) -> WriteResult<()>,
+        R: FnMut(u32, u32) -> WriteResult<Vec<u8>>,
+    {
+        if block >= self.total_blocks || page >= self.pages_per_block {
+            return Err(WriteError::InvalidAddress { block, page });
+        }
+
+        let page_size = self.page_size as usize;
+        let raw_size = page_size + self.oob_size as usize;
+        let reads_per_level = options.reads_per_level.max(1);
+        let mut best: Option<PageRecovery> = None;
+        let mut reads = 0u32;
+
+        // Every exit path below falls through to the level 0 reset
+        let outcome = (|| -> WriteResult<()> {
+            for level in 0..=options.method.max_level() {
+                if level > 0 {
+                    apply_ops(&options.method.level_ops(level))?;
+                }
+
+                let mut samples = Vec::with_capacity(reads_per_level as usize);
+                for _ in 0..reads_per_level {
+                    let raw = read_page(block, page)?;
+                    if raw.len() < raw_size {
+                        return Err(WriteError::DataSizeMismatch {
+                            expected: raw_size,
+                            actual: raw.len(),
+                        });
+                    }
+                    samples.push(raw);
+                    reads += 1;
+                }
+
+                let (voted, unstable_bits) = majority_vote(&samples);
+                let mut data = voted[..page_size].to_vec();
+                let oob = voted[page_size..raw_size].to_vec();
+                let sectors = decode_with_ecc_sectors(
+                    &mut data,
+                    &oob,
+                    &options.ecc_algorithm,
+                    options.ecc_algorithm.strength(),
+                );
+                let failed = sectors.iter().filter(|s| s.uncorrectable).count();
+
+                let attempt = PageRecovery {
+                    block,
+                    page,
+                    data,
+                    oob,
+                    recovered: failed == 0,
+                    retry_level: level,
+                    reads,
+                    corrected_bits: sectors.iter().map(|s| s.corrected_bits).sum(),
+                    unstable_bits,
+                };
+
+                let better = best.as_ref().map_or(true, |b| {
+                    attempt.recovered || attempt.unstable_bits < b.unstable_bits
+                });
+                let recovered = attempt.recovered;
+                if better {
+                    best = Some(attempt);
+                }
+                if recovered {
+                    break;
+                }
+            }
+            Ok(())
+        })();
+
+        let reset = if options.method.max_level() > 0 {
+            apply_ops(&options.method.level_ops(0))
+        } else {
+            Ok(())
+        };
+        outcome?;
+        reset?;
+
+        let mut result = best.expect("at least one retry level is always attempted");
+        result.reads = reads;
+        Ok(result)
+    }
+}
+
 // ============================================================================
 // Incremental Backup/Restore
 // ============================================================================
@@ -1022,6 +1568,24 @@ mod tests {
         assert_eq!(bbt.translate(50), replacement);
     }
 
+    #[test]
+    fn test_apply_marker_checks() {
+        let mut bbt = BadBlockTable::new(1024, 2);
+        let check = |block, marked_bad, test_failure| MarkerCheck {
+            block,
+            marked_bad,
+            test_failure,
+        };
+        bbt.apply_marker_checks(&[
+            check(1, false, None),
+            check(2, true, None),
+            check(3, false, Some(BadBlockReason::ProgramFail)),
+        ]);
+        assert!(!bbt.is_bad(1));
+        assert_eq!(bbt.entries[&2].reason, BadBlockReason::Factory);
+        assert_eq!(bbt.entries[&3].reason, BadBlockReason::ProgramFail);
+    }
+
     #[test]
     fn test_wear_leveling_manager() {
         let mut wm = WearLevelingManager::new(1024, 100000);
@@ -1212,4 +1776,297 @@ mod tests {
             })
         ));
     }
+
+    #[test]
+    fn test_majority_vote() {
+        let reads = vec![vec![0xF0, 0x00], vec![0xF1, 0x00], vec![0xF0, 0x80]];
+        let (voted, unstable) = majority_vote(&reads);
+        assert_eq!(voted, vec![0xF0, 0x00]);
+        assert_eq!(unstable, 2);
+    }
+
+    #[test]
+    fn test_onfi_read_retry_ops() {
+        let method = ReadRetryMethod::OnfiSetFeatures { levels: 8 };
+        assert_eq!(method.max_level(), 7);
+
+        let ops = method.level_ops(3);
+        assert_eq!(ops[0], RetryOp::Command(0xEF));
+        assert_eq!(ops[1], RetryOp::Address(ONFI_FEATURE_READ_RETRY));
+        assert_eq!(ops[2], RetryOp::Data(3));
+        assert_eq!(ops.len(), 6);
+
+        let packet = ops[2].to_packet();
+        assert_eq!(packet.cmd, Command::NandWriteData);
+        assert_eq!(packet.args[0], 3);
+    }
+
+    #[test]
+    fn test_vendor_read_retry_ops() {
+        let toshiba = ReadRetryMethod::Toshiba;
+        assert_eq!(toshiba.level_ops(0), vec![RetryOp::Command(0xFF)]);
+        let ops = toshiba.level_ops(1);
+        assert_eq!(ops[0], RetryOp::Command(0x5C));
+        assert_eq!(
+            ops[ops.len() - 2..],
+            [RetryOp::Command(0x26), RetryOp::Command(0x5D)]
+        );
+
+        let hynix = ReadRetryMethod::Hynix {
+            registers: vec![0xCC, 0xBF],
+            table: vec![vec![0x00, 0x00], vec![0x0A, 0x05]],
+        };
+        assert_eq!(hynix.max_level(), 1);
+        assert_eq!(
+            hynix.level_ops(1),
+            vec![
+                RetryOp::Command(0x36),
+                RetryOp::Address(0xCC),
+                RetryOp::Data(0x0A),
+                RetryOp::Address(0xBF),
+                RetryOp::Data(0x05),
+                RetryOp::Command(0x16),
+            ]
+        );
+
+        let spi = ReadRetryMethod::SpiNandFeature {
+            register: 0x70,
+            values: vec![0x00, 0x10, 0x20],
+        };
+        assert_eq!(
+            spi.level_ops(2),
+            vec![RetryOp::SpiNandSetFeature {
+                register: 0x70,
+                value: 0x20
+            }]
+        );
+    }
+
+    #[test]
+    fn test_recover_page_with_read_retry() {
+        use std::cell::Cell;
+
+        let programmer = ChipProgrammer::new(512, 64, 16, 16, 100000);
+        let options = RecoveryOptions {
+            method: ReadRetryMethod::OnfiSetFeatures { levels: 4 },
+            reads_per_level: 3,
+            ecc_algorithm: EccAlgorithm::Bch { t: 4 },
+        };
+
+        let level = Cell::new(0u8);
+        let read_count = Cell::new(0usize);
+        let result = programmer
+            .recover_page(
+                2,
+                5,
+                &options,
+                |ops| {
+                    if let Some(RetryOp::Data(l)) = ops.get(2) {
+                        level.set(*l);
+                    }
+                    Ok(())
+                },
+                |_, _| {
+                    let mut raw = vec![0xFFu8; 528];
+                    let n = read_count.get();
+                    read_count.set(n + 1);
+                    if level.get() < 2 {
+                        // Wrong read threshold: many stuck bits
+                        for byte in raw.iter_mut().take(40).step_by(4) {
+                            *byte = 0x00;
+                        }
+                    } else {
+                        // One transient flip per read, voted away
+                        raw[n % 512] = 0xFE;
+                    }
+                    Ok(raw)
+                },
+            )
+            .unwrap();
+
+        assert!(result.recovered);
+        assert_eq!(result.retry_level, 2);
+        assert_eq!(result.reads, 9);
+        assert!(result.data.iter().all(|&b| b == 0xFF));
+        assert_eq!(level.get(), 0);
+    }
+
+    #[test]
+    fn test_recover_page_resets_level_on_error() {
+        use std::cell::Cell;
+
+        let programmer = ChipProgrammer::new(512, 64, 16, 16, 100000);
+        let options = RecoveryOptions {
+            method: ReadRetryMethod::OnfiSetFeatures { levels: 4 },
+            reads_per_level: 1,
+            ecc_algorithm: EccAlgorithm::Bch { t: 4 },
+        };
+
+        let level = Cell::new(0u8);
+        let result = programmer.recover_page(
+            2,
+            5,
+            &options,
+            |ops| {
+                if let Some(RetryOp::Data(l)) = ops.get(2) {
+                    level.set(*l);
+                }
+                Ok(())
+            },
+            |_, _| {
+                if level.get() == 1 {
+                    // Short read at a shifted threshold
+                    Ok(vec![0x00; 100])
+                } else {
+                    let mut raw = vec![0xFFu8; 528];
+                    for byte in raw.iter_mut().take(40).step_by(4) {
+                        *byte = 0x00;
+                    }
+                    Ok(raw)
+                }
+            },
+        );
+
+        assert!(matches!(result, Err(WriteError::DataSizeMismatch { .. })));
+        assert_eq!(level.get(), 0);
+    }
+
+    #[test]
+    fn test_recover_page_invalid_address() {
+        let programmer = ChipProgrammer::new(512, 64, 16, 16, 100000);
+        let result = programmer.recover_page(
+            16,
+            0,
+            &RecoveryOptions::default(),
+            |_| Ok(()),
+            |_, _| Ok(vec![0xFF; 528]),
+        );
+        assert_eq!(
+            result.unwrap_err(),
+            WriteError::InvalidAddress { block: 16, page: 0 }
+        );
+    }
+
+    #[test]
+    fn test_pages_to_verify_with_pairing() {
+        let mut programmer = ChipProgrammer::new(2048, 128, 16, 64, 3000);
+        assert_eq!(programmer.pages_to_verify(6), vec![6]);
+
+        programmer.set_pairing(PagePairing::staircase(2, 1, 128).unwrap());
+        // Upper page 6 shares a wordline with lower page 3
+        assert_eq!(programmer.pages_to_verify(6), vec![3, 6]);
+        assert_eq!(programmer.pages_to_verify(3), vec![3]);
+    }
+
+    #[test]
+    fn test_verify_programmed_page_rechecks_lower_page() {
+        let mut programmer = ChipProgrammer::new(16, 8, 4, 0, 3000);
+        programmer.set_pairing(PagePairing::staircase(2, 1, 8).unwrap());
+        let block_data: Vec<u8> = (0..8).flat_map(|p| vec![p as u8; 16]).collect();
+
+        let mut reads = Vec::new();
+        let result = programmer.verify_programmed_page(1, 6, &block_data, |block, page| {
+            reads.push((block, page));
+            Ok(vec![page as u8; 16])
+        });
+        assert!(result.is_ok());
+        assert_eq!(reads, vec![(1, 3), (1, 6)]);
+
+        // Programming upper page 6 disturbed lower page 3
+        let result = programmer.verify_programmed_page(1, 6, &block_data, |_, page| {
+            Ok(vec![if page == 3 { 0xFF } else { page as u8 }; 16])
+        });
+        assert!(matches!(
+            result,
+            Err(WriteError::VerifyFailed {
+                block: 1,
+                page: 3,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_read_slc_page() {
+        let programmer = ChipProgrammer::new(2048, 128, 16, 64, 3000);
+        let mode = SlcMode::Command {
+            enter: 0xDA,
+            exit: 0xDF,
+        };
+
+        let mut log = Vec::new();
+        let data = programmer
+            .read_slc_page(
+                1,
+                0,
+                &mode,
+                |ops| {
+                    log.extend_from_slice(ops);
+                    Ok(())
+                },
+                |_, _| Ok(vec![0x5A; 2048]),
+            )
+            .unwrap();
+        assert_eq!(data.len(), 2048);
+        assert_eq!(log, vec![RetryOp::Command(0xDA), RetryOp::Command(0xDF)]);
+
+        let result = programmer.read_slc_page(
+            1,
+            0,
+            &SlcMode::Unsupported,
+            |_| Ok(()),
+            |_, _| Ok(Vec::new()),
+        );
+        assert!(matches!(result, Err(WriteError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_execute_nor_plan() {
+        use crate::spi_nor_plan::{NorContents, NorGeometry, NorPlanner};
+        use std::cell::RefCell;
+
+        let chip = crate::spi_nor::get_spi_nor_chip_info(&[0xEF, 0x40, 0x18]).unwrap();
+        let mut programmer = ChipProgrammer::for_spi_nor(&chip);
+        let planner = NorPlanner::new(NorGeometry::for_chip(&chip));
+
+        let current = vec![0x00u8; 0x20000];
+        let mut image = current.clone();
+        image[0x1000..0x2000].fill(0xA5);
+        let plan = planner
+            .plan(0, &image, NorContents::Data(&current))
+            .unwrap();
+
+        // Simulated flash; the first program of each page is lost
+        let flash = RefCell::new(current);
+        let mut dropped = HashSet::new();
+        let report = programmer
+            .execute_nor_plan(
+                &plan,
+                &image,
+                |erase| {
+                    let start = erase.address as usize;
+                    flash.borrow_mut()[start..start + erase.size as usize].fill(0xFF);
+                    Ok(())
+                },
+                |address, data| {
+                    if dropped.insert(address) {
+                        return Ok(());
+                    }
+                    let start = address as usize;
+                    for (cell, &byte) in flash.borrow_mut()[start..].iter_mut().zip(data) {
+                        *cell &= byte;
+                    }
+                    Ok(())
+                },
+                |address, len| Ok(flash.borrow()[address as usize..][..len].to_vec()),
+            )
+            .unwrap();
+
+        assert_eq!(*flash.borrow(), image);
+        assert_eq!(report.erases, 1);
+        assert_eq!(report.bytes_erased, 4096);
+        assert_eq!(report.pages_programmed, 16);
+        assert_eq!(report.verify_retries, 16);
+        assert_eq!(report.bytes_verified, 4096);
+    }
 }
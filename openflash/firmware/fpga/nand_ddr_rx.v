@@ -0,0 +1,110 @@
+// DQS-strobed DDR data capture for NV-DDR/NV-DDR2/NV-DDR3 and Toggle NAND
+//
+// The chip drives DQS edge-aligned with DQ, so DQ has to be sampled roughly
+// a quarter cycle after each DQS edge. The delay comes from the DQS_DELAY
+// register and is trained by the host (FpgaBackend::train_dqs). Each DQS
+// cycle yields one 16-bit word {falling, rising}.
+//
+// ECP5 delays DQS through a DELAYF element (128 taps of ~25 ps) and clocks
+// the capture registers on the delayed strobe; a toggle moves each word
+// into the clk domain. iCE40 has no input delay element, so DQ and DQS are
+// oversampled on clk, which must run at 4x the DQS frequency: a DQS edge
+// is detected in the samples and DQ is taken dqs_delay[1:0] + 1 clk cycles
+// (90 degrees each) after the edge sample. That limits iCE40 builds to
+// NV-DDR at 100 MT/s.
+//
+// Standalone block: no top level instantiates it yet (see the FPGA Backend
+// section of docs/HARDWARE_GUIDE.md for the scope).
+
+module nand_ddr_rx (
+    input  wire        clk,
+    input  wire        rst,
+    input  wire [7:0]  dqs_delay,
+    input  wire        read_en,      // RE# toggling, DQS valid after preamble
+
+    input  wire        dqs,
+    input  wire [7:0]  dq,
+
+    output reg         word_valid,
+    output reg  [15:0] word
+);
+`ifdef ECP5
+    wire dqs_d;
+    reg [7:0] applied_delay;
+    reg       delay_move;
+    reg       delay_dir;
+
+    DELAYF #(.DEL_MODE("USER_DEFINED"), .DEL_VALUE(0)) dqs_delay_line (
+        .A(dqs), .LOADN(~rst), .MOVE(delay_move), .DIRECTION(delay_dir),
+        .Z(dqs_d), .CFLAG()
+    );
+
+    // Step the delay line one tap per cycle towards the requested tap
+    always @(posedge clk) begin
+        delay_move <= 1'b0;
+        if (rst) begin
+            applied_delay <= 8'd0;
+        end else if (!delay_move && applied_delay != dqs_delay) begin
+            delay_move <= 1'b1;
+            delay_dir  <= applied_delay > dqs_delay;
+            applied_delay <= applied_delay > dqs_delay ? applied_delay - 8'd1 : applied_delay + 8'd1;
+        end
+    end
+
+    reg [7:0] rise_data;
+    reg [7:0] fall_data;
+    reg       fall_toggle;
+
+    always @(posedge dqs_d) rise_data <= dq;
+
+    always @(negedge dqs_d or posedge rst) begin
+        if (rst) begin
+            fall_toggle <= 1'b0;
+        end else begin
+            fall_data   <= dq;
+            fall_toggle <= ~fall_toggle;
+        end
+    end
+
+    // Falling edge completes a word; synchronize its toggle into clk
+    reg [2:0] toggle_sync;
+    always @(posedge clk) begin
+        toggle_sync <= {toggle_sync[1:0], fall_toggle};
+        word_valid  <= read_en && (toggle_sync[2] != toggle_sync[1]);
+        word        <= {fall_data, rise_data};
+    end
+`else
+    // Register DQ and DQS together, then find DQS edges in the samples
+    reg       dqs_r, dqs_p;
+    reg [7:0] dq_r;
+    reg [3:0] rise_sr;
+    reg [3:0] fall_sr;
+    reg [7:0] rise_data;
+
+    wire [1:0] phase = dqs_delay[1:0];
+
+    always @(posedge clk) begin
+        dqs_r <= dqs;
+        dq_r  <= dq;
+        dqs_p <= dqs_r;
+
+        if (rst) begin
+            rise_sr    <= 4'd0;
+            fall_sr    <= 4'd0;
+            word_valid <= 1'b0;
+        end else begin
+            // Bit n set: dq_r is n + 1 samples after the edge
+            rise_sr <= {rise_sr[2:0], dqs_r & ~dqs_p};
+            fall_sr <= {fall_sr[2:0], ~dqs_r & dqs_p};
+
+            word_valid <= 1'b0;
+            if (rise_sr[phase])
+                rise_data <= dq_r;
+            if (fall_sr[phase]) begin
+                word       <= {dq_r, rise_data};
+                word_valid <= read_en;
+            end
+        end
+    end
+`endif
+endmodule
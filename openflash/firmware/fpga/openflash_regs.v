@@ -0,0 +1,75 @@
+// OpenFlash FPGA gateware register file
+//
+// Expects packets already split into command and argument bytes by a
+// USB/UART bridge. The bridge, the top level and the NAND engine are out
+// of scope for this gateware; the host driver is tested against
+// GatewareModel in core/src/fpga.rs instead. FpgaReadReg (0x1E)
+// returns a 32-bit little-endian value, FpgaWriteReg (0x1F) writes one. The
+// register map matches core/src/fpga.rs.
+
+module openflash_regs #(
+    parameter [7:0]  FAMILY    = 8'd1,      // 0 = iCE40, 1 = ECP5
+    parameter [15:0] VERSION   = 16'h0100,
+    parameter [3:0]  IFACES    = 4'b1111,   // Toggle, NV-DDR3, NV-DDR2, NV-DDR
+    parameter [7:0]  DQS_TAPS  = 8'd128,    // ECP5 DELAYF; 4 on iCE40
+    parameter [15:0] MAX_RATE  = 16'd800    // MT/s; 100 on iCE40
+) (
+    input  wire        clk,
+    input  wire        rst,
+
+    input  wire        pkt_valid,
+    input  wire [7:0]  pkt_cmd,
+    input  wire [7:0]  pkt_reg,
+    input  wire [31:0] pkt_value,
+
+    output reg         rsp_valid,
+    output reg  [31:0] rsp_data,
+
+    output reg  [7:0]  interface_sel,  // DataInterface
+    output reg  [7:0]  timing_mode,
+    output reg  [15:0] data_rate,
+    output reg  [7:0]  dqs_delay
+);
+    localparam CMD_READ_REG  = 8'h1E;
+    localparam CMD_WRITE_REG = 8'h1F;
+
+    localparam REG_ID        = 8'h00;
+    localparam REG_VERSION   = 8'h01;
+    localparam REG_CAPS      = 8'h02;
+    localparam REG_INTERFACE = 8'h03;
+    localparam REG_DATA_RATE = 8'h04;
+    localparam REG_DQS_DELAY = 8'h05;
+
+    localparam [31:0] GATEWARE_ID = 32'h4F465047;  // "OFPG"
+
+    always @(posedge clk) begin
+        rsp_valid <= 1'b0;
+        if (rst) begin
+            interface_sel <= 8'd0;
+            timing_mode   <= 8'd0;
+            data_rate     <= 16'd10;
+            dqs_delay     <= 8'd0;
+        end else if (pkt_valid && pkt_cmd == CMD_READ_REG) begin
+            rsp_valid <= 1'b1;
+            case (pkt_reg)
+                REG_ID:        rsp_data <= GATEWARE_ID;
+                REG_VERSION:   rsp_data <= {FAMILY, 8'd0, VERSION};
+                REG_CAPS:      rsp_data <= {MAX_RATE, DQS_TAPS, 4'd0, IFACES};
+                REG_INTERFACE: rsp_data <= {16'd0, timing_mode, interface_sel};
+                REG_DATA_RATE: rsp_data <= {16'd0, data_rate};
+                REG_DQS_DELAY: rsp_data <= {24'd0, dqs_delay};
+                default:       rsp_data <= 32'd0;
+            endcase
+        end else if (pkt_valid && pkt_cmd == CMD_WRITE_REG) begin
+            case (pkt_reg)
+                REG_INTERFACE: begin
+                    interface_sel <= pkt_value[7:0];
+                    timing_mode   <= pkt_value[15:8];
+                end
+                REG_DATA_RATE: data_rate <= (pkt_value[15:0] > MAX_RATE) ? MAX_RATE : pkt_value[15:0];
+                REG_DQS_DELAY: dqs_delay <= (pkt_value[7:0] < DQS_TAPS) ? pkt_value[7:0] : DQS_TAPS - 8'd1;
+                default: ;
+            endcase
+        end
+    end
+endmodule
@@ -23,7 +23,6 @@ mod emmc;
 mod usb_handler;
 
 use pio_nand::{NandController, NandPins};
-use spi_nand::SpiNandController;
 use spi_nor::SpiNorController;
 use emmc::EmmcController;
 use usb_handler::UsbHandler;
@@ -49,6 +48,9 @@ static mut STATE: Option<State> = None;
 ///   GP3  - RE# (Read Enable, active low)
 ///   GP4  - CE# (Chip Enable, active low)
 ///   GP5  - R/B# (Ready/Busy, active low = busy)
+///   GP20 - CE2# (multi-CE packages, active low)
+///   GP21 - CE3#
+///   GP22 - CE4#
 ///
 /// Data bus:
 ///   GP6  - D0
@@ -68,11 +70,11 @@ static mut STATE: Option<State> = None;
 ///   GP19 - SPI0 TX (MOSI)
 ///
 /// === eMMC Mode (SPI) ===
-/// Uses same SPI0 pins as SPI NAND:
-///   GP16 - SPI0 RX (DAT0/MISO)
-///   GP17 - SPI0 CS# (directly controlled)
-///   GP18 - SPI0 SCK (CLK)
-///   GP19 - SPI0 TX (CMD/MOSI)
+/// SPI signals (on SPI1):
+///   GP12 - SPI1 RX (DAT0/MISO)
+///   GP13 - SPI1 CS# (directly controlled)
+///   GP14 - SPI1 SCK (CLK)
+///   GP15 - SPI1 TX (CMD/MOSI)
 ///
 /// Optional (shared):
 ///   GP14 - WP# (Write Protect, active low)
@@ -94,6 +96,11 @@ async fn main(spawner: Spawner) {
         we: Output::new(p.PIN_2, Level::High),   // Active low, idle high
         re: Output::new(p.PIN_3, Level::High),   // Active low, idle high
         ce: Output::new(p.PIN_4, Level::High),   // Active low, idle high (disabled)
+        ce_ext: [
+            Output::new(p.PIN_20, Level::High),  // CE2#
+            Output::new(p.PIN_21, Level::High),  // CE3#
+            Output::new(p.PIN_22, Level::High),  // CE4#
+        ],
         rb: Input::new(p.PIN_5, Pull::Up),       // Ready/Busy with pull-up
         
         // Data bus as flexible I/O
@@ -131,13 +138,11 @@ async fn main(spawner: Spawner) {
     let spi_nor = SpiNorController::new(spi, spi_cs);
     info!("SPI NOR controller initialized (SPI0)");
 
-    // Note: SPI NAND would use the same SPI0 bus with different CS
-    // For now, we use SPI0 for SPI NOR operations
-    // let spi_nand = SpiNandController::new(spi, spi_cs);
-    // info!("SPI NAND controller initialized");
+    // SPI NAND sits on the same SPI0 bus and CS#; SET_INTERFACE hands the
+    // bus over to a SpiNandController when the host selects SPI NAND
 
-    // Initialize eMMC controller (shares SPI1 for separate CS)
-    // Note: eMMC uses same SPI bus but different CS pin (GP20)
+    // Initialize eMMC controller on SPI1
+    // Note: eMMC CS# is GP13; GP20-22 are the extra NAND CE# lines
     let mut emmc_spi_config = SpiConfig::default();
     emmc_spi_config.frequency = 400_000; // Start slow for init (400kHz)
     
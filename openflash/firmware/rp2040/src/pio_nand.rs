@@ -59,6 +59,7 @@ pub struct NandPins<'d> {
     pub we: Output<'d>,   // Write Enable (active low)
     pub re: Output<'d>,   // Read Enable (active low)
     pub ce: Output<'d>,   // Chip Enable (active low)
+    pub ce_ext: [Output<'d>; 3], // CE2#-CE4# for multi-die packages (active low)
     pub rb: Input<'d>,    // Ready/Busy (low = busy)
     
     // Data bus D0-D7 as flexible pins
@@ -77,6 +78,7 @@ pub struct NandPins<'d> {
 pub struct NandController<'d> {
     pins: NandPins<'d>,
     timing: NandTiming,
+    active_ce: u8,
 }
 
 impl<'d> NandController<'d> {
@@ -84,6 +86,9 @@ impl<'d> NandController<'d> {
     pub fn new(mut pins: NandPins<'d>) -> Self {
         // Initialize control signals to idle state
         pins.ce.set_high();   // Chip disabled
+        for ce in pins.ce_ext.iter_mut() {
+            ce.set_high();
+        }
         pins.we.set_high();   // WE# idle high
         pins.re.set_high();   // RE# idle high
         pins.cle.set_low();   // CLE idle low
@@ -102,6 +107,7 @@ impl<'d> NandController<'d> {
         Self {
             pins,
             timing: NandTiming::default(),
+            active_ce: 0,
         }
     }
 
@@ -110,6 +116,32 @@ impl<'d> NandController<'d> {
         self.timing = timing;
     }
 
+    /// Select which CE# line subsequent operations drive (0 = CE#)
+    pub fn select_ce(&mut self, ce: u8) -> bool {
+        if ce as usize > self.pins.ce_ext.len() {
+            return false;
+        }
+        self.ce_high();
+        self.active_ce = ce;
+        true
+    }
+
+    /// Assert the active CE#
+    fn ce_low(&mut self) {
+        match self.active_ce {
+            0 => self.pins.ce.set_low(),
+            n => self.pins.ce_ext[n as usize - 1].set_low(),
+        }
+    }
+
+    /// Release the active CE#
+    fn ce_high(&mut self) {
+        match self.active_ce {
+            0 => self.pins.ce.set_high(),
+            n => self.pins.ce_ext[n as usize - 1].set_high(),
+        }
+    }
+
     /// Wait for NAND to become ready (R/B# goes high)
     pub async fn wait_ready(&mut self) {
         while self.pins.rb.is_low() {
@@ -187,7 +219,7 @@ impl<'d> NandController<'d> {
 
     /// Send a command byte to NAND
     pub async fn send_command(&mut self, cmd: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_high();
         self.pins.ale.set_low();
         self.delay_ns(self.timing.t_cls);
@@ -205,7 +237,7 @@ impl<'d> NandController<'d> {
 
     /// Send an address byte to NAND
     pub async fn send_address(&mut self, addr: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_high();
         self.delay_ns(self.timing.t_als);
@@ -223,7 +255,7 @@ impl<'d> NandController<'d> {
 
     /// Read a single byte from data bus
     pub fn read_byte(&mut self) -> u8 {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_low();
         
@@ -245,7 +277,7 @@ impl<'d> NandController<'d> {
 
     /// Write a single byte to data bus
     pub fn write_byte(&mut self, data: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_low();
         
@@ -276,14 +308,14 @@ impl<'d> NandController<'d> {
         
         self.read_data(&mut id);
         
-        self.pins.ce.set_high();
+        self.ce_high();
         id
     }
 
     /// Reset the NAND chip
     pub async fn reset(&mut self) {
         self.send_command(commands::RESET).await;
-        self.pins.ce.set_high();
+        self.ce_high();
         
         // Wait for reset to complete (typically < 5ms)
         Timer::after(Duration::from_millis(5)).await;
@@ -295,7 +327,7 @@ impl<'d> NandController<'d> {
         self.send_command(commands::READ_STATUS).await;
         self.delay_ns(100);
         let status = self.read_byte();
-        self.pins.ce.set_high();
+        self.ce_high();
         status
     }
 
@@ -322,7 +354,7 @@ impl<'d> NandController<'d> {
         // Read page data
         self.read_data(buffer);
         
-        self.pins.ce.set_high();
+        self.ce_high();
     }
 
     /// Read a page with OOB/spare area
@@ -352,7 +384,7 @@ impl<'d> NandController<'d> {
         // Read OOB area
         self.read_data(oob);
         
-        self.pins.ce.set_high();
+        self.ce_high();
     }
 
     /// Program (write) a page
@@ -410,6 +442,104 @@ impl<'d> NandController<'d> {
         (status & 0x01) == 0
     }
 
+    /// Send column 0 and a 3-byte row address
+    async fn send_page_address(&mut self, page_addr: u32) {
+        self.send_address(0x00).await;
+        self.send_address(0x00).await;
+        self.send_row_address(page_addr).await;
+    }
+
+    async fn send_row_address(&mut self, page_addr: u32) {
+        self.send_address((page_addr & 0xFF) as u8).await;
+        self.send_address(((page_addr >> 8) & 0xFF) as u8).await;
+        self.send_address(((page_addr >> 16) & 0xFF) as u8).await;
+    }
+
+    /// Start a sequential cache read: the first page is loaded into the page register
+    pub async fn cache_read_start(&mut self, page_addr: u32) {
+        self.send_command(commands::READ_1ST).await;
+        self.send_page_address(page_addr).await;
+        self.send_command(commands::READ_2ND).await;
+        self.wait_ready().await;
+    }
+
+    /// Move the next page to the cache register and read it out
+    ///
+    /// 31h starts loading the following page while this one is transferred;
+    /// 3Fh on the last page ends the sequence.
+    pub async fn cache_read_next(&mut self, last: bool, buffer: &mut [u8]) {
+        let cmd = if last { commands::CACHE_READ_END } else { commands::CACHE_READ_SEQ };
+        self.send_command(cmd).await;
+        self.wait_ready().await;
+        self.read_data(buffer);
+        self.ce_high();
+    }
+
+    /// Program a page through the cache register (15h), or finish with 10h
+    pub async fn cache_program(&mut self, page_addr: u32, data: &[u8], last: bool) -> bool {
+        self.send_command(commands::PAGE_PROGRAM_1ST).await;
+        self.send_page_address(page_addr).await;
+        self.write_data(data);
+        let cmd = if last { commands::PAGE_PROGRAM_2ND } else { commands::CACHE_PROGRAM };
+        self.send_command(cmd).await;
+        self.wait_ready().await;
+        let status = self.read_status().await;
+        (status & status::FAIL) == 0
+    }
+
+    /// Load one page per plane: 00h-addr-32h for each plane, 00h-addr-30h for the last
+    pub async fn multi_plane_read(&mut self, page_addrs: &[u32]) {
+        for (i, &page_addr) in page_addrs.iter().enumerate() {
+            self.send_command(commands::READ_1ST).await;
+            self.send_page_address(page_addr).await;
+            let last = i + 1 == page_addrs.len();
+            let cmd = if last { commands::READ_2ND } else { commands::MULTI_PLANE_READ };
+            self.send_command(cmd).await;
+            self.wait_ready().await;
+        }
+        self.ce_high();
+    }
+
+    /// Read one plane's page register after `multi_plane_read` (06h-addr-E0h)
+    pub async fn read_plane_data(&mut self, page_addr: u32, buffer: &mut [u8]) {
+        self.send_command(commands::CHANGE_READ_COLUMN_ENH).await;
+        self.send_page_address(page_addr).await;
+        self.send_command(commands::CHANGE_READ_COLUMN_END).await;
+        self.delay_ns(300); // tCCS
+        self.read_data(buffer);
+        self.ce_high();
+    }
+
+    /// Load a plane's data (80h-11h), or start programming all planes (80h-10h)
+    pub async fn multi_plane_program(&mut self, page_addr: u32, data: &[u8], last: bool) -> bool {
+        self.send_command(commands::PAGE_PROGRAM_1ST).await;
+        self.send_page_address(page_addr).await;
+        self.write_data(data);
+        let cmd = if last { commands::PAGE_PROGRAM_2ND } else { commands::MULTI_PLANE_PROGRAM };
+        self.send_command(cmd).await;
+        self.wait_ready().await;
+        if !last {
+            self.ce_high();
+            return true;
+        }
+        let status = self.read_status().await;
+        (status & status::FAIL) == 0
+    }
+
+    /// Erase one block per plane: 60h-row-D1h for each plane, 60h-row-D0h for the last
+    pub async fn multi_plane_erase(&mut self, page_addrs: &[u32]) -> bool {
+        for (i, &page_addr) in page_addrs.iter().enumerate() {
+            self.send_command(commands::BLOCK_ERASE_1ST).await;
+            self.send_row_address(page_addr).await;
+            let last = i + 1 == page_addrs.len();
+            let cmd = if last { commands::BLOCK_ERASE_2ND } else { commands::MULTI_PLANE_ERASE };
+            self.send_command(cmd).await;
+            self.wait_ready().await;
+        }
+        let status = self.read_status().await;
+        (status & status::FAIL) == 0
+    }
+
     /// Check if block is bad (reads spare area marker)
     pub async fn is_bad_block(&mut self, block_addr: u32) -> bool {
         let page_addr = block_addr * 64; // First page of block
@@ -430,7 +560,7 @@ impl<'d> NandController<'d> {
         self.wait_ready().await;
         
         let marker = self.read_byte();
-        self.pins.ce.set_high();
+        self.ce_high();
         
         // 0xFF = good block, anything else = bad
         marker != 0xFF
@@ -451,6 +581,14 @@ pub mod commands {
     pub const READ_PARAMETER_PAGE: u8 = 0xEC;
     pub const SET_FEATURES: u8 = 0xEF;
     pub const GET_FEATURES: u8 = 0xEE;
+    pub const CACHE_READ_SEQ: u8 = 0x31;
+    pub const CACHE_READ_END: u8 = 0x3F;
+    pub const CACHE_PROGRAM: u8 = 0x15;
+    pub const MULTI_PLANE_READ: u8 = 0x32;
+    pub const MULTI_PLANE_PROGRAM: u8 = 0x11;
+    pub const MULTI_PLANE_ERASE: u8 = 0xD1;
+    pub const CHANGE_READ_COLUMN_ENH: u8 = 0x06;
+    pub const CHANGE_READ_COLUMN_END: u8 = 0xE0;
 }
 
 /// Status register bits
@@ -21,6 +21,7 @@ pub mod commands {
     pub const PROGRAM_LOAD_X4: u8 = 0x32;
     pub const PROGRAM_EXECUTE: u8 = 0x10;
     pub const BLOCK_ERASE: u8 = 0xD8;
+    pub const SOFTWARE_DIE_SELECT: u8 = 0xC2;
 }
 
 /// Feature register addresses
@@ -60,6 +61,11 @@ impl<'d, SPI: embassy_rp::spi::Instance> SpiNandController<'d, SPI> {
         }
     }
 
+    /// Hand back the SPI bus and CS# so another controller can take them
+    pub fn release(self) -> (Spi<'d, SPI, embassy_rp::spi::Blocking>, Output<'d>) {
+        (self.spi, self.cs)
+    }
+
     /// Set page geometry after chip identification
     pub fn set_geometry(&mut self, page_size: u32, oob_size: u32) {
         self.page_size = page_size;
@@ -259,6 +265,54 @@ impl<'d, SPI: embassy_rp::spi::Instance> SpiNandController<'d, SPI> {
         self.set_feature(features::PROTECTION, 0x00);
     }
 
+    /// Select a die of a stacked package (Winbond W25M)
+    pub fn select_die(&mut self, die: u8) {
+        self.write_cmd(&[commands::SOFTWARE_DIE_SELECT, die]);
+    }
+
+    /// Switch between continuous read (BUF=0) and buffer read (BUF=1) mode
+    pub fn set_continuous_read(&mut self, enable: bool) {
+        let feature = self.get_feature(features::FEATURE);
+        if enable {
+            self.set_feature(features::FEATURE, feature & !0x08);
+        } else {
+            self.set_feature(features::FEATURE, feature | 0x08);
+        }
+    }
+
+    /// Start a continuous read at `row_addr` (BUF=0 must be set)
+    ///
+    /// Pages stream without a PAGE READ each, main data only (no OOB).
+    /// CS stays asserted until `end_continuous_read`.
+    pub async fn begin_continuous_read(&mut self, row_addr: u32) {
+        self.page_read_to_cache(row_addr).await;
+        self.cs_low();
+        // No column address in continuous mode, 24 dummy clocks instead
+        let _ = self.spi.blocking_write(&[commands::READ_FROM_CACHE, 0x00, 0x00, 0x00]);
+    }
+
+    /// Clock out the next bytes of a continuous read
+    pub fn continue_read(&mut self, buf: &mut [u8]) {
+        let _ = self.spi.blocking_read(buf);
+    }
+
+    /// Stop a continuous read and wait for the internal page load to finish
+    pub async fn end_continuous_read(&mut self) {
+        self.cs_high();
+        self.wait_ready().await;
+    }
+
+    /// Read a vendor ECC status register through its own opcode (Macronix 7Ch)
+    pub fn read_ecc_status(&mut self, cmd: u8) -> u8 {
+        let mut value = [0u8; 1];
+        self.cs_low();
+        // Opcode + dummy byte, then the status byte
+        let _ = self.spi.blocking_write(&[cmd, 0x00]);
+        let _ = self.spi.blocking_read(&mut value);
+        self.cs_high();
+        value[0]
+    }
+
     /// Get ECC status from last read
     pub fn get_ecc_status(&mut self) -> EccStatus {
         let status = self.read_status();
@@ -271,6 +325,11 @@ impl<'d, SPI: embassy_rp::spi::Instance> SpiNandController<'d, SPI> {
             _ => EccStatus::NoError,
         }
     }
+
+    /// Get page size
+    pub fn page_size(&self) -> u32 {
+        self.page_size
+    }
 }
 
 /// ECC status from internal ECC engine
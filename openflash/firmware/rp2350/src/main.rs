@@ -13,8 +13,11 @@
 use defmt::*;
 use defmt_rtt as _;
 use embassy_executor::Spawner;
+use embassy_futures::join::join;
 use embassy_rp::bind_interrupts;
-use embassy_rp::peripherals::USB;
+use embassy_rp::gpio::{Level, Output};
+use embassy_rp::peripherals::{PIO0, USB};
+use embassy_rp::pio::{self, Pio};
 use embassy_rp::usb::{Driver, InterruptHandler};
 use embassy_usb::class::cdc_acm::{CdcAcmClass, State};
 use embassy_usb::Builder;
@@ -29,8 +32,12 @@ mod usb_handler;
 
 bind_interrupts!(struct Irqs {
     USBCTRL_IRQ => InterruptHandler<USB>;
+    PIO0_IRQ_0 => pio::InterruptHandler<PIO0>;
 });
 
+/// Largest response: a 512-byte SPI NOR read
+const MAX_RESPONSE: usize = 512;
+
 /// Protocol version for v2.3.0
 const PROTOCOL_VERSION: u8 = 0x23;
 
@@ -54,6 +61,26 @@ async fn main(_spawner: Spawner) {
     info!("OpenFlash RP2350 Firmware v{}", FIRMWARE_VERSION);
     
     let p = embassy_rp::init(Default::default());
+
+    // SPI NOR on PIO0: IO0-IO7 on GP0-GP7, SCK on GP8, CS# on GP9
+    let Pio {
+        mut common, sm0, ..
+    } = Pio::new(p.PIO0, Irqs);
+    let io = [
+        common.make_pio_pin(p.PIN_0),
+        common.make_pio_pin(p.PIN_1),
+        common.make_pio_pin(p.PIN_2),
+        common.make_pio_pin(p.PIN_3),
+        common.make_pio_pin(p.PIN_4),
+        common.make_pio_pin(p.PIN_5),
+        common.make_pio_pin(p.PIN_6),
+        common.make_pio_pin(p.PIN_7),
+    ];
+    let sck = common.make_pio_pin(p.PIN_8);
+    let cs = Output::new(p.PIN_9, Level::High);
+    let bus = spi_nor::XspiBus::new(&mut common, sm0, io, sck, cs);
+    let mut nor = spi_nor::SpiNor::new(bus);
+    nor.init();
     
     // Create USB driver
     let driver = Driver::new(p.USB, Irqs);
@@ -88,20 +115,26 @@ async fn main(_spawner: Spawner) {
     // Build USB device
     let mut usb = builder.build();
     
-    // Command buffer
-    let mut cmd_buf: Vec<u8, 64> = Vec::new();
-    
     info!("USB initialized, waiting for host...");
-    
-    loop {
-        // Run USB device
-        usb.run().await;
-    }
+
+    let commands = async {
+        let mut cmd_buf = [0u8; 64];
+        loop {
+            class.wait_connection().await;
+            while let Ok(n) = class.read_packet(&mut cmd_buf).await {
+                let response = handle_command(&cmd_buf[..n], &mut nor).await;
+                for chunk in response.chunks(64) {
+                    let _ = class.write_packet(chunk).await;
+                }
+            }
+        }
+    };
+    join(usb.run(), commands).await;
 }
 
 /// Handle incoming USB commands
-async fn handle_command(cmd: &[u8]) -> Vec<u8, 64> {
-    let mut response: Vec<u8, 64> = Vec::new();
+async fn handle_command(cmd: &[u8], nor: &mut spi_nor::SpiNor<'_>) -> Vec<u8, MAX_RESPONSE> {
+    let mut response: Vec<u8, MAX_RESPONSE> = Vec::new();
     
     if cmd.is_empty() {
         return response;
@@ -131,6 +164,58 @@ async fn handle_command(cmd: &[u8]) -> Vec<u8, 64> {
             }
         }
         
+        // SPI NOR read JEDEC ID in the current I/O mode
+        // Args: [address bytes, dummy cycles, length]; octal modes need the
+        // address and dummy, length 0 = 3
+        0x60 => {
+            let arg = |i: usize| cmd.get(1 + i).copied().unwrap_or(0);
+            let len = match arg(2) {
+                0 => 3,
+                n => (n as usize).min(16),
+            };
+            let mut id = [0u8; 16];
+            nor.read_id(arg(0), arg(1), &mut id[..len]).await;
+            let _ = response.push(0x60);
+            let _ = response.push(0x00);
+            let _ = response.extend_from_slice(&id[..len]);
+        }
+
+        // SPI NOR read in the current I/O mode, data only
+        // Args: [addr_0, addr_1, addr_2, addr_3, length_lo, length_hi]
+        0x62 | 0x63 if cmd.len() >= 7 => {
+            let address = u32::from_le_bytes([cmd[1], cmd[2], cmd[3], cmd[4]]);
+            let len = (u16::from_le_bytes([cmd[5], cmd[6]]) as usize).min(MAX_RESPONSE);
+            let _ = response.resize(len, 0);
+            if nor.read(address, &mut response).await.is_err() {
+                response.clear();
+                let _ = response.push(cmd[0]);
+                let _ = response.push(0x01);
+            }
+        }
+
+        // SPI NOR I/O mode switch
+        // Args: [mode, read opcode, dummy cycles, command extension]
+        0x7C if cmd.len() >= 5 => {
+            let status = match nor.set_io_mode(cmd[1], cmd[2], cmd[3], cmd[4]) {
+                Ok(()) => 0x00,
+                Err(_) => 0x01,
+            };
+            let _ = response.push(0x7C);
+            let _ = response.push(status);
+        }
+
+        // Raw SPI NOR command in the current I/O mode
+        // Args: [length, bytes...]
+        0x7D if cmd.len() >= 2 => {
+            let len = (cmd[1] as usize).min(cmd.len() - 2);
+            let status = match nor.transfer(&cmd[2..2 + len]).await {
+                Ok(()) => 0x00,
+                Err(_) => 0x01,
+            };
+            let _ = response.push(0x7D);
+            let _ = response.push(status);
+        }
+
         // Unknown command
         _ => {
             let _ = response.push(0xFF);
@@ -1,6 +1,15 @@
 //! SPI NOR driver for RP2350
+//!
+//! Octal and DTR modes (up to 8D-8D-8D) run on a PIO state machine with
+//! eight data pins (GP0-GP7) and SCK (GP8) as side-set; CS# is GP9. SCK is
+//! kept slow enough that DTR reads are sampled on the next clock edge
+//! instead of on DQS.
 
 use defmt::*;
+use embassy_rp::gpio::Output;
+use embassy_rp::peripherals::PIO0;
+use embassy_rp::pio::{Common, Config, Direction, Pin, ShiftConfig, ShiftDirection, StateMachine};
+use fixed::types::U24F8;
 
 /// SPI NOR commands
 pub mod commands {
@@ -19,31 +28,320 @@ pub mod commands {
     pub const WRITE_DISABLE: u8 = 0x04;
     pub const READ_STATUS: u8 = 0x05;
     pub const WRITE_STATUS: u8 = 0x01;
+    pub const RESET_ENABLE: u8 = 0x66;
+    pub const RESET: u8 = 0x99;
+}
+
+/// I/O modes in protocol code order (see core `SpiNorIoMode`):
+/// command, address and data lanes, DTR
+const IO_MODES: [(u8, u8, u8, bool); 15] = [
+    (1, 1, 1, false),
+    (1, 1, 2, false),
+    (1, 2, 2, false),
+    (2, 2, 2, false),
+    (1, 1, 4, false),
+    (1, 4, 4, false),
+    (4, 4, 4, false),
+    (1, 1, 8, false),
+    (1, 8, 8, false),
+    (8, 8, 8, false),
+    (1, 1, 1, true),
+    (1, 2, 2, true),
+    (1, 4, 4, true),
+    (4, 4, 4, true),
+    (8, 8, 8, true),
+];
+
+/// PIO clock divider: SCK runs at sys_clk / (4 * divider), slow enough to
+/// sample DTR data without DQS
+const CLOCK_DIVIDER: u16 = 16;
+
+/// Lanes and rate of one transaction phase
+#[derive(Clone, Copy)]
+struct Phase {
+    lanes: u8,
+    dtr: bool,
+}
+
+impl Phase {
+    /// Units clocked per byte
+    fn units(self) -> usize {
+        8 / self.lanes as usize
+    }
+
+    /// WP# (IO2) and HOLD# (IO3) are held high below four lanes
+    fn hold_mask(self) -> u8 {
+        if self.lanes < 4 {
+            0b1100
+        } else {
+            0
+        }
+    }
+
+    /// Pin value carrying `unit` of `byte`, MSB first
+    fn unit_out(self, byte: u8, unit: usize) -> u8 {
+        let shift = 8 - self.lanes as usize * (unit + 1);
+        let mask = ((1u16 << self.lanes) - 1) as u8;
+        ((byte >> shift) & mask) | self.hold_mask()
+    }
+
+    /// Unit bits from sampled pins: MISO is IO1 in single-lane mode
+    fn unit_in(self, pins: u8) -> u8 {
+        match self.lanes {
+            1 => (pins >> 1) & 1,
+            8 => pins,
+            lanes => pins & ((1u8 << lanes) - 1),
+        }
+    }
+}
+
+/// xSPI bus on one PIO state machine
+///
+/// Every TX word drives IO0-IO7 and produces one SCK edge; every edge
+/// samples IO0-IO7 into the RX FIFO. SDR units take two edges with the
+/// same value and are sampled on the rising edge, DTR units take one edge
+/// each and are sampled on the next edge.
+pub struct XspiBus<'d> {
+    sm: StateMachine<'d, PIO0, 0>,
+    io: [Pin<'d, PIO0>; 8],
+    cs: Output<'d>,
+    origin: u8,
+    /// Edges since CS# went low
+    edges: usize,
+}
+
+impl<'d> XspiBus<'d> {
+    pub fn new(
+        common: &mut Common<'d, PIO0>,
+        mut sm: StateMachine<'d, PIO0, 0>,
+        io: [Pin<'d, PIO0>; 8],
+        sck: Pin<'d, PIO0>,
+        cs: Output<'d>,
+    ) -> Self {
+        let program = pio_proc::pio_asm!(
+            ".side_set 1",
+            ".wrap_target",
+            "out pins, 8 side 0",
+            "in pins, 8 side 1",
+            "out pins, 8 side 1",
+            "in pins, 8 side 0",
+            ".wrap",
+        );
+        let loaded = common.load_program(&program.program);
+
+        let io_refs: [&Pin<'d, PIO0>; 8] = core::array::from_fn(|i| &io[i]);
+        let mut cfg = Config::default();
+        cfg.use_program(&loaded, &[&sck]);
+        cfg.set_out_pins(&io_refs);
+        cfg.set_in_pins(&io_refs);
+        cfg.shift_out = ShiftConfig {
+            auto_fill: true,
+            threshold: 8,
+            direction: ShiftDirection::Right,
+        };
+        cfg.shift_in = ShiftConfig {
+            auto_fill: true,
+            threshold: 8,
+            direction: ShiftDirection::Left,
+        };
+        cfg.clock_divider = U24F8::from_num(CLOCK_DIVIDER);
+        sm.set_config(&cfg);
+        sm.set_pin_dirs(Direction::Out, &[&sck]);
+        sm.set_pin_dirs(Direction::In, &io_refs);
+        sm.set_enable(true);
+
+        Self {
+            sm,
+            io,
+            cs,
+            origin: loaded.origin,
+            edges: 0,
+        }
+    }
+
+    /// Drive the pins in `mask`, release the rest
+    ///
+    /// Only called between clocks, with SCK low and the state machine
+    /// stalled on its first instruction.
+    fn set_outputs(&mut self, mask: u8) {
+        self.sm.set_enable(false);
+        for (i, pin) in self.io.iter().enumerate() {
+            let dir = if mask & (1 << i) != 0 {
+                Direction::Out
+            } else {
+                Direction::In
+            };
+            self.sm.set_pin_dirs(dir, &[pin]);
+        }
+        self.sm.restart();
+        unsafe { self.sm.exec_jmp(self.origin) };
+        self.sm.set_enable(true);
+    }
+
+    /// Drive `value` and clock one edge; returns the pins sampled at it
+    async fn edge(&mut self, value: u8) -> u8 {
+        self.sm.tx().wait_push(value as u32).await;
+        self.edges += 1;
+        self.sm.rx().wait_pull().await as u8
+    }
+
+    /// Finish a half clock so SCK is low
+    async fn align(&mut self) {
+        if self.edges % 2 == 1 {
+            self.edge(0).await;
+        }
+    }
+
+    fn begin(&mut self) {
+        self.edges = 0;
+        self.cs.set_low();
+    }
+
+    async fn end(&mut self) {
+        self.align().await;
+        self.cs.set_high();
+        self.set_outputs(0);
+    }
+
+    async fn write(&mut self, phase: Phase, bytes: &[u8]) {
+        self.align().await;
+        let lanes = ((1u16 << phase.lanes) - 1) as u8;
+        self.set_outputs(lanes | phase.hold_mask());
+        for &byte in bytes {
+            for unit in 0..phase.units() {
+                let value = phase.unit_out(byte, unit);
+                self.edge(value).await;
+                if !phase.dtr {
+                    self.edge(value).await;
+                }
+            }
+        }
+    }
+
+    /// Clock `cycles` full clocks with the data lanes released
+    async fn dummy(&mut self, phase: Phase, cycles: u8) {
+        self.align().await;
+        self.set_outputs(phase.hold_mask());
+        for _ in 0..cycles as usize * 2 {
+            self.edge(phase.hold_mask()).await;
+        }
+    }
+
+    async fn read(&mut self, phase: Phase, buffer: &mut [u8]) {
+        self.align().await;
+        // Single-lane reads keep MOSI driven
+        let mosi = if phase.lanes == 1 { 1 } else { 0 };
+        self.set_outputs(phase.hold_mask() | mosi);
+        let idle = phase.hold_mask();
+        if phase.dtr {
+            // The edge that launches the first unit samples nothing
+            self.edge(idle).await;
+        }
+        for byte in buffer.iter_mut() {
+            let mut value = 0u16;
+            for _ in 0..phase.units() {
+                let pins = self.edge(idle).await;
+                if !phase.dtr {
+                    self.edge(idle).await;
+                }
+                value = (value << phase.lanes) | phase.unit_in(pins) as u16;
+            }
+            *byte = value as u8;
+        }
+    }
 }
 
 /// SPI NOR controller
-pub struct SpiNor {
+pub struct SpiNor<'d> {
+    bus: XspiBus<'d>,
     quad_enabled: bool,
     four_byte_addr: bool,
+    /// I/O mode code, 0 = 1S-1S-1S
+    io_mode: u8,
+    read_opcode: u8,
+    dummy_cycles: u8,
+    /// Second command byte in 8-lane modes: 0 none, 1 repeat, 2 invert
+    command_extension: u8,
 }
 
-impl SpiNor {
-    pub fn new() -> Self {
+impl<'d> SpiNor<'d> {
+    pub fn new(bus: XspiBus<'d>) -> Self {
         Self {
+            bus,
             quad_enabled: false,
             four_byte_addr: false,
+            io_mode: 0,
+            read_opcode: commands::FAST_READ,
+            dummy_cycles: 8,
+            command_extension: 0,
         }
     }
     
     pub fn init(&mut self) {
         info!("Initializing SPI NOR controller");
     }
-    
-    pub fn read_jedec_id(&self) -> [u8; 3] {
-        [0; 3]
+
+    /// Command, address and data phases of the current mode
+    fn phases(&self) -> (Phase, Phase, Phase) {
+        let (cmd, addr, data, dtr) = IO_MODES[self.io_mode as usize];
+        // 1S-xD-xD modes send the opcode single rate
+        let cmd_phase = Phase {
+            lanes: cmd,
+            dtr: dtr && cmd != 1,
+        };
+        (
+            cmd_phase,
+            Phase { lanes: addr, dtr },
+            Phase { lanes: data, dtr },
+        )
     }
-    
-    pub fn read(&self, _addr: u32, _buf: &mut [u8]) -> Result<(), SpiNorError> {
+
+    /// Opcode, address and dummy cycles; leaves CS# low
+    async fn start(&mut self, opcode: u8, address: Option<(u32, u8)>, dummy_cycles: u8) {
+        let (cmd_phase, addr_phase, data_phase) = self.phases();
+        let (bytes, len) = self.command_bytes(opcode);
+        self.bus.begin();
+        self.bus.write(cmd_phase, &bytes[..len]).await;
+        if let Some((address, address_bytes)) = address {
+            let be = address.to_be_bytes();
+            self.bus
+                .write(addr_phase, &be[4 - address_bytes as usize..])
+                .await;
+        }
+        if dummy_cycles > 0 {
+            self.bus.dummy(data_phase, dummy_cycles).await;
+        }
+    }
+
+    /// READ ID (9Fh) in the current mode, with the address bytes (address
+    /// 0) and dummy cycles octal modes need
+    pub async fn read_id(&mut self, address_bytes: u8, dummy_cycles: u8, buf: &mut [u8]) {
+        let address = (address_bytes > 0).then_some((0, address_bytes.min(4)));
+        self.start(commands::READ_JEDEC_ID, address, dummy_cycles)
+            .await;
+        let (_, _, data_phase) = self.phases();
+        self.bus.read(data_phase, buf).await;
+        self.bus.end().await;
+    }
+
+    pub async fn read_jedec_id(&mut self) -> [u8; 3] {
+        let mut id = [0; 3];
+        self.read_id(0, 0, &mut id).await;
+        id
+    }
+
+    /// Read with the current mode's read command
+    pub async fn read(&mut self, addr: u32, buf: &mut [u8]) -> Result<(), SpiNorError> {
+        let address_bytes = if self.four_byte_addr { 4 } else { 3 };
+        self.start(
+            self.read_opcode,
+            Some((addr, address_bytes)),
+            self.dummy_cycles,
+        )
+        .await;
+        let (_, _, data_phase) = self.phases();
+        self.bus.read(data_phase, buf).await;
+        self.bus.end().await;
         Ok(())
     }
     
@@ -54,6 +352,69 @@ impl SpiNor {
     pub fn erase_sector(&self, _addr: u32) -> Result<(), SpiNorError> {
         Ok(())
     }
+
+    /// Switch the bus to an I/O mode (SpiNorSetIoMode)
+    ///
+    /// The host has already switched the chip with raw commands; this only
+    /// reloads the PIO program for the new lane count and clocking.
+    pub fn set_io_mode(
+        &mut self,
+        mode: u8,
+        read_opcode: u8,
+        dummy_cycles: u8,
+        command_extension: u8,
+    ) -> Result<(), SpiNorError> {
+        let Some(&(cmd, _, data, dtr)) = IO_MODES.get(mode as usize) else {
+            return Err(SpiNorError::UnsupportedMode);
+        };
+        info!(
+            "SPI NOR I/O mode {}: {} cmd lanes, {} data lanes, dtr={}",
+            mode, cmd, data, dtr
+        );
+
+        self.io_mode = mode;
+        self.read_opcode = read_opcode;
+        self.dummy_cycles = dummy_cycles;
+        self.command_extension = command_extension;
+        self.quad_enabled = data == 4;
+        // Octal command modes always use 4-byte addresses
+        if cmd == 8 {
+            self.four_byte_addr = true;
+        }
+        Ok(())
+    }
+
+    /// Command bytes as clocked out: 8-lane modes add the extension byte
+    pub fn command_bytes(&self, opcode: u8) -> ([u8; 2], usize) {
+        let (cmd, _, _, _) = IO_MODES[self.io_mode as usize];
+        match (cmd, self.command_extension) {
+            (8, 1) => ([opcode, opcode], 2),
+            (8, 2) => ([opcode, !opcode], 2),
+            _ => ([opcode, 0], 1),
+        }
+    }
+
+    /// Send raw command bytes in the current mode (SpiNorTransfer)
+    ///
+    /// The opcode (with its extension byte) goes out as a command, the rest
+    /// on the command lanes at the command rate.
+    pub async fn transfer(&mut self, cmd: &[u8]) -> Result<(), SpiNorError> {
+        let Some((&opcode, rest)) = cmd.split_first() else {
+            return Ok(());
+        };
+        let (cmd_phase, _, _) = self.phases();
+        self.start(opcode, None, 0).await;
+        self.bus.write(cmd_phase, rest).await;
+        self.bus.end().await;
+        Ok(())
+    }
+
+    /// Software reset in the current mode, back to 1S-1S-1S
+    pub async fn reset(&mut self) -> Result<(), SpiNorError> {
+        self.transfer(&[commands::RESET_ENABLE]).await?;
+        self.transfer(&[commands::RESET]).await?;
+        self.set_io_mode(0, commands::FAST_READ, 8, 0)
+    }
 }
 
 #[derive(Debug)]
@@ -61,4 +422,5 @@ pub enum SpiNorError {
     Timeout,
     ProgramFailed,
     EraseFailed,
+    UnsupportedMode,
 }
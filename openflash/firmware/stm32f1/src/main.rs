@@ -8,7 +8,7 @@
 use defmt::*;
 use embassy_executor::Spawner;
 use embassy_stm32::bind_interrupts;
-use embassy_stm32::gpio::{Level, Output, Speed};
+use embassy_stm32::gpio::{Flex, Input, Level, Output, Pull, Speed};
 use embassy_stm32::peripherals::USB;
 use embassy_stm32::spi::{Spi, Config as SpiConfig};
 use embassy_stm32::usb::{Driver, InterruptHandler};
@@ -17,10 +17,12 @@ use embassy_usb::{Builder, Config};
 use {defmt_rtt as _, panic_probe as _};
 
 mod usb_handler;
+mod nand_gpio;
 mod spi_nand;
 mod spi_nor;
 mod emmc;
 
+use nand_gpio::{NandController, NandPins};
 use spi_nor::SpiNorController;
 use usb_handler::UsbHandler;
 
@@ -39,8 +41,28 @@ async fn main(spawner: Spawner) {
     let p = embassy_stm32::init(Default::default());
     info!("OpenFlash STM32F1 Firmware v1.26.0");
 
+    // Parallel NAND on GPIO, pin map in nand_gpio.rs
+    let nand = NandController::new(NandPins {
+        cle: Output::new(p.PA0, Level::Low, Speed::VeryHigh),
+        ale: Output::new(p.PA1, Level::Low, Speed::VeryHigh),
+        we: Output::new(p.PA2, Level::High, Speed::VeryHigh),
+        re: Output::new(p.PA3, Level::High, Speed::VeryHigh),
+        ce: Output::new(p.PB8, Level::High, Speed::VeryHigh),
+        ce2: Output::new(p.PA8, Level::High, Speed::VeryHigh),
+        rb: Input::new(p.PB9, Pull::Up),
+        d0: Flex::new(p.PB0),
+        d1: Flex::new(p.PB1),
+        d2: Flex::new(p.PB2),
+        d3: Flex::new(p.PB3),
+        d4: Flex::new(p.PB4),
+        d5: Flex::new(p.PB5),
+        d6: Flex::new(p.PB6),
+        d7: Flex::new(p.PB7),
+    });
+
     // Initialize SPI1 for SPI NOR flash
     // STM32F1 SPI1 pins: PA5 (SCK), PA6 (MISO), PA7 (MOSI)
+    // SPI NAND shares SPI1 and CS# (PA4); SET_INTERFACE hands the bus over
     let spi_config = SpiConfig::default();
     let spi = Spi::new_blocking(p.SPI1, p.PA5, p.PA7, p.PA6, spi_config);
     let cs = Output::new(p.PA4, Level::High, Speed::VeryHigh);
@@ -83,7 +105,7 @@ async fn main(spawner: Spawner) {
 
     info!("USB initialized");
 
-    let mut handler = UsbHandler::new(class);
+    let mut handler = UsbHandler::new(class, nand);
     handler.set_spi_nor(spi_nor);
 
     loop {
@@ -28,6 +28,20 @@ impl Default for NandTiming {
     }
 }
 
+impl NandTiming {
+    /// Fast timing for ONFI mode 4/5 chips (GPIO toggling adds its own margin)
+    pub fn fast() -> Self {
+        Self {
+            t_wp: 12,
+            t_rp: 12,
+            t_cls: 12,
+            t_als: 12,
+            t_clh: 5,
+            t_alh: 5,
+        }
+    }
+}
+
 /// Pin configuration for NAND interface on STM32F103 "Blue Pill"
 /// 
 /// Control signals (directly controlled):
@@ -35,8 +49,11 @@ impl Default for NandTiming {
 ///   PA1 - ALE (Address Latch Enable)
 ///   PA2 - WE# (Write Enable, active low)
 ///   PA3 - RE# (Read Enable, active low)
-///   PA4 - CE# (Chip Enable, active low)
-///   PA5 - R/B# (Ready/Busy input)
+///   PB8 - CE# (Chip Enable, active low)
+///   PB9 - R/B# (Ready/Busy input)
+///   PA8 - CE2# (Second Chip Enable for dual-die TSOP-48, active low)
+///
+/// PA4-PA7 belong to SPI1 (SPI NOR), so CE# and R/B# sit on PB8/PB9.
 ///
 /// Data bus (directly controlled):
 ///   PB0-PB7 - D0-D7
@@ -50,6 +67,7 @@ pub struct NandPins<'d> {
     pub we: Output<'d>,
     pub re: Output<'d>,
     pub ce: Output<'d>,
+    pub ce2: Output<'d>,
     pub rb: Input<'d>,
     
     pub d0: Flex<'d>,
@@ -65,12 +83,14 @@ pub struct NandPins<'d> {
 pub struct NandController<'d> {
     pins: NandPins<'d>,
     timing: NandTiming,
+    active_ce: u8,
 }
 
 impl<'d> NandController<'d> {
     pub fn new(mut pins: NandPins<'d>) -> Self {
         // Initialize control signals
         pins.ce.set_high();
+        pins.ce2.set_high();
         pins.we.set_high();
         pins.re.set_high();
         pins.cle.set_low();
@@ -89,6 +109,7 @@ impl<'d> NandController<'d> {
         Self {
             pins,
             timing: NandTiming::default(),
+            active_ce: 0,
         }
     }
 
@@ -96,6 +117,32 @@ impl<'d> NandController<'d> {
         self.timing = timing;
     }
 
+    /// Select which CE# line subsequent operations drive (0 = CE#, 1 = CE2#)
+    pub fn select_ce(&mut self, ce: u8) -> bool {
+        if ce > 1 {
+            return false;
+        }
+        self.ce_high();
+        self.active_ce = ce;
+        true
+    }
+
+    fn ce_low(&mut self) {
+        if self.active_ce == 0 {
+            self.pins.ce.set_low();
+        } else {
+            self.pins.ce2.set_low();
+        }
+    }
+
+    fn ce_high(&mut self) {
+        if self.active_ce == 0 {
+            self.pins.ce.set_high();
+        } else {
+            self.pins.ce2.set_high();
+        }
+    }
+
     pub async fn wait_ready(&mut self) {
         while self.pins.rb.is_low() {
             Timer::after(Duration::from_micros(1)).await;
@@ -154,7 +201,7 @@ impl<'d> NandController<'d> {
     }
 
     pub async fn send_command(&mut self, cmd: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_high();
         self.pins.ale.set_low();
         self.delay_ns(self.timing.t_cls);
@@ -170,7 +217,7 @@ impl<'d> NandController<'d> {
     }
 
     pub async fn send_address(&mut self, addr: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_high();
         self.delay_ns(self.timing.t_als);
@@ -186,7 +233,7 @@ impl<'d> NandController<'d> {
     }
 
     pub fn read_byte(&mut self) -> u8 {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_low();
         
@@ -205,7 +252,7 @@ impl<'d> NandController<'d> {
     }
 
     pub fn write_byte(&mut self, data: u8) {
-        self.pins.ce.set_low();
+        self.ce_low();
         self.pins.cle.set_low();
         self.pins.ale.set_low();
         
@@ -230,13 +277,13 @@ impl<'d> NandController<'d> {
         self.delay_ns(100);
         self.read_data(&mut id);
         
-        self.pins.ce.set_high();
+        self.ce_high();
         id
     }
 
     pub async fn reset(&mut self) {
         self.send_command(0xFF).await;
-        self.pins.ce.set_high();
+        self.ce_high();
         Timer::after(Duration::from_millis(5)).await;
         self.wait_ready().await;
     }
@@ -254,7 +301,7 @@ impl<'d> NandController<'d> {
         self.wait_ready().await;
         self.read_data(buffer);
         
-        self.pins.ce.set_high();
+        self.ce_high();
     }
 
     pub async fn program_page(&mut self, page_addr: u32, data: &[u8]) -> bool {
@@ -273,8 +320,106 @@ impl<'d> NandController<'d> {
         // Read status
         self.send_command(0x70).await;
         let status = self.read_byte();
-        self.pins.ce.set_high();
+        self.ce_high();
         
         (status & 0x01) == 0
     }
+
+    /// Read status (70h) and release CE#; bit 0 set means the operation failed
+    async fn read_status(&mut self) -> u8 {
+        self.send_command(0x70).await;
+        let status = self.read_byte();
+        self.ce_high();
+        status
+    }
+
+    /// Send column 0 and a 3-byte row address
+    async fn send_page_address(&mut self, page_addr: u32) {
+        self.send_address(0x00).await;
+        self.send_address(0x00).await;
+        self.send_row_address(page_addr).await;
+    }
+
+    async fn send_row_address(&mut self, page_addr: u32) {
+        self.send_address((page_addr & 0xFF) as u8).await;
+        self.send_address(((page_addr >> 8) & 0xFF) as u8).await;
+        self.send_address(((page_addr >> 16) & 0xFF) as u8).await;
+    }
+
+    /// Start a sequential cache read: the first page is loaded into the page register
+    pub async fn cache_read_start(&mut self, page_addr: u32) {
+        self.send_command(0x00).await;
+        self.send_page_address(page_addr).await;
+        self.send_command(0x30).await;
+        self.wait_ready().await;
+    }
+
+    /// Move the next page to the cache register and read it out
+    ///
+    /// 31h starts loading the following page while this one is transferred;
+    /// 3Fh on the last page ends the sequence.
+    pub async fn cache_read_next(&mut self, last: bool, buffer: &mut [u8]) {
+        self.send_command(if last { 0x3F } else { 0x31 }).await;
+        self.wait_ready().await;
+        self.read_data(buffer);
+        self.ce_high();
+    }
+
+    /// Program a page through the cache register (15h), or finish with 10h
+    pub async fn cache_program(&mut self, page_addr: u32, data: &[u8], last: bool) -> bool {
+        self.send_command(0x80).await;
+        self.send_page_address(page_addr).await;
+        self.write_data(data);
+        self.send_command(if last { 0x10 } else { 0x15 }).await;
+        self.wait_ready().await;
+        (self.read_status().await & 0x01) == 0
+    }
+
+    /// Load one page per plane: 00h-addr-32h for each plane, 00h-addr-30h for the last
+    pub async fn multi_plane_read(&mut self, page_addrs: &[u32]) {
+        for (i, &page_addr) in page_addrs.iter().enumerate() {
+            self.send_command(0x00).await;
+            self.send_page_address(page_addr).await;
+            let last = i + 1 == page_addrs.len();
+            self.send_command(if last { 0x30 } else { 0x32 }).await;
+            self.wait_ready().await;
+        }
+        self.ce_high();
+    }
+
+    /// Read one plane's page register after `multi_plane_read` (06h-addr-E0h)
+    pub async fn read_plane_data(&mut self, page_addr: u32, buffer: &mut [u8]) {
+        self.send_command(0x06).await;
+        self.send_page_address(page_addr).await;
+        self.send_command(0xE0).await;
+        self.delay_ns(300); // tCCS
+        self.read_data(buffer);
+        self.ce_high();
+    }
+
+    /// Load a plane's data (80h-11h), or start programming all planes (80h-10h)
+    pub async fn multi_plane_program(&mut self, page_addr: u32, data: &[u8], last: bool) -> bool {
+        self.send_command(0x80).await;
+        self.send_page_address(page_addr).await;
+        self.write_data(data);
+        self.send_command(if last { 0x10 } else { 0x11 }).await;
+        self.wait_ready().await;
+        if !last {
+            self.ce_high();
+            return true;
+        }
+        (self.read_status().await & 0x01) == 0
+    }
+
+    /// Erase one block per plane: 60h-row-D1h for each plane, 60h-row-D0h for the last
+    pub async fn multi_plane_erase(&mut self, page_addrs: &[u32]) -> bool {
+        for (i, &page_addr) in page_addrs.iter().enumerate() {
+            self.send_command(0x60).await;
+            self.send_row_address(page_addr).await;
+            let last = i + 1 == page_addrs.len();
+            self.send_command(if last { 0xD0 } else { 0xD1 }).await;
+            self.wait_ready().await;
+        }
+        (self.read_status().await & 0x01) == 0
+    }
 }
@@ -50,6 +50,28 @@ pub mod commands {
     pub const RESET: u8 = 0x99;
     pub const ENTER_4BYTE_MODE: u8 = 0xB7;
     pub const EXIT_4BYTE_MODE: u8 = 0xE9;
+    pub const WRITE_EXT_ADDR: u8 = 0xC5;
+    pub const READ_EXT_ADDR: u8 = 0xC8;
+    pub const WRITE_BANK_REG: u8 = 0x17;  // Spansion BRWR
+    pub const READ_BANK_REG: u8 = 0x16;   // Spansion BRRD
+
+    // Dedicated 4-byte address opcodes
+    pub const READ_4B: u8 = 0x13;
+    pub const FAST_READ_4B: u8 = 0x0C;
+    pub const PAGE_PROGRAM_4B: u8 = 0x12;
+    pub const SECTOR_ERASE_4B: u8 = 0x21;
+    pub const BLOCK_ERASE_32K_4B: u8 = 0x5C;
+    pub const BLOCK_ERASE_64K_4B: u8 = 0xDC;
+
+    // Unique ID, security registers and OTP
+    pub const READ_UNIQUE_ID: u8 = 0x4B;
+    pub const READ_SECURITY_REG: u8 = 0x48;
+    pub const PROGRAM_SECURITY_REG: u8 = 0x42;
+    pub const ERASE_SECURITY_REG: u8 = 0x44;
+    pub const ENTER_SECURED_OTP: u8 = 0xB1;
+    pub const EXIT_SECURED_OTP: u8 = 0xC1;
+    pub const READ_SECURITY_SCUR: u8 = 0x2B;
+    pub const WRITE_SECURITY_SCUR: u8 = 0x2F;
 }
 
 /// Status register 1 bits
@@ -64,6 +86,32 @@ pub mod status1 {
     pub const SRP0: u8 = 0x80;
 }
 
+/// Address mode codes (see core `FourByteMode`)
+pub mod four_byte_mode {
+    pub const THREE_BYTE: u8 = 0;
+    pub const DEDICATED_OPCODES: u8 = 1;
+    pub const ENTER_COMMAND: u8 = 2;
+    pub const ENTER_COMMAND_WREN: u8 = 3;
+    pub const EXTENDED_ADDRESS_REGISTER: u8 = 4;
+    pub const BANK_REGISTER: u8 = 5;
+    pub const ALWAYS: u8 = 6;
+}
+
+/// 4-byte address opcode for a 3-byte address opcode
+///
+/// 32K erase is left out: 5Ch is optional (W25Q256 lacks it), see
+/// `SpiNorController::erase_32k_4b`.
+fn dedicated_opcode(opcode: u8) -> u8 {
+    match opcode {
+        commands::READ => commands::READ_4B,
+        commands::FAST_READ => commands::FAST_READ_4B,
+        commands::PAGE_PROGRAM => commands::PAGE_PROGRAM_4B,
+        commands::SECTOR_ERASE => commands::SECTOR_ERASE_4B,
+        commands::BLOCK_ERASE_64K => commands::BLOCK_ERASE_64K_4B,
+        other => other,
+    }
+}
+
 
 /// SPI NOR controller for STM32F1
 ///
@@ -73,6 +121,12 @@ pub struct SpiNorController<'d, SPI: embassy_stm32::spi::Instance> {
     spi: Spi<'d, SPI, Blocking>,
     cs: Output<'d>,
     address_bytes: u8,
+    /// Address mode code, see `four_byte_mode`
+    four_byte_mode: u8,
+    /// A31-A24 currently in the extended address / bank register
+    bank: u8,
+    /// The chip implements 5Ch (4-byte 32K erase), from SFDP 4BAIT
+    erase_32k_4b: bool,
 }
 
 impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
@@ -89,9 +143,17 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
             spi,
             cs,
             address_bytes: 3,  // Default to 3-byte addressing
+            four_byte_mode: four_byte_mode::THREE_BYTE,
+            bank: 0,
+            erase_32k_4b: false,
         }
     }
 
+    /// Hand back the SPI bus and CS# so another controller can take them
+    pub fn release(self) -> (Spi<'d, SPI, Blocking>, Output<'d>) {
+        (self.spi, self.cs)
+    }
+
     /// Set address byte count (3 or 4 bytes)
     ///
     /// Use 4-byte addressing for chips > 16MB (128Mbit)
@@ -168,22 +230,21 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
     /// * `address` - Start address
     /// * `buf` - Buffer to store read data
     pub fn read(&mut self, address: u32, buf: &mut [u8]) {
-        self.cs_low();
-
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[commands::READ, addr[0], addr[1], addr[2], addr[3]]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::READ,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-            ]);
+        // Banked modes only reach one 16 MiB bank per command
+        let mut offset = 0;
+        while offset < buf.len() {
+            let address = address.wrapping_add(offset as u32);
+            let chunk = self.bank_chunk(address, buf.len() - offset);
+            self.select_bank(address);
+            self.cs_low();
+
+            let (cmd, len) = self.address_command(commands::READ, address);
+            let _ = self.spi.blocking_write(&cmd[..len]);
+
+            let _ = self.spi.blocking_read(&mut buf[offset..offset + chunk]);
+            self.cs_high();
+            offset += chunk;
         }
-
-        let _ = self.spi.blocking_read(buf);
-        self.cs_high();
     }
 
     /// Fast read with dummy cycle (command 0x0B)
@@ -195,27 +256,22 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
     /// * `address` - Start address
     /// * `buf` - Buffer to store read data
     pub fn fast_read(&mut self, address: u32, buf: &mut [u8]) {
-        self.cs_low();
-
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[
-                commands::FAST_READ,
-                addr[0], addr[1], addr[2], addr[3],
-                0x00, // dummy byte
-            ]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::FAST_READ,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-                0x00, // dummy byte
-            ]);
+        // Banked modes only reach one 16 MiB bank per command
+        let mut offset = 0;
+        while offset < buf.len() {
+            let address = address.wrapping_add(offset as u32);
+            let chunk = self.bank_chunk(address, buf.len() - offset);
+            self.select_bank(address);
+            self.cs_low();
+
+            let (cmd, len) = self.address_command(commands::FAST_READ, address);
+            let _ = self.spi.blocking_write(&cmd[..len]);
+            let _ = self.spi.blocking_write(&[0x00]); // dummy byte
+
+            let _ = self.spi.blocking_read(&mut buf[offset..offset + chunk]);
+            self.cs_high();
+            offset += chunk;
         }
-
-        let _ = self.spi.blocking_read(buf);
-        self.cs_high();
     }
 
 
@@ -351,24 +407,13 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
             return false;
         }
 
+        self.select_bank(address);
         self.write_enable();
 
         self.cs_low();
 
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[
-                commands::PAGE_PROGRAM,
-                addr[0], addr[1], addr[2], addr[3],
-            ]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::PAGE_PROGRAM,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-            ]);
-        }
+        let (cmd, len) = self.address_command(commands::PAGE_PROGRAM, address);
+        let _ = self.spi.blocking_write(&cmd[..len]);
 
         let _ = self.spi.blocking_write(data);
         self.cs_high();
@@ -389,24 +434,13 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
     /// # Returns
     /// `true` if erase succeeded, `false` if timeout or error.
     pub async fn sector_erase(&mut self, address: u32) -> bool {
+        self.select_bank(address);
         self.write_enable();
 
         self.cs_low();
 
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[
-                commands::SECTOR_ERASE,
-                addr[0], addr[1], addr[2], addr[3],
-            ]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::SECTOR_ERASE,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-            ]);
-        }
+        let (cmd, len) = self.address_command(commands::SECTOR_ERASE, address);
+        let _ = self.spi.blocking_write(&cmd[..len]);
 
         self.cs_high();
 
@@ -424,24 +458,17 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
     /// # Returns
     /// `true` if erase succeeded, `false` if timeout or error.
     pub async fn block_erase_32k(&mut self, address: u32) -> bool {
+        // 52h would take a 3-byte address here
+        if self.four_byte_mode == four_byte_mode::DEDICATED_OPCODES && !self.erase_32k_4b {
+            return false;
+        }
+        self.select_bank(address);
         self.write_enable();
 
         self.cs_low();
 
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[
-                commands::BLOCK_ERASE_32K,
-                addr[0], addr[1], addr[2], addr[3],
-            ]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::BLOCK_ERASE_32K,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-            ]);
-        }
+        let (cmd, len) = self.address_command(commands::BLOCK_ERASE_32K, address);
+        let _ = self.spi.blocking_write(&cmd[..len]);
 
         self.cs_high();
 
@@ -459,24 +486,13 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
     /// # Returns
     /// `true` if erase succeeded, `false` if timeout or error.
     pub async fn block_erase_64k(&mut self, address: u32) -> bool {
+        self.select_bank(address);
         self.write_enable();
 
         self.cs_low();
 
-        if self.address_bytes == 4 {
-            let addr = self.build_address(address);
-            let _ = self.spi.blocking_write(&[
-                commands::BLOCK_ERASE_64K,
-                addr[0], addr[1], addr[2], addr[3],
-            ]);
-        } else {
-            let _ = self.spi.blocking_write(&[
-                commands::BLOCK_ERASE_64K,
-                ((address >> 16) & 0xFF) as u8,
-                ((address >> 8) & 0xFF) as u8,
-                (address & 0xFF) as u8,
-            ]);
-        }
+        let (cmd, len) = self.address_command(commands::BLOCK_ERASE_64K, address);
+        let _ = self.spi.blocking_write(&cmd[..len]);
 
         self.cs_high();
 
@@ -510,6 +526,104 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
         self.write_cmd(&[commands::RESET]);
         // Wait for reset to complete (typically 30us)
         Timer::after(Duration::from_micros(100)).await;
+
+        // Reset drops the chip back to 3-byte mode, bank 0
+        let mode = self.four_byte_mode;
+        self.four_byte_mode = four_byte_mode::THREE_BYTE;
+        self.bank = 0;
+        self.address_bytes = 3;
+        self.set_address_mode(mode, self.erase_32k_4b);
+    }
+
+    // ========== Unique ID, Security Registers and OTP ==========
+
+    /// Read the factory unique ID (command 0x4B, 4 dummy bytes)
+    pub fn read_unique_id(&mut self, buf: &mut [u8]) {
+        self.cs_low();
+        let _ = self.spi.blocking_write(&[commands::READ_UNIQUE_ID, 0x00, 0x00, 0x00, 0x00]);
+        let _ = self.spi.blocking_read(buf);
+        self.cs_high();
+    }
+
+    /// Read a security register (command 0x48, 3-byte address + 1 dummy byte)
+    ///
+    /// Register n lives at address n << 12.
+    pub fn read_security_register(&mut self, address: u32, buf: &mut [u8]) {
+        self.cs_low();
+        let _ = self.spi.blocking_write(&[
+            commands::READ_SECURITY_REG,
+            ((address >> 16) & 0xFF) as u8,
+            ((address >> 8) & 0xFF) as u8,
+            (address & 0xFF) as u8,
+            0x00, // dummy byte
+        ]);
+        let _ = self.spi.blocking_read(buf);
+        self.cs_high();
+    }
+
+    /// Program a security register (command 0x42, up to 256 bytes)
+    pub async fn program_security_register(&mut self, address: u32, data: &[u8]) -> bool {
+        if data.is_empty() || data.len() > 256 {
+            return false;
+        }
+
+        self.write_enable();
+        self.cs_low();
+        let _ = self.spi.blocking_write(&[
+            commands::PROGRAM_SECURITY_REG,
+            ((address >> 16) & 0xFF) as u8,
+            ((address >> 8) & 0xFF) as u8,
+            (address & 0xFF) as u8,
+        ]);
+        let _ = self.spi.blocking_write(data);
+        self.cs_high();
+
+        self.wait_ready_timeout(10).await.is_some()
+    }
+
+    /// Erase a security register (command 0x44)
+    pub async fn erase_security_register(&mut self, address: u32) -> bool {
+        self.write_enable();
+        self.write_cmd(&[
+            commands::ERASE_SECURITY_REG,
+            ((address >> 16) & 0xFF) as u8,
+            ((address >> 8) & 0xFF) as u8,
+            (address & 0xFF) as u8,
+        ]);
+
+        self.wait_ready_timeout(500).await.is_some()
+    }
+
+    /// Enter secured OTP mode (Macronix ENSO, 0xB1)
+    ///
+    /// Reads and page programs address the OTP array until `exit_secured_otp`.
+    pub fn enter_secured_otp(&mut self) {
+        self.write_cmd(&[commands::ENTER_SECURED_OTP]);
+    }
+
+    /// Exit secured OTP mode (Macronix EXSO, 0xC1)
+    pub fn exit_secured_otp(&mut self) {
+        self.write_cmd(&[commands::EXIT_SECURED_OTP]);
+    }
+
+    /// Read the Macronix security register (RDSCUR, 0x2B)
+    pub fn read_security_scur(&mut self) -> u8 {
+        let mut value = [0u8; 1];
+        self.cs_low();
+        let _ = self.spi.blocking_write(&[commands::READ_SECURITY_SCUR]);
+        let _ = self.spi.blocking_read(&mut value);
+        self.cs_high();
+        value[0]
+    }
+
+    /// Lock down the secured OTP (WRSCUR, 0x2F sets LDSO)
+    ///
+    /// Irreversible; the host confirms before sending it.
+    pub async fn lock_secured_otp(&mut self) -> bool {
+        self.write_enable();
+        self.write_cmd(&[commands::WRITE_SECURITY_SCUR]);
+
+        self.wait_ready_timeout(10).await.is_some()
     }
 
     // ========== 4-Byte Address Mode ==========
@@ -529,4 +643,103 @@ impl<'d, SPI: embassy_stm32::spi::Instance> SpiNorController<'d, SPI> {
         self.write_cmd(&[commands::EXIT_4BYTE_MODE]);
         self.address_bytes = 3;
     }
+
+    /// Select how addresses above 16 MiB are reached (SpiNorSetAddressMode)
+    ///
+    /// Leaves the current mode first, so `THREE_BYTE` always returns the
+    /// chip to 3-byte mode with bank 0 selected. `erase_32k_4b` enables
+    /// 5Ch for 32K erases in `DEDICATED_OPCODES` mode.
+    pub fn set_address_mode(&mut self, mode: u8, erase_32k_4b: bool) -> bool {
+        if mode > four_byte_mode::ALWAYS {
+            return false;
+        }
+
+        match self.four_byte_mode {
+            four_byte_mode::ENTER_COMMAND => self.write_cmd(&[commands::EXIT_4BYTE_MODE]),
+            four_byte_mode::ENTER_COMMAND_WREN => {
+                self.write_enable();
+                self.write_cmd(&[commands::EXIT_4BYTE_MODE]);
+            }
+            four_byte_mode::EXTENDED_ADDRESS_REGISTER | four_byte_mode::BANK_REGISTER => {
+                if self.bank != 0 {
+                    self.write_bank(0);
+                }
+            }
+            _ => {}
+        }
+        self.bank = 0;
+        self.four_byte_mode = mode;
+        self.erase_32k_4b = erase_32k_4b;
+
+        match mode {
+            four_byte_mode::ENTER_COMMAND => self.write_cmd(&[commands::ENTER_4BYTE_MODE]),
+            four_byte_mode::ENTER_COMMAND_WREN => {
+                self.write_enable();
+                self.write_cmd(&[commands::ENTER_4BYTE_MODE]);
+            }
+            _ => {}
+        }
+        self.address_bytes = match mode {
+            four_byte_mode::DEDICATED_OPCODES
+            | four_byte_mode::ENTER_COMMAND
+            | four_byte_mode::ENTER_COMMAND_WREN
+            | four_byte_mode::ALWAYS => 4,
+            _ => 3,
+        };
+        true
+    }
+
+    /// Write A31-A24 to the extended address or bank register
+    fn write_bank(&mut self, bank: u8) {
+        if self.four_byte_mode == four_byte_mode::EXTENDED_ADDRESS_REGISTER {
+            self.write_enable();
+            self.write_cmd(&[commands::WRITE_EXT_ADDR, bank]);
+        } else {
+            self.write_cmd(&[commands::WRITE_BANK_REG, bank]);
+        }
+        self.bank = bank;
+    }
+
+    /// Bytes from `address` to the end of its bank, capped at `len`
+    fn bank_chunk(&self, address: u32, len: usize) -> usize {
+        if self.four_byte_mode != four_byte_mode::EXTENDED_ADDRESS_REGISTER
+            && self.four_byte_mode != four_byte_mode::BANK_REGISTER
+        {
+            return len;
+        }
+        let to_boundary = 0x100_0000 - (address & 0xFF_FFFF) as usize;
+        len.min(to_boundary)
+    }
+
+    /// Switch banks if `address` is outside the selected one (banked modes only)
+    fn select_bank(&mut self, address: u32) {
+        if self.four_byte_mode != four_byte_mode::EXTENDED_ADDRESS_REGISTER
+            && self.four_byte_mode != four_byte_mode::BANK_REGISTER
+        {
+            return;
+        }
+        let bank = (address >> 24) as u8;
+        if bank != self.bank {
+            self.write_bank(bank);
+        }
+    }
+
+    /// Opcode and address bytes for the current address mode
+    fn address_command(&self, opcode: u8, address: u32) -> ([u8; 5], usize) {
+        let opcode = if self.four_byte_mode == four_byte_mode::DEDICATED_OPCODES {
+            if opcode == commands::BLOCK_ERASE_32K && self.erase_32k_4b {
+                commands::BLOCK_ERASE_32K_4B
+            } else {
+                dedicated_opcode(opcode)
+            }
+        } else {
+            opcode
+        };
+        let addr = self.build_address(address);
+        if self.address_bytes == 4 {
+            ([opcode, addr[0], addr[1], addr[2], addr[3]], 5)
+        } else {
+            ([opcode, addr[1], addr[2], addr[3], 0], 4)
+        }
+    }
 }
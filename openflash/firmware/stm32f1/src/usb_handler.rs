@@ -9,10 +9,13 @@ use embassy_stm32::peripherals::SPI1;
 use embassy_usb::class::cdc_acm::CdcAcmClass;
 use embassy_usb::driver::Driver;
 
+use crate::nand_gpio::{NandController, NandTiming};
+use crate::spi_nand::SpiNandController;
 use crate::spi_nor::SpiNorController;
 
 const MAX_PAGE_SIZE: usize = 4352;
 const PACKET_SIZE: usize = 64;
+const MAX_PLANES: usize = 4;
 
 /// USB Protocol Commands
 #[repr(u8)]
@@ -24,12 +27,38 @@ pub enum Command {
     Reset = 0x08,
     SetInterface = 0x09,
 
-    // Parallel NAND commands (legacy 0x03-0x07)
-    NandCmd = 0x03,
-    NandAddr = 0x04,
-    NandReadPage = 0x05,
-    NandWritePage = 0x06,
-    ReadId = 0x07,
+    // Parallel NAND commands (0x10-0x1F, legacy 0x03-0x07 still accepted)
+    NandCmd = 0x10,
+    NandAddr = 0x11,
+    NandReadPage = 0x12,
+    NandWritePage = 0x13,
+    ReadId = 0x14,
+    NandErase = 0x15,
+    NandWriteData = 0x17,
+    NandReadData = 0x0A,
+    NandSelectCe = 0x18,
+    NandCacheRead = 0x19,
+    NandCacheProgram = 0x1A,
+    NandMultiPlaneRead = 0x1B,
+    NandMultiPlaneProgram = 0x1C,
+    NandMultiPlaneErase = 0x1D,
+
+    // SPI NAND commands (0x20-0x3F)
+    SpiNandReadId = 0x20,
+    SpiNandReset = 0x21,
+    SpiNandGetFeature = 0x22,
+    SpiNandSetFeature = 0x23,
+    SpiNandPageRead = 0x24,
+    SpiNandReadCache = 0x25,
+    SpiNandProgramLoad = 0x27,
+    SpiNandProgramExec = 0x29,
+    SpiNandBlockErase = 0x2A,
+    SpiNandWriteEnable = 0x2B,
+    SpiNandWriteDisable = 0x2C,
+    SpiNandReadEccStatus = 0x2D,
+    SpiNandDieSelect = 0x2E,
+    SpiNandReadCacheContinuous = 0x2F,
+    SpiNandReadCacheContinuousX4 = 0x30,
 
     // SPI NOR commands (0x60-0x7F)
     SpiNorReadJedecId = 0x60,
@@ -50,6 +79,15 @@ pub enum Command {
     SpiNorWriteEnable = 0x71,
     SpiNorWriteDisable = 0x72,
     SpiNorReset = 0x73,
+    SpiNorReadUniqueId = 0x74,
+    SpiNorReadSecurityReg = 0x75,
+    SpiNorProgramSecurityReg = 0x76,
+    SpiNorEraseSecurityReg = 0x77,
+    SpiNorEnterSecuredOtp = 0x78,
+    SpiNorExitSecuredOtp = 0x79,
+    SpiNorReadSecurityScur = 0x7A,
+    SpiNorLockSecuredOtp = 0x7B,
+    SpiNorSetAddressMode = 0x7E,
 }
 
 impl Command {
@@ -61,14 +99,39 @@ impl Command {
             0x08 => Some(Command::Reset),
             0x09 => Some(Command::SetInterface),
 
-            // Parallel NAND (legacy)
-            0x03 => Some(Command::NandCmd),
-            0x04 => Some(Command::NandAddr),
-            0x05 => Some(Command::NandReadPage),
-            0x06 => Some(Command::NandWritePage),
-            0x07 => Some(Command::ReadId),
+            // Parallel NAND (0x03-0x07 are the legacy codes)
+            0x10 | 0x03 => Some(Command::NandCmd),
+            0x11 | 0x04 => Some(Command::NandAddr),
+            0x12 | 0x05 => Some(Command::NandReadPage),
+            0x13 | 0x06 => Some(Command::NandWritePage),
+            0x14 | 0x07 => Some(Command::ReadId),
+            0x15 => Some(Command::NandErase),
+            0x17 => Some(Command::NandWriteData),
+            0x0A => Some(Command::NandReadData),
+            0x18 => Some(Command::NandSelectCe),
+            0x19 => Some(Command::NandCacheRead),
+            0x1A => Some(Command::NandCacheProgram),
+            0x1B => Some(Command::NandMultiPlaneRead),
+            0x1C => Some(Command::NandMultiPlaneProgram),
+            0x1D => Some(Command::NandMultiPlaneErase),
 
             // SPI NOR
+            0x20 => Some(Command::SpiNandReadId),
+            0x21 => Some(Command::SpiNandReset),
+            0x22 => Some(Command::SpiNandGetFeature),
+            0x23 => Some(Command::SpiNandSetFeature),
+            0x24 => Some(Command::SpiNandPageRead),
+            0x25 => Some(Command::SpiNandReadCache),
+            0x27 => Some(Command::SpiNandProgramLoad),
+            0x29 => Some(Command::SpiNandProgramExec),
+            0x2A => Some(Command::SpiNandBlockErase),
+            0x2B => Some(Command::SpiNandWriteEnable),
+            0x2C => Some(Command::SpiNandWriteDisable),
+            0x2D => Some(Command::SpiNandReadEccStatus),
+            0x2E => Some(Command::SpiNandDieSelect),
+            0x2F => Some(Command::SpiNandReadCacheContinuous),
+            0x30 => Some(Command::SpiNandReadCacheContinuousX4),
+
             0x60 => Some(Command::SpiNorReadJedecId),
             0x61 => Some(Command::SpiNorReadSfdp),
             0x62 => Some(Command::SpiNorRead),
@@ -87,6 +150,15 @@ impl Command {
             0x71 => Some(Command::SpiNorWriteEnable),
             0x72 => Some(Command::SpiNorWriteDisable),
             0x73 => Some(Command::SpiNorReset),
+            0x74 => Some(Command::SpiNorReadUniqueId),
+            0x75 => Some(Command::SpiNorReadSecurityReg),
+            0x76 => Some(Command::SpiNorProgramSecurityReg),
+            0x77 => Some(Command::SpiNorEraseSecurityReg),
+            0x78 => Some(Command::SpiNorEnterSecuredOtp),
+            0x79 => Some(Command::SpiNorExitSecuredOtp),
+            0x7A => Some(Command::SpiNorReadSecurityScur),
+            0x7B => Some(Command::SpiNorLockSecuredOtp),
+            0x7E => Some(Command::SpiNorSetAddressMode),
 
             _ => None,
         }
@@ -113,16 +185,20 @@ pub enum FlashInterface {
 
 pub struct UsbHandler<'d, D: Driver<'d>> {
     pub class: CdcAcmClass<'d, D>,
+    nand: NandController<'d>,
     spi_nor: Option<SpiNorController<'d, SPI1>>,
+    spi_nand: Option<SpiNandController<'d, SPI1>>,
     page_buffer: [u8; MAX_PAGE_SIZE],
     current_interface: FlashInterface,
 }
 
 impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
-    pub fn new(class: CdcAcmClass<'d, D>) -> Self {
+    pub fn new(class: CdcAcmClass<'d, D>, nand: NandController<'d>) -> Self {
         Self {
             class,
+            nand,
             spi_nor: None,
+            spi_nand: None,
             page_buffer: [0xFF; MAX_PAGE_SIZE],
             current_interface: FlashInterface::ParallelNand,
         }
@@ -165,13 +241,40 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
             Some(Command::Reset) => self.handle_reset().await,
             Some(Command::SetInterface) => self.handle_set_interface(args).await,
 
-            // Parallel NAND commands (legacy stubs)
+            // Parallel NAND commands
             Some(Command::NandCmd) => self.handle_nand_cmd(args).await,
             Some(Command::NandAddr) => self.handle_nand_addr(args).await,
+            Some(Command::NandWriteData) => self.handle_nand_write_data(args).await,
+            Some(Command::NandReadData) => self.handle_nand_read_data(args).await,
+            Some(Command::NandErase) => self.handle_erase(args).await,
+            Some(Command::NandSelectCe) => self.handle_select_ce(args).await,
+            Some(Command::NandCacheRead) => self.handle_cache_read(args).await,
+            Some(Command::NandCacheProgram) => self.handle_cache_program(args).await,
+            Some(Command::NandMultiPlaneRead) => self.handle_multi_plane_read(args).await,
+            Some(Command::NandMultiPlaneProgram) => self.handle_multi_plane_program(args).await,
+            Some(Command::NandMultiPlaneErase) => self.handle_multi_plane_erase(args).await,
             Some(Command::NandReadPage) => self.handle_read_page(args).await,
             Some(Command::NandWritePage) => self.handle_write_page(args).await,
             Some(Command::ReadId) => self.handle_read_id().await,
 
+            // SPI NAND commands
+            Some(Command::SpiNandReadId) => self.handle_spi_nand_read_id().await,
+            Some(Command::SpiNandReset) => self.handle_spi_nand_reset().await,
+            Some(Command::SpiNandGetFeature) => self.handle_spi_nand_get_feature(args).await,
+            Some(Command::SpiNandSetFeature) => self.handle_spi_nand_set_feature(args).await,
+            Some(Command::SpiNandPageRead) => self.handle_spi_nand_page_read(args).await,
+            Some(Command::SpiNandReadCache) => self.handle_spi_nand_read_cache(args).await,
+            Some(Command::SpiNandProgramLoad) => self.handle_spi_nand_program_load(args).await,
+            Some(Command::SpiNandProgramExec) => self.handle_spi_nand_program_exec(args).await,
+            Some(Command::SpiNandBlockErase) => self.handle_spi_nand_block_erase(args).await,
+            Some(Command::SpiNandWriteEnable) => self.handle_spi_nand_write_enable().await,
+            Some(Command::SpiNandWriteDisable) => self.handle_spi_nand_write_disable().await,
+            Some(Command::SpiNandReadEccStatus) => self.handle_spi_nand_read_ecc_status(args).await,
+            Some(Command::SpiNandDieSelect) => self.handle_spi_nand_die_select(args).await,
+            Some(cmd @ (Command::SpiNandReadCacheContinuous | Command::SpiNandReadCacheContinuousX4)) => {
+                self.handle_spi_nand_read_continuous(cmd, args).await
+            }
+
             // SPI NOR commands
             Some(Command::SpiNorReadJedecId) => self.handle_spi_nor_read_jedec_id().await,
             Some(Command::SpiNorReadSfdp) => self.handle_spi_nor_read_sfdp(args).await,
@@ -191,6 +294,15 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
             Some(Command::SpiNorWriteEnable) => self.handle_spi_nor_write_enable().await,
             Some(Command::SpiNorWriteDisable) => self.handle_spi_nor_write_disable().await,
             Some(Command::SpiNorReset) => self.handle_spi_nor_reset().await,
+            Some(Command::SpiNorReadUniqueId) => self.handle_spi_nor_read_unique_id(args).await,
+            Some(Command::SpiNorReadSecurityReg) => self.handle_spi_nor_read_security_reg(args).await,
+            Some(Command::SpiNorProgramSecurityReg) => self.handle_spi_nor_program_security_reg(args).await,
+            Some(Command::SpiNorEraseSecurityReg) => self.handle_spi_nor_erase_security_reg(args).await,
+            Some(Command::SpiNorEnterSecuredOtp) => self.handle_spi_nor_enter_secured_otp().await,
+            Some(Command::SpiNorExitSecuredOtp) => self.handle_spi_nor_exit_secured_otp().await,
+            Some(Command::SpiNorReadSecurityScur) => self.handle_spi_nor_read_security_scur().await,
+            Some(Command::SpiNorLockSecuredOtp) => self.handle_spi_nor_lock_secured_otp().await,
+            Some(Command::SpiNorSetAddressMode) => self.handle_spi_nor_set_address_mode(args).await,
 
             None => {
                 warn!("Unknown command: 0x{:02X}", cmd_byte);
@@ -207,8 +319,17 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
     }
 
     async fn handle_bus_config(&mut self, args: &[u8]) {
+        // args: [timing mode, tRP, tWP, tREA] in ns
         if args.len() >= 4 {
-            info!("BUS_CONFIG");
+            let (mode, t_rp, t_wp, t_rea) = (args[0], args[1] as u32, args[2] as u32, args[3] as u32);
+            // Data is sampled at the end of the RE# pulse, so it must cover tREA
+            let timing = if mode == 0 {
+                NandTiming::default()
+            } else {
+                NandTiming { t_rp: t_rp.max(t_rea), t_wp, ..NandTiming::fast() }
+            };
+            self.nand.set_timing(timing);
+            info!("BUS_CONFIG: mode {} tRP={} tWP={} tREA={}", mode, t_rp, t_wp, t_rea);
             self.send_response(&[Command::BusConfig as u8, Status::Ok as u8]).await;
         } else {
             self.send_response(&[Command::BusConfig as u8, Status::Error as u8]).await;
@@ -233,6 +354,7 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
                 }
             };
             self.current_interface = iface;
+            self.switch_spi_bus(iface);
             info!("SET_INTERFACE: {:?}", iface);
             self.send_response(&[Command::SetInterface as u8, Status::Ok as u8]).await;
         } else {
@@ -241,11 +363,13 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
     }
 
 
-    // ========== Parallel NAND Command Handlers (Legacy Stubs) ==========
+    // ========== Parallel NAND Command Handlers ==========
 
     async fn handle_nand_cmd(&mut self, args: &[u8]) {
         if !args.is_empty() {
-            info!("NAND_CMD: 0x{:02X}", args[0]);
+            let cmd = args[0];
+            info!("NAND_CMD: 0x{:02X}", cmd);
+            self.nand.send_command(cmd).await;
             self.send_response(&[Command::NandCmd as u8, Status::Ok as u8]).await;
         } else {
             self.send_response(&[Command::NandCmd as u8, Status::Error as u8]).await;
@@ -254,13 +378,44 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
 
     async fn handle_nand_addr(&mut self, args: &[u8]) {
         if !args.is_empty() {
-            info!("NAND_ADDR: 0x{:02X}", args[0]);
+            let addr = args[0];
+            info!("NAND_ADDR: 0x{:02X}", addr);
+            self.nand.send_address(addr).await;
             self.send_response(&[Command::NandAddr as u8, Status::Ok as u8]).await;
         } else {
             self.send_response(&[Command::NandAddr as u8, Status::Error as u8]).await;
         }
     }
 
+    /// Handle NAND Write Data command (0x17): one data-in cycle, e.g. a
+    /// SET FEATURES parameter byte
+    async fn handle_nand_write_data(&mut self, args: &[u8]) {
+        if !args.is_empty() {
+            let data = args[0];
+            info!("NAND_WRITE_DATA: 0x{:02X}", data);
+            self.nand.write_byte(data);
+            self.send_response(&[Command::NandWriteData as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::NandWriteData as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle NAND Read Data command (0x0A): data-out cycles after
+    /// NandCmd/NandAddr, e.g. READ PARAMETER PAGE or GET FEATURES
+    /// Args: [len u16]
+    async fn handle_nand_read_data(&mut self, args: &[u8]) {
+        if args.len() >= 2 {
+            let len = (u16::from_le_bytes([args[0], args[1]]) as usize).min(MAX_PAGE_SIZE);
+            info!("NAND_READ_DATA: len={}", len);
+            // The chip is busy (tR/tFEAT) until data-out is ready
+            self.nand.wait_ready().await;
+            self.nand.read_data(&mut self.page_buffer[..len]);
+            self.send_data_chunked(len).await;
+        } else {
+            self.send_response(&[Command::NandReadData as u8, Status::Error as u8]).await;
+        }
+    }
+
     async fn handle_read_page(&mut self, args: &[u8]) {
         if args.len() >= 6 {
             let page_addr = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
@@ -268,11 +423,7 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
             let size = page_size.min(MAX_PAGE_SIZE);
 
             info!("READ_PAGE: addr={}, size={}", page_addr, size);
-
-            for i in 0..size {
-                self.page_buffer[i] = 0xFF;
-            }
-
+            self.nand.read_page(page_addr, &mut self.page_buffer[..size]).await;
             self.send_data_chunked(size).await;
         } else {
             self.send_response(&[Command::NandReadPage as u8, Status::Error as u8]).await;
@@ -287,7 +438,9 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
 
             info!("WRITE_PAGE: addr={}, size={}", page_addr, size);
 
-            if self.receive_data_chunked(size).await {
+            if self.receive_data_chunked(size).await
+                && self.nand.program_page(page_addr, &self.page_buffer[..size]).await
+            {
                 self.send_response(&[Command::NandWritePage as u8, Status::Ok as u8]).await;
             } else {
                 self.send_response(&[Command::NandWritePage as u8, Status::Error as u8]).await;
@@ -297,12 +450,401 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
         }
     }
 
+    /// args: [row u32, page_size u16, count u16]; streams `count` pages (count > 0)
+    async fn handle_cache_read(&mut self, args: &[u8]) {
+        let count = if args.len() >= 8 { u16::from_le_bytes([args[6], args[7]]) } else { 0 };
+        // Reject count 0 before 00h-30h, or the chip is left mid cache read
+        if count == 0 {
+            self.send_response(&[Command::NandCacheRead as u8, Status::Error as u8]).await;
+            return;
+        }
+        let page_addr = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+        let size = (u16::from_le_bytes([args[4], args[5]]) as usize).min(MAX_PAGE_SIZE);
+
+        info!("CACHE_READ: addr={}, size={}, count={}", page_addr, size, count);
+        self.nand.cache_read_start(page_addr).await;
+        for i in 0..count {
+            let last = i + 1 == count;
+            self.nand.cache_read_next(last, &mut self.page_buffer[..size]).await;
+            self.send_data_chunked(size).await;
+        }
+    }
+
+    /// args: [row u32, page_size u16, last u8], followed by page data
+    async fn handle_cache_program(&mut self, args: &[u8]) {
+        if args.len() >= 7 {
+            let page_addr = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+            let size = (u16::from_le_bytes([args[4], args[5]]) as usize).min(MAX_PAGE_SIZE);
+            let last = args[6] != 0;
+
+            if self.receive_data_chunked(size).await
+                && self.nand.cache_program(page_addr, &self.page_buffer[..size], last).await
+            {
+                self.send_response(&[Command::NandCacheProgram as u8, Status::Ok as u8]).await;
+                return;
+            }
+            warn!("Cache program failed at {}", page_addr);
+        }
+        self.send_response(&[Command::NandCacheProgram as u8, Status::Error as u8]).await;
+    }
+
+    /// args: [page_size u16, planes u8, row u32 per plane]; streams one page per plane
+    async fn handle_multi_plane_read(&mut self, args: &[u8]) {
+        let Some(rows) = parse_plane_rows(args.get(2..).unwrap_or(&[])) else {
+            self.send_response(&[Command::NandMultiPlaneRead as u8, Status::Error as u8]).await;
+            return;
+        };
+        let size = (u16::from_le_bytes([args[0], args[1]]) as usize).min(MAX_PAGE_SIZE);
+
+        info!("MULTI_PLANE_READ: planes={}, size={}", rows.len(), size);
+        self.nand.multi_plane_read(&rows).await;
+        for &page_addr in rows.iter() {
+            self.nand.read_plane_data(page_addr, &mut self.page_buffer[..size]).await;
+            self.send_data_chunked(size).await;
+        }
+    }
+
+    /// args: [row u32, page_size u16, last u8], followed by page data
+    async fn handle_multi_plane_program(&mut self, args: &[u8]) {
+        if args.len() >= 7 {
+            let page_addr = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+            let size = (u16::from_le_bytes([args[4], args[5]]) as usize).min(MAX_PAGE_SIZE);
+            let last = args[6] != 0;
+
+            if self.receive_data_chunked(size).await
+                && self.nand.multi_plane_program(page_addr, &self.page_buffer[..size], last).await
+            {
+                self.send_response(&[Command::NandMultiPlaneProgram as u8, Status::Ok as u8]).await;
+                return;
+            }
+            warn!("Multi-plane program failed at {}", page_addr);
+        }
+        self.send_response(&[Command::NandMultiPlaneProgram as u8, Status::Error as u8]).await;
+    }
+
+    /// args: [row u32]
+    async fn handle_erase(&mut self, args: &[u8]) {
+        let ok = match args.get(..4) {
+            Some(row) => {
+                let page_addr = u32::from_le_bytes([row[0], row[1], row[2], row[3]]);
+                info!("ERASE: row={}", page_addr);
+                // A one-plane group is a plain 60h-row-D0h erase
+                self.nand.multi_plane_erase(&[page_addr]).await
+            }
+            None => false,
+        };
+        let status = if ok { Status::Ok } else { Status::Error };
+        self.send_response(&[Command::NandErase as u8, status as u8]).await;
+    }
+
+    /// args: [planes u8, row u32 per plane]
+    async fn handle_multi_plane_erase(&mut self, args: &[u8]) {
+        let ok = match parse_plane_rows(args) {
+            Some(rows) => {
+                info!("MULTI_PLANE_ERASE: planes={}", rows.len());
+                self.nand.multi_plane_erase(&rows).await
+            }
+            None => false,
+        };
+        let status = if ok { Status::Ok } else { Status::Error };
+        self.send_response(&[Command::NandMultiPlaneErase as u8, status as u8]).await;
+    }
+
+    /// Handle NAND Select CE command (0x18)
+    /// Args: [ce]; only CE# and CE2# are wired, so CE 2/3 answer Error
+    async fn handle_select_ce(&mut self, args: &[u8]) {
+        if !args.is_empty() && self.nand.select_ce(args[0]) {
+            info!("SELECT_CE: {}", args[0]);
+            self.send_response(&[Command::NandSelectCe as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::NandSelectCe as u8, Status::Error as u8]).await;
+        }
+    }
+
     async fn handle_read_id(&mut self) {
         info!("READ_ID");
-        let response = [Command::ReadId as u8, Status::Ok as u8, 0xEC, 0xD7, 0x10, 0x95, 0x44];
+        let id = self.nand.read_id().await;
+        let response = [
+            Command::ReadId as u8,
+            Status::Ok as u8,
+            id[0], id[1], id[2], id[3], id[4],
+        ];
         self.send_response(&response).await;
     }
 
+    // ========== SPI NAND Command Handlers ==========
+
+    /// Hand SPI1 to the controller of the selected interface
+    ///
+    /// SPI NAND and SPI NOR share the bus and CS# (PA4), so only one
+    /// controller owns it at a time.
+    fn switch_spi_bus(&mut self, iface: FlashInterface) {
+        match iface {
+            FlashInterface::SpiNand => {
+                if let Some(spi_nor) = self.spi_nor.take() {
+                    let (spi, cs) = spi_nor.release();
+                    self.spi_nand = Some(SpiNandController::new(spi, cs));
+                }
+            }
+            FlashInterface::SpiNor => {
+                if let Some(spi_nand) = self.spi_nand.take() {
+                    let (spi, cs) = spi_nand.release();
+                    self.spi_nor = Some(SpiNorController::new(spi, cs));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle SPI NAND Read ID command (0x20)
+    async fn handle_spi_nand_read_id(&mut self) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            info!("SPI_NAND_READ_ID");
+            let id = spi_nand.read_id();
+            let response = [
+                Command::SpiNandReadId as u8,
+                Status::Ok as u8,
+                id[0], id[1], id[2],
+            ];
+            self.send_response(&response).await;
+        } else {
+            self.send_response(&[Command::SpiNandReadId as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Reset command (0x21)
+    async fn handle_spi_nand_reset(&mut self) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            info!("SPI_NAND_RESET");
+            spi_nand.reset().await;
+            self.send_response(&[Command::SpiNandReset as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::SpiNandReset as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Get Feature command (0x22)
+    /// Args: [feature_addr]
+    async fn handle_spi_nand_get_feature(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if !args.is_empty() {
+                let value = spi_nand.get_feature(args[0]);
+                info!("SPI_NAND_GET_FEATURE: 0x{:02X} = 0x{:02X}", args[0], value);
+                self.send_response(&[Command::SpiNandGetFeature as u8, Status::Ok as u8, value]).await;
+            } else {
+                self.send_response(&[Command::SpiNandGetFeature as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandGetFeature as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Set Feature command (0x23)
+    /// Args: [feature_addr, value]
+    async fn handle_spi_nand_set_feature(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if args.len() >= 2 {
+                info!("SPI_NAND_SET_FEATURE: 0x{:02X} = 0x{:02X}", args[0], args[1]);
+                spi_nand.set_feature(args[0], args[1]);
+                self.send_response(&[Command::SpiNandSetFeature as u8, Status::Ok as u8]).await;
+            } else {
+                self.send_response(&[Command::SpiNandSetFeature as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandSetFeature as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Page Read command (0x24) - array to cache
+    /// Args: [row_0, row_1, row_2, row_3]
+    async fn handle_spi_nand_page_read(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if args.len() >= 4 {
+                let row = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+                info!("SPI_NAND_PAGE_READ: row={}", row);
+                spi_nand.page_read_to_cache(row).await;
+                self.send_response(&[Command::SpiNandPageRead as u8, Status::Ok as u8]).await;
+            } else {
+                self.send_response(&[Command::SpiNandPageRead as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandPageRead as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Read From Cache command (0x25)
+    /// Args: [col_lo, col_hi, length_lo, length_hi]
+    async fn handle_spi_nand_read_cache(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if args.len() >= 4 {
+                let column = u16::from_le_bytes([args[0], args[1]]);
+                let length = u16::from_le_bytes([args[2], args[3]]) as usize;
+                let size = length.min(MAX_PAGE_SIZE);
+
+                info!("SPI_NAND_READ_CACHE: col={}, len={}", column, size);
+                spi_nand.read_from_cache(column, &mut self.page_buffer[..size]);
+                self.send_data_chunked(size).await;
+            } else {
+                self.send_response(&[Command::SpiNandReadCache as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandReadCache as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Program Load command (0x27), data follows in chunks
+    /// Args: [col_lo, col_hi, length_lo, length_hi]
+    async fn handle_spi_nand_program_load(&mut self, args: &[u8]) {
+        if self.spi_nand.is_some() && args.len() >= 4 {
+            let column = u16::from_le_bytes([args[0], args[1]]);
+            let length = u16::from_le_bytes([args[2], args[3]]) as usize;
+            let size = length.min(MAX_PAGE_SIZE);
+
+            info!("SPI_NAND_PROGRAM_LOAD: col={}, len={}", column, size);
+            if self.receive_data_chunked(size).await {
+                if let Some(ref mut spi_nand) = self.spi_nand {
+                    spi_nand.program_load(column, &self.page_buffer[..size]);
+                }
+                self.send_response(&[Command::SpiNandProgramLoad as u8, Status::Ok as u8]).await;
+            } else {
+                self.send_response(&[Command::SpiNandProgramLoad as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandProgramLoad as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Program Execute command (0x29) - cache to array
+    /// Args: [row_0, row_1, row_2, row_3]
+    async fn handle_spi_nand_program_exec(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if args.len() >= 4 {
+                let row = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+                info!("SPI_NAND_PROGRAM_EXEC: row={}", row);
+                if spi_nand.program_execute(row).await {
+                    self.send_response(&[Command::SpiNandProgramExec as u8, Status::Ok as u8]).await;
+                } else {
+                    warn!("SPI NAND program failed at row {}", row);
+                    self.send_response(&[Command::SpiNandProgramExec as u8, Status::Error as u8]).await;
+                }
+            } else {
+                self.send_response(&[Command::SpiNandProgramExec as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandProgramExec as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Block Erase command (0x2A)
+    /// Args: [row_0, row_1, row_2, row_3]
+    async fn handle_spi_nand_block_erase(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if args.len() >= 4 {
+                let row = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+                info!("SPI_NAND_BLOCK_ERASE: row={}", row);
+                if spi_nand.erase_block(row).await {
+                    self.send_response(&[Command::SpiNandBlockErase as u8, Status::Ok as u8]).await;
+                } else {
+                    warn!("SPI NAND erase failed at row {}", row);
+                    self.send_response(&[Command::SpiNandBlockErase as u8, Status::Error as u8]).await;
+                }
+            } else {
+                self.send_response(&[Command::SpiNandBlockErase as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandBlockErase as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Write Enable command (0x2B)
+    async fn handle_spi_nand_write_enable(&mut self) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            spi_nand.write_enable();
+            self.send_response(&[Command::SpiNandWriteEnable as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::SpiNandWriteEnable as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Write Disable command (0x2C)
+    async fn handle_spi_nand_write_disable(&mut self) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            spi_nand.write_disable();
+            self.send_response(&[Command::SpiNandWriteDisable as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::SpiNandWriteDisable as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND vendor Read ECC Status command (0x2D)
+    /// Args: [opcode] (Macronix 7Ch)
+    async fn handle_spi_nand_read_ecc_status(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if !args.is_empty() {
+                let value = spi_nand.read_ecc_status(args[0]);
+                info!("SPI_NAND_READ_ECC_STATUS: 0x{:02X} = 0x{:02X}", args[0], value);
+                self.send_response(&[Command::SpiNandReadEccStatus as u8, Status::Ok as u8, value]).await;
+            } else {
+                self.send_response(&[Command::SpiNandReadEccStatus as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandReadEccStatus as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Die Select command (0x2E) - stacked packages (C2h)
+    /// Args: [die]
+    async fn handle_spi_nand_die_select(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            if !args.is_empty() {
+                info!("SPI_NAND_DIE_SELECT: die={}", args[0]);
+                spi_nand.select_die(args[0]);
+                self.send_response(&[Command::SpiNandDieSelect as u8, Status::Ok as u8]).await;
+            } else {
+                self.send_response(&[Command::SpiNandDieSelect as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNandDieSelect as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NAND Continuous Read commands (0x2F, Quad 0x30)
+    /// Args: [row_0, row_1, row_2, row_3, pages_0, pages_1, pages_2, pages_3]
+    ///
+    /// Streams `pages` pages of main data (no OOB) one page at a time. The
+    /// bus has no IO2/IO3, so Quad requests stream over MISO as well.
+    async fn handle_spi_nand_read_continuous(&mut self, cmd: Command, args: &[u8]) {
+        let Some(ref mut spi_nand) = self.spi_nand else {
+            self.send_response(&[cmd as u8, Status::Error as u8]).await;
+            return;
+        };
+        if args.len() < 8 {
+            self.send_response(&[cmd as u8, Status::Error as u8]).await;
+            return;
+        }
+        let row = u32::from_le_bytes([args[0], args[1], args[2], args[3]]);
+        let pages = u32::from_le_bytes([args[4], args[5], args[6], args[7]]);
+        let page_size = (spi_nand.page_size() as usize).min(MAX_PAGE_SIZE);
+        if pages == 0 {
+            self.send_response(&[cmd as u8, Status::Error as u8]).await;
+            return;
+        }
+
+        info!("SPI_NAND_READ_CONTINUOUS: row={}, pages={}", row, pages);
+        spi_nand.set_continuous_read(true);
+        spi_nand.begin_continuous_read(row).await;
+        for _ in 0..pages {
+            if let Some(ref mut spi_nand) = self.spi_nand {
+                spi_nand.continue_read(&mut self.page_buffer[..page_size]);
+            }
+            self.send_data_chunked(page_size).await;
+        }
+        if let Some(ref mut spi_nand) = self.spi_nand {
+            spi_nand.end_continuous_read().await;
+            spi_nand.set_continuous_read(false);
+        }
+    }
+
     // ========== SPI NOR Command Handlers ==========
 
     /// Handle SPI NOR Read JEDEC ID command (0x60)
@@ -622,6 +1164,160 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
         }
     }
 
+    /// Handle SPI NOR Read Unique ID command (0x74)
+    /// Args: [length]
+    async fn handle_spi_nor_read_unique_id(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            if !args.is_empty() {
+                let size = (args[0] as usize).min(32);
+                info!("SPI_NOR_READ_UNIQUE_ID: len={}", size);
+
+                let mut response = [0u8; 34];
+                response[0] = Command::SpiNorReadUniqueId as u8;
+                response[1] = Status::Ok as u8;
+                spi_nor.read_unique_id(&mut response[2..2 + size]);
+                self.send_response(&response[..2 + size]).await;
+            } else {
+                self.send_response(&[Command::SpiNorReadUniqueId as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorReadUniqueId as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Read Security Register command (0x75)
+    /// Args: [addr_lo, addr_mid, addr_hi, length_lo, length_hi]
+    async fn handle_spi_nor_read_security_reg(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            if args.len() >= 5 {
+                let address = u32::from_le_bytes([args[0], args[1], args[2], 0]);
+                let length = u16::from_le_bytes([args[3], args[4]]) as usize;
+                let size = length.min(MAX_PAGE_SIZE);
+
+                info!("SPI_NOR_READ_SECURITY_REG: addr=0x{:06X}, len={}", address, size);
+                spi_nor.read_security_register(address, &mut self.page_buffer[..size]);
+                self.send_data_chunked(size).await;
+            } else {
+                self.send_response(&[Command::SpiNorReadSecurityReg as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorReadSecurityReg as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Program Security Register command (0x76)
+    /// Args: [addr_lo, addr_mid, addr_hi, length_lo, length_hi]
+    async fn handle_spi_nor_program_security_reg(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            if args.len() >= 5 {
+                let address = u32::from_le_bytes([args[0], args[1], args[2], 0]);
+                let length = u16::from_le_bytes([args[3], args[4]]) as usize;
+                let size = length.min(256);
+
+                info!("SPI_NOR_PROGRAM_SECURITY_REG: addr=0x{:06X}, len={}", address, size);
+
+                if self.receive_data_chunked(size).await
+                    && spi_nor.program_security_register(address, &self.page_buffer[..size]).await
+                {
+                    self.send_response(&[Command::SpiNorProgramSecurityReg as u8, Status::Ok as u8]).await;
+                } else {
+                    warn!("SPI NOR security register program failed at 0x{:06X}", address);
+                    self.send_response(&[Command::SpiNorProgramSecurityReg as u8, Status::Error as u8]).await;
+                }
+            } else {
+                self.send_response(&[Command::SpiNorProgramSecurityReg as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorProgramSecurityReg as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Erase Security Register command (0x77)
+    /// Args: [addr_lo, addr_mid, addr_hi]
+    async fn handle_spi_nor_erase_security_reg(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            if args.len() >= 3 {
+                let address = u32::from_le_bytes([args[0], args[1], args[2], 0]);
+                info!("SPI_NOR_ERASE_SECURITY_REG: addr=0x{:06X}", address);
+
+                if spi_nor.erase_security_register(address).await {
+                    self.send_response(&[Command::SpiNorEraseSecurityReg as u8, Status::Ok as u8]).await;
+                } else {
+                    warn!("SPI NOR security register erase failed at 0x{:06X}", address);
+                    self.send_response(&[Command::SpiNorEraseSecurityReg as u8, Status::Error as u8]).await;
+                }
+            } else {
+                self.send_response(&[Command::SpiNorEraseSecurityReg as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorEraseSecurityReg as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Enter Secured OTP command (0x78)
+    async fn handle_spi_nor_enter_secured_otp(&mut self) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            info!("SPI_NOR_ENTER_SECURED_OTP");
+            spi_nor.enter_secured_otp();
+            self.send_response(&[Command::SpiNorEnterSecuredOtp as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::SpiNorEnterSecuredOtp as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Exit Secured OTP command (0x79)
+    async fn handle_spi_nor_exit_secured_otp(&mut self) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            info!("SPI_NOR_EXIT_SECURED_OTP");
+            spi_nor.exit_secured_otp();
+            self.send_response(&[Command::SpiNorExitSecuredOtp as u8, Status::Ok as u8]).await;
+        } else {
+            self.send_response(&[Command::SpiNorExitSecuredOtp as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Read Security Register (RDSCUR) command (0x7A)
+    async fn handle_spi_nor_read_security_scur(&mut self) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            let value = spi_nor.read_security_scur();
+            info!("SPI_NOR_READ_SECURITY_SCUR: 0x{:02X}", value);
+            self.send_response(&[Command::SpiNorReadSecurityScur as u8, Status::Ok as u8, value]).await;
+        } else {
+            self.send_response(&[Command::SpiNorReadSecurityScur as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Lock Secured OTP command (0x7B) - irreversible
+    async fn handle_spi_nor_lock_secured_otp(&mut self) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            info!("SPI_NOR_LOCK_SECURED_OTP");
+            if spi_nor.lock_secured_otp().await {
+                self.send_response(&[Command::SpiNorLockSecuredOtp as u8, Status::Ok as u8]).await;
+            } else {
+                warn!("SPI NOR secured OTP lock failed");
+                self.send_response(&[Command::SpiNorLockSecuredOtp as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorLockSecuredOtp as u8, Status::Error as u8]).await;
+        }
+    }
+
+    /// Handle SPI NOR Set Address Mode command (0x7E)
+    /// Args: [mode, flags]; flags bit 0 = chip has 5Ch (4-byte 32K erase)
+    async fn handle_spi_nor_set_address_mode(&mut self, args: &[u8]) {
+        if let Some(ref mut spi_nor) = self.spi_nor {
+            let erase_32k_4b = args.get(1).is_some_and(|flags| flags & 1 != 0);
+            if !args.is_empty() && spi_nor.set_address_mode(args[0], erase_32k_4b) {
+                info!("SPI_NOR_SET_ADDRESS_MODE: {}", args[0]);
+                self.send_response(&[Command::SpiNorSetAddressMode as u8, Status::Ok as u8]).await;
+            } else {
+                self.send_response(&[Command::SpiNorSetAddressMode as u8, Status::Error as u8]).await;
+            }
+        } else {
+            self.send_response(&[Command::SpiNorSetAddressMode as u8, Status::Error as u8]).await;
+        }
+    }
+
     // ========== Helper Methods ==========
 
     async fn send_response(&mut self, data: &[u8]) {
@@ -656,3 +1352,18 @@ impl<'d, D: Driver<'d>> UsbHandler<'d, D> {
         true
     }
 }
+
+/// Parse `[count u8, row u32 LE * count]` into a plane row list
+fn parse_plane_rows(args: &[u8]) -> Option<heapless::Vec<u32, MAX_PLANES>> {
+    let (&count, rest) = args.split_first()?;
+    let count = count as usize;
+    if count == 0 || count > MAX_PLANES || rest.len() < count * 4 {
+        return None;
+    }
+    Some(
+        rest.chunks_exact(4)
+            .take(count)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect(),
+    )
+}
@@ -0,0 +1,415 @@
+//! FlexSPI NOR interface for Teensy 4.x
+//!
+//! Uses the i.MX RT1062's FlexSPI2 controller for octal and DTR NOR flash
+//! (Macronix OctaFlash, Micron Xccela, Infineon Semper). Each transfer is a
+//! LUT sequence, so every I/O mode from 1S-1S-1S to 8D-8D-8D is just a
+//! different set of LUT instructions; DQS from the flash is used as the
+//! read strobe in DTR modes. Commands run as IP commands through the IP
+//! FIFOs.
+
+use imxrt_ral as ral;
+
+/// FlexSPI LUT instruction opcodes
+pub mod lut {
+    pub const STOP: u8 = 0x00;
+    pub const CMD_SDR: u8 = 0x01;
+    pub const RADDR_SDR: u8 = 0x02;
+    pub const WRITE_SDR: u8 = 0x08;
+    pub const READ_SDR: u8 = 0x09;
+    pub const DUMMY_SDR: u8 = 0x0C;
+    pub const CMD_DDR: u8 = 0x21;
+    pub const RADDR_DDR: u8 = 0x22;
+    pub const WRITE_DDR: u8 = 0x28;
+    pub const READ_DDR: u8 = 0x29;
+    pub const DUMMY_DDR: u8 = 0x2C;
+}
+
+/// I/O modes in protocol code order (see core `SpiNorIoMode`):
+/// command, address and data lanes, DTR
+const IO_MODES: [(u8, u8, u8, bool); 15] = [
+    (1, 1, 1, false),
+    (1, 1, 2, false),
+    (1, 2, 2, false),
+    (2, 2, 2, false),
+    (1, 1, 4, false),
+    (1, 4, 4, false),
+    (4, 4, 4, false),
+    (1, 1, 8, false),
+    (1, 8, 8, false),
+    (8, 8, 8, false),
+    (1, 1, 1, true),
+    (1, 2, 2, true),
+    (1, 4, 4, true),
+    (4, 4, 4, true),
+    (8, 8, 8, true),
+];
+
+/// LUT pad field for a lane count
+fn pads(lanes: u8) -> u8 {
+    match lanes {
+        8 => 3,
+        4 => 2,
+        2 => 1,
+        _ => 0,
+    }
+}
+
+/// One 16-bit LUT instruction
+fn instr(opcode: u8, lanes: u8, operand: u8) -> u16 {
+    ((opcode as u16) << 10) | ((pads(lanes) as u16) << 8) | operand as u16
+}
+
+/// FlexSPI2 register bits used here
+mod reg {
+    pub const MCR0_SWRESET: u32 = 1 << 0;
+    pub const MCR0_MDIS: u32 = 1 << 1;
+    /// Read strobe: 1 = loopback from the DQS pad, 3 = flash-provided DQS
+    pub const MCR0_RXCLKSRC_SHIFT: u32 = 4;
+    pub const INTR_IPCMDDONE: u32 = 1 << 0;
+    pub const INTR_IPCMDERR: u32 = 1 << 3;
+    pub const INTR_IPRXWA: u32 = 1 << 5;
+    pub const INTR_IPTXWE: u32 = 1 << 6;
+    pub const FIFO_CLEAR: u32 = 1 << 0;
+    pub const IPCR1_ISEQID_SHIFT: u32 = 16;
+    pub const IPCMD_TRG: u32 = 1 << 0;
+    pub const LUT_KEY: u32 = 0x5AF0_5AF0;
+    pub const LUTCR_LOCK: u32 = 1 << 0;
+    pub const LUTCR_UNLOCK: u32 = 1 << 1;
+    /// DLL override, zero delay: enough below ~100 MHz
+    pub const DLL_OVERRIDE: u32 = 1 << 8;
+}
+
+/// LUT sequence slots
+const SEQ_READ: usize = 0;
+const SEQ_COMMAND: usize = 1;
+
+/// IP FIFO watermark: one 64-bit entry
+const FIFO_WATERMARK: usize = 8;
+
+/// Status polls before giving up on an IP command
+const TIMEOUT_POLLS: u32 = 1_000_000;
+
+/// Up to eight LUT instructions, zero-filled (STOP)
+#[derive(Default)]
+struct Sequence {
+    instrs: [u16; 8],
+    len: usize,
+}
+
+impl Sequence {
+    fn push(&mut self, opcode: u8, lanes: u8, operand: u8) {
+        self.instrs[self.len] = instr(opcode, lanes, operand);
+        self.len += 1;
+    }
+
+    fn words(&self) -> [u32; 4] {
+        let mut words = [0u32; 4];
+        for (i, word) in words.iter_mut().enumerate() {
+            *word = self.instrs[i * 2] as u32 | (self.instrs[i * 2 + 1] as u32) << 16;
+        }
+        words
+    }
+}
+
+/// FlexSPI NOR controller
+pub struct FlexSpiNor {
+    regs: ral::flexspi::FLEXSPI2,
+    initialized: bool,
+    /// I/O mode code, 0 = 1S-1S-1S
+    io_mode: u8,
+    read_opcode: u8,
+    dummy_cycles: u8,
+    /// Second command byte in 8-lane modes: 0 none, 1 repeat, 2 invert
+    command_extension: u8,
+    address_bytes: u8,
+}
+
+impl FlexSpiNor {
+    pub fn new() -> Self {
+        Self {
+            // FlexSPI2 is owned by this driver only
+            regs: unsafe { ral::flexspi::FLEXSPI2::instance() },
+            initialized: false,
+            io_mode: 0,
+            read_opcode: 0x0B,
+            dummy_cycles: 8,
+            command_extension: 0,
+            address_bytes: 3,
+        }
+    }
+
+    /// Initialize FlexSPI2 in 1S-1S-1S
+    ///
+    /// Pads are muxed to FlexSPI2 port A by the board setup.
+    pub fn init(&mut self) {
+        let ccm = unsafe { ral::ccm::CCM::instance() };
+        // FlexSPI2 clock gate: CCGR7 CG1
+        ral::modify_reg!(ral::ccm, ccm, CCGR7, |r| r | (0b11 << 2));
+
+        ral::modify_reg!(ral::flexspi, self.regs, MCR0, |r| r & !reg::MCR0_MDIS);
+        ral::modify_reg!(ral::flexspi, self.regs, MCR0, |r| r | reg::MCR0_SWRESET);
+        while ral::read_reg!(ral::flexspi, self.regs, MCR0) & reg::MCR0_SWRESET != 0 {}
+        ral::write_reg!(ral::flexspi, self.regs, DLLACR, reg::DLL_OVERRIDE);
+        // Largest flash the controller maps on A1, in KB
+        self.regs.FLSHCR0[0].write(0x40_0000);
+        self.set_read_strobe(false);
+
+        self.initialized = true;
+        self.load_lut(SEQ_READ, self.read_lut());
+    }
+
+    /// Sample reads on the flash's DQS in DTR modes, on the loopback
+    /// strobe otherwise
+    fn set_read_strobe(&self, dqs: bool) {
+        let source = if dqs { 3 } else { 1 };
+        // RXCLKSRC only changes with the module disabled
+        ral::modify_reg!(ral::flexspi, self.regs, MCR0, |r| r | reg::MCR0_MDIS);
+        ral::modify_reg!(ral::flexspi, self.regs, MCR0, |r| {
+            (r & !(0b11 << reg::MCR0_RXCLKSRC_SHIFT)) | source << reg::MCR0_RXCLKSRC_SHIFT
+        });
+        ral::modify_reg!(ral::flexspi, self.regs, MCR0, |r| r & !reg::MCR0_MDIS);
+    }
+
+    fn load_lut(&self, seq: usize, words: [u32; 4]) {
+        ral::write_reg!(ral::flexspi, self.regs, LUTKEY, reg::LUT_KEY);
+        ral::write_reg!(ral::flexspi, self.regs, LUTCR, reg::LUTCR_UNLOCK);
+        for (i, &word) in words.iter().enumerate() {
+            self.regs.LUT[seq * 4 + i].write(word);
+        }
+        ral::write_reg!(ral::flexspi, self.regs, LUTKEY, reg::LUT_KEY);
+        ral::write_reg!(ral::flexspi, self.regs, LUTCR, reg::LUTCR_LOCK);
+    }
+
+    /// Switch the bus to an I/O mode (SpiNorSetIoMode)
+    ///
+    /// The host has already switched the chip with raw commands.
+    pub fn set_io_mode(
+        &mut self,
+        mode: u8,
+        read_opcode: u8,
+        dummy_cycles: u8,
+        command_extension: u8,
+    ) -> Result<(), FlexSpiError> {
+        if !self.initialized {
+            return Err(FlexSpiError::NotInitialized);
+        }
+        let Some(&(cmd, _, _, dtr)) = IO_MODES.get(mode as usize) else {
+            return Err(FlexSpiError::UnsupportedMode);
+        };
+
+        self.io_mode = mode;
+        self.read_opcode = read_opcode;
+        self.dummy_cycles = dummy_cycles;
+        self.command_extension = command_extension;
+        // Octal command modes always use 4-byte addresses
+        if cmd == 8 {
+            self.address_bytes = 4;
+        } else if mode == 0 {
+            self.address_bytes = 3;
+        }
+        self.set_read_strobe(dtr);
+        self.load_lut(SEQ_READ, self.read_lut());
+        Ok(())
+    }
+
+    /// Command, extension, address and dummy instructions for `opcode` in
+    /// the current mode
+    fn sequence(&self, opcode: u8, address_bytes: u8, dummy_cycles: u8) -> Sequence {
+        let (cmd, addr, data, dtr) = IO_MODES[self.io_mode as usize];
+        let (addr_op, dummy_op) = if dtr {
+            (lut::RADDR_DDR, lut::DUMMY_DDR)
+        } else {
+            (lut::RADDR_SDR, lut::DUMMY_SDR)
+        };
+        // 1S-xD-xD modes send the opcode single rate
+        let cmd_op = if dtr && cmd != 1 {
+            lut::CMD_DDR
+        } else {
+            lut::CMD_SDR
+        };
+
+        let mut seq = Sequence::default();
+        seq.push(cmd_op, cmd, opcode);
+        if cmd == 8 {
+            match self.command_extension {
+                1 => seq.push(cmd_op, cmd, opcode),
+                2 => seq.push(cmd_op, cmd, !opcode),
+                _ => {}
+            }
+        }
+        if address_bytes > 0 {
+            seq.push(addr_op, addr, address_bytes * 8);
+        }
+        if dummy_cycles > 0 {
+            // DDR dummy counts half cycles
+            let operand = if dtr { dummy_cycles * 2 } else { dummy_cycles };
+            seq.push(dummy_op, data, operand);
+        }
+        seq
+    }
+
+    /// Read LUT sequence (four 32-bit words) for the current mode
+    pub fn read_lut(&self) -> [u32; 4] {
+        let (_, _, data, dtr) = IO_MODES[self.io_mode as usize];
+        let mut seq = self.sequence(self.read_opcode, self.address_bytes, self.dummy_cycles);
+        // Length comes from the IP command data size register
+        let read_op = if dtr { lut::READ_DDR } else { lut::READ_SDR };
+        seq.push(read_op, data, 0x04);
+        seq.words()
+    }
+
+    /// Run the LUT sequence in the command slot as an IP command
+    fn start(&self, words: [u32; 4], address: u32, len: usize) {
+        self.load_lut(SEQ_COMMAND, words);
+        ral::write_reg!(ral::flexspi, self.regs, IPRXFCR, reg::FIFO_CLEAR);
+        ral::write_reg!(ral::flexspi, self.regs, IPTXFCR, reg::FIFO_CLEAR);
+        ral::write_reg!(ral::flexspi, self.regs, INTR, u32::MAX);
+        ral::write_reg!(ral::flexspi, self.regs, IPCR0, address);
+        ral::write_reg!(
+            ral::flexspi,
+            self.regs,
+            IPCR1,
+            len as u32 | (SEQ_COMMAND as u32) << reg::IPCR1_ISEQID_SHIFT
+        );
+        ral::write_reg!(ral::flexspi, self.regs, IPCMD, reg::IPCMD_TRG);
+    }
+
+    /// Wait for an interrupt flag, failing on an IP command error
+    fn wait(&self, flag: u32) -> Result<(), FlexSpiError> {
+        for _ in 0..TIMEOUT_POLLS {
+            let intr = ral::read_reg!(ral::flexspi, self.regs, INTR);
+            if intr & reg::INTR_IPCMDERR != 0 {
+                ral::write_reg!(ral::flexspi, self.regs, INTR, reg::INTR_IPCMDERR);
+                return Err(FlexSpiError::CommandError);
+            }
+            if intr & flag != 0 {
+                return Ok(());
+            }
+        }
+        Err(FlexSpiError::Timeout)
+    }
+
+    fn finish(&self) -> Result<(), FlexSpiError> {
+        self.wait(reg::INTR_IPCMDDONE)?;
+        ral::write_reg!(ral::flexspi, self.regs, INTR, reg::INTR_IPCMDDONE);
+        Ok(())
+    }
+
+    /// Drain `buffer.len()` bytes from the IP RX FIFO
+    fn receive(&self, buffer: &mut [u8]) -> Result<(), FlexSpiError> {
+        let mut chunks = buffer.chunks_mut(FIFO_WATERMARK).peekable();
+        while let Some(chunk) = chunks.next() {
+            if chunk.len() == FIFO_WATERMARK {
+                self.wait(reg::INTR_IPRXWA)?;
+            } else {
+                // Short tail: below the watermark, complete once the command is
+                self.wait(reg::INTR_IPCMDDONE)?;
+            }
+            let entry = [self.regs.RFDR[0].read(), self.regs.RFDR[1].read()];
+            let bytes = [entry[0].to_le_bytes(), entry[1].to_le_bytes()].concat();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+            if chunks.peek().is_some() || chunk.len() == FIFO_WATERMARK {
+                // Pop the entry
+                ral::write_reg!(ral::flexspi, self.regs, INTR, reg::INTR_IPRXWA);
+            }
+        }
+        self.finish()
+    }
+
+    /// Push `data` into the IP TX FIFO
+    fn send(&self, data: &[u8]) -> Result<(), FlexSpiError> {
+        for chunk in data.chunks(FIFO_WATERMARK) {
+            self.wait(reg::INTR_IPTXWE)?;
+            let mut bytes = [0u8; FIFO_WATERMARK];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            self.regs.TFDR[0].write(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+            self.regs.TFDR[1].write(u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]));
+            ral::write_reg!(ral::flexspi, self.regs, INTR, reg::INTR_IPTXWE);
+        }
+        self.finish()
+    }
+
+    /// Send raw command bytes in the current mode (SpiNorTransfer)
+    ///
+    /// The opcode goes out as a command, the rest as data on the command
+    /// lanes at the command rate.
+    pub fn transfer(&self, cmd: &[u8]) -> Result<(), FlexSpiError> {
+        if !self.initialized {
+            return Err(FlexSpiError::NotInitialized);
+        }
+        let Some((&opcode, rest)) = cmd.split_first() else {
+            return Ok(());
+        };
+        let (lanes, _, _, dtr) = IO_MODES[self.io_mode as usize];
+        let mut seq = self.sequence(opcode, 0, 0);
+        if !rest.is_empty() {
+            let write_op = if dtr && lanes != 1 {
+                lut::WRITE_DDR
+            } else {
+                lut::WRITE_SDR
+            };
+            seq.push(write_op, lanes, 0x04);
+        }
+        self.start(seq.words(), 0, rest.len());
+        if rest.is_empty() {
+            self.finish()
+        } else {
+            self.send(rest)
+        }
+    }
+
+    /// READ ID (9Fh) in the current mode, with the address bytes (address
+    /// 0) and dummy cycles octal modes need
+    pub fn read_id(
+        &self,
+        address_bytes: u8,
+        dummy_cycles: u8,
+        buffer: &mut [u8],
+    ) -> Result<(), FlexSpiError> {
+        if !self.initialized {
+            return Err(FlexSpiError::NotInitialized);
+        }
+        let (_, _, data, dtr) = IO_MODES[self.io_mode as usize];
+        let mut seq = self.sequence(0x9F, address_bytes, dummy_cycles);
+        let read_op = if dtr { lut::READ_DDR } else { lut::READ_SDR };
+        seq.push(read_op, data, 0x04);
+        self.start(seq.words(), 0, buffer.len());
+        self.receive(buffer)
+    }
+
+    /// Read using the current mode's read LUT
+    pub fn read(&self, address: u32, buffer: &mut [u8]) -> Result<(), FlexSpiError> {
+        if !self.initialized {
+            return Err(FlexSpiError::NotInitialized);
+        }
+        for (i, chunk) in buffer.chunks_mut(u16::MAX as usize).enumerate() {
+            let offset = (i * u16::MAX as usize) as u32;
+            self.start(self.read_lut(), address + offset, chunk.len());
+            self.receive(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for FlexSpiNor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// FlexSPI errors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlexSpiError {
+    NotInitialized,
+    UnsupportedMode,
+    Timeout,
+    /// IP command error (INTR.IPCMDERR)
+    CommandError,
+}
+
+/// Initialize FlexSPI NOR controller
+pub fn init_flexspi() -> FlexSpiNor {
+    let mut flexspi = FlexSpiNor::new();
+    flexspi.init();
+    flexspi
+}
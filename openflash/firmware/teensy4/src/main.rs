@@ -18,6 +18,7 @@
 use teensy4_panic as _;
 
 mod flexio_nand;
+mod flexspi_nor;
 mod gpio_nand;
 mod protocol;
 mod spi;
@@ -104,6 +105,9 @@ fn main() -> ! {
     // Initialize FlexIO for high-speed NAND timing
     let flexio = flexio_nand::init_flexio();
 
+    // Initialize FlexSPI for octal/DTR SPI NOR
+    let mut flexspi = flexspi_nor::init_flexspi();
+
     // Main loop
     loop {
         // Poll USB for commands
@@ -114,6 +118,7 @@ fn main() -> ! {
                 &nand_gpio,
                 &spi,
                 &flexio,
+                &mut flexspi,
             );
             
             usb_device.send_response(&response);
@@ -139,6 +144,7 @@ fn process_command(
     _nand: &gpio_nand::NandGpio,
     _spi: &spi::SpiController,
     _flexio: &flexio_nand::FlexioNand,
+    flexspi: &mut flexspi_nor::FlexSpiNor,
 ) -> &'static [u8] {
     if cmd.is_empty() {
         return &[0xFF];
@@ -195,6 +201,68 @@ fn process_command(
             &response[..4]
         }
 
+        // SPI NOR read JEDEC ID in the current I/O mode
+        // Args: [address bytes, dummy cycles, length]; octal modes need the
+        // address and dummy, length 0 = 3
+        0x60 => {
+            let arg = |i: usize| cmd.get(1 + i).copied().unwrap_or(0);
+            let len = match arg(2) {
+                0 => 3,
+                n => (n as usize).min(16),
+            };
+            response[0] = 0x60;
+            match flexspi.read_id(arg(0), arg(1), &mut response[2..2 + len]) {
+                Ok(()) => {
+                    response[1] = protocol::Status::Ok as u8;
+                    &response[..2 + len]
+                }
+                Err(_) => {
+                    response[1] = protocol::Status::Error as u8;
+                    &response[..2]
+                }
+            }
+        }
+
+        // SPI NOR read in the current I/O mode
+        // Args: [addr_0, addr_1, addr_2, addr_3, length_lo, length_hi]
+        0x62 | 0x63 if cmd.len() >= 7 => {
+            let address = u32::from_le_bytes([cmd[1], cmd[2], cmd[3], cmd[4]]);
+            let len = (u16::from_le_bytes([cmd[5], cmd[6]]) as usize).min(USB_PACKET_SIZE);
+            match flexspi.read(address, &mut response[..len]) {
+                Ok(()) => &response[..len],
+                Err(_) => {
+                    response[0] = cmd[0];
+                    response[1] = protocol::Status::Error as u8;
+                    &response[..2]
+                }
+            }
+        }
+
+        // SPI NOR I/O mode switch
+        // Args: [mode, read opcode, dummy cycles, command extension]
+        0x7C if cmd.len() >= 5 => {
+            let status = match flexspi.set_io_mode(cmd[1], cmd[2], cmd[3], cmd[4]) {
+                Ok(()) => protocol::Status::Ok,
+                Err(_) => protocol::Status::Error,
+            };
+            response[0] = 0x7C;
+            response[1] = status as u8;
+            &response[..2]
+        }
+
+        // Raw SPI NOR command in the current I/O mode
+        // Args: [length, bytes...]
+        0x7D if cmd.len() >= 2 => {
+            let len = (cmd[1] as usize).min(cmd.len() - 2);
+            let status = match flexspi.transfer(&cmd[2..2 + len]) {
+                Ok(()) => protocol::Status::Ok,
+                Err(_) => protocol::Status::Error,
+            };
+            response[0] = 0x7D;
+            response[1] = status as u8;
+            &response[..2]
+        }
+
         // Unknown command
         _ => {
             response[0] = 0xFF;
@@ -74,6 +74,8 @@ pub enum Command {
     SpiNorBlockErase32K = 0x68,
     SpiNorBlockErase64K = 0x69,
     SpiNorChipErase = 0x6A,
+    SpiNorSetIoMode = 0x7C,
+    SpiNorTransfer = 0x7D,
 
     // Teensy-specific commands
     TeensyGetSpeed = 0xF0,      // Get USB speed info
@@ -136,6 +138,8 @@ impl Command {
             0x68 => Some(Command::SpiNorBlockErase32K),
             0x69 => Some(Command::SpiNorBlockErase64K),
             0x6A => Some(Command::SpiNorChipErase),
+            0x7C => Some(Command::SpiNorSetIoMode),
+            0x7D => Some(Command::SpiNorTransfer),
             0xF0 => Some(Command::TeensyGetSpeed),
             0xF1 => Some(Command::TeensySdInit),
             0xF2 => Some(Command::TeensySdRead),
@@ -1,13 +1,32 @@
 //! Tauri IPC commands for OpenFlash GUI
 
 use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::sync::Mutex;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::config::AppConfig;
 use crate::device::{ChipInfo, DeviceInfo, DeviceManager, FlashInterface, UfsLunInfo, 
                     DevicePlatform, DeviceCapabilities, ConnectionType};
+use crate::flasher::{self, FlashConfig};
 use crate::mock;
+use openflash_core::ecc::{BlockEccSummary, EccAlgorithm, EccReport};
+use openflash_core::jedec::JEDEC_PARAM_PAGE_ADDR;
+use openflash_core::nand_ops::{NandOpCapabilities, NandOpScheduler};
+use openflash_core::onfi::{
+    detect_nand_chip, enumerate_nand_targets, negotiate_timing_mode, read_parameter_page,
+    select_ce_packet, BusTimingConfig, NandBlockRun, NandChipInfo, NandTopology, OnfiParameterPage,
+    ONFI_PARAM_PAGE_ADDR,
+};
+use openflash_core::protocol::{Command, Packet};
+use openflash_core::sfdp::Sfdp;
+use openflash_core::spi_nand::{SpiNandChipInfo, SpiNandEccReport, SpiNandReader};
+use openflash_core::spi_nor::SpiNorChipInfo;
+use openflash_core::spi_nor_addr::SpiNorAddressing;
+use openflash_core::spi_nor_otp::{OtpDump, SpiNorOtp};
+use openflash_core::spi_nor_plan::{NorContents, NorGeometry, NorPlanner};
+use openflash_core::spi_nor_protect::{ProtectionMap, StatusRegisters, UnprotectPlan};
+use openflash_core::write_ops::{ChipProgrammer, NorPlanReport, WriteError};
 
 #[tauri::command]
 pub fn enable_mock_mode() -> Result<(), String> {
@@ -135,12 +154,50 @@ pub async fn get_chip_info(
     match interface {
         FlashInterface::ParallelNand => {
             let chip_id = read_nand_id(device_manager.clone()).await?;
-            if let Some(info) = openflash_core::onfi::get_chip_info(&chip_id) {
+            let (platform_max, max_chip_enables) = {
+                let manager = device_manager.lock().map_err(|e| e.to_string())?;
+                manager.get_platform().map_or((0, 1), |platform| {
+                    (
+                        platform.max_nand_timing_mode(),
+                        platform.nand_chip_enables(),
+                    )
+                })
+            };
+            let id = chip_id.clone();
+            let detected = run_nand_ops(device_manager.clone(), move |transfer| {
+                // Chips without a parameter page (pre-ONFI) just fail to parse
+                let onfi = read_parameter_page(ONFI_PARAM_PAGE_ADDR, &mut *transfer).ok();
+                let jedec = read_parameter_page(JEDEC_PARAM_PAGE_ADDR, &mut *transfer).ok();
+                if let Some(page) = onfi.as_deref().and_then(OnfiParameterPage::parse) {
+                    negotiate_nand_timing(&page, platform_max, transfer)?;
+                }
+                let Some((info, _)) = detect_nand_chip(&id, onfi.as_deref(), jedec.as_deref())
+                else {
+                    return Ok(None);
+                };
+                // Firmware without CE# select or data-out cycles: CE# 0, LUN 0 only
+                let topology = enumerate_nand_dies(&info, max_chip_enables, transfer)
+                    .unwrap_or_else(|_| {
+                        NandTopology::from_chip_info(&NandChipInfo {
+                            chip_enables: 1,
+                            luns: 1,
+                            ..info.clone()
+                        })
+                    });
+                Ok(Some((info, topology)))
+            })
+            .await?;
+            {
+                let mut manager = device_manager.lock().map_err(|e| e.to_string())?;
+                manager.set_nand_topology(detected.as_ref().map(|(_, topology)| topology.clone()));
+            }
+            if let Some((info, topology)) = detected {
                 Ok(ChipInfo {
                     manufacturer: info.manufacturer,
                     model: info.model,
                     chip_id,
-                    size_mb: info.size_mb,
+                    // Every die found, not just CE# 0
+                    size_mb: (topology.size_bytes() / (1024 * 1024)) as u32,
                     page_size: info.page_size,
                     block_size: info.block_size,
                     interface: FlashInterface::ParallelNand,
@@ -155,6 +212,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: Some(info.oob_size),
                 })
             } else {
                 Ok(ChipInfo {
@@ -176,6 +234,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: None,
                 })
             }
         }
@@ -201,6 +260,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: Some(info.oob_size),
                 })
             } else {
                 Ok(ChipInfo {
@@ -222,6 +282,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: None,
                 })
             }
         }
@@ -253,6 +314,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: None,
                 })
             } else {
                 let mfr_name = openflash_core::spi_nor::get_spi_nor_manufacturer_name(jedec_arr[0]);
@@ -278,6 +340,7 @@ pub async fn get_chip_info(
                     ufs_version: None,
                     serial_number: None,
                     boot_lun_enabled: None,
+                    oob_size: None,
                 })
             }
         }
@@ -307,6 +370,7 @@ pub async fn get_chip_info(
                 ufs_version: None,
                 serial_number: None,
                 boot_lun_enabled: None,
+                oob_size: None,
             })
         }
     }
@@ -332,6 +396,21 @@ pub fn get_interface(
     Ok(manager.get_interface())
 }
 
+/// Switch the programmer's active flash interface
+async fn select_device_interface(
+    dev: &crate::device::UsbDevice,
+    interface: openflash_core::protocol::FlashInterface,
+) -> Result<(), String> {
+    let response = dev
+        .send_command(Command::SetInterface, &[interface as u8])
+        .await?;
+    if response.len() >= 2 && response[1] == 0x00 {
+        Ok(())
+    } else {
+        Err(format!("Failed to select {:?} interface", interface))
+    }
+}
+
 /// Read SPI NAND chip ID
 #[tauri::command]
 pub async fn read_spi_nand_id(
@@ -348,6 +427,8 @@ pub async fn read_spi_nand_id(
     };
 
     let dev = device.lock().await;
+    // SPI NAND and SPI NOR share the bus; this hands it to the SpiNand controller
+    select_device_interface(&dev, openflash_core::protocol::FlashInterface::SpiNand).await?;
     let response = dev
         .send_command(openflash_core::protocol::Command::SpiNandReadId, &[])
         .await?;
@@ -359,6 +440,115 @@ pub async fn read_spi_nand_id(
     }
 }
 
+#[derive(Serialize)]
+pub struct SpiNandDump {
+    /// Page + OOB of every page
+    pub data: Vec<u8>,
+    pub ecc_report: SpiNandEccReport,
+}
+
+/// Dump the whole SPI NAND package with on-die ECC, reporting per-page status
+#[tauri::command]
+pub async fn dump_spi_nand(
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<SpiNandDump, String> {
+    run_spi_nand(device_manager, |info, transact| {
+        let (data, ecc_report) = SpiNandReader::new(info).read_chip_with_ecc(transact)?;
+        Ok(SpiNandDump { data, ecc_report })
+    })
+    .await
+}
+
+/// Packet transfer handed to the core SPI NAND flows
+type NandTransfer<'a> = Box<dyn FnMut(&Packet) -> Result<Vec<u8>, String> + 'a>;
+
+/// Send one packet; cache and continuous reads answer with bare data, the
+/// rest with a status
+async fn spi_nand_usb_transfer(
+    dev: &crate::device::UsbDevice,
+    packet: &Packet,
+    page_size: usize,
+) -> Result<Vec<u8>, String> {
+    match packet.cmd {
+        Command::SpiNandReadCache | Command::SpiNandReadCacheX4 => {
+            let len = u16::from_le_bytes([packet.args[2], packet.args[3]]) as usize;
+            dev.read_data(packet.cmd, &packet.args, len).await
+        }
+        Command::SpiNandReadCacheContinuous | Command::SpiNandReadCacheContinuousX4 => {
+            let pages = u32::from_le_bytes(packet.args[4..8].try_into().unwrap()) as usize;
+            dev.read_data(packet.cmd, &packet.args, pages * page_size)
+                .await
+        }
+        _ => {
+            let response = dev.send_command(packet.cmd, &packet.args).await?;
+            response_payload(packet, response)
+        }
+    }
+}
+
+/// Identify the SPI NAND chip and run a core flow against it
+async fn run_spi_nand<R, F>(
+    device_manager: State<'_, Mutex<DeviceManager>>,
+    f: F,
+) -> Result<R, String>
+where
+    R: Send + 'static,
+    F: FnOnce(&SpiNandChipInfo, &mut NandTransfer<'_>) -> Result<R, String> + Send + 'static,
+{
+    let chip_id = read_spi_nand_id(device_manager.clone()).await?;
+    let info = openflash_core::spi_nand::get_spi_nand_chip_info(&chip_id)
+        .ok_or("Unknown SPI NAND chip")?;
+
+    let page_size = info.page_size as usize;
+
+    if mock::is_mock_connected() {
+        // The mock generates page data from the row of the last PAGE READ
+        let mut row = [0u8; 4];
+        let mut transact: NandTransfer = Box::new(move |packet: &Packet| match packet.cmd {
+            Command::SpiNandReadCache | Command::SpiNandReadCacheX4 => {
+                let mut args = [0u8; 6];
+                args[0..4].copy_from_slice(&row);
+                args[4..6].copy_from_slice(&packet.args[2..4]);
+                Ok(mock::process_mock_command(Command::SpiNandReadCache, &args))
+            }
+            Command::SpiNandReadCacheContinuous | Command::SpiNandReadCacheContinuousX4 => {
+                let first = u32::from_le_bytes(packet.args[0..4].try_into().unwrap());
+                let pages = u32::from_le_bytes(packet.args[4..8].try_into().unwrap());
+                let mut args = [0u8; 6];
+                args[4..6].copy_from_slice(&(page_size as u16).to_le_bytes());
+                let mut data = Vec::new();
+                for row in first..first + pages {
+                    args[0..4].copy_from_slice(&row.to_le_bytes());
+                    data.extend(mock::process_mock_command(Command::SpiNandReadCache, &args));
+                }
+                Ok(data)
+            }
+            _ => {
+                if packet.cmd == Command::SpiNandPageRead {
+                    row.copy_from_slice(&packet.args[0..4]);
+                }
+                response_payload(packet, mock::process_mock_command(packet.cmd, &packet.args))
+            }
+        });
+        return f(&info, &mut transact);
+    }
+
+    let device = {
+        let manager = device_manager.lock().map_err(|e| e.to_string())?;
+        manager.get_active_device().ok_or("No device connected")?
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let dev = device.blocking_lock();
+        let mut transact: NandTransfer = Box::new(|packet: &Packet| {
+            tauri::async_runtime::block_on(spi_nand_usb_transfer(&dev, packet, page_size))
+        });
+        f(&info, &mut transact)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 // ============================================================================
 // SPI NOR Flash commands (v1.6)
 // ============================================================================
@@ -379,6 +569,8 @@ pub async fn read_spi_nor_jedec_id(
     };
 
     let dev = device.lock().await;
+    // SPI NAND and SPI NOR share the bus; this hands it to the SpiNor controller
+    select_device_interface(&dev, openflash_core::protocol::FlashInterface::SpiNor).await?;
     let response = dev
         .send_command(openflash_core::protocol::Command::SpiNorReadJedecId, &[])
         .await?;
@@ -396,26 +588,15 @@ pub async fn spi_nor_sector_erase(
     address: u32,
     device_manager: State<'_, Mutex<DeviceManager>>,
 ) -> Result<(), String> {
-    if mock::is_mock_connected() {
-        return Ok(());
-    }
-
-    let device = {
-        let manager = device_manager.lock().map_err(|e| e.to_string())?;
-        manager.get_active_device().ok_or("No device connected")?
-    };
-
-    let dev = device.lock().await;
-    let args = address.to_le_bytes();
-    let response = dev
-        .send_command(openflash_core::protocol::Command::SpiNorSectorErase, &args)
-        .await?;
-
-    if response.len() >= 2 && response[1] == 0x00 {
-        Ok(())
-    } else {
-        Err("Sector erase failed".to_string())
-    }
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        unprotect_spi_nor(info, addressing, transfer, address & !0xFFF, 4096)?;
+        let packet = Packet::new(Command::SpiNorSectorErase, &address.to_le_bytes());
+        addressing
+            .with_mode(transfer, |transfer| transfer(&packet, &[]))
+            .map(|_| ())
+            .map_err(|_| "Sector erase failed".to_string())
+    })
+    .await
 }
 
 /// SPI NOR block erase (64KB)
@@ -423,6 +604,36 @@ pub async fn spi_nor_sector_erase(
 pub async fn spi_nor_block_erase(
     address: u32,
     device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<(), String> {
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        unprotect_spi_nor(info, addressing, transfer, address & !0xFFFF, 65536)?;
+        let packet = Packet::new(Command::SpiNorBlockErase64K, &address.to_le_bytes());
+        addressing
+            .with_mode(transfer, |transfer| transfer(&packet, &[]))
+            .map(|_| ())
+            .map_err(|_| "Block erase failed".to_string())
+    })
+    .await
+}
+
+/// SPI NOR chip erase
+#[tauri::command]
+pub async fn spi_nor_chip_erase(
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<(), String> {
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        unprotect_spi_nor(info, addressing, transfer, 0, info.size_bytes)?;
+        transfer(&Packet::new(Command::SpiNorChipErase, &[]), &[])
+            .map(|_| ())
+            .map_err(|_| "Chip erase failed".to_string())
+    })
+    .await
+}
+
+/// SPI NOR unlock all protection
+#[tauri::command]
+pub async fn spi_nor_unlock_all(
+    device_manager: State<'_, Mutex<DeviceManager>>,
 ) -> Result<(), String> {
     if mock::is_mock_connected() {
         return Ok(());
@@ -434,54 +645,237 @@ pub async fn spi_nor_block_erase(
     };
 
     let dev = device.lock().await;
-    let args = address.to_le_bytes();
+    // Write 0x00 to status register 1 to clear all protection bits
     let response = dev
         .send_command(
-            openflash_core::protocol::Command::SpiNorBlockErase64K,
-            &args,
+            openflash_core::protocol::Command::SpiNorWriteStatus1,
+            &[0x00],
         )
         .await?;
 
     if response.len() >= 2 && response[1] == 0x00 {
         Ok(())
     } else {
-        Err("Block erase failed".to_string())
+        Err("Failed to unlock protection".to_string())
     }
 }
 
-/// SPI NOR chip erase
+#[derive(Serialize)]
+pub struct SpiNorDump {
+    pub data: Vec<u8>,
+    /// Unique ID and OTP areas (if requested)
+    pub otp: Option<OtpDump>,
+}
+
+/// Dump SPI NOR, entering the chip's 4-byte address mode and leaving it
+/// in 3-byte mode afterwards; `include_otp` also reads the unique ID,
+/// security registers and OTP
 #[tauri::command]
-pub async fn spi_nor_chip_erase(
+pub async fn dump_spi_nor(
+    start: u32,
+    length: Option<u32>,
+    include_otp: Option<bool>,
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<SpiNorDump, String> {
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        let length = length.unwrap_or(addressing.size_bytes.saturating_sub(start));
+        let data = addressing.read(transfer, start, length)?;
+        let otp = if include_otp.unwrap_or(false) {
+            Some(SpiNorOtp::for_chip(info).dump(transfer)?)
+        } else {
+            None
+        };
+        Ok(SpiNorDump { data, otp })
+    })
+    .await
+}
+
+/// Program SPI NOR page by page (the range must already be erased)
+#[tauri::command]
+pub async fn write_spi_nor(
+    address: u32,
+    data: Vec<u8>,
     device_manager: State<'_, Mutex<DeviceManager>>,
 ) -> Result<(), String> {
-    if mock::is_mock_connected() {
-        return Ok(());
-    }
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        unprotect_spi_nor(info, addressing, transfer, address, data.len() as u32)?;
+        addressing.program(transfer, address, &data, 256)
+    })
+    .await
+}
 
-    let device = {
-        let manager = device_manager.lock().map_err(|e| e.to_string())?;
-        manager.get_active_device().ok_or("No device connected")?
-    };
+/// Program an image into SPI NOR, erasing and programming only what differs
+/// from the chip, then reading the touched ranges back
+///
+/// `address` and the image length must be multiples of the sector size.
+#[tauri::command]
+pub async fn program_spi_nor(
+    address: u32,
+    data: Vec<u8>,
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<NorPlanReport, String> {
+    run_spi_nor(device_manager, move |info, addressing, transfer| {
+        let mut geometry = NorGeometry::for_chip(info);
+        geometry.retain_supported(addressing);
+        let current = addressing.read(transfer, address, data.len() as u32)?;
+        let plan = NorPlanner::new(geometry)
+            .plan(address, &data, NorContents::Data(&current))
+            .map_err(|e| e.to_string())?;
+        unprotect_spi_nor(info, addressing, transfer, address, data.len() as u32)?;
+
+        let transfer = RefCell::new(transfer);
+        ChipProgrammer::for_spi_nor(info)
+            .execute_nor_plan(
+                &plan,
+                &data,
+                |erase| {
+                    let packet = erase.packet().ok_or_else(|| {
+                        WriteError::Unsupported(format!("{} byte erase", erase.size))
+                    })?;
+                    addressing
+                        .with_mode(&mut **transfer.borrow_mut(), |transfer| {
+                            transfer(&packet, &[])
+                        })
+                        .map(|_| ())
+                        .map_err(WriteError::IoError)
+                },
+                |address, page| {
+                    addressing
+                        .program(&mut **transfer.borrow_mut(), address, page, info.page_size)
+                        .map_err(WriteError::IoError)
+                },
+                |address, len| {
+                    addressing
+                        .read(&mut **transfer.borrow_mut(), address, len as u32)
+                        .map_err(WriteError::IoError)
+                },
+            )
+            .map_err(|e| e.to_string())
+    })
+    .await
+}
 
-    let dev = device.lock().await;
-    let response = dev
-        .send_command(openflash_core::protocol::Command::SpiNorChipErase, &[])
-        .await?;
+/// Packet transfer handed to the core SPI NOR flows
+type NorTransfer<'a> = Box<dyn FnMut(&Packet, &[u8]) -> Result<Vec<u8>, String> + 'a>;
 
+/// Payload of a `[cmd, status, ...]` response
+fn response_payload(packet: &Packet, response: Vec<u8>) -> Result<Vec<u8>, String> {
     if response.len() >= 2 && response[1] == 0x00 {
-        Ok(())
+        Ok(response[2..].to_vec())
     } else {
-        Err("Chip erase failed".to_string())
+        Err(format!("{:?} failed", packet.cmd))
     }
 }
 
-/// SPI NOR unlock all protection
-#[tauri::command]
-pub async fn spi_nor_unlock_all(
-    device_manager: State<'_, Mutex<DeviceManager>>,
+/// Send one packet; reads answer with bare data, the rest with a status
+async fn spi_nor_usb_transfer(
+    dev: &crate::device::UsbDevice,
+    packet: &Packet,
+    data: &[u8],
+) -> Result<Vec<u8>, String> {
+    match packet.cmd {
+        Command::SpiNorRead | Command::SpiNorFastRead => {
+            let len = u16::from_le_bytes([packet.args[4], packet.args[5]]) as usize;
+            dev.read_data(packet.cmd, &packet.args, len).await
+        }
+        Command::SpiNorReadSfdp => {
+            // Status header, then the data
+            let len = u16::from_le_bytes([packet.args[3], packet.args[4]]) as usize;
+            let response = dev.read_data(packet.cmd, &packet.args, len + 2).await?;
+            response_payload(packet, response)
+        }
+        Command::SpiNorReadSecurityReg => {
+            let len = u16::from_le_bytes([packet.args[3], packet.args[4]]) as usize;
+            dev.read_data(packet.cmd, &packet.args, len).await
+        }
+        _ => {
+            let response = dev
+                .send_command_with_data(packet.cmd, &packet.args, data)
+                .await?;
+            response_payload(packet, response)
+        }
+    }
+}
+
+/// Lift block protection over `start..start + len` before writing or
+/// erasing it
+///
+/// Status register writes are read back, since SRP0 with /WP held low
+/// silently ignores them.
+fn unprotect_spi_nor(
+    info: &SpiNorChipInfo,
+    addressing: &SpiNorAddressing,
+    transfer: &mut NorTransfer<'_>,
+    start: u32,
+    len: u32,
 ) -> Result<(), String> {
+    let map = ProtectionMap::for_chip(info);
+    let regs = StatusRegisters::read(transfer)?;
+    let plan = map
+        .plan_unprotect(&regs, start, len)
+        .map_err(|e| format!("Can't unprotect 0x{:08X}: {:?}", start, e))?;
+    let packets = plan.packets(addressing.mode.address_bytes());
+    addressing.with_mode(transfer, |transfer| {
+        packets
+            .iter()
+            .try_for_each(|packet| transfer(packet, &[]).map(|_| ()))
+    })?;
+
+    if let UnprotectPlan::WriteRegisters {
+        requires_wp_high, ..
+    } = plan
+    {
+        let regs = StatusRegisters::read(transfer)?;
+        let cleared = map.plan_unprotect(&regs, start, len);
+        if !matches!(cleared, Ok(UnprotectPlan::Unprotected)) {
+            return Err(if requires_wp_high {
+                "Status registers are locked by SRP0; pull /WP high".to_string()
+            } else {
+                format!("0x{:08X} is still write-protected", start)
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Chip info from the ID table, or from SFDP for chips missing from it
+fn identify_spi_nor(
+    jedec_id: [u8; 3],
+    transfer: &mut NorTransfer<'_>,
+) -> Result<(SpiNorChipInfo, SpiNorAddressing), String> {
+    if let Some(info) = openflash_core::spi_nor::get_spi_nor_chip_info(&jedec_id) {
+        let addressing = SpiNorAddressing::for_chip(&info, None);
+        return Ok((info, addressing));
+    }
+    let sfdp = Sfdp::read(&mut *transfer)?.ok_or("Unknown SPI NOR chip without SFDP")?;
+    let info = sfdp.chip_info(jedec_id);
+    let addressing = SpiNorAddressing::for_chip(&info, Some(&sfdp));
+    Ok((info, addressing))
+}
+
+/// Identify the SPI NOR chip and run a core flow against it
+///
+/// The flow runs on a blocking thread since the core drives the device
+/// through a synchronous transfer closure.
+async fn run_spi_nor<R, F>(
+    device_manager: State<'_, Mutex<DeviceManager>>,
+    f: F,
+) -> Result<R, String>
+where
+    R: Send + 'static,
+    F: FnOnce(&SpiNorChipInfo, &SpiNorAddressing, &mut NorTransfer<'_>) -> Result<R, String>
+        + Send
+        + 'static,
+{
+    let jedec_id = read_spi_nor_jedec_id(device_manager.clone()).await?;
+    let jedec_arr: [u8; 3] = jedec_id[..3].try_into().map_err(|_| "Invalid JEDEC ID")?;
+
     if mock::is_mock_connected() {
-        return Ok(());
+        let mut transfer: NorTransfer = Box::new(|packet: &Packet, _: &[u8]| {
+            response_payload(packet, mock::process_mock_command(packet.cmd, &packet.args))
+        });
+        let (info, addressing) = identify_spi_nor(jedec_arr, &mut transfer)?;
+        return f(&info, &addressing, &mut transfer);
     }
 
     let device = {
@@ -489,20 +883,16 @@ pub async fn spi_nor_unlock_all(
         manager.get_active_device().ok_or("No device connected")?
     };
 
-    let dev = device.lock().await;
-    // Write 0x00 to status register 1 to clear all protection bits
-    let response = dev
-        .send_command(
-            openflash_core::protocol::Command::SpiNorWriteStatus1,
-            &[0x00],
-        )
-        .await?;
-
-    if response.len() >= 2 && response[1] == 0x00 {
-        Ok(())
-    } else {
-        Err("Failed to unlock protection".to_string())
-    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let dev = device.blocking_lock();
+        let mut transfer: NorTransfer = Box::new(|packet: &Packet, data: &[u8]| {
+            tauri::async_runtime::block_on(spi_nor_usb_transfer(&dev, packet, data))
+        });
+        let (info, addressing) = identify_spi_nor(jedec_arr, &mut transfer)?;
+        f(&info, &addressing, &mut transfer)
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 // ============================================================================
@@ -564,6 +954,7 @@ pub async fn read_ufs_device_info(
             ufs_version: Some("UFS 3.1".to_string()),
             serial_number: Some("S4EVNX0M123456".to_string()),
             boot_lun_enabled: Some(true),
+            oob_size: None,
         });
     }
 
@@ -613,6 +1004,7 @@ pub async fn read_ufs_device_info(
             ufs_version: Some(version.as_str().to_string()),
             serial_number: None,
             boot_lun_enabled: Some(desc.boot_enable != 0),
+            oob_size: None,
         })
     } else {
         Err("Failed to parse UFS device descriptor".to_string())
@@ -690,6 +1082,210 @@ pub async fn dump_nand(
     Ok(data)
 }
 
+/// Dump whole blocks, using cache or multi-plane reads where `capabilities`
+/// (from the chip's ONFI parameter page) allows; plain page reads otherwise
+#[tauri::command]
+pub async fn dump_nand_blocks(
+    first_block: u32,
+    block_count: u32,
+    page_size: u16,
+    pages_per_block: u32,
+    capabilities: Option<NandOpCapabilities>,
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<Vec<u8>, String> {
+    let scheduler = NandOpScheduler::new(capabilities.unwrap_or_default(), pages_per_block);
+    let topology = nand_topology(&device_manager)?;
+    run_nand_ops(device_manager, move |transfer| {
+        let mut data = Vec::new();
+        for run in nand_block_runs(topology.as_ref(), first_block, block_count)? {
+            select_nand_run(topology.as_ref(), &run, transfer)?;
+            data.extend(scheduler.read_blocks(
+                run.first_block,
+                run.block_count,
+                page_size,
+                &mut *transfer,
+            )?);
+        }
+        Ok(data)
+    })
+    .await
+}
+
+/// Erase and program whole blocks with cache or multi-plane operations
+#[tauri::command]
+pub async fn program_nand_blocks(
+    first_block: u32,
+    page_size: u16,
+    pages_per_block: u32,
+    capabilities: Option<NandOpCapabilities>,
+    data: Vec<u8>,
+    device_manager: State<'_, Mutex<DeviceManager>>,
+) -> Result<(), String> {
+    let block_bytes = page_size as usize * pages_per_block as usize;
+    if block_bytes == 0 || data.len() % block_bytes != 0 {
+        return Err("Data must be a whole number of blocks".to_string());
+    }
+    let block_count = (data.len() / block_bytes) as u32;
+    let scheduler = NandOpScheduler::new(capabilities.unwrap_or_default(), pages_per_block);
+    let topology = nand_topology(&device_manager)?;
+    run_nand_ops(device_manager, move |transfer| {
+        let mut offset = 0;
+        for run in nand_block_runs(topology.as_ref(), first_block, block_count)? {
+            let run_data = &data[offset..offset + run.block_count as usize * block_bytes];
+            offset += run_data.len();
+            select_nand_run(topology.as_ref(), &run, transfer)?;
+            scheduler.erase_blocks(run.first_block, run.block_count, &mut *transfer)?;
+            scheduler.program_blocks(run.first_block, page_size, run_data, &mut *transfer)?;
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Topology from the last parallel NAND detection
+fn nand_topology(
+    device_manager: &State<'_, Mutex<DeviceManager>>,
+) -> Result<Option<NandTopology>, String> {
+    let manager = device_manager.lock().map_err(|e| e.to_string())?;
+    Ok(manager.get_nand_topology().cloned())
+}
+
+/// Packet transfer handed to `NandOpScheduler`: packet, data sent after it,
+/// bytes streamed back
+type NandOpTransfer<'a> = Box<dyn FnMut(&Packet, &[u8], usize) -> Result<Vec<u8>, String> + 'a>;
+
+/// Switch the chip (SET FEATURES 01h) and the programmer (`BusConfig`) to the
+/// fastest timing mode both support, checking each mode by re-reading the
+/// parameter page at the new timing
+fn negotiate_nand_timing(
+    page: &OnfiParameterPage,
+    platform_max: u8,
+    transfer: &mut NandOpTransfer<'_>,
+) -> Result<BusTimingConfig, String> {
+    let transfer = RefCell::new(transfer);
+    negotiate_timing_mode(
+        page,
+        platform_max,
+        |packets| {
+            for packet in packets {
+                (transfer.borrow_mut())(packet, &[], 0)?;
+            }
+            Ok(())
+        },
+        |_| {
+            let data = read_parameter_page(ONFI_PARAM_PAGE_ADDR, &mut **transfer.borrow_mut())?;
+            Ok(OnfiParameterPage::parse(&data).is_some())
+        },
+    )
+}
+
+/// Probe each CE# the platform drives, and each LUN behind it, for dies
+/// matching the detected chip
+fn enumerate_nand_dies(
+    info: &NandChipInfo,
+    max_chip_enables: u8,
+    transfer: &mut NandOpTransfer<'_>,
+) -> Result<NandTopology, String> {
+    let probe = NandTopology::from_chip_info(info);
+    let transfer = RefCell::new(transfer);
+    let topology = enumerate_nand_targets(
+        info,
+        max_chip_enables,
+        |ce| {
+            let mut transfer = transfer.borrow_mut();
+            if max_chip_enables > 1 {
+                transfer(&select_ce_packet(ce), &[], 0)?;
+            }
+            let mut id = transfer(&Packet::new(Command::NandReadId, &[]), &[], 0)?;
+            id.truncate(5);
+            Ok(id)
+        },
+        |target| probe.read_status(target, &mut **transfer.borrow_mut()),
+    )?;
+    if max_chip_enables > 1 {
+        (transfer.borrow_mut())(&select_ce_packet(0), &[], 0)?;
+    }
+    Ok(topology)
+}
+
+/// Split a flat block range across the detected dies; without a detected
+/// topology the range is used as is on the selected CE#
+fn nand_block_runs(
+    topology: Option<&NandTopology>,
+    first_block: u32,
+    block_count: u32,
+) -> Result<Vec<NandBlockRun>, String> {
+    match topology {
+        Some(topology) => topology
+            .block_runs(first_block, block_count)
+            .ok_or_else(|| "Block range is outside the detected chip".to_string()),
+        None => Ok(vec![NandBlockRun {
+            ce: 0,
+            first_block,
+            block_count,
+        }]),
+    }
+}
+
+/// Select a run's CE# on packages with more than one
+fn select_nand_run(
+    topology: Option<&NandTopology>,
+    run: &NandBlockRun,
+    transfer: &mut NandOpTransfer<'_>,
+) -> Result<(), String> {
+    if topology.is_some_and(|topology| topology.chip_enables() > 1) {
+        transfer(&select_ce_packet(run.ce), &[], 0)?;
+    }
+    Ok(())
+}
+
+/// Run scheduled parallel NAND operations on a blocking thread
+async fn run_nand_ops<R, F>(
+    device_manager: State<'_, Mutex<DeviceManager>>,
+    f: F,
+) -> Result<R, String>
+where
+    R: Send + 'static,
+    F: FnOnce(&mut NandOpTransfer<'_>) -> Result<R, String> + Send + 'static,
+{
+    if mock::is_mock_connected() {
+        // The mock only knows plain page reads and programs
+        let mut transfer: NandOpTransfer = Box::new(|packet: &Packet, _: &[u8], read_len| {
+            let response = mock::process_mock_command(packet.cmd, &packet.args);
+            match packet.cmd {
+                Command::NandReadPage if response.len() == read_len => Ok(response),
+                Command::NandWritePage | Command::NandErase => Ok(Vec::new()),
+                _ => Err(format!("{:?} not supported by the mock device", packet.cmd)),
+            }
+        });
+        return f(&mut transfer);
+    }
+
+    let device = {
+        let manager = device_manager.lock().map_err(|e| e.to_string())?;
+        manager.get_active_device().ok_or("No device connected")?
+    };
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let dev = device.blocking_lock();
+        let mut transfer: NandOpTransfer = Box::new(|packet: &Packet, data: &[u8], read_len| {
+            tauri::async_runtime::block_on(async {
+                if read_len > 0 {
+                    dev.read_data(packet.cmd, &packet.args, read_len).await
+                } else {
+                    let response = dev
+                        .send_command_with_data(packet.cmd, &packet.args, data)
+                        .await?;
+                    response_payload(packet, response)
+                }
+            })
+        });
+        f(&mut transfer)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 #[tauri::command]
 pub fn analyze_dump(
     data: Vec<u8>,
@@ -1157,6 +1753,58 @@ pub async fn dump_nand_with_progress(
     Ok(data)
 }
 
+// ============================================================================
+// ECC statistics commands
+// ============================================================================
+
+#[derive(Serialize, Deserialize)]
+pub struct EccReportResponse {
+    pub report: EccReport,
+    pub blocks: Vec<BlockEccSummary>,
+    pub histogram: Vec<u32>,
+    pub total_corrected_bits: u64,
+    pub uncorrectable_sectors: usize,
+    pub erased_sectors: usize,
+}
+
+/// Run ECC over a raw page+OOB dump and return per-sector statistics
+#[tauri::command]
+pub fn ecc_report_dump(
+    data: Vec<u8>,
+    page_size: u32,
+    oob_size: u32,
+    pages_per_block: u32,
+    ecc_algorithm: EccAlgorithm,
+) -> Result<EccReportResponse, String> {
+    let config = FlashConfig {
+        page_size,
+        oob_size,
+        pages_per_block,
+        total_blocks: 0,
+        ecc_algorithm,
+    };
+    let (_, report) = flasher::process_dump_with_ecc(&data, &config)?;
+
+    Ok(EccReportResponse {
+        blocks: report.block_summaries(),
+        histogram: report.histogram(),
+        total_corrected_bits: report.total_corrected_bits(),
+        uncorrectable_sectors: report.uncorrectable_sectors(),
+        erased_sectors: report.erased_sectors(),
+        report,
+    })
+}
+
+/// Export an ECC report as "json" or "csv"
+#[tauri::command]
+pub fn export_ecc_report(report: EccReport, format: String) -> Result<String, String> {
+    match format.as_str() {
+        "json" => report.to_json().map_err(|e| e.to_string()),
+        "csv" => Ok(report.to_csv()),
+        _ => Err(format!("Unsupported export format: {}", format)),
+    }
+}
+
 // ============================================================================
 // Configuration commands
 // ============================================================================
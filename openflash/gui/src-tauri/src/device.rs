@@ -9,6 +9,7 @@ use tokio::net::TcpStream;
 #[cfg(unix)]
 use tokio::net::UnixStream;
 
+use openflash_core::onfi::NandTopology;
 use openflash_core::protocol::{Command, Packet};
 
 const VENDOR_ID: u16 = 0xC0DE;
@@ -107,6 +108,33 @@ impl DevicePlatform {
     pub fn has_sd_card(&self) -> bool {
         matches!(self, DevicePlatform::Teensy41)
     }
+
+    /// Fastest ONFI SDR timing mode the platform's parallel NAND bus can drive
+    pub fn max_nand_timing_mode(&self) -> u8 {
+        match self {
+            DevicePlatform::ArduinoGiga => 5,
+            DevicePlatform::Stm32f4
+            | DevicePlatform::Rp2350
+            | DevicePlatform::Teensy40
+            | DevicePlatform::Teensy41 => 4,
+            DevicePlatform::Rp2040 => 3,
+            DevicePlatform::Esp32 => 2,
+            DevicePlatform::Stm32f1
+            | DevicePlatform::RaspberryPi
+            | DevicePlatform::OrangePi
+            | DevicePlatform::BananaPi => 1,
+            DevicePlatform::Unknown => 0,
+        }
+    }
+
+    /// Parallel NAND CE# lines the firmware can select with `NandSelectCe`
+    pub fn nand_chip_enables(&self) -> u8 {
+        match self {
+            DevicePlatform::Rp2040 => 4, // GP4, GP20-GP22
+            DevicePlatform::Stm32f1 => 2,
+            _ => 1,
+        }
+    }
 }
 
 /// Device capabilities bitmap
@@ -205,6 +233,9 @@ pub struct ChipInfo {
     pub serial_number: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub boot_lun_enabled: Option<bool>,
+    // NAND specific fields: block_size is in pages and reads can include OOB
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub oob_size: Option<u32>,
 }
 
 /// UFS Logical Unit information
@@ -341,6 +372,65 @@ impl UsbDevice {
 
         Ok(data)
     }
+
+    /// Send a command whose answer is `len` raw data bytes (SPI NOR reads)
+    pub async fn read_data(
+        &self,
+        cmd: Command,
+        args: &[u8],
+        len: usize,
+    ) -> Result<Vec<u8>, String> {
+        let packet = Packet::new(cmd, args);
+        self.interface
+            .bulk_out(EP_OUT, packet.to_bytes().to_vec())
+            .await
+            .status
+            .map_err(|e| format!("USB write error: {:?}", e))?;
+
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let buf = RequestBuffer::new(64);
+            let result = self.interface.bulk_in(EP_IN, buf).await;
+            result
+                .status
+                .map_err(|e| format!("USB read error: {:?}", e))?;
+
+            let to_copy = (len - data.len()).min(result.data.len());
+            data.extend_from_slice(&result.data[..to_copy]);
+        }
+
+        Ok(data)
+    }
+
+    /// Send a command, then stream `data` after it (SPI NOR page program)
+    pub async fn send_command_with_data(
+        &self,
+        cmd: Command,
+        args: &[u8],
+        data: &[u8],
+    ) -> Result<Vec<u8>, String> {
+        let packet = Packet::new(cmd, args);
+        self.interface
+            .bulk_out(EP_OUT, packet.to_bytes().to_vec())
+            .await
+            .status
+            .map_err(|e| format!("USB write error: {:?}", e))?;
+
+        for chunk in data.chunks(64) {
+            self.interface
+                .bulk_out(EP_OUT, chunk.to_vec())
+                .await
+                .status
+                .map_err(|e| format!("USB write error: {:?}", e))?;
+        }
+
+        let buf = RequestBuffer::new(64);
+        let result = self.interface.bulk_in(EP_IN, buf).await;
+        result
+            .status
+            .map_err(|e| format!("USB read error: {:?}", e))?;
+        Ok(result.data)
+    }
 }
 
 pub struct DeviceManager {
@@ -349,6 +439,8 @@ pub struct DeviceManager {
     interface: FlashInterface,
     current_platform: Option<DevicePlatform>,
     current_capabilities: Option<DeviceCapabilities>,
+    /// Dies found by the last parallel NAND detection
+    nand_topology: Option<NandTopology>,
 }
 
 impl DeviceManager {
@@ -359,6 +451,7 @@ impl DeviceManager {
             interface: FlashInterface::ParallelNand,
             current_platform: None,
             current_capabilities: None,
+            nand_topology: None,
         }
     }
 
@@ -378,6 +471,14 @@ impl DeviceManager {
         self.current_capabilities.as_ref()
     }
 
+    pub fn set_nand_topology(&mut self, topology: Option<NandTopology>) {
+        self.nand_topology = topology;
+    }
+
+    pub fn get_nand_topology(&self) -> Option<&NandTopology> {
+        self.nand_topology.as_ref()
+    }
+
     pub fn scan_devices(&mut self) -> Vec<DeviceInfo> {
         self.devices.clear();
 
@@ -546,6 +647,7 @@ impl DeviceManager {
         self.active_device = None;
         self.current_platform = None;
         self.current_capabilities = None;
+        self.nand_topology = None;
         for dev in &mut self.devices {
             dev.connected = false;
         }
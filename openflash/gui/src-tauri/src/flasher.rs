@@ -1,6 +1,6 @@
 //! High-level NAND flash operations
 
-use openflash_core::ecc::{decode_with_ecc, EccAlgorithm};
+use openflash_core::ecc::{decode_with_ecc_sectors, EccAlgorithm, EccReport};
 use serde::{Deserialize, Serialize};
 
 /// Flash operation configuration
@@ -25,12 +25,16 @@ impl Default for FlashConfig {
     }
 }
 
-/// Process raw dump with ECC
-pub fn process_dump_with_ecc(raw_data: &[u8], config: &FlashConfig) -> Result<Vec<u8>, String> {
+/// Process raw dump with ECC, collecting per-sector ECC statistics
+pub fn process_dump_with_ecc(
+    raw_data: &[u8],
+    config: &FlashConfig,
+) -> Result<(Vec<u8>, EccReport), String> {
     let page_with_oob = config.page_size as usize + config.oob_size as usize;
     let mut processed = Vec::new();
+    let mut report = EccReport::new(config.pages_per_block);
 
-    for chunk in raw_data.chunks(page_with_oob) {
+    for (page, chunk) in raw_data.chunks(page_with_oob).enumerate() {
         if chunk.len() < config.page_size as usize {
             break;
         }
@@ -46,22 +50,19 @@ pub fn process_dump_with_ecc(raw_data: &[u8], config: &FlashConfig) -> Result<Ve
 
         // Apply ECC correction if we have OOB data
         if !oob_data.is_empty() && config.ecc_algorithm != EccAlgorithm::None {
-            match decode_with_ecc(&mut data, oob_data, &config.ecc_algorithm) {
-                Ok(corrected) => {
-                    if corrected > 0 {
-                        // Log corrected bits
-                    }
-                }
-                Err(_) => {
-                    // Mark page as potentially corrupted
-                }
-            }
+            let sectors = decode_with_ecc_sectors(
+                &mut data,
+                oob_data,
+                &config.ecc_algorithm,
+                config.ecc_algorithm.strength(),
+            );
+            report.add_page(page as u32, &sectors);
         }
 
         processed.extend(data);
     }
 
-    Ok(processed)
+    Ok((processed, report))
 }
 
 /// Extract only data pages (skip OOB)
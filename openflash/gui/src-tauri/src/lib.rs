@@ -35,6 +35,8 @@ pub fn run() {
             command::read_nand_id,
             command::get_chip_info,
             command::dump_nand,
+            command::dump_nand_blocks,
+            command::program_nand_blocks,
             command::dump_nand_with_progress,
             command::analyze_dump,
             command::get_config,
@@ -44,12 +46,16 @@ pub fn run() {
             command::set_interface,
             command::get_interface,
             command::read_spi_nand_id,
+            command::dump_spi_nand,
             // SPI NOR commands (v1.6)
             command::read_spi_nor_jedec_id,
             command::spi_nor_sector_erase,
             command::spi_nor_block_erase,
             command::spi_nor_chip_erase,
             command::spi_nor_unlock_all,
+            command::dump_spi_nor,
+            command::write_spi_nor,
+            command::program_spi_nor,
             // UFS commands (v1.6)
             command::read_ufs_device_info,
             command::ufs_select_lun,
@@ -57,6 +63,9 @@ pub fn run() {
             command::ai_analyze_dump,
             command::ai_detect_patterns,
             command::ai_get_recommendations,
+            // ECC statistics commands
+            command::ecc_report_dump,
+            command::export_ecc_report,
             // Platform commands (v2.3)
             command::get_device_info,
             command::get_platform_info,
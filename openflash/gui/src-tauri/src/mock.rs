@@ -236,6 +236,7 @@ pub fn get_mock_chip_info() -> ChipInfo {
         ufs_version: None,
         serial_number: None,
         boot_lun_enabled: None,
+        oob_size: Some(64),
     }
 }
 
@@ -324,6 +325,30 @@ pub fn process_mock_command(cmd: Command, args: &[u8]) -> Vec<u8> {
         Command::SpiNorWriteEnable => vec![0x71, 0x00],
         Command::SpiNorWriteDisable => vec![0x72, 0x00],
         Command::SpiNorReset => vec![0x73, 0x00],
+        Command::SpiNorReadUniqueId => {
+            let len = args.first().copied().unwrap_or(8) as usize;
+            let mut data = vec![0x74, 0x00];
+            data.extend((0..len).map(|i| 0xD0 + i as u8));
+            data
+        }
+        Command::SpiNorReadSecurityReg => {
+            // Blank (erased) security registers
+            let len = if args.len() >= 5 {
+                u16::from_le_bytes([args[3], args[4]]) as usize
+            } else {
+                256
+            };
+            let mut data = vec![0x75, 0x00];
+            data.resize(2 + len, 0xFF);
+            data
+        }
+        Command::SpiNorProgramSecurityReg => vec![0x76, 0x00],
+        Command::SpiNorEraseSecurityReg => vec![0x77, 0x00],
+        Command::SpiNorEnterSecuredOtp => vec![0x78, 0x00],
+        Command::SpiNorExitSecuredOtp => vec![0x79, 0x00],
+        Command::SpiNorReadSecurityScur => vec![0x7A, 0x00, 0x00],
+        Command::SpiNorLockSecuredOtp => vec![0x7B, 0x00],
+        Command::SpiNorSetAddressMode => vec![0x7E, 0x00],
 
         // UFS commands
         Command::UfsInit => vec![0x80, 0x00],
@@ -408,6 +433,8 @@ pub fn process_mock_command(cmd: Command, args: &[u8]) -> Vec<u8> {
         Command::SpiNandBlockErase => vec![0x2A, 0x00],
         Command::SpiNandWriteEnable => vec![0x2B, 0x00],
         Command::SpiNandWriteDisable => vec![0x2C, 0x00],
+        Command::SpiNandReadEccStatus => vec![0x2D, 0x00, 0x00],
+        Command::SpiNandDieSelect => vec![0x2E, 0x00],
 
         _ => vec![0x00, 0x01], // Unknown command error
     }
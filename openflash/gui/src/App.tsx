@@ -5,6 +5,7 @@ import { writeFile, readFile } from "@tauri-apps/plugin-fs";
 import { HexViewer } from "./components/HexViewer";
 import { BitmapView } from "./components/BitmapView";
 import { AiAnalysis } from "./components/AiAnalysis";
+import { EccHeatMap } from "./components/EccHeatMap";
 import { SpiNorOperations } from "./components/SpiNorOperations";
 import { UfsLunSelector } from "./components/UfsLunSelector";
 import { PlatformInfo } from "./components/PlatformInfo";
@@ -13,6 +14,7 @@ import "./styles.css";
 import "./components/HexViewer.css";
 import "./components/BitmapView.css";
 import "./components/AiAnalysis.css";
+import "./components/EccHeatMap.css";
 import "./components/SpiNorOperations.css";
 import "./components/UfsLunSelector.css";
 import "./components/PlatformInfo.css";
@@ -90,6 +92,8 @@ interface ChipInfo {
   ufs_version?: string;
   serial_number?: string;
   boot_lun_enabled?: boolean;
+  // NAND specific fields: block_size is in pages
+  oob_size?: number;
 }
 
 interface AnalysisResult {
@@ -100,7 +104,7 @@ interface AnalysisResult {
   data_pages: number;
 }
 
-type Tab = "operations" | "hexview" | "bitmap" | "analysis" | "ai";
+type Tab = "operations" | "hexview" | "bitmap" | "ecc" | "analysis" | "ai";
 
 function App() {
   const [devices, setDevices] = useState<DeviceInfo[]>([]);
@@ -109,6 +113,8 @@ function App() {
   const [status, setStatus] = useState<string>("Ready");
   const [dumpProgress, setDumpProgress] = useState<number>(0);
   const [dumpData, setDumpData] = useState<Uint8Array | null>(null);
+  // Page+OOB copy of a NAND dump, for the ECC heat map
+  const [rawDump, setRawDump] = useState<Uint8Array | null>(null);
   const [analysis, setAnalysis] = useState<AnalysisResult | null>(null);
   const [activeTab, setActiveTab] = useState<Tab>("operations");
   const [isWorking, setIsWorking] = useState(false);
@@ -231,25 +237,33 @@ function App() {
       setActiveTab("operations");
 
       const pageSize = chipInfo.page_size;
+      const oobSize = chipInfo.oob_size ?? 0;
+      const rawPageSize = pageSize + oobSize;
       const totalPages = Math.min(
         (chipInfo.size_mb * 1024 * 1024) / pageSize,
         65536
       );
       const chunkSize = 64;
 
+      // NAND pages are read with their OOB; the main view gets the data area
       const allData = new Uint8Array(totalPages * pageSize);
-      let offset = 0;
+      const raw = oobSize > 0 ? new Uint8Array(totalPages * rawPageSize) : null;
 
       for (let page = 0; page < totalPages; page += chunkSize) {
         const numPages = Math.min(chunkSize, totalPages - page);
-        const data = await invoke<number[]>("dump_nand", {
-          startPage: page,
-          numPages,
-          pageSize,
-        });
-
-        allData.set(new Uint8Array(data), offset);
-        offset += data.length;
+        const data = new Uint8Array(
+          await invoke<number[]>("dump_nand", {
+            startPage: page,
+            numPages,
+            pageSize: rawPageSize,
+          })
+        );
+
+        raw?.set(data, page * rawPageSize);
+        for (let i = 0; i < numPages; i++) {
+          const start = i * rawPageSize;
+          allData.set(data.subarray(start, start + pageSize), (page + i) * pageSize);
+        }
 
         const progress = Math.round(((page + numPages) / totalPages) * 100);
         setDumpProgress(progress);
@@ -257,6 +271,7 @@ function App() {
       }
 
       setDumpData(allData);
+      setRawDump(raw);
       setDumpProgress(100);
       setStatus("Dump complete!");
 
@@ -321,6 +336,7 @@ function App() {
         setStatus("Loading...");
         const data = await readFile(path);
         setDumpData(new Uint8Array(data));
+        setRawDump(null);
         setStatus(`Loaded ${formatBytes(data.length)}`);
         await invoke("add_recent_file", { path });
         
@@ -567,6 +583,13 @@ function App() {
             >
               🗺️ Bitmap
             </button>
+            <button 
+              className={activeTab === "ecc" ? "active" : ""} 
+              onClick={() => setActiveTab("ecc")}
+              disabled={!rawDump}
+            >
+              🧮 ECC
+            </button>
             <button 
               className={activeTab === "analysis" ? "active" : ""} 
               onClick={() => setActiveTab("analysis")}
@@ -665,6 +688,17 @@ function App() {
               </section>
             )}
 
+            {activeTab === "ecc" && rawDump && chipInfo?.oob_size && (
+              <section className="panel ecc-panel">
+                <EccHeatMap
+                  data={rawDump}
+                  pageSize={chipInfo.page_size}
+                  oobSize={chipInfo.oob_size}
+                  pagesPerBlock={chipInfo.block_size}
+                />
+              </section>
+            )}
+
             {activeTab === "analysis" && analysis && (
               <section className="panel analysis">
                 <h2>Analysis Results</h2>
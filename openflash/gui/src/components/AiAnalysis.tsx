@@ -176,6 +176,7 @@ export function AiAnalysis({ data, pageSize, pagesPerBlock, onPatternSelect }: A
   function getPatternIcon(type: string): string {
     const icons: Record<string, string> = {
       "Encrypted": "🔐",
+      "Scrambled": "🔀",
       "Compressed": "📦",
       "Executable": "⚙️",
       "Text": "📝",
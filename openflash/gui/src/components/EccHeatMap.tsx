@@ -0,0 +1,271 @@
+import { useMemo, useState } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { save } from "@tauri-apps/plugin-dialog";
+import { writeTextFile } from "@tauri-apps/plugin-fs";
+
+type EccAlgorithm = "None" | "Hamming" | { Bch: { t: number } };
+
+interface SectorEccRecord {
+  page: number;
+  sector: number;
+  corrected_bits: number;
+  uncorrectable: boolean;
+  erased: boolean;
+}
+
+interface EccReport {
+  pages_per_block: number;
+  sectors: SectorEccRecord[];
+}
+
+interface BlockEccSummary {
+  block: number;
+  sectors: number;
+  corrected_bits: number;
+  max_corrected_bits: number;
+  uncorrectable_sectors: number;
+  erased_sectors: number;
+  histogram: number[];
+}
+
+interface EccReportResponse {
+  report: EccReport;
+  blocks: BlockEccSummary[];
+  histogram: number[];
+  total_corrected_bits: number;
+  uncorrectable_sectors: number;
+  erased_sectors: number;
+}
+
+interface EccHeatMapProps {
+  data: Uint8Array;
+  pageSize: number;
+  oobSize: number;
+  pagesPerBlock: number;
+  width?: number;
+}
+
+const ALGORITHMS: { label: string; value: EccAlgorithm }[] = [
+  { label: "Hamming", value: "Hamming" },
+  { label: "BCH-4", value: { Bch: { t: 4 } } },
+  { label: "BCH-8", value: { Bch: { t: 8 } } },
+  { label: "BCH-16", value: { Bch: { t: 16 } } },
+];
+
+export function EccHeatMap({
+  data,
+  pageSize,
+  oobSize,
+  pagesPerBlock,
+  width = 64,
+}: EccHeatMapProps) {
+  const [algorithmIndex, setAlgorithmIndex] = useState(1);
+  const [result, setResult] = useState<EccReportResponse | null>(null);
+  const [isRunning, setIsRunning] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+  const [hoveredBlock, setHoveredBlock] = useState<BlockEccSummary | null>(null);
+
+  const strength = useMemo(() => {
+    const algorithm = ALGORITHMS[algorithmIndex].value;
+    if (algorithm === "Hamming") return 1;
+    if (algorithm === "None") return 0;
+    return algorithm.Bch.t;
+  }, [algorithmIndex]);
+
+  async function runReport() {
+    setIsRunning(true);
+    setError(null);
+
+    try {
+      const response = await invoke<EccReportResponse>("ecc_report_dump", {
+        data: Array.from(data),
+        pageSize,
+        oobSize,
+        pagesPerBlock,
+        eccAlgorithm: ALGORITHMS[algorithmIndex].value,
+      });
+      setResult(response);
+    } catch (e) {
+      setError(`ECC report failed: ${e}`);
+    } finally {
+      setIsRunning(false);
+    }
+  }
+
+  async function exportReport(format: "json" | "csv") {
+    if (!result) return;
+
+    try {
+      const content = await invoke<string>("export_ecc_report", {
+        report: result.report,
+        format,
+      });
+      const path = await save({
+        filters: [{ name: format.toUpperCase(), extensions: [format] }],
+        defaultPath: `ecc_report.${format}`,
+      });
+      if (path) {
+        await writeTextFile(path, content);
+      }
+    } catch (e) {
+      setError(`Export failed: ${e}`);
+    }
+  }
+
+  const imageDataUrl = useMemo(() => {
+    if (!result || result.blocks.length === 0) return "";
+
+    const lastBlock = result.blocks[result.blocks.length - 1].block;
+    const height = Math.ceil((lastBlock + 1) / width);
+    const canvas = document.createElement("canvas");
+    canvas.width = width;
+    canvas.height = height;
+    const ctx = canvas.getContext("2d");
+    if (!ctx) return "";
+
+    const imgData = ctx.createImageData(width, height);
+    const pixels = imgData.data;
+
+    // Blocks without ECC data stay dark
+    for (let i = 0; i < pixels.length; i += 4) {
+      pixels[i] = 20;
+      pixels[i + 1] = 20;
+      pixels[i + 2] = 30;
+      pixels[i + 3] = 255;
+    }
+
+    for (const block of result.blocks) {
+      const [r, g, b] = blockColor(block, strength);
+      const pixelIndex = block.block * 4;
+      pixels[pixelIndex] = r;
+      pixels[pixelIndex + 1] = g;
+      pixels[pixelIndex + 2] = b;
+    }
+
+    ctx.putImageData(imgData, 0, 0);
+    return canvas.toDataURL();
+  }, [result, width, strength]);
+
+  const handleMouseMove = (e: React.MouseEvent<HTMLImageElement>) => {
+    if (!result) return;
+    const img = e.currentTarget;
+    const rect = img.getBoundingClientRect();
+    const height = img.naturalHeight;
+    const x = Math.floor(((e.clientX - rect.left) * width) / rect.width);
+    const y = Math.floor(((e.clientY - rect.top) * height) / rect.height);
+    const blockIndex = y * width + x;
+    setHoveredBlock(result.blocks.find((b) => b.block === blockIndex) ?? null);
+  };
+
+  const maxHistogram = result ? Math.max(1, ...result.histogram) : 1;
+
+  return (
+    <div className="ecc-heatmap">
+      <div className="ecc-header">
+        <div className="ecc-controls">
+          <select
+            value={algorithmIndex}
+            onChange={(e) => setAlgorithmIndex(Number(e.target.value))}
+            disabled={isRunning}
+          >
+            {ALGORITHMS.map((a, i) => (
+              <option key={a.label} value={i}>{a.label}</option>
+            ))}
+          </select>
+          <button onClick={runReport} disabled={isRunning || oobSize === 0}>
+            {isRunning ? "⏳ Scanning..." : "🧮 Run ECC Scan"}
+          </button>
+          <button onClick={() => exportReport("json")} disabled={!result} className="secondary">
+            JSON
+          </button>
+          <button onClick={() => exportReport("csv")} disabled={!result} className="secondary">
+            CSV
+          </button>
+        </div>
+
+        {result && (
+          <div className="ecc-stats">
+            <div className="stat">
+              <span className="stat-value">{result.report.sectors.length.toLocaleString()}</span>
+              <span className="stat-label">Sectors</span>
+            </div>
+            <div className="stat">
+              <span className="stat-value">{result.total_corrected_bits.toLocaleString()}</span>
+              <span className="stat-label">Corrected bits</span>
+            </div>
+            <div className="stat">
+              <span className={`stat-value ${result.uncorrectable_sectors > 0 ? "warning" : ""}`}>
+                {result.uncorrectable_sectors.toLocaleString()}
+              </span>
+              <span className="stat-label">Uncorrectable</span>
+            </div>
+            <div className="stat">
+              <span className="stat-value">{result.erased_sectors.toLocaleString()}</span>
+              <span className="stat-label">Erased</span>
+            </div>
+          </div>
+        )}
+      </div>
+
+      {error && <div className="ecc-error">{error}</div>}
+
+      {!result && !error && (
+        <div className="empty">
+          <p>Run an ECC scan over a page+OOB dump to see bit errors per block</p>
+        </div>
+      )}
+
+      {result && (
+        <div className="ecc-content">
+          <div className="ecc-map">
+            <img
+              src={imageDataUrl}
+              alt="ECC heat map"
+              className="ecc-map-image"
+              onMouseMove={handleMouseMove}
+              onMouseLeave={() => setHoveredBlock(null)}
+            />
+            <div className="ecc-block-info">
+              {hoveredBlock ? (
+                <>
+                  <span>Block {hoveredBlock.block}</span>
+                  <span>Max {hoveredBlock.max_corrected_bits} bits/sector</span>
+                  <span>{hoveredBlock.corrected_bits} corrected</span>
+                  <span>{hoveredBlock.uncorrectable_sectors} uncorrectable</span>
+                  <span>{hoveredBlock.erased_sectors} erased</span>
+                </>
+              ) : (
+                <span>Hover a block for details</span>
+              )}
+            </div>
+          </div>
+
+          <div className="ecc-histogram">
+            <label>Corrected bits per sector</label>
+            {result.histogram.map((count, bits) => (
+              <div key={bits} className="ecc-histogram-row">
+                <span className="ecc-histogram-label">{bits}</span>
+                <div className="ecc-histogram-bar">
+                  <div
+                    className="ecc-histogram-fill"
+                    style={{ width: `${(count / maxHistogram) * 100}%` }}
+                  />
+                </div>
+                <span className="ecc-histogram-count">{count.toLocaleString()}</span>
+              </div>
+            ))}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
+
+// Green for clean blocks, shading to red as the worst sector nears ECC strength
+function blockColor(block: BlockEccSummary, strength: number): [number, number, number] {
+  if (block.uncorrectable_sectors > 0) return [168, 85, 247];
+  if (block.max_corrected_bits === 0) return [34, 197, 94];
+
+  const ratio = Math.min(1, block.max_corrected_bits / Math.max(1, strength));
+  return [Math.round(245 + 10 * ratio), Math.round(200 - 140 * ratio), 40];
+}